@@ -201,7 +201,7 @@ impl DelayedFieldData {
             },
         };
 
-        let snapshot_id = resolver.generate_delayed_field_id(width);
+        let snapshot_id = resolver.generate_delayed_field_id(width)?;
         self.delayed_fields.insert(snapshot_id, change);
         Ok(snapshot_id)
     }
@@ -211,12 +211,12 @@ impl DelayedFieldData {
         value: u128,
         width: u32,
         resolver: &dyn DelayedFieldResolver,
-    ) -> DelayedFieldID {
+    ) -> PartialVMResult<DelayedFieldID> {
         let change = DelayedChange::Create(DelayedFieldValue::Snapshot(value));
-        let snapshot_id = resolver.generate_delayed_field_id(width);
+        let snapshot_id = resolver.generate_delayed_field_id(width)?;
 
         self.delayed_fields.insert(snapshot_id, change);
-        snapshot_id
+        Ok(snapshot_id)
     }
 
     pub fn create_new_derived(
@@ -230,7 +230,7 @@ impl DelayedFieldData {
                 code_invariant_error("Calculated DerivedStringSnapshot width exceeds u32")
             })?;
         let change = DelayedChange::Create(DelayedFieldValue::Derived(value));
-        let snapshot_id = resolver.generate_delayed_field_id(width);
+        let snapshot_id = resolver.generate_delayed_field_id(width)?;
 
         self.delayed_fields.insert(snapshot_id, change);
         Ok(snapshot_id)
@@ -291,7 +291,7 @@ impl DelayedFieldData {
             },
         };
 
-        let new_id = resolver.generate_delayed_field_id(width);
+        let new_id = resolver.generate_delayed_field_id(width)?;
         self.delayed_fields.insert(new_id, change);
         Ok(new_id)
     }
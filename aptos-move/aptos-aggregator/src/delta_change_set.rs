@@ -534,7 +534,7 @@ mod test {
             Err(code_invariant_error("Error message from BadStorage.").into())
         }
 
-        fn generate_delayed_field_id(&self, _width: u32) -> Self::Identifier {
+        fn generate_delayed_field_id(&self, _width: u32) -> Result<Self::Identifier, PanicError> {
             unimplemented!("Irrelevant for the test")
         }
 
@@ -8,6 +8,7 @@ use crate::{
         DeltaHistoryMergeOffsetFailureReason, PanicOr,
     },
 };
+use serde::{Deserialize, Serialize};
 
 /// Tracks values seen by aggregator. In particular, stores information about
 /// the biggest and the smallest deltas that were applied successfully during
@@ -52,7 +53,7 @@ use crate::{
 ///   4. check -D does drop below zero
 ///
 /// Checking +X is irrelevant since +A >= +Z, and so Z is not stored here.
-#[derive(Clone, Hash, Copy, Default, PartialOrd, Ord, PartialEq, Eq)]
+#[derive(Clone, Hash, Copy, Default, PartialOrd, Ord, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DeltaHistory {
     pub max_achieved_positive_delta: u128,
     pub min_achieved_negative_delta: u128,
@@ -170,9 +170,39 @@ pub trait TDelayedFieldView {
         max_value: u128,
     ) -> Result<bool, PanicOr<DelayedFieldsSpeculativeError>>;
 
+    /// Fetches the outcomes of a batch of try_add calls, each given as
+    /// (id, base_delta, delta, max_value), as delayed_field_try_add_delta_outcome would
+    /// (in order, each entry with the base delta provided by the caller). The history
+    /// updates of the batch are kept only if the outcomes satisfy the commit policy.
+    ///
+    /// The policy must be monotone, i.e. remain unsatisfied if more entries fail. Views
+    /// that record the histories may then (on a rejected batch) keep only what is needed
+    /// to validate the failures. By default, nothing is recorded that needs to be undone.
+    fn try_add_deltas_with_policy(
+        &self,
+        deltas: &[(Self::Identifier, SignedU128, SignedU128, u128)],
+        _commit_policy: &dyn Fn(&[bool]) -> bool,
+    ) -> Result<Vec<bool>, PanicOr<DelayedFieldsSpeculativeError>> {
+        deltas
+            .iter()
+            .map(|(id, base_delta, delta, max_value)| {
+                self.delayed_field_try_add_delta_outcome(id, base_delta, delta, *max_value)
+            })
+            .collect()
+    }
+
+    /// Fetches the outcomes of a batch of try_add calls (see try_add_deltas_with_policy),
+    /// keeping the history updates only if all of them succeeded.
+    fn try_add_delta_all_or_nothing(
+        &self,
+        deltas: &[(Self::Identifier, SignedU128, SignedU128, u128)],
+    ) -> Result<Vec<bool>, PanicOr<DelayedFieldsSpeculativeError>> {
+        self.try_add_deltas_with_policy(deltas, &|outcomes| outcomes.iter().all(|o| *o))
+    }
+
     /// Returns a unique per-block identifier that can be used when creating a
-    /// new aggregator V2.
-    fn generate_delayed_field_id(&self, width: u32) -> Self::Identifier;
+    /// new aggregator V2. Fails if the identifiers of the block are exhausted.
+    fn generate_delayed_field_id(&self, width: u32) -> Result<Self::Identifier, PanicError>;
 
     fn validate_delayed_field_id(&self, id: &Self::Identifier) -> Result<(), PanicError>;
 
@@ -252,7 +282,7 @@ where
 
     /// Returns a unique per-block identifier that can be used when creating a
     /// new aggregator V2.
-    fn generate_delayed_field_id(&self, _width: u32) -> Self::Identifier {
+    fn generate_delayed_field_id(&self, _width: u32) -> Result<Self::Identifier, PanicError> {
         unimplemented!("generate_delayed_field_id not implemented")
     }
 
@@ -113,11 +113,11 @@ impl TDelayedFieldView for FakeAggregatorView {
         Ok(math.unsigned_add_delta(base, delta).is_ok())
     }
 
-    fn generate_delayed_field_id(&self, width: u32) -> Self::Identifier {
+    fn generate_delayed_field_id(&self, width: u32) -> Result<Self::Identifier, PanicError> {
         let mut counter = self.counter.borrow_mut();
         let id = *counter;
         *counter += 1;
-        DelayedFieldID::new_with_width(id, width)
+        Ok(DelayedFieldID::new_with_width(id, width))
     }
 
     fn validate_delayed_field_id(&self, id: &Self::Identifier) -> Result<(), PanicError> {
@@ -19,6 +19,7 @@ use move_vm_types::{
     },
     values::{Struct, Value},
 };
+use serde::{Deserialize, Serialize};
 
 // Wrapping another error, to add a variant that represents
 // something that should never happen - i.e. a code invariant error,
@@ -157,13 +158,20 @@ pub enum DelayedFieldsSpeculativeError {
         achieved: SignedU128,
         overflow: SignedU128,
     },
+    /// DelayedField holds a value of a different kind than required by the
+    /// operation (e.g. try_add on a snapshot).
+    WrongKind {
+        id: DelayedFieldID,
+        expected: DelayedFieldValueKind,
+        found: DelayedFieldValueKind,
+    },
     InconsistentRead,
 }
 
 impl NonPanic for DelayedFieldsSpeculativeError {}
 
 /// Value of a DelayedField (i.e. aggregator or snapshot)
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum DelayedFieldValue {
     Aggregator(u128),
     Snapshot(u128),
@@ -171,7 +179,23 @@ pub enum DelayedFieldValue {
     Derived(Vec<u8>),
 }
 
+/// Kind of the value of a DelayedField
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DelayedFieldValueKind {
+    Aggregator,
+    Snapshot,
+    Derived,
+}
+
 impl DelayedFieldValue {
+    pub fn kind(&self) -> DelayedFieldValueKind {
+        match self {
+            DelayedFieldValue::Aggregator(_) => DelayedFieldValueKind::Aggregator,
+            DelayedFieldValue::Snapshot(_) => DelayedFieldValueKind::Snapshot,
+            DelayedFieldValue::Derived(_) => DelayedFieldValueKind::Derived,
+        }
+    }
+
     pub fn into_aggregator_value(self) -> Result<u128, PanicError> {
         match self {
             DelayedFieldValue::Aggregator(value) => Ok(value),
@@ -295,6 +319,7 @@ impl TryFromMoveValue for DelayedFieldValue {
     }
 }
 
+#[derive(Clone, Copy)]
 pub enum ReadPosition {
     BeforeCurrentTxn,
     AfterCurrentTxn,
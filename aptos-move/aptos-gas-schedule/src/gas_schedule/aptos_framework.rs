@@ -157,6 +157,8 @@ crate::gas_schedule::macros::define_gas_parameters!(
         [algebra_ark_h2c_bls12381g2_xmd_sha256_sswu_per_msg_byte: InternalGasPerByte, { 8.. => "algebra.ark_h2c_bls12381g2_xmd_sha256_sswu_per_msg_byte" }, 176],
         // BLS12-381 algebra gas parameters end.
 
+        [algebra_serialized_size_base: InternalGas, { RELEASE_V1_12.. => "algebra.serialized_size.base" }, 38],
+
         [bls12381_base: InternalGas, "bls12381.base", 551],
 
         [bls12381_per_pubkey_deserialize: InternalGasPerArg, "bls12381.per_pubkey_deserialize", 400684],
@@ -114,6 +114,7 @@ pub enum FeatureFlag {
     PrimaryAPTFungibleStoreAtUserAddress,
     ObjectNativeDerivedAddress,
     DispatchableFungibleAsset,
+    StrictAlgebraCanonicalDeserialization,
 }
 
 fn generate_features_blob(writer: &CodeWriter, data: &[u64]) {
@@ -292,6 +293,9 @@ impl From<FeatureFlag> for AptosFeatureFlag {
                 AptosFeatureFlag::OBJECT_NATIVE_DERIVED_ADDRESS
             },
             FeatureFlag::DispatchableFungibleAsset => AptosFeatureFlag::DISPATCHABLE_FUNGIBLE_ASSET,
+            FeatureFlag::StrictAlgebraCanonicalDeserialization => {
+                AptosFeatureFlag::STRICT_ALGEBRA_CANONICAL_DESERIALIZATION
+            },
         }
     }
 }
@@ -399,6 +403,9 @@ impl From<AptosFeatureFlag> for FeatureFlag {
                 FeatureFlag::ObjectNativeDerivedAddress
             },
             AptosFeatureFlag::DISPATCHABLE_FUNGIBLE_ASSET => FeatureFlag::DispatchableFungibleAsset,
+            AptosFeatureFlag::STRICT_ALGEBRA_CANONICAL_DESERIALIZATION => {
+                FeatureFlag::StrictAlgebraCanonicalDeserialization
+            },
         }
     }
 }
@@ -17,6 +17,7 @@ use bytes::Bytes;
 use move_binary_format::errors::{PartialVMError, PartialVMResult};
 use move_core_types::{language_storage::StructTag, value::MoveTypeLayout, vm_status::StatusCode};
 use move_vm_types::delayed_values::delayed_field_id::DelayedFieldID;
+use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap};
 
 /// Allows to query resources from the state.
@@ -101,6 +102,55 @@ pub trait TResourceGroupView {
         maybe_layout: Option<&Self::Layout>,
     ) -> PartialVMResult<Option<Bytes>>;
 
+    /// Returns the (sorted) tags of the resources that exist in the group, including the
+    /// speculative writes observed by the resolver. The size of the group is also read, so
+    /// that in the parallel execution, the tags are validated against concurrent writes.
+    fn group_tags(&self, group_key: &Self::GroupKey) -> PartialVMResult<Vec<Self::ResourceTag>>;
+
+    /// Returns both the resource in the group and the size of the group, as commonly needed
+    /// when preparing the change set. Resolvers may override to resolve both with a single
+    /// access to the group (e.g. initialization or waiting on dependencies).
+    fn get_resource_from_group_with_group_size(
+        &self,
+        group_key: &Self::GroupKey,
+        resource_tag: &Self::ResourceTag,
+        maybe_layout: Option<&Self::Layout>,
+    ) -> PartialVMResult<(Option<Bytes>, ResourceGroupSize)> {
+        let maybe_bytes = self.get_resource_from_group(group_key, resource_tag, maybe_layout)?;
+        let group_size = self.resource_group_size(group_key)?;
+        Ok((maybe_bytes, group_size))
+    }
+
+    /// Returns the resources at the given tags in the group, in the order of the tags (with
+    /// the layout at the same position). Resolvers may override to initialize the group once
+    /// for all the reads, instead of once per tag.
+    fn get_resources_from_group(
+        &self,
+        group_key: &Self::GroupKey,
+        resource_tags: &[Self::ResourceTag],
+        maybe_layouts: &[Option<&Self::Layout>],
+    ) -> PartialVMResult<Vec<Option<Bytes>>> {
+        if resource_tags.len() != maybe_layouts.len() {
+            return Err(
+                PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR).with_message(
+                    format!(
+                        "Number of layouts ({}) does not match the number of tags ({})",
+                        maybe_layouts.len(),
+                        resource_tags.len()
+                    ),
+                ),
+            );
+        }
+
+        resource_tags
+            .iter()
+            .zip(maybe_layouts)
+            .map(|(resource_tag, maybe_layout)| {
+                self.get_resource_from_group(group_key, resource_tag, *maybe_layout)
+            })
+            .collect()
+    }
+
     /// Needed for charging storage fees for a resource group write, as that requires knowing
     /// the size of the resource group AFTER the changeset of the transaction is applied (while
     /// the resource_group_size method provides the total group size BEFORE). To compute the
@@ -287,7 +337,7 @@ where
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum ResourceGroupSize {
     Concrete(u64),
     /// Combined represents what would the size be if we know individual
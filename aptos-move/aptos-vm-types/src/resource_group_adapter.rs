@@ -250,6 +250,73 @@ impl TResourceGroupView for ResourceGroupAdapter<'_> {
             .cloned())
     }
 
+    fn group_tags(&self, group_key: &Self::GroupKey) -> PartialVMResult<Vec<Self::ResourceTag>> {
+        if let Some(group_view) = self.maybe_resource_group_view {
+            return group_view.group_tags(group_key);
+        }
+        self.load_to_cache(group_key)?;
+        Ok(self
+            .group_cache
+            .borrow()
+            .get(group_key)
+            .expect("Must be cached")
+            .0
+            .keys()
+            .cloned()
+            .collect())
+    }
+
+    fn get_resources_from_group(
+        &self,
+        group_key: &Self::GroupKey,
+        resource_tags: &[Self::ResourceTag],
+        maybe_layouts: &[Option<&MoveTypeLayout>],
+    ) -> PartialVMResult<Vec<Option<Bytes>>> {
+        if let Some(group_view) = self.maybe_resource_group_view {
+            return group_view.get_resources_from_group(group_key, resource_tags, maybe_layouts);
+        }
+        if resource_tags.len() != maybe_layouts.len() {
+            return Err(
+                PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR).with_message(
+                    format!(
+                        "Number of layouts ({}) does not match the number of tags ({})",
+                        maybe_layouts.len(),
+                        resource_tags.len()
+                    ),
+                ),
+            );
+        }
+
+        self.load_to_cache(group_key)?;
+        let group_cache = self.group_cache.borrow();
+        let (group, _) = group_cache.get(group_key).expect("Must be cached");
+        Ok(resource_tags
+            .iter()
+            .map(|resource_tag| group.get(resource_tag).cloned())
+            .collect())
+    }
+
+    fn get_resource_from_group_with_group_size(
+        &self,
+        group_key: &Self::GroupKey,
+        resource_tag: &Self::ResourceTag,
+        maybe_layout: Option<&MoveTypeLayout>,
+    ) -> PartialVMResult<(Option<Bytes>, ResourceGroupSize)> {
+        if self.group_size_kind != GroupSizeKind::None {
+            if let Some(group_view) = self.maybe_resource_group_view {
+                return group_view.get_resource_from_group_with_group_size(
+                    group_key,
+                    resource_tag,
+                    maybe_layout,
+                );
+            }
+        }
+
+        let maybe_bytes = self.get_resource_from_group(group_key, resource_tag, maybe_layout)?;
+        let group_size = self.resource_group_size(group_key)?;
+        Ok((maybe_bytes, group_size))
+    }
+
     fn release_group_cache(
         &self,
     ) -> Option<HashMap<Self::GroupKey, BTreeMap<Self::ResourceTag, Bytes>>> {
@@ -281,7 +348,7 @@ mod tests {
         errors::StateviewError, state_storage_usage::StateStorageUsage, state_value::StateValue,
         TStateView,
     };
-    use claims::{assert_gt, assert_none, assert_ok_eq, assert_some, assert_some_eq};
+    use claims::{assert_err, assert_gt, assert_none, assert_ok_eq, assert_some, assert_some_eq};
     use std::cmp::max;
     use test_case::test_case;
 
@@ -388,6 +455,17 @@ mod tests {
                 .and_then(|entry| entry.contents.get(resource_tag).cloned().map(Into::into)))
         }
 
+        fn group_tags(
+            &self,
+            group_key: &Self::GroupKey,
+        ) -> PartialVMResult<Vec<Self::ResourceTag>> {
+            Ok(self
+                .group
+                .get(group_key)
+                .map(|entry| entry.contents.keys().cloned().collect())
+                .unwrap_or_default())
+        }
+
         fn resource_size_in_group(
             &self,
             _group_key: &Self::GroupKey,
@@ -507,6 +585,59 @@ mod tests {
         assert_eq!(bcs::to_bytes(&cache_key_1_contents).unwrap(), *key_1_blob);
     }
 
+    #[test]
+    fn test_get_resources_by_tags() {
+        let state_view = MockStateView::new();
+        let adapter = ResourceGroupAdapter::new(None, &state_view, 5, false);
+
+        let key_1 = StateKey::raw(&[1]);
+        let tag_0 = mock_tag_0();
+        let tag_1 = mock_tag_1();
+        let tag_2 = mock_tag_2();
+
+        assert_ok_eq!(
+            adapter.get_resources_from_group(
+                &key_1,
+                &[tag_1.clone(), tag_2.clone(), tag_0.clone()],
+                &[None, None, None]
+            ),
+            vec![
+                Some(Bytes::from(vec![1; 500])),
+                None,
+                Some(Bytes::from(vec![0; 1000]))
+            ]
+        );
+        assert_ok_eq!(
+            adapter.get_resources_from_group(&key_1, &[], &[]),
+            Vec::<Option<Bytes>>::new()
+        );
+        assert_err!(adapter.get_resources_from_group(&key_1, &[tag_0], &[]));
+
+        // The group is loaded once for all the tags.
+        let cache = adapter.release_group_cache().unwrap();
+        assert_eq!(cache.len(), 1);
+        assert_some!(cache.get(&key_1));
+    }
+
+    #[test_case(false)]
+    #[test_case(true)]
+    fn test_group_tags(with_view: bool) {
+        let state_view = MockStateView::new();
+        let adapter = ResourceGroupAdapter::new(
+            with_view.then_some(&state_view as &dyn ResourceGroupView),
+            &state_view,
+            12,
+            true,
+        );
+
+        assert_ok_eq!(adapter.group_tags(&StateKey::raw(&[1])), vec![
+            mock_tag_0(),
+            mock_tag_1()
+        ]);
+        assert_ok_eq!(adapter.group_tags(&StateKey::raw(&[0])), vec![]);
+        assert_ok_eq!(adapter.group_tags(&StateKey::raw(&[2])), vec![]);
+    }
+
     #[test_case(9, false)]
     #[test_case(12, true)] // Without view, this falls back to as_blob
     fn size_as_blob_len(
@@ -43,7 +43,10 @@ use aptos_types::{
     account_config,
     account_config::{new_block_event_key, AccountResource},
     block_executor::{
-        config::{BlockExecutorConfig, BlockExecutorConfigFromOnchain, BlockExecutorLocalConfig},
+        config::{
+            BlockExecutorConfig, BlockExecutorConfigFromOnchain, BlockExecutorLocalConfig,
+            BlockExecutorOptInConfig,
+        },
         partitioner::PartitionedTransactions,
     },
     block_metadata::BlockMetadata,
@@ -117,6 +120,7 @@ static NUM_EXECUTION_SHARD: OnceCell<usize> = OnceCell::new();
 static NUM_PROOF_READING_THREADS: OnceCell<usize> = OnceCell::new();
 static PARANOID_TYPE_CHECKS: OnceCell<bool> = OnceCell::new();
 static DISCARD_FAILED_BLOCKS: OnceCell<bool> = OnceCell::new();
+static BLOCK_EXECUTOR_OPT_IN_CONFIG: OnceCell<BlockExecutorOptInConfig> = OnceCell::new();
 static PROCESSED_TRANSACTIONS_DETAILED_COUNTERS: OnceCell<bool> = OnceCell::new();
 static TIMED_FEATURE_OVERRIDE: OnceCell<TimedFeatureOverride> = OnceCell::new();
 
@@ -354,6 +358,21 @@ impl AptosVM {
         }
     }
 
+    /// Sets the opt-in checks and tuning of the block executor when invoked the first time.
+    pub fn set_block_executor_opt_in_config_once(config: BlockExecutorOptInConfig) {
+        // Only the first call succeeds, due to OnceCell semantics.
+        BLOCK_EXECUTOR_OPT_IN_CONFIG.set(config).ok();
+    }
+
+    /// Get the opt-in checks and tuning of the block executor if already set, otherwise return
+    /// default (all disabled).
+    pub fn get_block_executor_opt_in_config() -> BlockExecutorOptInConfig {
+        BLOCK_EXECUTOR_OPT_IN_CONFIG
+            .get()
+            .cloned()
+            .unwrap_or_default()
+    }
+
     // Set the override profile for timed features.
     pub fn set_timed_feature_override(profile: TimedFeatureOverride) {
         TIMED_FEATURE_OVERRIDE.set(profile).ok();
@@ -2489,6 +2508,8 @@ impl VMExecutor for AptosVM {
                     concurrency_level: Self::get_concurrency_level(),
                     allow_fallback: true,
                     discard_failed_blocks: Self::get_discard_failed_blocks(),
+                    opt_in: Self::get_block_executor_opt_in_config(),
+                    ..Default::default()
                 },
                 onchain: onchain_config,
             },
@@ -268,7 +268,16 @@ impl<'e, E: ExecutorView> TDelayedFieldView for StorageAdapter<'e, E> {
             .delayed_field_try_add_delta_outcome(id, base_delta, delta, max_value)
     }
 
-    fn generate_delayed_field_id(&self, width: u32) -> Self::Identifier {
+    fn try_add_deltas_with_policy(
+        &self,
+        deltas: &[(Self::Identifier, SignedU128, SignedU128, u128)],
+        commit_policy: &dyn Fn(&[bool]) -> bool,
+    ) -> Result<Vec<bool>, PanicOr<DelayedFieldsSpeculativeError>> {
+        self.executor_view
+            .try_add_deltas_with_policy(deltas, commit_policy)
+    }
+
+    fn generate_delayed_field_id(&self, width: u32) -> Result<Self::Identifier, PanicError> {
         self.executor_view.generate_delayed_field_id(width)
     }
 
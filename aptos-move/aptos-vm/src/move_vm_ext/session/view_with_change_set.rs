@@ -34,7 +34,7 @@ use move_binary_format::errors::PartialVMResult;
 use move_core_types::{language_storage::StructTag, value::MoveTypeLayout};
 use move_vm_types::delayed_values::delayed_field_id::DelayedFieldID;
 use std::{
-    collections::{BTreeMap, HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     sync::Arc,
 };
 
@@ -153,7 +153,7 @@ impl<'r> TDelayedFieldView for ExecutorViewWithChangeSet<'r> {
         }
     }
 
-    fn generate_delayed_field_id(&self, width: u32) -> Self::Identifier {
+    fn generate_delayed_field_id(&self, width: u32) -> Result<Self::Identifier, PanicError> {
         self.base_executor_view.generate_delayed_field_id(width)
     }
 
@@ -304,6 +304,36 @@ impl<'r> TResourceGroupView for ExecutorViewWithChangeSet<'r> {
         }
     }
 
+    fn group_tags(&self, group_key: &Self::GroupKey) -> PartialVMResult<Vec<Self::ResourceTag>> {
+        use AbstractResourceWriteOp::*;
+
+        let mut tags: BTreeSet<_> = self
+            .base_resource_group_view
+            .group_tags(group_key)?
+            .into_iter()
+            .collect();
+        match self.change_set.resource_write_set().get(group_key) {
+            Some(WriteResourceGroup(group_write)) => {
+                for (tag, (write_op, _)) in group_write.inner_ops() {
+                    if write_op.is_deletion() {
+                        tags.remove(tag);
+                    } else {
+                        tags.insert(tag.clone());
+                    }
+                }
+            },
+            Some(ResourceGroupInPlaceDelayedFieldChange(_)) | None => {},
+            Some(Write(_) | WriteWithDelayedFields(_) | InPlaceDelayedFieldChange(_)) => {
+                // There should be no collisions, we cannot have group key refer to a resource.
+                return Err(code_invariant_error(format!(
+                    "Non-ResourceGroup write found for key in group_tags call for key {group_key:?}"
+                ))
+                .into());
+            },
+        }
+        Ok(tags.into_iter().collect())
+    }
+
     fn release_group_cache(
         &self,
     ) -> Option<HashMap<Self::GroupKey, BTreeMap<Self::ResourceTag, Bytes>>> {
@@ -106,7 +106,7 @@ impl TDelayedFieldView for AptosBlankStorage {
         unreachable!()
     }
 
-    fn generate_delayed_field_id(&self, _width: u32) -> Self::Identifier {
+    fn generate_delayed_field_id(&self, _width: u32) -> Result<Self::Identifier, PanicError> {
         unreachable!()
     }
 
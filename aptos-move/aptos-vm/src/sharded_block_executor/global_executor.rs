@@ -1,8 +1,12 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::sharded_block_executor::{
-    local_executor_shard::GlobalCrossShardClient, sharded_executor_service::ShardedExecutorService,
+use crate::{
+    aptos_vm::AptosVM,
+    sharded_block_executor::{
+        local_executor_shard::GlobalCrossShardClient,
+        sharded_executor_service::ShardedExecutorService,
+    },
 };
 use aptos_logger::trace;
 use aptos_types::{
@@ -64,6 +68,8 @@ impl<S: StateView + Sync + Send + 'static> GlobalExecutor<S> {
                     concurrency_level: self.concurrency_level,
                     allow_fallback: true,
                     discard_failed_blocks: false,
+                    opt_in: AptosVM::get_block_executor_opt_in_config(),
+                    ..Default::default()
                 },
                 onchain: onchain_config,
             },
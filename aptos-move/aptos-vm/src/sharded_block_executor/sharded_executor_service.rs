@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    aptos_vm::AptosVM,
     block_executor::BlockAptosVM,
     sharded_block_executor::{
         aggr_overridden_state_view::{AggregatorOverriddenStateView, TOTAL_SUPPLY_AGGR_BASE_VAL},
@@ -234,6 +235,8 @@ impl<S: StateView + Sync + Send + 'static> ShardedExecutorService<S> {
                                 concurrency_level: concurrency_level_per_shard,
                                 allow_fallback: true,
                                 discard_failed_blocks: false,
+                                opt_in: AptosVM::get_block_executor_opt_in_config(),
+                                shard_id: Some(self.shard_id),
                             },
                             onchain: onchain_config,
                         },
@@ -1,7 +1,11 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{types::InputOutputKey, value_exchange::filter_value_for_exchange};
+use crate::{
+    counters::ViewCounterLabels,
+    types::InputOutputKey,
+    value_exchange::{DelayedFieldLayoutCache, ReadsNeedingExchange},
+};
 use anyhow::bail;
 use aptos_aggregator::{
     delta_math::DeltaHistory,
@@ -10,39 +14,53 @@ use aptos_aggregator::{
         ReadPosition,
     },
 };
+use aptos_crypto::HashValue;
 use aptos_mvhashmap::{
     types::{
-        MVDataError, MVDataOutput, MVDelayedFieldsError, MVGroupError, StorageVersion, TxnIndex,
-        ValueWithLayout, Version,
+        Incarnation, MVDataError, MVDataOutput, MVDelayedFieldsError, MVGroupError, MVModulesError,
+        MVModulesOutput, StorageVersion, TxnIndex, ValueWithLayout, Version,
     },
     versioned_data::VersionedData,
     versioned_delayed_fields::TVersionedDelayedFieldView,
     versioned_group_data::VersionedGroupData,
+    versioned_modules::VersionedModules,
+    MVHashMap,
 };
 use aptos_types::{
-    delayed_fields::PanicError, state_store::state_value::StateValueMetadata,
-    transaction::BlockExecutableTransaction as Transaction, write_set::TransactionWrite,
+    delayed_fields::PanicError,
+    executable::{Executable, ExecutableDescriptor},
+    state_store::state_value::StateValueMetadata,
+    transaction::BlockExecutableTransaction as Transaction,
+    write_set::TransactionWrite,
 };
 use aptos_vm_types::resolver::ResourceGroupSize;
 use derivative::Derivative;
 use move_core_types::value::MoveTypeLayout;
+#[cfg(any(test, feature = "serde"))]
+use serde::{Deserialize, Serialize};
+#[cfg(test)]
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::{
+    cell::RefCell,
+    cmp::Reverse,
     collections::{
         hash_map::{
             Entry,
             Entry::{Occupied, Vacant},
         },
-        BTreeMap, HashMap, HashSet,
+        BTreeMap, BTreeSet, HashMap, HashSet,
     },
+    fmt::{self, Debug},
     sync::Arc,
 };
 
 /// The enum variants should not be re-ordered, as it defines a relation
-/// Existence < Metadata < Value.
+/// Existence < Metadata < Size < Value.
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
-pub(crate) enum ReadKind {
+pub enum ReadKind {
     Exists,
     Metadata,
+    Size,
     Value,
 }
 
@@ -51,8 +69,14 @@ pub(crate) enum ReadKind {
 /// The captured state is fine-grained, e.g. it distinguishes between reading
 /// a full value, and other kinds of reads that may access only the metadata
 /// information, or check whether data exists at a given key.
+///
+/// With the serde feature, the shared values and layouts are serialized by content (the
+/// layout by its derived form, the value by its own serialization). Deserialization then
+/// allocates new Arcs, so a round-trip preserves the reads, but not the sharing (e.g. with
+/// the values in MVHashMap).
 #[derive(Derivative)]
 #[derivative(Clone(bound = ""), Debug(bound = ""), PartialEq(bound = ""))]
+#[cfg_attr(any(test, feature = "serde"), derive(Serialize, Deserialize))]
 pub(crate) enum DataRead<V> {
     // Version supersedes V comparison.
     Versioned(
@@ -63,13 +87,54 @@ pub(crate) enum DataRead<V> {
         #[derivative(PartialEq = "ignore", Debug = "ignore")] Arc<V>,
         #[derivative(PartialEq = "ignore", Debug = "ignore")] Option<Arc<MoveTypeLayout>>,
     ),
+    /// Value read (without a layout) captured by the hash of the value instead of the value
+    /// itself. Version supersedes the comparison of the hashed values (as for Versioned).
+    Hashed(Version, #[derivative(PartialEq = "ignore")] HashedValue),
     Metadata(Option<StateValueMetadata>),
+    /// The metadata and the size (in bytes) of the value, None if the value does not exist.
+    /// The size read also captures the metadata, so that it contains the Metadata kind.
+    Size(Option<(StateValueMetadata, u64)>),
     Exists(bool),
     /// Read resolved an aggregatorV1 delta to a value.
     /// TODO[agg_v1](cleanup): deprecate.
     Resolved(u128),
 }
 
+/// The information retained about a (large) value read, instead of the value. The value
+/// must exist, hence the metadata is always available.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "serde"), derive(Serialize, Deserialize))]
+pub(crate) struct HashedValue {
+    len: usize,
+    hash: HashValue,
+    metadata: StateValueMetadata,
+}
+
+impl HashedValue {
+    fn new<V: TransactionWrite>(value: &V) -> Option<Self> {
+        let bytes = value.bytes()?;
+        Some(Self {
+            len: bytes.len(),
+            hash: HashValue::sha3_256_of(bytes),
+            metadata: value.as_state_value_metadata()?,
+        })
+    }
+
+    /// Returns true if the value is the same as the hashed value (at any version). Only values
+    /// without a layout (i.e. without delayed fields) may match, as the hashed value was read
+    /// without a layout.
+    pub(crate) fn matches<V: TransactionWrite>(&self, value: &ValueWithLayout<V>) -> bool {
+        let value = match value {
+            ValueWithLayout::RawFromStorage(v) | ValueWithLayout::Exchanged(v, None) => v,
+            ValueWithLayout::Exchanged(_, Some(_)) => return false,
+        };
+
+        // The length is compared first, to avoid hashing values that can not match.
+        value.bytes().map(|bytes| bytes.len()) == Some(self.len)
+            && HashedValue::new(value.as_ref()).as_ref() == Some(self)
+    }
+}
+
 // Represents the result of comparing DataReads ('self' and 'other').
 #[derive(Debug)]
 enum DataReadComparison {
@@ -85,18 +150,49 @@ enum DataReadComparison {
 }
 
 impl<V: TransactionWrite> DataRead<V> {
-    // Assigns highest rank to Versioned / Resolved, then Metadata, then Exists.
-    // (e.g. versioned read implies metadata and existence information, and
+    // Assigns highest rank to Versioned / Resolved, then Size, then Metadata, then Exists.
+    // (e.g. versioned read implies size, metadata and existence information, and
     // metadata information implies existence information).
     fn get_kind(&self) -> ReadKind {
         use DataRead::*;
         match self {
-            Versioned(_, _, _) | Resolved(_) => ReadKind::Value,
+            Versioned(_, _, _) | Hashed(_, _) | Resolved(_) => ReadKind::Value,
+            Size(_) => ReadKind::Size,
             Metadata(_) => ReadKind::Metadata,
             Exists(_) => ReadKind::Exists,
         }
     }
 
+    /// Returns the kind of the read and its version (for the value reads that have one).
+    pub(crate) fn version_summary(&self) -> ReadVersionSummary {
+        let version = match self {
+            DataRead::Versioned(version, _, _) | DataRead::Hashed(version, _) => {
+                Some(version.clone())
+            },
+            _ => None,
+        };
+        ReadVersionSummary::Data {
+            kind: self.get_kind(),
+            version,
+        }
+    }
+
+    // The approximate memory used by the read, including the read value (with its bytes),
+    // but not the layout, which may be shared by many reads (accounted for by the caller).
+    fn estimated_memory_bytes(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + match self {
+                DataRead::Versioned(_, value, _) => {
+                    std::mem::size_of::<V>() + value.bytes().map_or(0, |bytes| bytes.len())
+                },
+                DataRead::Hashed(_, _)
+                | DataRead::Metadata(_)
+                | DataRead::Size(_)
+                | DataRead::Exists(_)
+                | DataRead::Resolved(_) => 0,
+            }
+    }
+
     // A convenience method, since the same key can be read in different modes, producing
     // different DataRead / ReadKinds. Returns true if self has >= kind than other, i.e.
     // contains more or equal information, and is consistent with the information in other.
@@ -137,11 +233,33 @@ impl<V: TransactionWrite> DataRead<V> {
                 // For deletion, as_state_value_metadata returns None, also asserted by tests.
                 DataRead::Metadata(v.as_state_value_metadata())
             },
+            (DataRead::Versioned(_, v, _), ReadKind::Size) => Self::size_of(v.as_ref()),
             (DataRead::Versioned(_, v, _), ReadKind::Exists) => DataRead::Exists(!v.is_deletion()),
+            (DataRead::Hashed(_, hashed_value), ReadKind::Metadata) => {
+                DataRead::Metadata(Some(hashed_value.metadata.clone()))
+            },
+            (DataRead::Hashed(_, hashed_value), ReadKind::Size) => DataRead::Size(Some((
+                hashed_value.metadata.clone(),
+                hashed_value.len as u64,
+            ))),
+            (DataRead::Hashed(_, _), ReadKind::Exists) => DataRead::Exists(true),
             (DataRead::Resolved(_), ReadKind::Metadata) => {
                 DataRead::Metadata(Some(StateValueMetadata::none()))
             },
+            // Resolved values are served as serialized u128s.
+            (DataRead::Resolved(_), ReadKind::Size) => DataRead::Size(Some((
+                StateValueMetadata::none(),
+                std::mem::size_of::<u128>() as u64,
+            ))),
             (DataRead::Resolved(_), ReadKind::Exists) => DataRead::Exists(true),
+            (DataRead::Size(maybe_metadata_and_size), ReadKind::Metadata) => DataRead::Metadata(
+                maybe_metadata_and_size
+                    .as_ref()
+                    .map(|(metadata, _)| metadata.clone()),
+            ),
+            (DataRead::Size(maybe_metadata_and_size), ReadKind::Exists) => {
+                DataRead::Exists(maybe_metadata_and_size.is_some())
+            },
             (DataRead::Metadata(maybe_metadata), ReadKind::Exists) => {
                 DataRead::Exists(maybe_metadata.is_some())
             },
@@ -149,15 +267,139 @@ impl<V: TransactionWrite> DataRead<V> {
         })
     }
 
+    /// Returns the read to capture instead of the provided read: a value read without a
+    /// layout, of a value larger than the threshold, is captured by the hash of the value.
+    pub(crate) fn hashed_if_larger_than(self, threshold: usize) -> Self {
+        let maybe_hashed_read = match &self {
+            DataRead::Versioned(version, v, None)
+                if v.bytes().is_some_and(|bytes| bytes.len() > threshold) =>
+            {
+                HashedValue::new(v.as_ref())
+                    .map(|hashed_value| DataRead::Hashed(version.clone(), hashed_value))
+            },
+            _ => None,
+        };
+        maybe_hashed_read.unwrap_or(self)
+    }
+
+    fn size_of(value: &V) -> Self {
+        DataRead::Size(value.as_state_value_metadata().map(|metadata| {
+            (
+                metadata,
+                value.bytes().map_or(0, |bytes| bytes.len() as u64),
+            )
+        }))
+    }
+
     pub(crate) fn from_value_with_layout(version: Version, value: ValueWithLayout<V>) -> Self {
         match value {
-            // If value was never exchanged, then metadata can be the highest one without full value.
-            ValueWithLayout::RawFromStorage(v) => DataRead::Metadata(v.as_state_value_metadata()),
+            // If value was never exchanged, then size can be the highest one without full value
+            // (the exchange preserves the sizes of the values).
+            ValueWithLayout::RawFromStorage(v) => Self::size_of(v.as_ref()),
             ValueWithLayout::Exchanged(v, layout) => {
                 DataRead::Versioned(version, v.clone(), layout)
             },
         }
     }
+
+    /// Returns true if the read is consistent with the value (e.g. read again from storage),
+    /// compared by content instead of the version. A value read with a layout was exchanged,
+    /// hence it is compared only by the metadata and the size (preserved by the exchange).
+    #[cfg(any(test, feature = "serde"))]
+    pub(crate) fn matches_value(&self, value: &V) -> bool {
+        match self {
+            DataRead::Versioned(_, v, None) => {
+                v.bytes() == value.bytes()
+                    && v.as_state_value_metadata() == value.as_state_value_metadata()
+            },
+            DataRead::Versioned(_, v, Some(_)) => Self::size_of(v) == Self::size_of(value),
+            DataRead::Hashed(_, hashed_value) => {
+                HashedValue::new(value).as_ref() == Some(hashed_value)
+            },
+            DataRead::Resolved(resolved) => value
+                .as_u128()
+                .is_ok_and(|maybe_u128| maybe_u128 == Some(*resolved)),
+            DataRead::Metadata(_) | DataRead::Size(_) | DataRead::Exists(_) => {
+                Self::size_of(value).downcast(self.get_kind()).as_ref() == Some(self)
+            },
+        }
+    }
+
+    /// Returns true if the reads are the same, comparing the values of the versioned reads
+    /// (with the layouts), and the hashed values, in addition to the versions.
+    #[cfg(any(test, feature = "serde"))]
+    fn is_identical(&self, other: &Self) -> bool {
+        self == other
+            && match (self, other) {
+                (
+                    DataRead::Versioned(_, v, layout),
+                    DataRead::Versioned(_, other_v, other_layout),
+                ) => {
+                    v.bytes() == other_v.bytes()
+                        && v.as_state_value_metadata() == other_v.as_state_value_metadata()
+                        && layout == other_layout
+                },
+                (DataRead::Hashed(_, hashed_value), DataRead::Hashed(_, other_hashed_value)) => {
+                    hashed_value == other_hashed_value
+                },
+                _ => true,
+            }
+    }
+
+    /// Describes the read by its kind, version, length and the presence of the layout, but
+    /// never the raw bytes of the value.
+    fn describe(&self) -> String {
+        fn describe_version(version: &Version) -> String {
+            match version {
+                Ok((txn_idx, incarnation)) => {
+                    format!("txn {} (incarnation {})", txn_idx, incarnation)
+                },
+                Err(StorageVersion) => "storage version".to_string(),
+            }
+        }
+
+        match self {
+            DataRead::Versioned(version, v, layout) => format!(
+                "Value at {}, {}, {} layout",
+                describe_version(version),
+                v.bytes().map_or("deletion".to_string(), |bytes| format!(
+                    "{} bytes",
+                    bytes.len()
+                )),
+                if layout.is_some() { "with" } else { "without" },
+            ),
+            DataRead::Hashed(version, hashed_value) => format!(
+                "Hashed value at {}, {} bytes, without layout",
+                describe_version(version),
+                hashed_value.len,
+            ),
+            DataRead::Metadata(maybe_metadata) => {
+                format!("Metadata, exists: {}", maybe_metadata.is_some())
+            },
+            DataRead::Size(maybe_metadata_and_size) => match maybe_metadata_and_size {
+                Some((_, size)) => format!("Size, {} bytes", size),
+                None => "Size, does not exist".to_string(),
+            },
+            DataRead::Exists(exists) => format!("Exists, exists: {}", exists),
+            DataRead::Resolved(_) => "Resolved aggregator v1 value".to_string(),
+        }
+    }
+}
+
+/// Describes the read captured by a transaction and the current state of the data at the
+/// key (or the error of fetching it), for the logs of the validation failures.
+pub(crate) fn describe_mismatch<V: TransactionWrite>(
+    captured: &DataRead<V>,
+    current: Result<&DataRead<V>, &MVDataError>,
+) -> String {
+    let current = match current {
+        Ok(current) => current.describe(),
+        Err(MVDataError::Uninitialized) => "Uninitialized".to_string(),
+        Err(MVDataError::Unresolved(_)) => "Unresolved aggregator v1 delta".to_string(),
+        Err(MVDataError::Dependency(dep_idx)) => format!("Dependency on txn {}", dep_idx),
+        Err(MVDataError::DeltaApplicationFailure) => "Delta application failure".to_string(),
+    };
+    format!("captured: {}; current: {}", captured.describe(), current)
 }
 
 /// Additional state regarding groups that may be provided to the VM during transaction
@@ -168,27 +410,44 @@ impl<V: TransactionWrite> DataRead<V> {
 /// does not depend on a single "latest" entry, but collected sizes of many "latest" entries).
 #[derive(Derivative, Clone)]
 #[derivative(Default(bound = ""))]
+#[cfg_attr(
+    any(test, feature = "serde"),
+    derive(Serialize, Deserialize),
+    serde(bound(
+        serialize = "T::Value: Serialize",
+        deserialize = "T::Value: Deserialize<'de>"
+    ))
+)]
 pub(crate) struct GroupRead<T: Transaction> {
     /// The size of the resource group can be read (used for gas charging).
     pub(crate) collected_size: Option<ResourceGroupSize>,
     /// Reads to individual resources in the group, keyed by a tag.
     pub(crate) inner_reads: HashMap<T::Tag, DataRead<T::Value>>,
+    /// Set if any of the inner reads is a value read with a layout, i.e. may contain
+    /// delayed fields. Allows skipping the group when scanning for the exchange.
+    has_value_read_with_layout: bool,
 }
 
 /// Defines different ways `DelayedFieldResolver` can be used to read its values
 /// from the state.
 /// The enum variants should not be re-ordered, as it defines a relation
-/// HistoryBounded < Value
+/// Exists < HistoryBounded < Committed < Value
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum DelayedFieldReadKind {
+    /// Only the existence of the delayed field is returned.
+    Exists,
     /// The returned value is guaranteed to be correct.
     HistoryBounded,
+    /// The returned value is the latest committed value, without the deltas of the lower
+    /// transactions that are not yet committed.
+    Committed,
     /// The returned value is based on last committed value, ignoring
     /// any pending changes.
     Value,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "serde"), derive(Serialize, Deserialize))]
 pub enum DelayedFieldRead {
     // Represents a full read - that value has been returned to the caller,
     // meaning that read is valid only if value is identical.
@@ -207,6 +466,19 @@ pub enum DelayedFieldRead {
         max_value: u128,
         inner_aggregator_value: u128,
     },
+    // Represents an existence check - only whether the delayed field exists has been
+    // returned to the caller, and so the read is valid as long as the existence is the same
+    // (e.g. regardless of the deltas applied to the value by the lower transactions).
+    Exists {
+        exists: bool,
+    },
+    // Represents a read of the latest committed value (e.g. by the epilogue), ignoring the
+    // pending changes of the lower transactions. As the reads are validated against the
+    // committed values (when the transaction is committed), the read is valid only if the
+    // value is identical, as for Value.
+    Committed {
+        value: DelayedFieldValue,
+    },
 }
 
 impl DelayedFieldRead {
@@ -214,7 +486,9 @@ impl DelayedFieldRead {
         use DelayedFieldRead::*;
         match self {
             Value { .. } => DelayedFieldReadKind::Value,
+            Committed { .. } => DelayedFieldReadKind::Committed,
             HistoryBounded { .. } => DelayedFieldReadKind::HistoryBounded,
+            Exists { .. } => DelayedFieldReadKind::Exists,
         }
     }
 
@@ -224,7 +498,8 @@ impl DelayedFieldRead {
         min_kind: DelayedFieldReadKind,
     ) -> Option<DelayedFieldRead> {
         let self_kind = self.get_kind();
-        // Respecting the ordering based on information: HistoryBounded < Value
+        // Respecting the ordering based on information: Exists < HistoryBounded < Committed
+        // < Value
         if self_kind >= min_kind {
             Some(self.clone())
         } else {
@@ -238,7 +513,12 @@ impl DelayedFieldRead {
     fn contains(&self, other: &DelayedFieldRead) -> DataReadComparison {
         use DelayedFieldRead::*;
         match (&self, other) {
-            (Value { value: v1, .. }, Value { value: v2, .. }) => {
+            (
+                Value { value: v1 } | Committed { value: v1 },
+                Value { value: v2 } | Committed { value: v2 },
+            ) => {
+                // A (speculative) value read that differs from the committed value read
+                // cannot be valid, as both are validated against the same committed value.
                 if v1 == v2 {
                     DataReadComparison::Contains
                 } else {
@@ -263,9 +543,11 @@ impl DelayedFieldRead {
                     DataReadComparison::Inconsistent
                 }
             },
-            (HistoryBounded { .. }, Value { .. }) => DataReadComparison::Insufficient,
+            (HistoryBounded { .. }, Value { .. } | Committed { .. }) => {
+                DataReadComparison::Insufficient
+            },
             (
-                Value { value: v1 },
+                Value { value: v1 } | Committed { value: v1 },
                 HistoryBounded {
                     restriction: h2,
                     max_value: m2,
@@ -282,6 +564,184 @@ impl DelayedFieldRead {
                     DataReadComparison::Inconsistent
                 }
             },
+            (Exists { exists: e1 }, Exists { exists: e2 }) => {
+                if e1 == e2 {
+                    DataReadComparison::Contains
+                } else {
+                    DataReadComparison::Inconsistent
+                }
+            },
+            (Exists { .. }, Value { .. } | HistoryBounded { .. } | Committed { .. }) => {
+                DataReadComparison::Insufficient
+            },
+            // Values are only read from the delayed fields that exist.
+            (Value { .. } | HistoryBounded { .. } | Committed { .. }, Exists { exists }) => {
+                if *exists {
+                    DataReadComparison::Contains
+                } else {
+                    DataReadComparison::Inconsistent
+                }
+            },
+        }
+    }
+}
+
+/// The outcome of validating the delayed field reads of a transaction, see
+/// CapturedReads::validate_delayed_field_reads_batched.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum DelayedFieldValidation<I> {
+    Valid,
+    /// A speculative failure was observed during the execution, regardless of the reads.
+    SpeculativeFailure,
+    /// The read of the delayed field with the id is no longer valid.
+    Invalid(I),
+}
+
+// Returns whether the captured read of a delayed field is consistent with its latest committed
+// value (or with the error when reading it).
+fn is_delayed_field_read_valid(
+    read: &DelayedFieldRead,
+    current_value: Result<DelayedFieldValue, MVDelayedFieldsError>,
+) -> Result<bool, PanicError> {
+    use MVDelayedFieldsError::*;
+    Ok(match (read, current_value) {
+        (
+            DelayedFieldRead::Value { value } | DelayedFieldRead::Committed { value },
+            Ok(current_value),
+        ) => value == &current_value,
+        (
+            DelayedFieldRead::HistoryBounded {
+                restriction,
+                max_value,
+                ..
+            },
+            Ok(current_value),
+        ) => restriction
+            .validate_against_base_value(current_value.into_aggregator_value()?, *max_value)
+            .is_ok(),
+        (DelayedFieldRead::Exists { exists }, Ok(_)) => *exists,
+        (DelayedFieldRead::Exists { exists }, Err(NotFound)) => !*exists,
+        (_, Err(NotFound) | Err(Dependency(_)) | Err(DeltaApplicationFailure)) => false,
+    })
+}
+
+/// The kind and the version of a captured read, without the read value. Allows the executor
+/// to decide whether a read could be affected by a write, e.g. to skip the re-validation of
+/// the reads at a version that is not below the writer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ReadVersionSummary {
+    /// A read of a resource or a group member. Only the value reads capture a version, the
+    /// other kinds are validated by the content (e.g. the existence).
+    Data {
+        kind: ReadKind,
+        version: Option<Version>,
+    },
+    Module(ModuleReadVersion),
+}
+
+/// The version of a module observed by a read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "serde"), derive(Serialize, Deserialize))]
+pub(crate) enum ModuleReadVersion {
+    /// The module was not published in the block (read from storage).
+    Storage,
+    /// The module was published by the transaction at the index.
+    Published(TxnIndex),
+    /// The read observed an estimate of the transaction at the index, hence it never
+    /// validates (the module was not resolved).
+    Dependency(TxnIndex),
+}
+
+/// A captured module read. Published modules are identified by the index of the publishing
+/// transaction and the descriptor with the hash of the module.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "serde"), derive(Serialize, Deserialize))]
+pub(crate) struct ModuleRead<K> {
+    pub(crate) key: Arc<K>,
+    pub(crate) version: ModuleReadVersion,
+    // Some(ExecutableDescriptor::Published(hash)) iff the version is Published.
+    pub(crate) executable_descriptor: Option<ExecutableDescriptor>,
+}
+
+impl<K> ModuleRead<K> {
+    pub(crate) fn from_fetched_module<M, X>(
+        key: Arc<K>,
+        fetched_module: &anyhow::Result<(TxnIndex, MVModulesOutput<M, X>), MVModulesError>,
+    ) -> Self {
+        let (version, executable_descriptor) = match fetched_module {
+            Ok((idx, output)) => (
+                ModuleReadVersion::Published(*idx),
+                Some(Self::descriptor_of(output)),
+            ),
+            Err(MVModulesError::NotFound) => (ModuleReadVersion::Storage, None),
+            Err(MVModulesError::Dependency(idx)) => (ModuleReadVersion::Dependency(*idx), None),
+        };
+
+        Self {
+            key,
+            version,
+            executable_descriptor,
+        }
+    }
+
+    fn descriptor_of<M, X>(output: &MVModulesOutput<M, X>) -> ExecutableDescriptor {
+        match output {
+            MVModulesOutput::Executable((_, ExecutableDescriptor::Published(hash)))
+            | MVModulesOutput::Module((_, hash)) => ExecutableDescriptor::Published(*hash),
+            MVModulesOutput::Executable((_, ExecutableDescriptor::Storage)) => {
+                ExecutableDescriptor::Storage
+            },
+        }
+    }
+
+    /// Returns true if the fetched module corresponds to the same version as the read.
+    fn is_same_version<M, X>(
+        &self,
+        fetched_module: anyhow::Result<(TxnIndex, MVModulesOutput<M, X>), MVModulesError>,
+    ) -> bool {
+        match (self.version, fetched_module) {
+            (ModuleReadVersion::Published(read_idx), Ok((idx, output))) => {
+                read_idx == idx
+                    && self.executable_descriptor.as_ref() == Some(&Self::descriptor_of(&output))
+            },
+            (ModuleReadVersion::Storage, Err(MVModulesError::NotFound)) => true,
+            _ => false,
+        }
+    }
+}
+
+/// A captured read, as provided by iterating over the reads of CapturedReads. Plain resource
+/// reads, reads of resources within groups and module reads are all covered (for modules,
+/// only the key is provided).
+#[derive(Derivative)]
+#[derivative(Clone(bound = ""), Copy(bound = ""), Debug(bound = ""))]
+pub(crate) enum CapturedRead<'a, T: Transaction> {
+    Data(&'a T::Key, &'a DataRead<T::Value>),
+    GroupMember(&'a T::Key, &'a T::Tag, &'a DataRead<T::Value>),
+    Module(&'a T::Key),
+}
+
+impl<'a, T: Transaction> CapturedRead<'a, T> {
+    pub(crate) fn key(&self) -> &'a T::Key {
+        match self {
+            CapturedRead::Data(key, _)
+            | CapturedRead::GroupMember(key, _, _)
+            | CapturedRead::Module(key) => key,
+        }
+    }
+
+    pub(crate) fn tag(&self) -> Option<&'a T::Tag> {
+        match self {
+            CapturedRead::GroupMember(_, tag, _) => Some(tag),
+            CapturedRead::Data(_, _) | CapturedRead::Module(_) => None,
+        }
+    }
+
+    /// Returns None for module reads, for which no DataRead is captured.
+    pub(crate) fn data_read(&self) -> Option<&'a DataRead<T::Value>> {
+        match self {
+            CapturedRead::Data(_, read) | CapturedRead::GroupMember(_, _, read) => Some(read),
+            CapturedRead::Module(_) => None,
         }
     }
 }
@@ -295,14 +755,32 @@ impl DelayedFieldRead {
 /// read that has a kind <= already captured read (for that key / tag).
 #[derive(Derivative)]
 #[derivative(Default(bound = "", new = "true"))]
+#[cfg_attr(
+    any(test, feature = "serde"),
+    derive(Serialize, Deserialize),
+    serde(bound(
+        serialize = "T::Key: Serialize, T::Value: Serialize, T::Identifier: Serialize",
+        deserialize = "T::Key: Deserialize<'de>, T::Value: Deserialize<'de>, \
+                       T::Identifier: Deserialize<'de>"
+    ))
+)]
 pub(crate) struct CapturedReads<T: Transaction> {
-    data_reads: HashMap<T::Key, DataRead<T::Value>>,
-    group_reads: HashMap<T::Key, GroupRead<T>>,
-    // Currently, we record paths for triggering module R/W fallback.
-    // TODO: implement a general functionality once the fallback is removed.
-    pub(crate) module_reads: Vec<T::Key>,
+    // Keys are shared pointers (interned per block by the view), in order to avoid cloning
+    // the keys for every captured read.
+    data_reads: HashMap<Arc<T::Key>, DataRead<T::Value>>,
+    group_reads: HashMap<Arc<T::Key>, GroupRead<T>>,
+    // Module reads are validated by version, and their paths are also used for triggering
+    // the module R/W fallback.
+    pub(crate) module_reads: Vec<ModuleRead<T::Key>>,
 
     delayed_field_reads: HashMap<T::Identifier, DelayedFieldRead>,
+    // The number of data reads that are value reads with a layout (i.e. may contain delayed
+    // fields). When zero, the scan of data reads for the exchange is skipped.
+    num_data_reads_with_layout: usize,
+    // The number of reads (data reads and groups) inspected by the exchange scans.
+    #[cfg(test)]
+    #[cfg_attr(any(test, feature = "serde"), serde(skip))]
+    num_scanned_reads: AtomicUsize,
 
     /// If there is a speculative failure (e.g. delta application failure, or an
     /// observed inconsistency), the transaction output is irrelevant (must be
@@ -313,6 +791,17 @@ pub(crate) struct CapturedReads<T: Transaction> {
     /// Set if the invarint on CapturedReads intended use is violated. Leads to an alert
     /// and sequential execution fallback.
     incorrect_use: bool,
+    /// Set if a value read with a layout exceeded the exchange size threshold, and hence was
+    /// not scanned for the delayed fields written by the transaction. This is an expected
+    /// condition (no alert), which leads to the sequential execution fallback.
+    exchange_size_threshold_exceeded: bool,
+    /// Set when the reads are taken (for validation). Taking the reads again, or capturing
+    /// reads afterwards (which would then never be validated) is an incorrect use.
+    taken: bool,
+    /// Set when the reads are not needed (e.g. for simulation or gas estimation), in which
+    /// case the data, group size and delayed field reads are not recorded (and the taken
+    /// reads are empty).
+    capture_disabled: bool,
 }
 
 #[derive(Debug)]
@@ -324,40 +813,71 @@ enum UpdateResult {
 }
 
 impl<T: Transaction> CapturedReads<T> {
-    // Return an iterator over the captured reads.
+    // Return the captured reads needing exchange, and the keys of the values larger than the
+    // size threshold (not scanned). The ids of the delayed fields contained in the scanned
+    // values are added to scanned_ids.
     pub(crate) fn get_read_values_with_delayed_fields(
         &self,
         delayed_write_set_ids: &HashSet<T::Identifier>,
         skip: &HashSet<T::Key>,
-    ) -> Result<BTreeMap<T::Key, (StateValueMetadata, u64, Arc<MoveTypeLayout>)>, PanicError> {
-        self.data_reads
-            .iter()
-            .filter_map(|(key, data_read)| {
-                if skip.contains(key) {
-                    return None;
-                }
+        scanned_ids: &mut BTreeSet<T::Identifier>,
+        size_threshold: Option<usize>,
+        layout_cache: &DelayedFieldLayoutCache,
+        counter_labels: &ViewCounterLabels,
+    ) -> Result<ReadsNeedingExchange<T::Key>, PanicError> {
+        let mut reads_needing_exchange = ReadsNeedingExchange::default();
+        if self.num_data_reads_with_layout == 0 {
+            return Ok(reads_needing_exchange);
+        }
 
-                if let DataRead::Versioned(_version, value, Some(layout)) = data_read {
-                    filter_value_for_exchange::<T>(value, layout, delayed_write_set_ids, key)
-                } else {
-                    None
-                }
-            })
-            .collect()
+        for (key, data_read) in &self.data_reads {
+            let key: &T::Key = key;
+            if skip.contains(key) {
+                continue;
+            }
+            self.record_scanned_read();
+
+            if let DataRead::Versioned(_version, value, Some(layout)) = data_read {
+                reads_needing_exchange.add::<T>(
+                    value,
+                    layout,
+                    delayed_write_set_ids,
+                    key,
+                    scanned_ids,
+                    size_threshold,
+                    layout_cache,
+                    counter_labels,
+                )?;
+            }
+        }
+        Ok(reads_needing_exchange)
     }
 
-    // Return an iterator over the captured group reads that contain a delayed field
+    // Return an iterator over the captured group reads that may contain a delayed field,
+    // i.e. have a value read with a layout. Other groups are skipped without inspecting
+    // their inner reads.
     pub(crate) fn get_group_read_values_with_delayed_fields<'a>(
         &'a self,
         skip: &'a HashSet<T::Key>,
     ) -> impl Iterator<Item = (&T::Key, &GroupRead<T>)> {
-        self.group_reads.iter().filter(|(key, group_read)| {
-            !skip.contains(key)
-                && group_read
-                    .inner_reads
-                    .iter()
-                    .any(|(_, data_read)| matches!(data_read, DataRead::Versioned(_, _, Some(_))))
-        })
+        self.group_reads
+            .iter()
+            .map(|(key, group_read)| (key.as_ref(), group_read))
+            .filter(|(key, group_read)| {
+                group_read.has_value_read_with_layout && !skip.contains(*key)
+            })
+            .inspect(|_| self.record_scanned_read())
+    }
+
+    #[inline]
+    fn record_scanned_read(&self) {
+        #[cfg(test)]
+        self.num_scanned_reads.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[cfg(test)]
+    pub(crate) fn num_scanned_reads(&self) -> usize {
+        self.num_scanned_reads.load(Ordering::Relaxed)
     }
 
     // Given a hashmap entry for a key, incorporate a new DataRead. This checks
@@ -398,15 +918,75 @@ impl<T: Transaction> CapturedReads<T> {
         }
     }
 
+    /// Takes the captured reads, leaving the empty reads (marked as taken) in place.
+    pub(crate) fn take(&mut self) -> Result<Self, PanicError> {
+        if self.taken {
+            self.incorrect_use = true;
+            return Err(code_invariant_error("Captured reads were already taken"));
+        }
+
+        let reads = std::mem::take(self);
+        self.taken = true;
+        Ok(reads)
+    }
+
+    /// Clears the captured reads and all the flags (as if newly created), keeping the
+    /// allocated capacity of the maps, so that the reads can be reused by an execution.
+    pub(crate) fn clear(&mut self) {
+        self.data_reads.clear();
+        self.group_reads.clear();
+        self.module_reads.clear();
+        self.delayed_field_reads.clear();
+        self.num_data_reads_with_layout = 0;
+        #[cfg(test)]
+        {
+            *self.num_scanned_reads.get_mut() = 0;
+        }
+        self.speculative_failure = false;
+        self.incorrect_use = false;
+        self.exchange_size_threshold_exceeded = false;
+        self.taken = false;
+        self.capture_disabled = false;
+    }
+
+    fn check_not_taken(&mut self) -> Result<(), PanicError> {
+        if self.taken {
+            self.incorrect_use = true;
+            return Err(code_invariant_error(
+                "Read captured after the captured reads were taken",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Disables the recording of the subsequently captured data, group size and delayed
+    /// field reads.
+    pub(crate) fn disable_capture(&mut self) {
+        self.capture_disabled = true;
+    }
+
+    pub(crate) fn is_capture_disabled(&self) -> bool {
+        self.capture_disabled
+    }
+
     pub(crate) fn capture_group_size(
         &mut self,
-        group_key: T::Key,
+        group_key: impl Into<Arc<T::Key>>,
         group_size: ResourceGroupSize,
     ) -> anyhow::Result<()> {
-        let group = self.group_reads.entry(group_key).or_default();
+        if self.capture_disabled {
+            return Ok(());
+        }
+        if let Err(err) = self.check_not_taken() {
+            bail!(err.to_string());
+        }
+
+        let group = self.group_reads.entry(group_key.into()).or_default();
 
         if let Some(recorded_size) = group.collected_size {
             if recorded_size != group_size {
+                // Record speculative failure.
+                self.speculative_failure = true;
                 bail!("Inconsistent recorded group size");
             }
         }
@@ -415,6 +995,235 @@ impl<T: Transaction> CapturedReads<T> {
         Ok(())
     }
 
+    /// Returns true if a read of a plain resource (i.e. not of a group member or size) was
+    /// captured at the given key.
+    pub(crate) fn is_resource_read(&self, state_key: &T::Key) -> bool {
+        self.data_reads.contains_key(state_key)
+    }
+
+    /// Returns the keys of the groups with captured reads (of the members or the size).
+    pub(crate) fn group_keys(&self) -> impl Iterator<Item = &T::Key> {
+        self.group_reads.keys().map(|key| key.as_ref())
+    }
+
+    pub(crate) fn module_keys(&self) -> impl Iterator<Item = &T::Key> {
+        self.module_reads.iter().map(|read| read.key.as_ref())
+    }
+
+    /// Returns the captured data reads (of plain resources), sorted by the keys.
+    #[cfg(any(test, feature = "serde"))]
+    pub(crate) fn sorted_data_reads(&self) -> Vec<(&T::Key, &DataRead<T::Value>)> {
+        let mut reads: Vec<_> = self
+            .data_reads
+            .iter()
+            .map(|(key, read)| (key.as_ref(), read))
+            .collect();
+        reads.sort_by(|(a, _), (b, _)| a.cmp(b));
+        reads
+    }
+
+    /// Returns the captured group reads, sorted by the keys of the groups.
+    #[cfg(any(test, feature = "serde"))]
+    pub(crate) fn sorted_group_reads(&self) -> Vec<(&T::Key, &GroupRead<T>)> {
+        let mut reads: Vec<_> = self
+            .group_reads
+            .iter()
+            .map(|(key, group)| (key.as_ref(), group))
+            .collect();
+        reads.sort_by(|(a, _), (b, _)| a.cmp(b));
+        reads
+    }
+
+    /// Returns the reads that differ from the reads captured in other (e.g. by the same
+    /// transaction, in a replay of the block), including the reads captured by only one of
+    /// them. The divergences are ordered by the kind (resources, groups, modules and delayed
+    /// fields), then by the key (and the tag). Values are compared by content.
+    #[cfg(any(test, feature = "serde"))]
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub(crate) fn diff<'a>(&'a self, other: &'a Self) -> Vec<ReadDivergence<'a, T>> {
+        let data_reads = |reads: &'a Self| {
+            reads
+                .data_reads
+                .iter()
+                .map(|(key, read)| (key.as_ref(), read))
+        };
+        let group_reads = |reads: &'a Self| {
+            reads
+                .group_reads
+                .iter()
+                .map(|(key, group)| (key.as_ref(), group))
+        };
+        let module_reads = |reads: &'a Self| {
+            reads
+                .module_reads
+                .iter()
+                .map(|read| (read.key.as_ref(), read))
+        };
+        let mut divergences = Vec::new();
+
+        for (key, left, right) in paired_reads(data_reads(self), data_reads(other)) {
+            if !left.zip(right).is_some_and(|(l, r)| l.is_identical(r)) {
+                divergences.push(ReadDivergence::Resource { key, left, right });
+            }
+        }
+
+        for (key, left, right) in paired_reads(group_reads(self), group_reads(other)) {
+            let (left_size, right_size) = (
+                left.and_then(|group| group.collected_size),
+                right.and_then(|group| group.collected_size),
+            );
+            if left_size != right_size {
+                divergences.push(ReadDivergence::GroupSize {
+                    key,
+                    left: left_size,
+                    right: right_size,
+                });
+            }
+
+            for (tag, left, right) in paired_reads(
+                left.into_iter().flat_map(|group| &group.inner_reads),
+                right.into_iter().flat_map(|group| &group.inner_reads),
+            ) {
+                if !left.zip(right).is_some_and(|(l, r)| l.is_identical(r)) {
+                    divergences.push(ReadDivergence::GroupMember {
+                        key,
+                        tag,
+                        left,
+                        right,
+                    });
+                }
+            }
+        }
+
+        // A module may be read multiple times, the reads of a key are compared in order.
+        for (key, left, right) in paired_reads(module_reads(self), module_reads(other)) {
+            if left != right {
+                divergences.push(ReadDivergence::Module { key, left, right });
+            }
+        }
+
+        for (id, left, right) in paired_reads(
+            self.delayed_field_reads.iter(),
+            other.delayed_field_reads.iter(),
+        ) {
+            if left != right {
+                divergences.push(ReadDivergence::DelayedField { id, left, right });
+            }
+        }
+
+        divergences
+    }
+
+    /// Returns the hash of all captured reads (and of the failure flags, which determine the
+    /// validation outcome) serialized in a canonical order, i.e. the same fingerprint for the
+    /// read-sets that are equal by content. Module reads are ordered by the key, and multiple
+    /// reads of the same module are kept in the order of capture.
+    #[cfg(any(test, feature = "serde"))]
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub(crate) fn fingerprint(&self) -> Result<[u8; HashValue::LENGTH], PanicError>
+    where
+        T::Key: Serialize,
+        T::Value: Serialize,
+        T::Identifier: Serialize,
+    {
+        let mut module_reads: Vec<_> = self.module_reads.iter().collect();
+        module_reads.sort_by(|a, b| a.key.cmp(&b.key));
+        let delayed_field_reads: BTreeMap<_, _> = self.delayed_field_reads.iter().collect();
+
+        let bytes = bcs::to_bytes(&(
+            self.sorted_data_reads(),
+            self.sorted_group_reads(),
+            module_reads,
+            delayed_field_reads,
+            self.speculative_failure,
+            self.incorrect_use,
+        ))
+        .map_err(|e| {
+            code_invariant_error(format!("Failed to serialize captured reads: {:?}", e))
+        })?;
+        Ok(*HashValue::sha3_256_of(&bytes).as_ref())
+    }
+
+    /// Returns the summary of the read at the key (and the tag, for a group member), if any.
+    /// For a module, the summary of the first read of the module is returned (all reads of
+    /// a module must observe the same version to be valid). Intended for the taken reads of
+    /// an execution (e.g. when the writes of a transaction are applied), not the live view.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub(crate) fn read_version_for(
+        &self,
+        state_key: &T::Key,
+        maybe_tag: Option<&T::Tag>,
+    ) -> Option<ReadVersionSummary> {
+        match maybe_tag {
+            Some(tag) => self
+                .group_reads
+                .get(state_key)
+                .and_then(|group| group.inner_reads.get(tag))
+                .map(DataRead::version_summary),
+            None => self
+                .data_reads
+                .get(state_key)
+                .map(DataRead::version_summary)
+                .or_else(|| {
+                    self.module_reads
+                        .iter()
+                        .find(|read| read.key.as_ref() == state_key)
+                        .map(|read| ReadVersionSummary::Module(read.version))
+                }),
+        }
+    }
+
+    /// Returns the summaries of the reads at the keys (and the tags), in the same order, as
+    /// returned by read_version_for.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub(crate) fn read_versions_for(
+        &self,
+        keys: &[(T::Key, Option<T::Tag>)],
+    ) -> Vec<Option<ReadVersionSummary>> {
+        keys.iter()
+            .map(|(state_key, maybe_tag)| self.read_version_for(state_key, maybe_tag.as_ref()))
+            .collect()
+    }
+
+    /// Returns the approximate memory used by the captured reads, i.e. the entries of the reads
+    /// (with the read values and their bytes), each distinct layout once, and the delayed field
+    /// reads. The keys are interned (shared by the reads of the block), hence only the pointers
+    /// to the keys are accounted for.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub(crate) fn estimated_memory_bytes(&self) -> usize {
+        let key_bytes = std::mem::size_of::<Arc<T::Key>>();
+        let mut layouts = HashSet::new();
+        let mut data_read_bytes = |read: &DataRead<T::Value>| {
+            if let DataRead::Versioned(_, _, Some(layout)) = read {
+                layouts.insert(Arc::as_ptr(layout));
+            }
+            read.estimated_memory_bytes()
+        };
+
+        let mut total = 0;
+        for read in self.data_reads.values() {
+            total += key_bytes + data_read_bytes(read);
+        }
+        for group in self.group_reads.values() {
+            total += key_bytes + std::mem::size_of::<GroupRead<T>>();
+            for read in group.inner_reads.values() {
+                total += std::mem::size_of::<T::Tag>() + data_read_bytes(read);
+            }
+        }
+        total += layouts.len() * std::mem::size_of::<MoveTypeLayout>();
+        total += self.module_reads.len() * std::mem::size_of::<ModuleRead<T::Key>>();
+        for read in self.delayed_field_reads.values() {
+            total += std::mem::size_of::<T::Identifier>() + std::mem::size_of::<DelayedFieldRead>();
+            if let DelayedFieldRead::Value { value } | DelayedFieldRead::Committed { value } = read
+            {
+                // The memory allocated for the value, in addition to its inline size.
+                total +=
+                    value.get_approximate_memory_size() - std::mem::size_of::<DelayedFieldValue>();
+            }
+        }
+        total
+    }
+
     pub(crate) fn group_size(&self, group_key: &T::Key) -> Option<ResourceGroupSize> {
         self.group_reads
             .get(group_key)
@@ -425,16 +1234,37 @@ impl<T: Transaction> CapturedReads<T> {
     // speculative nature of reads).
     pub(crate) fn capture_read(
         &mut self,
-        state_key: T::Key,
+        state_key: impl Into<Arc<T::Key>>,
         maybe_tag: Option<T::Tag>,
         read: DataRead<T::Value>,
     ) -> anyhow::Result<()> {
+        if self.capture_disabled {
+            return Ok(());
+        }
+        if let Err(err) = self.check_not_taken() {
+            bail!(err.to_string());
+        }
+
+        // A value read replaces an existing read only if it has a lower kind, hence the
+        // replaced read may never have a layout (and be accounted for twice below).
+        let with_layout = matches!(read, DataRead::Versioned(_, _, Some(_)));
+
         let ret = match maybe_tag {
             Some(tag) => {
-                let group = self.group_reads.entry(state_key).or_default();
-                Self::update_entry(group.inner_reads.entry(tag), read)
+                let group = self.group_reads.entry(state_key.into()).or_default();
+                let ret = Self::update_entry(group.inner_reads.entry(tag), read);
+                if with_layout && matches!(ret, UpdateResult::Inserted | UpdateResult::Updated) {
+                    group.has_value_read_with_layout = true;
+                }
+                ret
+            },
+            None => {
+                let ret = Self::update_entry(self.data_reads.entry(state_key.into()), read);
+                if with_layout && matches!(ret, UpdateResult::Inserted | UpdateResult::Updated) {
+                    self.num_data_reads_with_layout += 1;
+                }
+                ret
             },
-            None => Self::update_entry(self.data_reads.entry(state_key), read),
         };
 
         match ret {
@@ -459,8 +1289,8 @@ impl<T: Transaction> CapturedReads<T> {
         kind: ReadKind,
     ) -> Option<DataRead<T::Value>> {
         assert!(
-            kind != ReadKind::Metadata || maybe_tag.is_none(),
-            "May not request metadata of a group member"
+            !matches!(kind, ReadKind::Metadata | ReadKind::Size) || maybe_tag.is_none(),
+            "May not request metadata or size of a group member"
         );
 
         match maybe_tag {
@@ -481,6 +1311,11 @@ impl<T: Transaction> CapturedReads<T> {
         update: bool,
         read: DelayedFieldRead,
     ) -> Result<(), PanicOr<DelayedFieldsSpeculativeError>> {
+        if self.capture_disabled {
+            return Ok(());
+        }
+        self.check_not_taken()?;
+
         let result = match self.delayed_field_reads.entry(id) {
             Vacant(e) => {
                 e.insert(read);
@@ -545,11 +1380,126 @@ impl<T: Transaction> CapturedReads<T> {
             .and_then(|r| r.filter_by_kind(min_kind))
     }
 
+    /// Returns the captured read of the delayed field (of any kind).
+    pub(crate) fn get_delayed_field_read(&self, id: &T::Identifier) -> Option<&DelayedFieldRead> {
+        self.delayed_field_reads.get(id)
+    }
+
+    /// Restores the captured read of the delayed field to a read previously returned by
+    /// get_delayed_field_read (removing it if there was none), e.g. when the staged history
+    /// updates of a batch of try_add calls are rolled back.
+    pub(crate) fn restore_delayed_field_read(
+        &mut self,
+        id: T::Identifier,
+        read: Option<DelayedFieldRead>,
+    ) {
+        match read {
+            Some(read) => {
+                self.delayed_field_reads.insert(id, read);
+            },
+            None => {
+                self.delayed_field_reads.remove(&id);
+            },
+        }
+    }
+
+    /// Returns the ids of the captured delayed field reads (by value or bounded by history).
+    pub(crate) fn delayed_field_read_ids(&self) -> impl Iterator<Item = &T::Identifier> {
+        self.delayed_field_reads.keys()
+    }
+
     pub(crate) fn is_incorrect_use(&self) -> bool {
         self.incorrect_use
     }
 
-    pub(crate) fn validate_data_reads(
+    pub(crate) fn is_exchange_size_threshold_exceeded(&self) -> bool {
+        self.exchange_size_threshold_exceeded
+    }
+
+    pub(crate) fn is_speculative_failure(&self) -> bool {
+        self.speculative_failure
+    }
+
+    /// Returns the pairs of data reads observed across a writer boundary: a key read at the
+    /// version written by a transaction, and another key written by the same transaction, but
+    /// read at an earlier version (or from storage). No consistent snapshot contains both
+    /// reads, hence one of them eventually fails the validation. Analysis-only (the reads are
+    /// not affected), the written keys of a transaction are provided by writes_by_txn.
+    /// Group member reads are not considered, as a write to a group need not write the member.
+    pub(crate) fn check_snapshot_consistency(
+        &self,
+        writes_by_txn: &dyn Fn(TxnIndex) -> HashSet<T::Key>,
+    ) -> Vec<InconsistencyReport<T::Key>> {
+        let versioned_reads: Vec<(&T::Key, &Version)> = self
+            .data_reads
+            .iter()
+            .filter_map(|(key, read)| match read {
+                DataRead::Versioned(version, _, _) | DataRead::Hashed(version, _) => {
+                    Some((key.as_ref(), version))
+                },
+                _ => None,
+            })
+            .collect();
+
+        let mut writes: HashMap<TxnIndex, HashSet<T::Key>> = HashMap::new();
+        let mut reports = Vec::new();
+        for (key, version) in &versioned_reads {
+            let Ok((writer, incarnation)) = version else {
+                continue;
+            };
+            let written = writes
+                .entry(*writer)
+                .or_insert_with(|| writes_by_txn(*writer));
+
+            for (stale_key, stale_version) in &versioned_reads {
+                let read_before_writer = match stale_version {
+                    Ok((txn_idx, _)) => txn_idx < writer,
+                    Err(StorageVersion) => true,
+                };
+                if read_before_writer && written.contains(*stale_key) {
+                    reports.push(InconsistencyReport {
+                        key: (*key).clone(),
+                        version: (*writer, *incarnation),
+                        stale_key: (*stale_key).clone(),
+                        stale_version: (*stale_version).clone(),
+                    });
+                }
+            }
+        }
+
+        reports.sort_by(|a, b| (&a.key, &a.stale_key).cmp(&(&b.key, &b.stale_key)));
+        reports
+    }
+
+    /// Returns the captured group size reads that do not match the size of the group recomputed
+    /// by recompute_size, together with the tags of the members contributing to the recomputed
+    /// size (None if the size could not be recomputed, in which case the read is not reported).
+    /// Meant to re-validate the reads of committed transactions (e.g. after the reads are taken),
+    /// as the recomputation may be expensive (e.g. serializing the whole group).
+    pub(crate) fn check_group_size_reads(
+        &self,
+        recompute_size: &dyn Fn(&T::Key) -> Option<(u64, Vec<T::Tag>)>,
+    ) -> Vec<GroupSizeDiscrepancy<T::Key, T::Tag>> {
+        let mut discrepancies: Vec<_> = self
+            .group_reads
+            .iter()
+            .filter_map(|(group_key, group)| {
+                let captured_size = group.collected_size?;
+                let (recomputed_size, tags) = recompute_size(group_key)?;
+                (captured_size.get() != recomputed_size).then(|| GroupSizeDiscrepancy {
+                    group_key: group_key.as_ref().clone(),
+                    captured_size,
+                    recomputed_size,
+                    tags,
+                })
+            })
+            .collect();
+
+        discrepancies.sort_by(|a, b| a.group_key.cmp(&b.group_key));
+        discrepancies
+    }
+
+    pub(crate) fn validate_data_reads(
         &self,
         data_map: &VersionedData<T::Key, T::Value>,
         idx_to_validate: TxnIndex,
@@ -558,29 +1508,90 @@ impl<T: Transaction> CapturedReads<T> {
             return false;
         }
 
+        self.data_reads
+            .iter()
+            .all(|(k, r)| Self::validate_data_read(data_map, k, r, idx_to_validate))
+    }
+
+    /// Validates only the reads captured at the keys of the given groups, which suffices if
+    /// lower transactions changed only the metadata of these groups. A group is read as a
+    /// resource only for its metadata (or existence), while the reads of the members and of
+    /// the size are captured (and validated) separately, and are not affected.
+    // Not used by the executor, which validates all the reads of a transaction.
+    #[allow(unused)]
+    pub(crate) fn validate_group_metadata_reads<'a>(
+        &self,
+        data_map: &VersionedData<T::Key, T::Value>,
+        group_keys: impl IntoIterator<Item = &'a T::Key>,
+        idx_to_validate: TxnIndex,
+    ) -> bool
+    where
+        T::Key: 'a,
+    {
+        if self.speculative_failure {
+            return false;
+        }
+
+        group_keys.into_iter().all(|key| {
+            self.data_reads.get(key).map_or(true, |r| {
+                Self::validate_data_read(data_map, key, r, idx_to_validate)
+            })
+        })
+    }
+
+    /// Returns the description of the first captured data read that fails the validation (see
+    /// describe_mismatch), or None if all the data reads are valid.
+    pub(crate) fn describe_invalid_data_read(
+        &self,
+        data_map: &VersionedData<T::Key, T::Value>,
+        idx_to_validate: TxnIndex,
+    ) -> Option<String> {
+        use MVDataOutput::*;
+
+        self.data_reads
+            .iter()
+            .find(|(k, r)| !Self::validate_data_read(data_map, k, r, idx_to_validate))
+            .map(|(k, r)| {
+                let current = data_map
+                    .fetch_data(k, idx_to_validate)
+                    .map(|output| match output {
+                        Versioned(version, v) => DataRead::from_value_with_layout(version, v),
+                        Resolved(value) => DataRead::Resolved(value),
+                    });
+                format!("{:?}: {}", k, describe_mismatch(r, current.as_ref()))
+            })
+    }
+
+    fn validate_data_read(
+        data_map: &VersionedData<T::Key, T::Value>,
+        key: &T::Key,
+        read: &DataRead<T::Value>,
+        idx_to_validate: TxnIndex,
+    ) -> bool {
         use MVDataError::*;
         use MVDataOutput::*;
-        self.data_reads.iter().all(|(k, r)| {
-            match data_map.fetch_data(k, idx_to_validate) {
-                Ok(Versioned(version, v)) => {
-                    matches!(
-                        DataRead::from_value_with_layout(version, v).contains(r),
-                        DataReadComparison::Contains
-                    )
-                },
-                Ok(Resolved(value)) => matches!(
-                    DataRead::Resolved(value).contains(r),
+        match data_map.fetch_data(key, idx_to_validate) {
+            Ok(Versioned(version, v)) => match read {
+                // The content is compared even at the same version, as the base value may be
+                // exchanged in place (at the storage version).
+                DataRead::Hashed(_, hashed_value) => hashed_value.matches(&v),
+                _ => matches!(
+                    DataRead::from_value_with_layout(version, v).contains(read),
                     DataReadComparison::Contains
                 ),
-                // Dependency implies a validation failure, and if the original read were to
-                // observe an unresolved delta, it would set the aggregator base value in the
-                // multi-versioned data-structure, resolve, and record the resolved value.
-                Err(Dependency(_))
-                | Err(Unresolved(_))
-                | Err(DeltaApplicationFailure)
-                | Err(Uninitialized) => false,
-            }
-        })
+            },
+            Ok(Resolved(value)) => matches!(
+                DataRead::Resolved(value).contains(read),
+                DataReadComparison::Contains
+            ),
+            // Dependency implies a validation failure, and if the original read were to
+            // observe an unresolved delta, it would set the aggregator base value in the
+            // multi-versioned data-structure, resolve, and record the resolved value.
+            Err(Dependency(_))
+            | Err(Unresolved(_))
+            | Err(DeltaApplicationFailure)
+            | Err(Uninitialized) => false,
+        }
     }
 
     pub(crate) fn validate_group_reads(
@@ -588,92 +1599,215 @@ impl<T: Transaction> CapturedReads<T> {
         group_map: &VersionedGroupData<T::Key, T::Tag, T::Value>,
         idx_to_validate: TxnIndex,
     ) -> bool {
-        use MVGroupError::*;
-
         if self.speculative_failure {
             return false;
         }
 
-        self.group_reads.iter().all(|(key, group)| {
-            let mut ret = true;
-            if let Some(size) = group.collected_size {
-                ret &= Ok(size) == group_map.get_group_size(key, idx_to_validate);
+        self.group_reads
+            .iter()
+            .all(|(key, group)| Self::validate_group_read(group_map, key, group, idx_to_validate))
+    }
+
+    fn validate_group_read(
+        group_map: &VersionedGroupData<T::Key, T::Tag, T::Value>,
+        key: &T::Key,
+        group: &GroupRead<T>,
+        idx_to_validate: TxnIndex,
+    ) -> bool {
+        use MVGroupError::*;
+
+        let mut ret = true;
+        if let Some(size) = group.collected_size {
+            ret &= Ok(size) == group_map.get_group_size(key, idx_to_validate);
+        }
+
+        ret && group.inner_reads.iter().all(|(tag, r)| {
+            match group_map.fetch_tagged_data(key, tag, idx_to_validate) {
+                Ok((version, v)) => {
+                    matches!(
+                        DataRead::from_value_with_layout(version, v).contains(r),
+                        DataReadComparison::Contains
+                    )
+                },
+                Err(TagNotFound) => {
+                    let sentinel_deletion =
+                        Arc::<T::Value>::new(TransactionWrite::from_state_value(None));
+                    assert!(sentinel_deletion.is_deletion());
+                    matches!(
+                        DataRead::Versioned(Err(StorageVersion), sentinel_deletion, None)
+                            .contains(r),
+                        DataReadComparison::Contains
+                    )
+                },
+                Err(Dependency(_)) => false,
+                Err(Uninitialized) => {
+                    unreachable!("May not be uninitialized if captured for validation");
+                },
+                Err(TagSerializationError(_)) => {
+                    unreachable!("Should not require tag serialization");
+                },
             }
+        })
+    }
+
+    /// Returns the reads that are still valid for a re-execution of the transaction at
+    /// idx_to_validate, to pre-populate the captured reads of the new incarnation (so that
+    /// they are served without accessing the multi-versioned data-structure again). A data
+    /// read or a group (with all its inner reads and size) is retained if it would pass the
+    /// validation. Module reads are never served from the captured reads, and delayed field
+    /// reads are validated only at commit time, so neither are retained (nor the flags).
+    pub(crate) fn into_validated_subset<X: Executable>(
+        &self,
+        versioned_map: &MVHashMap<T::Key, T::Tag, T::Value, X, T::Identifier>,
+        idx_to_validate: TxnIndex,
+    ) -> CapturedReads<T> {
+        let mut ret = CapturedReads::new();
+        if self.speculative_failure {
+            return ret;
+        }
 
-            ret && group.inner_reads.iter().all(|(tag, r)| {
-                match group_map.fetch_tagged_data(key, tag, idx_to_validate) {
-                    Ok((version, v)) => {
-                        matches!(
-                            DataRead::from_value_with_layout(version, v).contains(r),
-                            DataReadComparison::Contains
-                        )
-                    },
-                    Err(TagNotFound) => {
-                        let sentinel_deletion =
-                            Arc::<T::Value>::new(TransactionWrite::from_state_value(None));
-                        assert!(sentinel_deletion.is_deletion());
-                        matches!(
-                            DataRead::Versioned(Err(StorageVersion), sentinel_deletion, None)
-                                .contains(r),
-                            DataReadComparison::Contains
-                        )
-                    },
-                    Err(Dependency(_)) => false,
-                    Err(Uninitialized) => {
-                        unreachable!("May not be uninitialized if captured for validation");
-                    },
-                    Err(TagSerializationError(_)) => {
-                        unreachable!("Should not require tag serialization");
-                    },
+        for (key, read) in &self.data_reads {
+            if Self::validate_data_read(versioned_map.data(), key, read, idx_to_validate) {
+                if matches!(read, DataRead::Versioned(_, _, Some(_))) {
+                    ret.num_data_reads_with_layout += 1;
                 }
+                ret.data_reads.insert(key.clone(), read.clone());
+            }
+        }
+        ret.group_reads = self
+            .group_reads
+            .iter()
+            .filter(|(key, group)| {
+                Self::validate_group_read(versioned_map.group_data(), key, group, idx_to_validate)
             })
+            .map(|(key, group)| (key.clone(), group.clone()))
+            .collect();
+        ret
+    }
+
+    pub(crate) fn validate_module_reads(
+        &self,
+        module_map: &VersionedModules<T::Key, T::Value, impl Executable>,
+        idx_to_validate: TxnIndex,
+    ) -> bool {
+        if self.speculative_failure {
+            return false;
+        }
+
+        self.module_reads.iter().all(|read| {
+            read.is_same_version(module_map.fetch_module_with_version(&read.key, idx_to_validate))
         })
     }
 
     // This validation needs to be called at commit time
     // (as it internally uses read_latest_committed_value to get the current value).
+    #[cfg_attr(not(test), allow(dead_code))]
     pub(crate) fn validate_delayed_field_reads(
         &self,
         delayed_fields: &dyn TVersionedDelayedFieldView<T::Identifier>,
         idx_to_validate: TxnIndex,
     ) -> Result<bool, PanicError> {
+        let validation =
+            self.validate_delayed_field_reads_batched(delayed_fields, idx_to_validate)?;
+        Ok(validation == DelayedFieldValidation::Valid)
+    }
+
+    /// Validates the delayed field reads (at commit time, as validate_delayed_field_reads),
+    /// reading the committed values for all Value and HistoryBounded reads in a single batch
+    /// (i.e. as of the same commit). The Value reads are validated first, then HistoryBounded
+    /// and then Exists reads (which are read individually, as a delayed field that does not
+    /// exist may be valid for them), each in the order of the ids. Stops at the first read
+    /// that is no longer valid, and returns its id.
+    pub(crate) fn validate_delayed_field_reads_batched(
+        &self,
+        delayed_fields: &dyn TVersionedDelayedFieldView<T::Identifier>,
+        idx_to_validate: TxnIndex,
+    ) -> Result<DelayedFieldValidation<T::Identifier>, PanicError> {
         if self.speculative_failure {
-            return Ok(false);
+            return Ok(DelayedFieldValidation::SpeculativeFailure);
         }
 
-        use MVDelayedFieldsError::*;
-        for (id, read_value) in &self.delayed_field_reads {
-            match delayed_fields.read_latest_committed_value(
-                id,
+        let mut reads: Vec<_> = self.delayed_field_reads.iter().collect();
+        reads.sort_by_key(|(id, read)| (Reverse(read.get_kind()), **id));
+        let batched_ids: Vec<_> = reads
+            .iter()
+            .take_while(|(_, read)| read.get_kind() != DelayedFieldReadKind::Exists)
+            .map(|(id, _)| **id)
+            .collect();
+
+        // If the batch fails, it does not tell which id, hence the reads are then validated
+        // individually.
+        let mut batched_values = delayed_fields
+            .read_latest_committed_values(
+                &batched_ids,
                 idx_to_validate,
                 ReadPosition::BeforeCurrentTxn,
-            ) {
-                Ok(current_value) => match read_value {
-                    DelayedFieldRead::Value { value, .. } => {
-                        if value != &current_value {
-                            return Ok(false);
-                        }
-                    },
-                    DelayedFieldRead::HistoryBounded {
-                        restriction,
-                        max_value,
-                        ..
-                    } => match restriction.validate_against_base_value(
-                        current_value.into_aggregator_value()?,
-                        *max_value,
-                    ) {
-                        Ok(_) => {},
-                        Err(_) => {
-                            return Ok(false);
-                        },
-                    },
-                },
-                Err(NotFound) | Err(Dependency(_)) | Err(DeltaApplicationFailure) => {
-                    return Ok(false);
-                },
+            )
+            .ok()
+            .map(Vec::into_iter);
+        for (id, read) in reads {
+            let current_value = match batched_values.as_mut().and_then(Iterator::next) {
+                Some(value) => Ok(value),
+                None => delayed_fields.read_latest_committed_value(
+                    id,
+                    idx_to_validate,
+                    ReadPosition::BeforeCurrentTxn,
+                ),
+            };
+            if !is_delayed_field_read_valid(read, current_value)? {
+                return Ok(DelayedFieldValidation::Invalid(*id));
             }
         }
-        Ok(true)
+        Ok(DelayedFieldValidation::Valid)
+    }
+
+    /// Iterates (without cloning) over all captured resource, group member and module reads
+    /// at the keys satisfying the provided predicate. Allows checks that concern only a part
+    /// of the read-set (e.g. reads of a particular address space).
+    pub(crate) fn iter_reads_matching<'a>(
+        &'a self,
+        pred: impl Fn(&T::Key) -> bool + 'a,
+    ) -> impl Iterator<Item = CapturedRead<'a, T>> + 'a {
+        self.data_reads
+            .iter()
+            .map(|(key, read)| CapturedRead::Data(key.as_ref(), read))
+            .chain(self.group_reads.iter().flat_map(|(key, group)| {
+                group
+                    .inner_reads
+                    .iter()
+                    .map(move |(tag, read)| CapturedRead::GroupMember(key.as_ref(), tag, read))
+            }))
+            .chain(
+                self.module_reads
+                    .iter()
+                    .map(|read| CapturedRead::Module(read.key.as_ref())),
+            )
+            .filter(move |read| pred(read.key()))
+    }
+
+    /// Returns the (distinct) keys of the captured reads satisfying the provided predicate.
+    pub(crate) fn read_keys_matching<'a>(
+        &'a self,
+        pred: impl Fn(&T::Key) -> bool + 'a,
+    ) -> HashSet<&'a T::Key> {
+        self.iter_reads_matching(pred)
+            .map(|read| read.key())
+            .collect()
+    }
+
+    /// Returns the summary of the captured reads, without the counts of the fetches.
+    pub(crate) fn read_summary(&self) -> ReadSummary {
+        ReadSummary {
+            num_resource_reads: self.data_reads.len(),
+            num_group_reads: self
+                .group_reads
+                .values()
+                .map(|group| group.inner_reads.len())
+                .sum(),
+            num_module_reads: self.module_reads.len(),
+            num_delayed_field_reads: self.delayed_field_reads.len(),
+            ..ReadSummary::default()
+        }
     }
 
     pub(crate) fn get_read_summary(
@@ -681,25 +1815,25 @@ impl<T: Transaction> CapturedReads<T> {
     ) -> HashSet<InputOutputKey<T::Key, T::Tag, T::Identifier>> {
         let mut ret = HashSet::new();
         for (key, read) in &self.data_reads {
-            if let DataRead::Versioned(_, _, _) = read {
-                ret.insert(InputOutputKey::Resource(key.clone()));
+            if let DataRead::Versioned(_, _, _) | DataRead::Hashed(_, _) = read {
+                ret.insert(InputOutputKey::Resource(key.as_ref().clone()));
             }
         }
 
         for (key, group_reads) in &self.group_reads {
             for (tag, read) in &group_reads.inner_reads {
                 if let DataRead::Versioned(_, _, _) = read {
-                    ret.insert(InputOutputKey::Group(key.clone(), tag.clone()));
+                    ret.insert(InputOutputKey::Group(key.as_ref().clone(), tag.clone()));
                 }
             }
         }
 
-        for key in &self.module_reads {
-            ret.insert(InputOutputKey::Resource(key.clone()));
+        for read in &self.module_reads {
+            ret.insert(InputOutputKey::Resource(read.key.as_ref().clone()));
         }
 
         for (key, read) in &self.delayed_field_reads {
-            if let DelayedFieldRead::Value { .. } = read {
+            if let DelayedFieldRead::Value { .. } | DelayedFieldRead::Committed { .. } = read {
                 ret.insert(InputOutputKey::DelayedField(*key));
             }
         }
@@ -707,6 +1841,15 @@ impl<T: Transaction> CapturedReads<T> {
         ret
     }
 
+    pub(crate) fn capture_module_read(
+        &mut self,
+        read: ModuleRead<T::Key>,
+    ) -> Result<(), PanicError> {
+        self.check_not_taken()?;
+        self.module_reads.push(read);
+        Ok(())
+    }
+
     pub(crate) fn mark_failure(&mut self) {
         self.speculative_failure = true;
     }
@@ -714,18 +1857,221 @@ impl<T: Transaction> CapturedReads<T> {
     pub(crate) fn mark_incorrect_use(&mut self) {
         self.incorrect_use = true;
     }
+
+    pub(crate) fn mark_exchange_size_threshold_exceeded(&mut self) {
+        self.exchange_size_threshold_exceeded = true;
+    }
+}
+
+/// Statistics of the reads performed by the execution of a transaction, for profiling.
+/// In the parallel mode, all kinds of reads are captured and counted, while in the
+/// sequential mode only the value reads are recorded.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ReadSummary {
+    /// Distinct resources read.
+    pub(crate) num_resource_reads: usize,
+    /// Distinct group members read.
+    pub(crate) num_group_reads: usize,
+    pub(crate) num_module_reads: usize,
+    /// Distinct delayed fields read.
+    pub(crate) num_delayed_field_reads: usize,
+    /// Reads of resources and group members served from the captured reads (parallel mode).
+    pub(crate) num_captured_read_hits: usize,
+    /// Fetches of resources and group members from the multi-versioned (or unsync) map.
+    pub(crate) num_map_fetches: usize,
+    /// Fetches of values from the base view (storage).
+    pub(crate) num_base_view_fetches: usize,
+}
+
+/// A pair of captured data reads at versions inconsistent with each other: the transaction
+/// that wrote the version observed by the read of the key also wrote the stale key, which
+/// was read at an earlier version.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct InconsistencyReport<K> {
+    pub(crate) key: K,
+    /// The version of the key, written by the transaction at the index.
+    pub(crate) version: (TxnIndex, Incarnation),
+    pub(crate) stale_key: K,
+    /// The version of the stale key, preceding the writer of the key.
+    pub(crate) stale_version: Version,
+}
+
+/// A read that differs between two captured reads (see CapturedReads::diff), with the read
+/// on each side (None if not captured on that side).
+#[cfg(any(test, feature = "serde"))]
+#[derive(Derivative)]
+#[derivative(Debug(bound = ""), PartialEq(bound = ""))]
+pub(crate) enum ReadDivergence<'a, T: Transaction> {
+    Resource {
+        key: &'a T::Key,
+        left: Option<&'a DataRead<T::Value>>,
+        right: Option<&'a DataRead<T::Value>>,
+    },
+    GroupSize {
+        key: &'a T::Key,
+        left: Option<ResourceGroupSize>,
+        right: Option<ResourceGroupSize>,
+    },
+    GroupMember {
+        key: &'a T::Key,
+        tag: &'a T::Tag,
+        left: Option<&'a DataRead<T::Value>>,
+        right: Option<&'a DataRead<T::Value>>,
+    },
+    Module {
+        key: &'a T::Key,
+        left: Option<&'a ModuleRead<T::Key>>,
+        right: Option<&'a ModuleRead<T::Key>>,
+    },
+    DelayedField {
+        id: &'a T::Identifier,
+        left: Option<&'a DelayedFieldRead>,
+        right: Option<&'a DelayedFieldRead>,
+    },
+}
+
+/// Pairs the reads of the two sides by the key, ordered by the key. Multiple reads of the same
+/// key are paired in order, with None on the side that has fewer reads of the key.
+#[cfg(any(test, feature = "serde"))]
+fn paired_reads<'a, K: Ord + ?Sized, R>(
+    left: impl Iterator<Item = (&'a K, &'a R)>,
+    right: impl Iterator<Item = (&'a K, &'a R)>,
+) -> Vec<(&'a K, Option<&'a R>, Option<&'a R>)> {
+    let mut reads: BTreeMap<&K, (Vec<&R>, Vec<&R>)> = BTreeMap::new();
+    for (key, read) in left {
+        reads.entry(key).or_default().0.push(read);
+    }
+    for (key, read) in right {
+        reads.entry(key).or_default().1.push(read);
+    }
+
+    reads
+        .into_iter()
+        .flat_map(|(key, (left, right))| {
+            (0..left.len().max(right.len()))
+                .map(move |i| (key, left.get(i).copied(), right.get(i).copied()))
+        })
+        .collect()
+}
+
+/// A captured group size read that does not match the size of the group recomputed from the
+/// group contents, at the index of the reading (committed) transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct GroupSizeDiscrepancy<K, Tag> {
+    pub(crate) group_key: K,
+    pub(crate) captured_size: ResourceGroupSize,
+    pub(crate) recomputed_size: u64,
+    /// The tags of the group members contributing to the recomputed size.
+    pub(crate) tags: Vec<Tag>,
+}
+
+impl<K: Debug, Tag: Debug> fmt::Display for GroupSizeDiscrepancy<K, Tag> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "group {:?}: captured size {:?} (= {}), recomputed size {} of members {:?}",
+            self.group_key,
+            self.captured_size,
+            self.captured_size.get(),
+            self.recomputed_size,
+            self.tags
+        )
+    }
+}
+
+/// The captured reads of the prior executions (no longer recorded), kept by a worker to be
+/// reused by its next executions, instead of allocating the maps from scratch. Not shared
+/// between the workers.
+pub(crate) struct CapturedReadsPool<T: Transaction> {
+    reads: RefCell<Vec<CapturedReads<T>>>,
+}
+
+impl<T: Transaction> CapturedReadsPool<T> {
+    // Each execution takes at most one captured reads from the pool, and returns at most
+    // one (of the prior incarnation), hence a small pool suffices.
+    const MAX_POOLED_READS: usize = 4;
+
+    pub(crate) fn new() -> Self {
+        Self {
+            reads: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Returns the cleared captured reads from the pool, or new captured reads if the pool
+    /// is empty.
+    pub(crate) fn acquire(&self) -> CapturedReads<T> {
+        self.reads.borrow_mut().pop().unwrap_or_default()
+    }
+
+    /// Returns the captured reads to the pool, unless they are still referenced (e.g. by an
+    /// ongoing validation), or the pool is full.
+    pub(crate) fn recycle(&self, reads: Arc<CapturedReads<T>>) {
+        let mut pooled_reads = self.reads.borrow_mut();
+        if pooled_reads.len() < Self::MAX_POOLED_READS {
+            if let Ok(mut reads) = Arc::try_unwrap(reads) {
+                reads.clear();
+                pooled_reads.push(reads);
+            }
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn num_pooled_reads(&self) -> usize {
+        self.reads.borrow().len()
+    }
 }
 
 #[derive(Derivative)]
 #[derivative(Default(bound = "", new = "true"))]
+#[cfg_attr(
+    any(test, feature = "serde"),
+    derive(Serialize, Deserialize),
+    serde(bound(
+        serialize = "T::Key: Serialize, T::Identifier: Serialize",
+        deserialize = "T::Key: Deserialize<'de>, T::Identifier: Deserialize<'de>"
+    ))
+)]
 pub(crate) struct UnsyncReadSet<T: Transaction> {
     pub(crate) resource_reads: HashSet<T::Key>,
     pub(crate) module_reads: HashSet<T::Key>,
     pub(crate) group_reads: HashMap<T::Key, HashSet<T::Tag>>,
     pub(crate) delayed_field_reads: HashSet<T::Identifier>,
+    // Set when the read set is taken, which may only happen once.
+    taken: bool,
 }
 
 impl<T: Transaction> UnsyncReadSet<T> {
+    /// Takes the read set, leaving the empty read set (marked as taken) in place.
+    pub(crate) fn take(&mut self) -> Result<Self, PanicError> {
+        if self.taken {
+            return Err(code_invariant_error("Unsync read set was already taken"));
+        }
+
+        let reads = std::mem::take(self);
+        self.taken = true;
+        Ok(reads)
+    }
+
+    /// Clears the recorded reads (also if taken), keeping the allocated capacity.
+    pub(crate) fn clear(&mut self) {
+        self.resource_reads.clear();
+        self.module_reads.clear();
+        self.group_reads.clear();
+        self.delayed_field_reads.clear();
+        self.taken = false;
+    }
+
+    /// Returns the summary of the recorded reads, without the counts of the fetches.
+    pub(crate) fn read_summary(&self) -> ReadSummary {
+        ReadSummary {
+            num_resource_reads: self.resource_reads.len(),
+            num_group_reads: self.group_reads.values().map(HashSet::len).sum(),
+            num_module_reads: self.module_reads.len(),
+            num_delayed_field_reads: self.delayed_field_reads.len(),
+            ..ReadSummary::default()
+        }
+    }
+
     pub(crate) fn get_read_summary(
         &self,
     ) -> HashSet<InputOutputKey<T::Key, T::Tag, T::Identifier>> {
@@ -752,19 +2098,43 @@ impl<T: Transaction> UnsyncReadSet<T> {
     }
 }
 
+/// The reads taken from a view after the execution: the captured reads in the parallel
+/// setting, or the read set in the sequential setting. With the serde feature, the reads
+/// can be serialized (e.g. to replay the execution of a transaction later).
+#[cfg(any(test, feature = "serde"))]
+#[derive(Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T::Key: Serialize, T::Value: Serialize, T::Identifier: Serialize",
+    deserialize = "T::Key: Deserialize<'de>, T::Value: Deserialize<'de>, \
+                   T::Identifier: Deserialize<'de>"
+))]
+pub(crate) enum TakenReads<T: Transaction> {
+    Parallel(CapturedReads<T>),
+    Sequential(UnsyncReadSet<T>),
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::proptest_types::types::{raw_metadata, KeyType, MockEvent, ValueType};
-    use aptos_mvhashmap::types::StorageVersion;
-    use claims::{assert_err, assert_gt, assert_matches, assert_none, assert_ok, assert_some_eq};
+    use aptos_aggregator::{
+        bounded_math::SignedU128,
+        delayed_change::{DelayedApplyEntry, DelayedEntry},
+        delta_change_set::DeltaOp,
+    };
+    use aptos_mvhashmap::{types::StorageVersion, MVHashMap};
+    use aptos_types::{executable::ExecutableTestType, write_set::WriteOpKind};
+    use claims::{
+        assert_err, assert_gt, assert_matches, assert_none, assert_ok, assert_ok_eq, assert_some_eq,
+    };
     use move_vm_types::delayed_values::delayed_field_id::DelayedFieldID;
     use test_case::test_case;
 
     #[test]
     fn data_read_kind() {
         // Test the strict ordering of enum variants for the read kinds.
-        assert_gt!(ReadKind::Value, ReadKind::Metadata);
+        assert_gt!(ReadKind::Value, ReadKind::Size);
+        assert_gt!(ReadKind::Size, ReadKind::Metadata);
         assert_gt!(ReadKind::Metadata, ReadKind::Exists);
 
         // Test that get_kind returns the proper kind for data read instances.
@@ -793,6 +2163,11 @@ mod test {
             DataRead::Metadata::<ValueType>(None).get_kind(),
             ReadKind::Metadata
         );
+        assert_eq!(
+            DataRead::Size::<ValueType>(Some((StateValueMetadata::none(), 1))).get_kind(),
+            ReadKind::Size
+        );
+        assert_eq!(DataRead::Size::<ValueType>(None).get_kind(), ReadKind::Size);
         assert_eq!(
             DataRead::Exists::<ValueType>(true).get_kind(),
             ReadKind::Exists
@@ -1294,4 +2669,1232 @@ mod test {
         captured_reads.mark_failure();
         assert!(captured_reads.speculative_failure);
     }
+
+    #[test]
+    fn take_reads_once() {
+        let mut captured_reads = CapturedReads::<TestTransactionType>::new();
+        let key = KeyType::<u32>(20, false);
+        assert_ok!(captured_reads.capture_read(key, None, DataRead::Exists(true)));
+
+        let taken_reads = captured_reads.take().unwrap();
+        assert_some_eq!(
+            taken_reads.get_by_kind(&key, None, ReadKind::Exists),
+            DataRead::Exists(true)
+        );
+        assert!(!taken_reads.incorrect_use);
+        assert_none!(captured_reads.get_by_kind(&key, None, ReadKind::Exists));
+        assert!(!captured_reads.incorrect_use);
+
+        // The reads may not be taken twice.
+        assert_err!(captured_reads.take());
+        assert!(captured_reads.incorrect_use);
+    }
+
+    #[test]
+    fn delayed_field_exists_capture() {
+        let mut captured_reads = CapturedReads::<TestTransactionType>::new();
+        let id = DelayedFieldID::new_for_test_for_u64(1);
+        let exists = DelayedFieldRead::Exists { exists: true };
+        let value = DelayedFieldRead::Value {
+            value: DelayedFieldValue::Aggregator(10),
+        };
+
+        assert_ok!(captured_reads.capture_delayed_field_read(id, false, exists.clone()));
+        assert_some_eq!(
+            captured_reads.get_delayed_field_by_kind(&id, DelayedFieldReadKind::Exists),
+            exists.clone()
+        );
+        assert_none!(captured_reads.get_delayed_field_by_kind(&id, DelayedFieldReadKind::Value));
+
+        // A stronger read replaces the existence check.
+        assert_ok!(captured_reads.capture_delayed_field_read(id, false, value.clone()));
+        assert_some_eq!(
+            captured_reads.get_delayed_field_by_kind(&id, DelayedFieldReadKind::Exists),
+            value.clone()
+        );
+        // But not the other way around.
+        assert_err!(captured_reads.capture_delayed_field_read(id, false, exists));
+        assert!(captured_reads.incorrect_use);
+
+        // A value can not be read from a delayed field that did not exist.
+        let mut captured_reads = CapturedReads::<TestTransactionType>::new();
+        assert_ok!(captured_reads.capture_delayed_field_read(
+            id,
+            false,
+            DelayedFieldRead::Exists { exists: false }
+        ));
+        assert_err!(captured_reads.capture_delayed_field_read(id, false, value));
+        assert!(captured_reads.speculative_failure);
+    }
+
+    #[test]
+    fn inconsistent_group_size_capture() {
+        let mut captured_reads = CapturedReads::<TestTransactionType>::new();
+        let key = KeyType::<u32>(20, false);
+        let size = ResourceGroupSize::Combined {
+            num_tagged_resources: 1,
+            all_tagged_resources_size: 10,
+        };
+        assert_ok!(captured_reads.capture_group_size(key, size));
+        assert_ok!(captured_reads.capture_group_size(key, size));
+        assert!(!captured_reads.speculative_failure);
+
+        assert_err!(captured_reads.capture_group_size(key, ResourceGroupSize::zero_combined()));
+        assert!(captured_reads.speculative_failure);
+        assert!(!captured_reads.incorrect_use);
+        assert_some_eq!(captured_reads.group_size(&key), size);
+    }
+
+    #[test]
+    fn capture_after_take() {
+        let key = KeyType::<u32>(20, false);
+        let id = DelayedFieldID::new_for_test_for_u64(1);
+        let captures: [&dyn Fn(&mut CapturedReads<TestTransactionType>) -> bool; 5] = [
+            &|reads| {
+                reads
+                    .capture_read(key, None, DataRead::Exists(true))
+                    .is_err()
+            },
+            &|reads| {
+                reads
+                    .capture_read(key, Some(30), DataRead::Exists(true))
+                    .is_err()
+            },
+            &|reads| {
+                reads
+                    .capture_group_size(key, ResourceGroupSize::zero_combined())
+                    .is_err()
+            },
+            &|reads| {
+                let read = DelayedFieldRead::Value {
+                    value: DelayedFieldValue::Aggregator(1),
+                };
+                matches!(
+                    reads.capture_delayed_field_read(id, false, read),
+                    Err(PanicOr::CodeInvariantError(_))
+                )
+            },
+            &|reads| {
+                let read = ModuleRead {
+                    key: Arc::new(KeyType::<u32>(1, true)),
+                    version: ModuleReadVersion::Storage,
+                    executable_descriptor: None,
+                };
+                reads.capture_module_read(read).is_err()
+            },
+        ];
+
+        for capture in captures {
+            let mut captured_reads = CapturedReads::<TestTransactionType>::new();
+            assert_ok!(captured_reads.take());
+            // Reads captured after the reads were taken would never be validated.
+            assert!(capture(&mut captured_reads));
+            assert!(captured_reads.incorrect_use);
+            assert_eq!(captured_reads.read_summary(), ReadSummary::default());
+        }
+    }
+
+    #[test]
+    fn take_unsync_read_set_once() {
+        let mut read_set = UnsyncReadSet::<TestTransactionType>::new();
+        read_set.resource_reads.insert(KeyType::<u32>(20, false));
+
+        let taken_read_set = assert_ok!(read_set.take());
+        assert_eq!(taken_read_set.resource_reads.len(), 1);
+        assert!(read_set.resource_reads.is_empty());
+        assert_err!(read_set.take());
+    }
+
+    #[test]
+    fn snapshot_consistency() {
+        let versioned = |version: Version| {
+            DataRead::Versioned(
+                version,
+                Arc::new(ValueType::with_len_and_metadata(
+                    1,
+                    StateValueMetadata::none(),
+                )),
+                None,
+            )
+        };
+        let key = |k: u32| KeyType::<u32>(k, false);
+        let writes = HashMap::from([
+            (2, HashSet::from([key(4)])),
+            (5, HashSet::from([key(1), key(2), key(4), key(5), key(6)])),
+            (6, HashSet::from([key(3)])),
+        ]);
+        let writes_by_txn = |txn_idx: TxnIndex| writes.get(&txn_idx).cloned().unwrap_or_default();
+
+        let mut captured_reads = CapturedReads::<TestTransactionType>::new();
+        assert_ok!(captured_reads.capture_read(key(1), None, versioned(Ok((5, 1)))));
+        assert_ok!(captured_reads.capture_read(key(3), None, versioned(Ok((6, 0)))));
+        assert!(captured_reads
+            .check_snapshot_consistency(&writes_by_txn)
+            .is_empty());
+
+        // Reads of keys written by txn 5 at earlier versions are reported.
+        assert_ok!(captured_reads.capture_read(key(2), None, versioned(Err(StorageVersion))));
+        let hashed_value =
+            HashedValue::new(&ValueType::with_len_and_metadata(1, raw_metadata(1))).unwrap();
+        assert_ok!(captured_reads.capture_read(
+            key(4),
+            None,
+            DataRead::Hashed(Ok((2, 0)), hashed_value)
+        ));
+        // Reads without a version and group member reads are not considered.
+        assert_ok!(captured_reads.capture_read(key(5), None, DataRead::Exists(true)));
+        assert_ok!(captured_reads.capture_read(key(6), Some(10), versioned(Err(StorageVersion))));
+
+        assert_eq!(
+            captured_reads.check_snapshot_consistency(&writes_by_txn),
+            vec![
+                InconsistencyReport {
+                    key: key(1),
+                    version: (5, 1),
+                    stale_key: key(2),
+                    stale_version: Err(StorageVersion),
+                },
+                InconsistencyReport {
+                    key: key(1),
+                    version: (5, 1),
+                    stale_key: key(4),
+                    stale_version: Ok((2, 0)),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn group_size_reads_check() {
+        let key = |k: u32| KeyType::<u32>(k, false);
+        let mut captured_reads = CapturedReads::<TestTransactionType>::new();
+        assert_ok!(captured_reads.capture_group_size(key(1), ResourceGroupSize::Concrete(10)));
+        assert_ok!(captured_reads.capture_group_size(key(2), ResourceGroupSize::Concrete(20)));
+        assert_ok!(captured_reads.capture_group_size(key(3), ResourceGroupSize::Concrete(30)));
+        // Groups without a size read are not considered.
+        assert_ok!(captured_reads.capture_read(key(4), Some(7), DataRead::Exists(true)));
+        // The reads are checked after they are taken.
+        let taken_reads = assert_ok!(captured_reads.take());
+
+        // The size of group 2 is mocked to not match the captured size, and the size of group 3
+        // can not be recomputed.
+        let recomputed_sizes = HashMap::from([
+            (key(1), (10, vec![5, 6])),
+            (key(2), (25, vec![5, 8])),
+            (key(4), (40, vec![7])),
+        ]);
+        let recompute_size = |group_key: &KeyType<u32>| recomputed_sizes.get(group_key).cloned();
+
+        let discrepancies = taken_reads.check_group_size_reads(&recompute_size);
+        assert_eq!(discrepancies, vec![GroupSizeDiscrepancy {
+            group_key: key(2),
+            captured_size: ResourceGroupSize::Concrete(20),
+            recomputed_size: 25,
+            tags: vec![5, 8],
+        }]);
+        let message = discrepancies[0].to_string();
+        assert!(message.contains(&format!("{:?}", key(2))));
+        assert!(message.contains("captured size Concrete(20) (= 20)"));
+        assert!(message.contains("recomputed size 25 of members [5, 8]"));
+    }
+
+    #[test]
+    fn diff_reads() {
+        let key = |k: u32| KeyType::<u32>(k, false);
+        let module_key = KeyType::<u32>(7, true);
+        let id = DelayedFieldID::new_for_test_for_u64(8);
+        let versioned = |len: usize| {
+            DataRead::Versioned(
+                Ok((2, 0)),
+                Arc::new(ValueType::with_len_and_metadata(len, raw_metadata(1))),
+                None,
+            )
+        };
+        let aggregator_read = |value: u128| DelayedFieldRead::Value {
+            value: DelayedFieldValue::Aggregator(value),
+        };
+        let published_read = ModuleRead {
+            key: Arc::new(module_key),
+            version: ModuleReadVersion::Published(1),
+            executable_descriptor: Some(ExecutableDescriptor::Published(HashValue::zero())),
+        };
+
+        let mut left = CapturedReads::<TestTransactionType>::new();
+        let mut right = CapturedReads::<TestTransactionType>::new();
+        for reads in [&mut left, &mut right] {
+            assert_ok!(reads.capture_read(key(2), None, DataRead::Exists(true)));
+            assert_ok!(reads.capture_read(key(4), Some(5), DataRead::Exists(false)));
+        }
+        assert!(left.diff(&right).is_empty());
+
+        // The same version of a value read, but with different values.
+        assert_ok!(left.capture_read(key(1), None, versioned(10)));
+        assert_ok!(right.capture_read(key(1), None, versioned(20)));
+        assert_ok!(left.capture_read(key(3), None, DataRead::Exists(true)));
+        assert_ok!(left.capture_group_size(key(4), ResourceGroupSize::Concrete(10)));
+        assert_ok!(right.capture_group_size(key(4), ResourceGroupSize::Concrete(15)));
+        assert_ok!(right.capture_read(key(4), Some(6), DataRead::Exists(true)));
+        assert_ok!(left.capture_module_read(ModuleRead {
+            key: Arc::new(module_key),
+            version: ModuleReadVersion::Storage,
+            executable_descriptor: None,
+        }));
+        assert_ok!(right.capture_module_read(published_read));
+        assert_ok!(left.capture_delayed_field_read(id, false, aggregator_read(1)));
+        assert_ok!(right.capture_delayed_field_read(id, false, aggregator_read(2)));
+
+        let divergences = left.diff(&right);
+        assert_eq!(divergences, vec![
+            ReadDivergence::Resource {
+                key: &key(1),
+                left: Some(&versioned(10)),
+                right: Some(&versioned(20)),
+            },
+            ReadDivergence::Resource {
+                key: &key(3),
+                left: Some(&DataRead::Exists(true)),
+                right: None,
+            },
+            ReadDivergence::GroupSize {
+                key: &key(4),
+                left: Some(ResourceGroupSize::Concrete(10)),
+                right: Some(ResourceGroupSize::Concrete(15)),
+            },
+            ReadDivergence::GroupMember {
+                key: &key(4),
+                tag: &6,
+                left: None,
+                right: Some(&DataRead::Exists(true)),
+            },
+            ReadDivergence::Module {
+                key: &module_key,
+                left: Some(&ModuleRead {
+                    key: Arc::new(module_key),
+                    version: ModuleReadVersion::Storage,
+                    executable_descriptor: None,
+                }),
+                right: Some(&ModuleRead {
+                    key: Arc::new(module_key),
+                    version: ModuleReadVersion::Published(1),
+                    executable_descriptor: Some(ExecutableDescriptor::Published(HashValue::zero())),
+                }),
+            },
+            ReadDivergence::DelayedField {
+                id: &id,
+                left: Some(&aggregator_read(1)),
+                right: Some(&aggregator_read(2)),
+            },
+        ]);
+        // The versions of the value reads are the same, only the values differ.
+        assert!(!versioned(10).is_identical(&versioned(20)));
+        assert!(versioned(10).is_identical(&versioned(10)));
+        assert_eq!(right.diff(&left).len(), divergences.len());
+    }
+
+    #[test]
+    fn reads_fingerprint() {
+        let key = |k: u32| KeyType::<u32>(k, false);
+        let id = DelayedFieldID::new_for_test_for_u64(8);
+        let versioned = || {
+            DataRead::Versioned(
+                Ok((2, 0)),
+                Arc::new(ValueType::with_len_and_metadata(10, raw_metadata(1))),
+                None,
+            )
+        };
+        let history_read = |max_achieved_positive_delta: u128| DelayedFieldRead::HistoryBounded {
+            restriction: DeltaHistory {
+                max_achieved_positive_delta,
+                min_achieved_negative_delta: 0,
+                min_overflow_positive_delta: None,
+                max_underflow_negative_delta: None,
+            },
+            max_value: 100,
+            inner_aggregator_value: 4,
+        };
+        let module_read = |k: u32| ModuleRead {
+            key: Arc::new(KeyType::<u32>(k, true)),
+            version: ModuleReadVersion::Storage,
+            executable_descriptor: None,
+        };
+
+        // The same reads, captured in a different order (and with different Arcs).
+        let mut left = CapturedReads::<TestTransactionType>::new();
+        assert_ok!(left.capture_read(key(1), None, versioned()));
+        assert_ok!(left.capture_read(key(2), None, DataRead::Exists(true)));
+        assert_ok!(left.capture_read(key(4), Some(5), DataRead::Exists(false)));
+        assert_ok!(left.capture_read(key(4), Some(6), DataRead::Exists(true)));
+        assert_ok!(left.capture_group_size(key(4), ResourceGroupSize::Concrete(10)));
+        assert_ok!(left.capture_module_read(module_read(7)));
+        assert_ok!(left.capture_module_read(module_read(9)));
+        assert_ok!(left.capture_delayed_field_read(id, false, history_read(3)));
+
+        let mut right = CapturedReads::<TestTransactionType>::new();
+        assert_ok!(right.capture_delayed_field_read(id, false, history_read(3)));
+        assert_ok!(right.capture_module_read(module_read(9)));
+        assert_ok!(right.capture_module_read(module_read(7)));
+        assert_ok!(right.capture_group_size(key(4), ResourceGroupSize::Concrete(10)));
+        assert_ok!(right.capture_read(key(4), Some(6), DataRead::Exists(true)));
+        assert_ok!(right.capture_read(key(4), Some(5), DataRead::Exists(false)));
+        assert_ok!(right.capture_read(key(2), None, DataRead::Exists(true)));
+        assert_ok!(right.capture_read(key(1), None, versioned()));
+
+        let fingerprint = left.fingerprint().unwrap();
+        assert_ok_eq!(right.fingerprint(), fingerprint);
+        assert_ne!(
+            CapturedReads::<TestTransactionType>::new()
+                .fingerprint()
+                .unwrap(),
+            fingerprint
+        );
+
+        // A stricter delayed field history is a different read.
+        assert_ok!(right.capture_delayed_field_read(id, true, history_read(5)));
+        assert_ne!(right.fingerprint().unwrap(), fingerprint);
+
+        left.mark_failure();
+        assert_ne!(left.fingerprint().unwrap(), fingerprint);
+    }
+
+    #[test]
+    fn validate_delayed_field_reads_batched() {
+        let versioned_map =
+            MVHashMap::<KeyType<u32>, u32, ValueType, ExecutableTestType, DelayedFieldID>::new();
+        let delayed_fields = versioned_map.delayed_fields();
+        let ids: Vec<_> = (1..=5).map(DelayedFieldID::new_for_test_for_u64).collect();
+        for (id, value) in ids.iter().zip([10, 20, 30, 40]) {
+            delayed_fields.set_base_value(*id, DelayedFieldValue::Aggregator(value));
+        }
+        let history_read =
+            |max_value: u128, inner_aggregator_value: u128| DelayedFieldRead::HistoryBounded {
+                restriction: DeltaHistory {
+                    max_achieved_positive_delta: 5,
+                    min_achieved_negative_delta: 0,
+                    min_overflow_positive_delta: None,
+                    max_underflow_negative_delta: None,
+                },
+                max_value,
+                inner_aggregator_value,
+            };
+
+        let mut captured_reads = CapturedReads::<TestTransactionType>::new();
+        for (id, value) in ids.iter().zip([10, 20]) {
+            assert_ok!(captured_reads.capture_delayed_field_read(
+                *id,
+                false,
+                DelayedFieldRead::Value {
+                    value: DelayedFieldValue::Aggregator(value),
+                }
+            ));
+        }
+        // Valid as long as the values (after adding 5) do not exceed 100 and 50, respectively.
+        assert_ok!(captured_reads.capture_delayed_field_read(ids[2], false, history_read(100, 30)));
+        assert_ok!(captured_reads.capture_delayed_field_read(ids[3], false, history_read(50, 40)));
+        // A delayed field that does not exist, hence not in the batch.
+        assert_ok!(captured_reads.capture_delayed_field_read(
+            ids[4],
+            false,
+            DelayedFieldRead::Exists { exists: false }
+        ));
+        assert_ok_eq!(
+            captured_reads.validate_delayed_field_reads_batched(delayed_fields, 1),
+            DelayedFieldValidation::Valid
+        );
+
+        // The transaction at index 0 adds 10 to both history bounded aggregators, and only the
+        // last one then exceeds its max value.
+        for id in &ids[2..4] {
+            assert_ok!(delayed_fields.record_change(
+                *id,
+                0,
+                DelayedEntry::Apply(DelayedApplyEntry::AggregatorDelta {
+                    delta: DeltaOp::new(SignedU128::Positive(10), 1000, DeltaHistory::new()),
+                }),
+            ));
+        }
+        assert!(delayed_fields.try_commit(0, ids[2..4].to_vec()).is_ok());
+        assert_ok_eq!(
+            captured_reads.validate_delayed_field_reads_batched(delayed_fields, 1),
+            DelayedFieldValidation::Invalid(ids[3])
+        );
+        assert_ok_eq!(
+            captured_reads.validate_delayed_field_reads(delayed_fields, 1),
+            false
+        );
+
+        captured_reads.mark_failure();
+        assert_ok_eq!(
+            captured_reads.validate_delayed_field_reads_batched(delayed_fields, 1),
+            DelayedFieldValidation::SpeculativeFailure
+        );
+    }
+
+    #[test]
+    fn serialized_reads_round_trip() {
+        let mut captured_reads = CapturedReads::<TestTransactionType>::new();
+        let value_key = KeyType::<u32>(1, false);
+        let hashed_key = KeyType::<u32>(2, false);
+        let group_key = KeyType::<u32>(3, false);
+        let id = DelayedFieldID::new_for_test_for_u64(4);
+
+        let value = Arc::new(ValueType::with_len_and_metadata(5, raw_metadata(1)));
+        let layout = Arc::new(MoveTypeLayout::U64);
+        assert_ok!(captured_reads.capture_read(
+            value_key,
+            None,
+            DataRead::Versioned(Ok((3, 1)), value.clone(), Some(layout.clone()))
+        ));
+        let hashed_value =
+            HashedValue::new(&ValueType::with_len_and_metadata(200, raw_metadata(2))).unwrap();
+        assert_ok!(captured_reads.capture_read(
+            hashed_key,
+            None,
+            DataRead::Hashed(Err(StorageVersion), hashed_value.clone())
+        ));
+        assert_ok!(captured_reads.capture_read(group_key, Some(5), DataRead::Exists(true)));
+        assert_ok!(captured_reads.capture_read(
+            group_key,
+            Some(6),
+            DataRead::Size(Some((raw_metadata(3), 10)))
+        ));
+        let group_size = ResourceGroupSize::Combined {
+            num_tagged_resources: 2,
+            all_tagged_resources_size: 20,
+        };
+        assert_ok!(captured_reads.capture_group_size(group_key, group_size));
+        let delayed_field_read = DelayedFieldRead::HistoryBounded {
+            restriction: DeltaHistory {
+                max_achieved_positive_delta: 3,
+                min_achieved_negative_delta: 1,
+                min_overflow_positive_delta: Some(8),
+                max_underflow_negative_delta: None,
+            },
+            max_value: 10,
+            inner_aggregator_value: 4,
+        };
+        assert_ok!(captured_reads.capture_delayed_field_read(
+            id,
+            false,
+            delayed_field_read.clone()
+        ));
+        assert_ok!(captured_reads.capture_module_read(ModuleRead {
+            key: Arc::new(KeyType::<u32>(6, true)),
+            version: ModuleReadVersion::Published(2),
+            executable_descriptor: Some(ExecutableDescriptor::Published(HashValue::sha3_256_of(
+                b"module"
+            ))),
+        }));
+
+        let bytes = bcs::to_bytes(&captured_reads).unwrap();
+        let deserialized: CapturedReads<TestTransactionType> = bcs::from_bytes(&bytes).unwrap();
+        // Maps are serialized in a canonical order, hence equal reads serialize equally.
+        assert_eq!(bcs::to_bytes(&deserialized).unwrap(), bytes);
+        assert_eq!(deserialized.read_summary(), captured_reads.read_summary());
+
+        // The value and the layout are restored by content, without sharing the Arcs.
+        match deserialized.get_by_kind(&value_key, None, ReadKind::Value) {
+            Some(DataRead::Versioned(
+                Ok((3, 1)),
+                deserialized_value,
+                Some(deserialized_layout),
+            )) => {
+                assert_eq!(deserialized_value.bytes(), value.bytes());
+                assert_eq!(
+                    deserialized_value.as_state_value_metadata(),
+                    value.as_state_value_metadata()
+                );
+                assert_eq!(deserialized_layout, layout);
+                assert_eq!(Arc::strong_count(&value), 2);
+            },
+            read => unreachable!("Unexpected read {:?}", read),
+        }
+        assert_matches!(
+            deserialized.get_by_kind(&hashed_key, None, ReadKind::Value),
+            Some(DataRead::Hashed(Err(StorageVersion), h)) if h == hashed_value
+        );
+        for tag in [5, 6] {
+            assert_some_eq!(
+                deserialized.get_by_kind(&group_key, Some(&tag), ReadKind::Exists),
+                DataRead::Exists(true)
+            );
+        }
+        assert_some_eq!(deserialized.group_size(&group_key), group_size);
+        assert_some_eq!(
+            deserialized.get_delayed_field_by_kind(&id, DelayedFieldReadKind::HistoryBounded),
+            delayed_field_read
+        );
+        assert_eq!(deserialized.module_reads, captured_reads.module_reads);
+    }
+
+    #[test]
+    fn exchange_scan_skips_reads_without_layout() {
+        let mut captured_reads = CapturedReads::<TestTransactionType>::new();
+        let exists = DataRead::Exists(true);
+        let metadata = DataRead::Metadata(Some(StateValueMetadata::none()));
+        let versioned_with_layout = DataRead::Versioned(
+            Err(StorageVersion),
+            Arc::new(ValueType::with_len_and_metadata(
+                1,
+                StateValueMetadata::none(),
+            )),
+            Some(Arc::new(MoveTypeLayout::U64)),
+        );
+
+        assert_ok!(captured_reads.capture_read(KeyType::<u32>(1, false), None, exists.clone()));
+        assert_ok!(captured_reads.capture_read(
+            KeyType::<u32>(2, false),
+            Some(5),
+            metadata.clone()
+        ));
+        assert_ok!(captured_reads.capture_read(KeyType::<u32>(2, false), Some(6), exists.clone()));
+        assert_ok!(captured_reads.capture_read(KeyType::<u32>(3, false), Some(5), exists.clone()));
+
+        // Only exists and metadata reads: no read is inspected by the scans.
+        assert_ok_eq!(
+            captured_reads.get_read_values_with_delayed_fields(
+                &HashSet::new(),
+                &HashSet::new(),
+                &mut BTreeSet::new(),
+                None,
+                &DelayedFieldLayoutCache::new(),
+                &ViewCounterLabels::new(true, None),
+            ),
+            ReadsNeedingExchange::default()
+        );
+        let no_skip = HashSet::new();
+        assert_eq!(
+            captured_reads
+                .get_group_read_values_with_delayed_fields(&no_skip)
+                .count(),
+            0
+        );
+        assert_eq!(captured_reads.num_scanned_reads(), 0);
+
+        // Upgrading an exists read of a group member to a value read with a layout.
+        assert_ok!(captured_reads.capture_read(
+            KeyType::<u32>(3, false),
+            Some(5),
+            versioned_with_layout.clone()
+        ));
+        let group_keys: Vec<_> = captured_reads
+            .get_group_read_values_with_delayed_fields(&no_skip)
+            .map(|(key, _)| *key)
+            .collect();
+        assert_eq!(group_keys, vec![KeyType::<u32>(3, false)]);
+        assert_eq!(captured_reads.num_scanned_reads(), 1);
+
+        let skip = HashSet::from([KeyType::<u32>(3, false)]);
+        assert_eq!(
+            captured_reads
+                .get_group_read_values_with_delayed_fields(&skip)
+                .count(),
+            0
+        );
+        assert_eq!(captured_reads.num_scanned_reads(), 1);
+
+        assert_eq!(captured_reads.num_data_reads_with_layout, 0);
+        assert_ok!(captured_reads.capture_read(
+            KeyType::<u32>(1, false),
+            None,
+            versioned_with_layout
+        ));
+        assert_eq!(captured_reads.num_data_reads_with_layout, 1);
+    }
+
+    #[test]
+    fn iter_reads_matching() {
+        let mut captured_reads = CapturedReads::<TestTransactionType>::new();
+        let exists = DataRead::Exists(true);
+        let resolved = DataRead::Resolved::<ValueType>(200);
+        let metadata = DataRead::Metadata(Some(raw_metadata(1)));
+
+        // Keys below 10 are matched by the predicate.
+        let is_matching = |key: &KeyType<u32>| key.0 < 10;
+
+        assert_ok!(captured_reads.capture_read(KeyType::<u32>(1, false), None, exists.clone()));
+        assert_ok!(captured_reads.capture_read(KeyType::<u32>(11, false), None, resolved.clone()));
+        assert_ok!(captured_reads.capture_read(
+            KeyType::<u32>(2, false),
+            Some(5),
+            metadata.clone()
+        ));
+        assert_ok!(captured_reads.capture_read(KeyType::<u32>(2, false), Some(6), exists.clone()));
+        assert_ok!(captured_reads.capture_read(KeyType::<u32>(12, false), Some(5), exists.clone()));
+        for key in [KeyType::<u32>(3, true), KeyType::<u32>(13, true)] {
+            captured_reads.module_reads.push(ModuleRead {
+                key: Arc::new(key),
+                version: ModuleReadVersion::Storage,
+                executable_descriptor: None,
+            });
+        }
+
+        let mut data_reads = vec![];
+        let mut group_member_reads = vec![];
+        let mut module_reads = vec![];
+        for read in captured_reads.iter_reads_matching(is_matching) {
+            match read {
+                CapturedRead::Data(key, data_read) => {
+                    assert_none!(read.tag());
+                    assert_some_eq!(read.data_read(), data_read);
+                    data_reads.push((*key, data_read.clone()));
+                },
+                CapturedRead::GroupMember(key, tag, data_read) => {
+                    assert_some_eq!(read.tag(), tag);
+                    assert_some_eq!(read.data_read(), data_read);
+                    group_member_reads.push((*key, *tag, data_read.clone()));
+                },
+                CapturedRead::Module(key) => {
+                    assert_none!(read.tag());
+                    assert_none!(read.data_read());
+                    module_reads.push(*key);
+                },
+            }
+        }
+        group_member_reads.sort_by_key(|(_, tag, _)| *tag);
+
+        assert_eq!(data_reads, vec![(KeyType::<u32>(1, false), exists.clone())]);
+        assert_eq!(group_member_reads, vec![
+            (KeyType::<u32>(2, false), 5, metadata),
+            (KeyType::<u32>(2, false), 6, exists),
+        ]);
+        assert_eq!(module_reads, vec![KeyType::<u32>(3, true)]);
+
+        assert_eq!(
+            captured_reads.read_keys_matching(is_matching),
+            HashSet::from([
+                &KeyType::<u32>(1, false),
+                &KeyType::<u32>(2, false),
+                &KeyType::<u32>(3, true)
+            ])
+        );
+        assert_eq!(captured_reads.iter_reads_matching(|_| true).count(), 7);
+        assert_eq!(captured_reads.iter_reads_matching(|_| false).count(), 0);
+    }
+
+    #[test]
+    fn read_version_summaries() {
+        let mut captured_reads = CapturedReads::<TestTransactionType>::new();
+        let versioned = |version: Version| {
+            DataRead::Versioned(
+                version,
+                Arc::new(ValueType::with_len_and_metadata(1, raw_metadata(1))),
+                None,
+            )
+        };
+        let key = |k: u32| KeyType::<u32>(k, false);
+        let module_key = KeyType::<u32>(7, true);
+
+        assert_ok!(captured_reads.capture_read(key(1), None, versioned(Ok((3, 1)))));
+        assert_ok!(captured_reads.capture_read(key(2), None, DataRead::Exists(true)));
+        assert_ok!(captured_reads.capture_read(key(4), Some(5), versioned(Err(StorageVersion))));
+        assert_ok!(captured_reads.capture_read(key(4), Some(6), DataRead::Exists(false)));
+        assert_ok!(captured_reads.capture_module_read(ModuleRead {
+            key: Arc::new(module_key),
+            version: ModuleReadVersion::Published(2),
+            executable_descriptor: Some(ExecutableDescriptor::Published(HashValue::zero())),
+        }));
+        let taken_reads = captured_reads.take().unwrap();
+
+        let keys = [
+            (key(1), None),
+            (key(2), None),
+            (key(3), None),
+            (key(4), Some(5)),
+            (key(4), Some(6)),
+            (key(4), Some(7)),
+            (module_key, None),
+        ];
+        assert_eq!(taken_reads.read_versions_for(&keys), vec![
+            Some(ReadVersionSummary::Data {
+                kind: ReadKind::Value,
+                version: Some(Ok((3, 1))),
+            }),
+            Some(ReadVersionSummary::Data {
+                kind: ReadKind::Exists,
+                version: None,
+            }),
+            None,
+            Some(ReadVersionSummary::Data {
+                kind: ReadKind::Value,
+                version: Some(Err(StorageVersion)),
+            }),
+            Some(ReadVersionSummary::Data {
+                kind: ReadKind::Exists,
+                version: None,
+            }),
+            None,
+            Some(ReadVersionSummary::Module(ModuleReadVersion::Published(2))),
+        ]);
+        // A group member read is not a read of the group key itself.
+        assert_none!(taken_reads.read_version_for(&key(4), None));
+    }
+
+    #[test]
+    fn estimated_memory_bytes() {
+        let layout = Arc::new(MoveTypeLayout::U64);
+        let versioned = |len: usize, layout: Option<Arc<MoveTypeLayout>>| {
+            DataRead::Versioned(
+                Ok((3, 1)),
+                Arc::new(ValueType::with_len_and_metadata(len, raw_metadata(1))),
+                layout,
+            )
+        };
+        let key = |k: u32| KeyType::<u32>(k, false);
+
+        let mut captured_reads = CapturedReads::<TestTransactionType>::new();
+        assert_ok!(captured_reads.capture_read(key(1), None, versioned(10, Some(layout.clone()))));
+        let one_read = captured_reads.estimated_memory_bytes();
+
+        // The layout is shared with the previous read, so it is only accounted for once.
+        assert_ok!(captured_reads.capture_read(key(2), None, versioned(10, Some(layout.clone()))));
+        let two_reads = captured_reads.estimated_memory_bytes();
+        assert_eq!(
+            two_reads,
+            2 * one_read - std::mem::size_of::<MoveTypeLayout>()
+        );
+
+        // A distinct layout is accounted for separately.
+        assert_ok!(captured_reads.capture_read(
+            key(3),
+            None,
+            versioned(10, Some(Arc::new(MoveTypeLayout::U64)))
+        ));
+        assert_eq!(
+            captured_reads.estimated_memory_bytes(),
+            two_reads + one_read
+        );
+
+        // The estimate grows linearly with the length of the read bytes.
+        let mut small = CapturedReads::<TestTransactionType>::new();
+        let mut large = CapturedReads::<TestTransactionType>::new();
+        assert_ok!(small.capture_read(key(4), Some(5), versioned(10, None)));
+        assert_ok!(large.capture_read(key(4), Some(5), versioned(1010, None)));
+        assert_eq!(
+            large.estimated_memory_bytes(),
+            small.estimated_memory_bytes() + 1000
+        );
+
+        // Derived delayed field values account for their data.
+        let mut delayed = CapturedReads::<TestTransactionType>::new();
+        let empty = delayed.estimated_memory_bytes();
+        assert_ok!(delayed.capture_delayed_field_read(
+            DelayedFieldID::new_for_test_for_u64(1),
+            false,
+            DelayedFieldRead::Value {
+                value: DelayedFieldValue::Derived(vec![0; 100]),
+            }
+        ));
+        assert_eq!(
+            delayed.estimated_memory_bytes(),
+            empty
+                + std::mem::size_of::<DelayedFieldID>()
+                + std::mem::size_of::<DelayedFieldRead>()
+                + 100
+        );
+    }
+
+    #[test]
+    fn validate_module_reads() {
+        let mvhashmap =
+            MVHashMap::<KeyType<u32>, u32, ValueType, ExecutableTestType, DelayedFieldID>::new();
+        let modules = mvhashmap.modules();
+        let module_key = KeyType::<u32>(1, true);
+        let storage_module_key = KeyType::<u32>(2, true);
+        let unrelated_module_key = KeyType::<u32>(3, true);
+        modules.write(module_key, 0, ValueType::from_value(vec![0_u8], true));
+
+        let read_module = |captured_reads: &mut CapturedReads<TestTransactionType>, key| {
+            let fetched_module = modules.fetch_module_with_version(&key, 3);
+            assert_ok!(
+                captured_reads.capture_module_read(ModuleRead::from_fetched_module(
+                    Arc::new(key),
+                    &fetched_module,
+                ))
+            );
+        };
+
+        let mut captured_reads = CapturedReads::<TestTransactionType>::new();
+        read_module(&mut captured_reads, module_key);
+        read_module(&mut captured_reads, storage_module_key);
+        assert_eq!(
+            captured_reads.module_reads[0].version,
+            ModuleReadVersion::Published(0)
+        );
+        assert_matches!(
+            captured_reads.module_reads[0].executable_descriptor,
+            Some(ExecutableDescriptor::Published(_))
+        );
+        assert_eq!(
+            captured_reads.module_reads[1].version,
+            ModuleReadVersion::Storage
+        );
+        assert_none!(captured_reads.module_reads[1].executable_descriptor);
+        assert!(captured_reads.validate_module_reads(modules, 3));
+
+        // An unrelated module publish does not invalidate the reads, and its key is not
+        // among the read keys (hence, it does not trigger the module R/W fallback).
+        modules.write(
+            unrelated_module_key,
+            1,
+            ValueType::from_value(vec![1_u8], true),
+        );
+        assert!(captured_reads.validate_module_reads(modules, 3));
+        assert!(!captured_reads
+            .read_keys_matching(|_| true)
+            .contains(&unrelated_module_key));
+
+        // Republishing the module by a lower transaction invalidates the read, but not for
+        // the transactions below the republishing transaction.
+        modules.write(module_key, 2, ValueType::from_value(vec![0_u8], true));
+        assert!(!captured_reads.validate_module_reads(modules, 3));
+        assert!(captured_reads.validate_module_reads(modules, 2));
+        modules.remove(&module_key, 2);
+        assert!(captured_reads.validate_module_reads(modules, 3));
+
+        // Publishing the module read from storage invalidates the read.
+        modules.write(
+            storage_module_key,
+            1,
+            ValueType::from_value(vec![2_u8], true),
+        );
+        assert!(!captured_reads.validate_module_reads(modules, 3));
+
+        // A read that observed a dependency never validates.
+        modules.mark_estimate(&storage_module_key, 1);
+        let mut dependency_reads = CapturedReads::<TestTransactionType>::new();
+        read_module(&mut dependency_reads, storage_module_key);
+        assert_eq!(
+            dependency_reads.module_reads[0].version,
+            ModuleReadVersion::Dependency(1)
+        );
+        assert!(!dependency_reads.validate_module_reads(modules, 3));
+    }
+
+    #[test]
+    fn hashed_value_read() {
+        let mvhashmap =
+            MVHashMap::<KeyType<u32>, u32, ValueType, ExecutableTestType, DelayedFieldID>::new();
+        let data = mvhashmap.data();
+        let key = KeyType::<u32>(1, false);
+        let value = Arc::new(ValueType::with_len_and_metadata(100, raw_metadata(1)));
+        data.write(key, 0, 0, value.clone(), None);
+
+        let fetch_read = || match data.fetch_data(&key, 1) {
+            Ok(MVDataOutput::Versioned(version, v)) => DataRead::from_value_with_layout(version, v),
+            _ => unreachable!("Value must be written"),
+        };
+
+        // Values up to the threshold are retained by the captured read.
+        let mut captured_reads = CapturedReads::<TestTransactionType>::new();
+        let read = fetch_read().hashed_if_larger_than(100);
+        assert_matches!(read, DataRead::Versioned(_, _, _));
+        assert_ok!(captured_reads.capture_read(key, None, read));
+        // Held by the map, the captured read and the test.
+        assert_eq!(Arc::strong_count(&value), 3);
+
+        let mut captured_reads = CapturedReads::<TestTransactionType>::new();
+        assert_ok!(captured_reads.capture_read(key, None, fetch_read().hashed_if_larger_than(99)));
+        assert_eq!(Arc::strong_count(&value), 2);
+        assert_matches!(
+            captured_reads.get_by_kind(&key, None, ReadKind::Value),
+            Some(DataRead::Hashed(Ok((0, 0)), _))
+        );
+        assert_some_eq!(
+            captured_reads.get_by_kind(&key, None, ReadKind::Metadata),
+            DataRead::Metadata(Some(raw_metadata(1)))
+        );
+        assert_some_eq!(
+            captured_reads.get_by_kind(&key, None, ReadKind::Exists),
+            DataRead::Exists(true)
+        );
+        assert!(captured_reads.validate_data_reads(data, 1));
+
+        // The same value written by a new incarnation (a version change) still validates.
+        data.write(
+            key,
+            0,
+            1,
+            Arc::new(ValueType::with_len_and_metadata(100, raw_metadata(1))),
+            None,
+        );
+        assert!(captured_reads.validate_data_reads(data, 1));
+
+        // Content change (of the same length and metadata) is detected.
+        data.write(
+            key,
+            0,
+            2,
+            Arc::new(ValueType::new(
+                Some(vec![7_u8; 100].into()),
+                raw_metadata(1),
+                WriteOpKind::Creation,
+            )),
+            None,
+        );
+        assert!(!captured_reads.validate_data_reads(data, 1));
+
+        // So is the metadata change.
+        data.write(
+            key,
+            0,
+            3,
+            Arc::new(ValueType::with_len_and_metadata(100, raw_metadata(2))),
+            None,
+        );
+        assert!(!captured_reads.validate_data_reads(data, 1));
+
+        // A value with a layout never matches the hashed value (read without a layout).
+        data.write(
+            key,
+            0,
+            4,
+            Arc::new(ValueType::with_len_and_metadata(100, raw_metadata(1))),
+            Some(Arc::new(MoveTypeLayout::U64)),
+        );
+        assert!(!captured_reads.validate_data_reads(data, 1));
+
+        // The content is compared also at an unchanged version, as the base value may be
+        // exchanged in place (at the storage version).
+        let base_key = KeyType::<u32>(2, false);
+        data.set_base_value(
+            base_key,
+            ValueWithLayout::RawFromStorage(Arc::new(ValueType::with_len_and_metadata(
+                100,
+                raw_metadata(1),
+            ))),
+        );
+        let read = match data.fetch_data(&base_key, 1) {
+            Ok(MVDataOutput::Versioned(version, v)) => DataRead::from_value_with_layout(version, v),
+            _ => unreachable!("Base value must be set"),
+        };
+        let mut captured_reads = CapturedReads::<TestTransactionType>::new();
+        assert_ok!(captured_reads.capture_read(base_key, None, read.hashed_if_larger_than(99)));
+        assert!(captured_reads.validate_data_reads(data, 1));
+
+        data.set_base_value(
+            base_key,
+            ValueWithLayout::Exchanged(
+                Arc::new(ValueType::new(
+                    Some(vec![7_u8; 100].into()),
+                    raw_metadata(1),
+                    WriteOpKind::Creation,
+                )),
+                None,
+            ),
+        );
+        assert!(!captured_reads.validate_data_reads(data, 1));
+    }
+
+    #[test]
+    fn describe_validation_mismatch() {
+        let mvhashmap =
+            MVHashMap::<KeyType<u32>, u32, ValueType, ExecutableTestType, DelayedFieldID>::new();
+        let data = mvhashmap.data();
+        let key = KeyType::<u32>(1, false);
+        data.set_base_value(
+            key,
+            ValueWithLayout::Exchanged(
+                Arc::new(ValueType::with_len_and_metadata(8, raw_metadata(1))),
+                None,
+            ),
+        );
+
+        let mut captured_reads = CapturedReads::<TestTransactionType>::new();
+        let read = match data.fetch_data(&key, 1) {
+            Ok(MVDataOutput::Versioned(version, v)) => DataRead::from_value_with_layout(version, v),
+            _ => unreachable!("Base value must be set"),
+        };
+        assert_ok!(captured_reads.capture_read(key, None, read));
+        assert_none!(captured_reads.describe_invalid_data_read(data, 1));
+
+        // A lower transaction writes a larger value with a layout.
+        data.write(
+            key,
+            0,
+            2,
+            Arc::new(ValueType::with_len_and_metadata(16, raw_metadata(1))),
+            Some(Arc::new(MoveTypeLayout::U64)),
+        );
+        assert!(!captured_reads.validate_data_reads(data, 1));
+        let description = captured_reads.describe_invalid_data_read(data, 1).unwrap();
+        assert_eq!(
+            description,
+            format!(
+                "{:?}: captured: Value at storage version, 8 bytes, without layout; \
+                 current: Value at txn 0 (incarnation 2), 16 bytes, with layout",
+                key
+            )
+        );
+        // The raw bytes are never included.
+        assert!(!description.contains('['));
+
+        // The current state may also be an error of the fetch.
+        data.mark_estimate(&key, 0);
+        assert_some_eq!(
+            captured_reads.describe_invalid_data_read(data, 1),
+            format!(
+                "{:?}: captured: Value at storage version, 8 bytes, without layout; \
+                 current: Dependency on txn 0",
+                key
+            )
+        );
+        assert_eq!(
+            describe_mismatch::<ValueType>(&DataRead::Size(None), Err(&MVDataError::Uninitialized)),
+            "captured: Size, does not exist; current: Uninitialized"
+        );
+    }
+
+    #[test]
+    fn size_read() {
+        let versioned = DataRead::Versioned(
+            Ok((7, 0)),
+            Arc::new(ValueType::with_len_and_metadata(2, raw_metadata(1))),
+            None,
+        );
+        let size = DataRead::Size(Some((raw_metadata(1), 2)));
+        let other_size = DataRead::Size(Some((raw_metadata(1), 3)));
+        let deletion_size = DataRead::Size(None);
+        let metadata = DataRead::Metadata(Some(raw_metadata(1)));
+
+        assert_contains!(versioned, size);
+        assert_contains!(size, metadata);
+        assert_contains!(size, DataRead::Exists(true));
+        assert_contains!(deletion_size, DataRead::Exists(false));
+        assert_contains!(
+            DataRead::Resolved::<ValueType>(200),
+            DataRead::Size(Some((StateValueMetadata::none(), 16)))
+        );
+        assert_insufficient!(size, versioned);
+        assert_insufficient!(metadata, size);
+        assert_inconsistent_same_kind!(size, other_size);
+        assert_inconsistent_same_kind!(size, deletion_size);
+        assert_inconsistent_downcast!(versioned, other_size);
+        assert_inconsistent_downcast!(deletion_size, metadata);
+
+        // The value read (captured after the size read) upgrades the captured read.
+        let key = KeyType::<u32>(1, false);
+        let mut captured_reads = CapturedReads::<TestTransactionType>::new();
+        assert_ok!(captured_reads.capture_read(key, None, size.clone()));
+        assert_none!(captured_reads.get_by_kind(&key, None, ReadKind::Value));
+        assert_ok!(captured_reads.capture_read(key, None, versioned.clone()));
+        assert_some_eq!(
+            captured_reads.get_by_kind(&key, None, ReadKind::Value),
+            versioned
+        );
+        assert_some_eq!(captured_reads.get_by_kind(&key, None, ReadKind::Size), size);
+        // A value read inconsistent with the captured size read is detected.
+        let mut captured_reads = CapturedReads::<TestTransactionType>::new();
+        assert_ok!(captured_reads.capture_read(key, None, other_size));
+        assert_err!(captured_reads.capture_read(key, None, versioned));
+        assert!(captured_reads.speculative_failure);
+
+        // A size read from storage (never exchanged) is validated by the size and metadata.
+        let mvhashmap =
+            MVHashMap::<KeyType<u32>, u32, ValueType, ExecutableTestType, DelayedFieldID>::new();
+        let data = mvhashmap.data();
+        data.set_base_value(
+            key,
+            ValueWithLayout::RawFromStorage(Arc::new(ValueType::with_len_and_metadata(
+                2,
+                raw_metadata(1),
+            ))),
+        );
+        let mut captured_reads = CapturedReads::<TestTransactionType>::new();
+        assert_ok!(captured_reads.capture_read(key, None, size));
+        assert!(captured_reads.validate_data_reads(data, 1));
+
+        // The same size with different contents still validates, a different size does not.
+        data.write(
+            key,
+            0,
+            0,
+            Arc::new(ValueType::new(
+                Some(vec![7_u8; 2].into()),
+                raw_metadata(1),
+                WriteOpKind::Creation,
+            )),
+            None,
+        );
+        assert!(captured_reads.validate_data_reads(data, 1));
+        data.write(
+            key,
+            0,
+            1,
+            Arc::new(ValueType::with_len_and_metadata(3, raw_metadata(1))),
+            None,
+        );
+        assert!(!captured_reads.validate_data_reads(data, 1));
+    }
+
+    #[test]
+    fn clear_and_recycle() {
+        let key = |k: u32| KeyType::<u32>(k, false);
+        let versioned = |len: usize| {
+            DataRead::Versioned(
+                Ok((3, 1)),
+                Arc::new(ValueType::with_len_and_metadata(len, raw_metadata(1))),
+                Some(Arc::new(MoveTypeLayout::U64)),
+            )
+        };
+        let populated_reads = || {
+            let mut captured_reads = CapturedReads::<TestTransactionType>::new();
+            assert_ok!(captured_reads.capture_read(key(1), None, versioned(10)));
+            assert_ok!(captured_reads.capture_read(key(2), Some(5), DataRead::Exists(true)));
+            assert_ok!(captured_reads.capture_group_size(key(2), ResourceGroupSize::Concrete(10)));
+            assert_ok!(captured_reads.capture_module_read(ModuleRead {
+                key: Arc::new(KeyType::<u32>(7, true)),
+                version: ModuleReadVersion::Published(2),
+                executable_descriptor: None,
+            }));
+            assert_ok!(captured_reads.capture_delayed_field_read(
+                DelayedFieldID::new_for_test_for_u64(1),
+                false,
+                DelayedFieldRead::Value {
+                    value: DelayedFieldValue::Aggregator(5),
+                }
+            ));
+            captured_reads
+        };
+
+        let mut captured_reads = populated_reads();
+        captured_reads.mark_failure();
+        captured_reads.mark_incorrect_use();
+        captured_reads.disable_capture();
+        let data_reads_capacity = captured_reads.data_reads.capacity();
+        captured_reads.clear();
+
+        assert_eq!(captured_reads.read_summary(), ReadSummary::default());
+        assert_eq!(captured_reads.num_data_reads_with_layout, 0);
+        assert!(!captured_reads.is_speculative_failure());
+        assert!(!captured_reads.is_incorrect_use());
+        assert!(!captured_reads.is_capture_disabled());
+        assert_eq!(captured_reads.data_reads.capacity(), data_reads_capacity);
+        // The cleared reads may be taken again.
+        assert_ok!(captured_reads.take());
+        captured_reads.clear();
+        assert_ok!(captured_reads.capture_read(key(1), None, versioned(10)));
+
+        let pool = CapturedReadsPool::<TestTransactionType>::new();
+        let recycled_reads = Arc::new(populated_reads());
+        // Still referenced reads (e.g. by a validation) are not pooled.
+        let validated_reads = recycled_reads.clone();
+        pool.recycle(recycled_reads);
+        assert_eq!(pool.num_pooled_reads(), 0);
+        pool.recycle(validated_reads);
+        assert_eq!(pool.num_pooled_reads(), 1);
+
+        let reused_reads = pool.acquire();
+        assert_eq!(pool.num_pooled_reads(), 0);
+        assert_eq!(reused_reads.read_summary(), ReadSummary::default());
+        assert!(reused_reads.data_reads.capacity() > 0);
+
+        for _ in 0..CapturedReadsPool::<TestTransactionType>::MAX_POOLED_READS + 1 {
+            pool.recycle(Arc::new(populated_reads()));
+        }
+        assert_eq!(
+            pool.num_pooled_reads(),
+            CapturedReadsPool::<TestTransactionType>::MAX_POOLED_READS
+        );
+        assert_eq!(pool.acquire().read_summary(), ReadSummary::default());
+    }
 }
@@ -1,6 +1,10 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::{
+    captured_reads::ReadSummary,
+    view_health::{SpeculativeHaltReason, ViewHealthSummary},
+};
 use aptos_metrics_core::{
     exponential_buckets, register_avg_counter_vec, register_histogram, register_histogram_vec,
     register_int_counter, register_int_counter_vec, Histogram, HistogramVec, IntCounter,
@@ -9,6 +13,7 @@ use aptos_metrics_core::{
 use aptos_mvhashmap::BlockStateStats;
 use aptos_types::fee_statement::FeeStatement;
 use once_cell::sync::Lazy;
+use std::borrow::Cow;
 
 pub struct GasType;
 
@@ -21,6 +26,26 @@ impl GasType {
     pub const TOTAL_GAS: &'static str = "total_gas";
 }
 
+pub struct ReadType;
+
+impl ReadType {
+    pub const BASE_VIEW_FETCHES: &'static str = "base_view_fetches";
+    pub const CAPTURED_READ_HITS: &'static str = "captured_read_hits";
+    pub const DELAYED_FIELD_READS: &'static str = "delayed_field_reads";
+    pub const GROUP_READS: &'static str = "group_reads";
+    pub const MAP_FETCHES: &'static str = "map_fetches";
+    pub const MODULE_READS: &'static str = "module_reads";
+    pub const RESOURCE_READS: &'static str = "resource_reads";
+}
+
+pub struct ExchangeType;
+
+impl ExchangeType {
+    pub const IDENTIFIERS_TO_VALUES: &'static str = "identifiers_to_values";
+    pub const IDENTIFIER_EXTRACTION: &'static str = "identifier_extraction";
+    pub const VALUES_TO_IDENTIFIERS: &'static str = "values_to_identifiers";
+}
+
 pub struct Mode;
 
 impl Mode {
@@ -28,6 +53,46 @@ impl Mode {
     pub const SEQUENTIAL: &'static str = "sequential";
 }
 
+pub struct Shard;
+
+impl Shard {
+    /// Shard ids at or above the maximum are reported as OTHER, bounding the cardinality.
+    pub const MAX_LABELED_SHARD_ID: usize = 64;
+    pub const NONE: &'static str = "none";
+    pub const OTHER: &'static str = "other";
+}
+
+/// Label values (mode and shard) of the counters updated by the views of the transactions,
+/// allowing to tell apart the (fallback) sequential and the parallel executions, as well as
+/// the block executors of different shards (in the sharded execution) within a process.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ViewCounterLabels {
+    mode: &'static str,
+    shard: Cow<'static, str>,
+}
+
+impl ViewCounterLabels {
+    pub(crate) fn new(is_parallel: bool, shard_id: Option<usize>) -> Self {
+        let mode = if is_parallel {
+            Mode::PARALLEL
+        } else {
+            Mode::SEQUENTIAL
+        };
+        let shard = match shard_id {
+            None => Cow::Borrowed(Shard::NONE),
+            Some(shard_id) if shard_id < Shard::MAX_LABELED_SHARD_ID => {
+                Cow::Owned(shard_id.to_string())
+            },
+            Some(_) => Cow::Borrowed(Shard::OTHER),
+        };
+        Self { mode, shard }
+    }
+
+    pub(crate) fn values(&self) -> [&str; 2] {
+        [self.mode, self.shard.as_ref()]
+    }
+}
+
 fn time_buckets() -> std::vec::Vec<f64> {
     exponential_buckets(
         /*start=*/ 1e-6, /*factor=*/ 2.0, /*count=*/ 30,
@@ -58,6 +123,125 @@ pub static MODULE_PUBLISHING_FALLBACK_COUNT: Lazy<IntCounter> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Count of group size reads that did not match the size of the group contents, detected
+/// when group size consistency checks are enabled.
+pub static GROUP_SIZE_CONSISTENCY_ERROR_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_execution_group_size_consistency_error_count",
+        "Count of group size reads inconsistent with the serialized group contents",
+        &["mode", "shard"]
+    )
+    .unwrap()
+});
+
+/// Count of sentinel ops (created from storage state values), the conversion of which did
+/// not preserve the bytes or metadata of the state value.
+pub static SENTINEL_OP_CONVERSION_ERROR_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_execution_sentinel_op_conversion_error_count",
+        "Count of state value to sentinel op conversions that did not round-trip",
+        &["mode", "shard"]
+    )
+    .unwrap()
+});
+
+/// Count of delayed field values of an unexpected kind (e.g. a snapshot where an aggregator
+/// was expected), indicating a bug in the delayed field exchange bookkeeping.
+pub static DELAYED_FIELD_TYPE_MISMATCH_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_execution_delayed_field_type_mismatch_count",
+        "Count of delayed field values of an unexpected kind",
+        &["mode", "shard"]
+    )
+    .unwrap()
+});
+
+/// Count of reads that found aggregator (v1) deltas, but no base value to apply them to,
+/// requiring the base value of the aggregator to be fetched from storage.
+pub static AGGREGATOR_V1_UNRESOLVED_READ_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_execution_aggregator_v1_unresolved_read_count",
+        "Count of aggregator v1 reads with unresolved deltas (no base value)",
+        &["mode", "shard"]
+    )
+    .unwrap()
+});
+
+/// Count of keys read both as a plain resource and as a resource group within a block (e.g.
+/// when a resource is migrated into a resource group), by the direction of the flip.
+pub static RESOURCE_GROUP_REPRESENTATION_FLIP_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_execution_resource_group_representation_flip_count",
+        "Count of keys read as both a resource and a resource group within a block",
+        &["mode", "shard", "direction"]
+    )
+    .unwrap()
+});
+
+/// Count of value reads, the supplied layout of which did not match the layout stored with
+/// the exchanged value, detected when layout mismatch telemetry is enabled.
+pub static LAYOUT_MISMATCH_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_execution_layout_mismatch_count",
+        "Count of reads with a layout different from the layout of the stored value",
+        &["mode", "shard"]
+    )
+    .unwrap()
+});
+
+/// Count of pairs of captured reads across a writer boundary, detected by the parallel
+/// executions when snapshot consistency checks are enabled.
+pub static SNAPSHOT_INCONSISTENCY_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_execution_snapshot_inconsistency_count",
+        "Count of pairs of reads at versions inconsistent with each other in Block STM",
+        &["mode", "shard"]
+    )
+    .unwrap()
+});
+
+/// Count of group size reads of committed transactions that did not match the group size
+/// recomputed from the group contents, when the post-commit group size validation is enabled.
+pub static GROUP_SIZE_DISCREPANCY_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_execution_group_size_discrepancy_count",
+        "Count of committed group size reads inconsistent with the recomputed group sizes",
+        &["mode", "shard"]
+    )
+    .unwrap()
+});
+
+/// Count of transactions with a delayed field read that was no longer valid at commit.
+pub static DELAYED_FIELD_VALIDATION_FAILURES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_execution_delayed_field_validation_failures",
+        "Count of transactions with delayed field reads invalidated at commit in Block STM",
+        &["mode", "shard"]
+    )
+    .unwrap()
+});
+
+/// Count of dependency waits that were not resolved within the timeout of the scheduler.
+pub static DEPENDENCY_WAIT_TIMEOUT_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_execution_dependency_wait_timeout_count",
+        "Count of dependency waits that timed out in Block STM",
+        &["mode", "shard"]
+    )
+    .unwrap()
+});
+
+/// Count of speculative executions halted by the views of the transactions, per reason (see
+/// SpeculativeHaltReason).
+pub static SPECULATIVE_HALT_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_execution_speculative_halt_count",
+        "Count of speculative executions halted by the views in Block STM, per reason",
+        &["mode", "shard", "reason"]
+    )
+    .unwrap()
+});
+
 /// Count of speculative transaction re-executions due to a failed validation.
 pub static SPECULATIVE_ABORT_COUNT: Lazy<IntCounter> = Lazy::new(|| {
     register_int_counter!(
@@ -153,15 +337,46 @@ pub static TASK_EXECUTE_SECONDS: Lazy<Histogram> = Lazy::new(|| {
     .unwrap()
 });
 
-pub static DEPENDENCY_WAIT_SECONDS: Lazy<Histogram> = Lazy::new(|| {
-    register_histogram!(
+pub static DEPENDENCY_WAIT_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
         "aptos_execution_dependency_wait",
         "The time spent in waiting for dependency in Block STM",
+        &["mode", "shard"],
         time_buckets(),
     )
     .unwrap()
 });
 
+pub static VALUE_EXCHANGE_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "aptos_execution_value_exchange_seconds",
+        "The time spent in exchanging delayed field values and identifiers in a value, by type",
+        &["mode", "shard", "exchange_type"],
+        time_buckets(),
+    )
+    .unwrap()
+});
+
+pub static VALUE_EXCHANGE_BYTES: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "aptos_execution_value_exchange_bytes",
+        "Histogram for the sizes of the values with delayed fields exchanged, by type",
+        &["mode", "shard", "exchange_type"],
+        output_buckets(),
+    )
+    .unwrap()
+});
+
+pub static VALUE_EXCHANGE_GENERATED_IDENTIFIERS: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "aptos_execution_value_exchange_generated_identifiers",
+        "Histogram for the numbers of delayed field identifiers generated per value exchange",
+        &["mode", "shard"],
+        output_buckets(),
+    )
+    .unwrap()
+});
+
 pub static BLOCK_GAS: Lazy<HistogramVec> = Lazy::new(|| {
     register_histogram_vec!(
         "aptos_execution_block_gas",
@@ -213,6 +428,36 @@ pub static BLOCK_COMMITTED_TXNS: Lazy<HistogramVec> = Lazy::new(|| {
     .unwrap()
 });
 
+pub static TXN_READS: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "aptos_execution_txn_reads",
+        "Histogram for the numbers of reads and fetches of an executed txn, by type",
+        &["mode", "shard", "read_type"],
+        output_buckets(),
+    )
+    .unwrap()
+});
+
+pub static BLOCK_VIEW_HEALTH: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "aptos_execution_block_view_health",
+        "Histogram for the per-block statistics of the health of the views, by type",
+        &["mode", "shard", "stat"],
+        output_buckets(),
+    )
+    .unwrap()
+});
+
+pub static BLOCK_VIEW_DEPENDENCY_WAIT_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "aptos_execution_block_view_dependency_wait_seconds",
+        "The per-block time spent by the views in waiting for dependencies in Block STM",
+        &["mode", "shard"],
+        time_buckets(),
+    )
+    .unwrap()
+});
+
 pub static BLOCK_VIEW_DISTINCT_KEYS: Lazy<HistogramVec> = Lazy::new(|| {
     register_avg_counter_vec(
         "aptos_execution_block_view_distinct_keys",
@@ -294,6 +539,74 @@ pub(crate) fn update_txn_gas_counters(txn_fee_statements: &Vec<FeeStatement>, is
     }
 }
 
+pub(crate) fn update_txn_read_counters(
+    read_summary: &ReadSummary,
+    counter_labels: &ViewCounterLabels,
+) {
+    let [mode, shard] = counter_labels.values();
+    for (read_type, count) in [
+        (ReadType::RESOURCE_READS, read_summary.num_resource_reads),
+        (ReadType::GROUP_READS, read_summary.num_group_reads),
+        (ReadType::MODULE_READS, read_summary.num_module_reads),
+        (
+            ReadType::DELAYED_FIELD_READS,
+            read_summary.num_delayed_field_reads,
+        ),
+        (
+            ReadType::CAPTURED_READ_HITS,
+            read_summary.num_captured_read_hits,
+        ),
+        (ReadType::MAP_FETCHES, read_summary.num_map_fetches),
+        (
+            ReadType::BASE_VIEW_FETCHES,
+            read_summary.num_base_view_fetches,
+        ),
+    ] {
+        TXN_READS
+            .with_label_values(&[mode, shard, read_type])
+            .observe(count as f64);
+    }
+}
+
+pub(crate) fn update_view_health_counters(
+    summary: &ViewHealthSummary,
+    counter_labels: &ViewCounterLabels,
+) {
+    let [mode, shard] = counter_labels.values();
+    for reason in SpeculativeHaltReason::ALL {
+        BLOCK_VIEW_HEALTH
+            .with_label_values(&[
+                mode,
+                shard,
+                &format!("speculative_halts_{}", reason.as_str()),
+            ])
+            .observe(summary.num_speculative_halts(reason) as f64);
+    }
+    for (stat, value) in [
+        ("incorrect_uses", summary.num_incorrect_uses as u64),
+        ("dependency_waits", summary.num_dependency_waits as u64),
+        ("storage_fetches", summary.num_storage_fetches as u64),
+        ("storage_fetch_bytes", summary.storage_fetch_bytes),
+        ("exchanges", summary.num_exchanges as u64),
+        ("exchange_bytes", summary.exchange_bytes),
+        (
+            "group_initializations",
+            summary.num_group_initializations as u64,
+        ),
+        (
+            "minted_delayed_field_ids",
+            summary.num_minted_delayed_field_ids as u64,
+        ),
+    ] {
+        BLOCK_VIEW_HEALTH
+            .with_label_values(&[mode, shard, stat])
+            .observe(value as f64);
+    }
+    BLOCK_VIEW_DEPENDENCY_WAIT_SECONDS
+        .with_label_values(&counter_labels.values())
+        .observe(summary.dependency_wait_time.as_secs_f64());
+}
+
 pub(crate) fn update_state_counters(block_state_stats: BlockStateStats, is_parallel: bool) {
     let mode_str = if is_parallel {
         Mode::PARALLEL
@@ -321,3 +634,32 @@ pub(crate) fn update_state_counters(block_state_stats: BlockStateStats, is_paral
         .with_label_values(&[mode_str, "delayed_field"])
         .observe(block_state_stats.base_delayed_fields_size as f64);
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn view_counter_labels() {
+        assert_eq!(ViewCounterLabels::new(true, None).values(), [
+            Mode::PARALLEL,
+            Shard::NONE
+        ]);
+        assert_eq!(ViewCounterLabels::new(false, Some(0)).values(), [
+            Mode::SEQUENTIAL,
+            "0"
+        ]);
+        assert_eq!(
+            ViewCounterLabels::new(true, Some(Shard::MAX_LABELED_SHARD_ID - 1)).values(),
+            [Mode::PARALLEL, "63"]
+        );
+
+        // The shard label values are bounded.
+        for shard_id in [Shard::MAX_LABELED_SHARD_ID, usize::MAX] {
+            assert_eq!(ViewCounterLabels::new(false, Some(shard_id)).values(), [
+                Mode::SEQUENTIAL,
+                Shard::OTHER
+            ]);
+        }
+    }
+}
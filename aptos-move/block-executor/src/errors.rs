@@ -11,6 +11,10 @@ pub(crate) enum ParallelBlockExecutionError {
     // aborting the parallel execution pipeline and falling back to the sequential execution.
     // TODO: provide proper multi-versioning for code (like data) for the cache.
     ModulePathReadWriteError,
+    // A value read by a transaction exceeded the exchange size threshold, and may contain the
+    // delayed fields written by the transaction. Such values are not scanned in the parallel
+    // execution, which falls back to the sequential execution instead.
+    ExchangeSizeThresholdExceeded,
     /// unrecoverable VM error
     FatalVMError,
 }
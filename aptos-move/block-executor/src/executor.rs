@@ -3,21 +3,30 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    captured_reads::{CapturedReads, CapturedReadsPool, DelayedFieldValidation},
     counters,
     counters::{
-        PARALLEL_EXECUTION_SECONDS, RAYON_EXECUTION_SECONDS, TASK_EXECUTE_SECONDS,
-        TASK_VALIDATE_SECONDS, VM_INIT_SECONDS, WORK_WITH_TASK_SECONDS,
+        ViewCounterLabels, PARALLEL_EXECUTION_SECONDS, RAYON_EXECUTION_SECONDS,
+        TASK_EXECUTE_SECONDS, TASK_VALIDATE_SECONDS, VM_INIT_SECONDS, WORK_WITH_TASK_SECONDS,
     },
     errors::*,
     executor_utilities::*,
     explicit_sync_wrapper::ExplicitSyncWrapper,
+    key_interner::KeyInterner,
+    layout_mismatch::LayoutMismatchObserver,
     limit_processor::BlockGasLimitProcessor,
     scheduler::{DependencyStatus, ExecutionTaskType, Scheduler, SchedulerTask, Wave},
+    tag_layout_registry::TagLayoutRegistry,
     task::{ExecutionStatus, ExecutorTask, TransactionOutput},
     txn_commit_hook::TransactionCommitHook,
     txn_last_input_output::{KeyKind, TxnLastInputOutput},
     types::ReadWriteSummary,
-    view::{LatestView, ParallelState, SequentialState, ViewState},
+    value_exchange::{DefaultValueToIdentifierMappingFactory, ValueToIdentifierMappingFactory},
+    view::{
+        group_contents_size, LatestView, ParallelState, ParallelStateConfig, SequentialState,
+        SequentialStateRef, ViewState,
+    },
+    view_health::ViewHealthSummary,
 };
 use aptos_aggregator::{
     delayed_change::{ApplyBase, DelayedChange},
@@ -49,23 +58,36 @@ use core::panic;
 use fail::fail_point;
 use move_core_types::{value::MoveTypeLayout, vm_status::StatusCode};
 use num_cpus;
+use parking_lot::Mutex;
 use rayon::ThreadPool;
 use std::{
     cell::RefCell,
     collections::{BTreeMap, HashMap, HashSet},
     marker::{PhantomData, Sync},
     sync::{
-        atomic::{AtomicBool, AtomicU32, Ordering},
+        atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering},
         Arc,
     },
+    time::Duration,
 };
 
-pub struct BlockExecutor<T, E, S, L, X> {
+// The maximum number of validation failures that are described in the logs per block.
+const MAX_VALIDATION_FAILURE_LOGS_PER_BLOCK: usize = 16;
+
+pub struct BlockExecutor<T, E, S, L, X>
+where
+    T: Transaction,
+    S: TStateView<Key = T::Key>,
+    X: Executable,
+{
     // Number of active concurrent tasks, corresponding to the maximum number of rayon
     // threads that may be concurrently participating in parallel execution.
     config: BlockExecutorConfig,
     executor_thread_pool: Arc<ThreadPool>,
     transaction_commit_hook: Option<L>,
+    // Creates the mappings used by the views to exchange delayed field values with
+    // identifiers.
+    mapping_factory: Box<dyn ValueToIdentifierMappingFactory<T, S, X> + Send + Sync>,
     phantom: PhantomData<(T, E, S, L, X)>,
 }
 
@@ -93,10 +115,22 @@ where
             config,
             executor_thread_pool,
             transaction_commit_hook,
+            mapping_factory: Box::new(DefaultValueToIdentifierMappingFactory),
             phantom: PhantomData,
         }
     }
 
+    /// Replaces the factory of the mappings used by the views to exchange delayed field
+    /// values with identifiers (by default, all delayed field values are exchanged). A custom
+    /// factory must uphold the invariants documented on ValueToIdentifierMappingFactory.
+    pub fn with_mapping_factory(
+        mut self,
+        mapping_factory: Box<dyn ValueToIdentifierMappingFactory<T, S, X> + Send + Sync>,
+    ) -> Self {
+        self.mapping_factory = mapping_factory;
+        self
+    }
+
     fn execute(
         idx_to_execute: TxnIndex,
         incarnation: Incarnation,
@@ -106,13 +140,22 @@ where
         executor: &E,
         base_view: &S,
         latest_view: ParallelState<T, X>,
+        snapshot_consistency_checks: bool,
+        lift_aggregator_v1_values: bool,
+        view_health: &mut ViewHealthSummary,
+        captured_reads_pool: &CapturedReadsPool<T>,
+        mapping_factory: &dyn ValueToIdentifierMappingFactory<T, S, X>,
     ) -> Result<bool, PanicOr<ParallelBlockExecutionError>> {
         let _timer = TASK_EXECUTE_SECONDS.start_timer();
         let txn = &signature_verified_block[idx_to_execute as usize];
 
         // VM execution.
-        let sync_view = LatestView::new(base_view, ViewState::Sync(latest_view), idx_to_execute);
+        let sync_view = LatestView::new(base_view, ViewState::Sync(latest_view), idx_to_execute)
+            .with_mapping_factory(mapping_factory)
+            .with_lifted_aggregator_v1_values(lift_aggregator_v1_values);
         let execute_result = executor.execute_transaction(&sync_view, txn, idx_to_execute);
+        counters::update_txn_read_counters(&sync_view.read_summary(), sync_view.counter_labels());
+        view_health.merge(&sync_view.health_fragment());
 
         let mut prev_modified_keys = last_input_output
             .modified_keys(idx_to_execute)
@@ -122,7 +165,33 @@ where
             .delayed_field_keys(idx_to_execute)
             .map_or(HashSet::new(), |keys| keys.collect());
 
-        let mut read_set = sync_view.take_parallel_reads();
+        // The reads of the prior incarnation are replaced by the record, and may then be reused.
+        let prev_read_set = last_input_output.read_set(idx_to_execute);
+        let mut read_set = sync_view.take_parallel_reads()?;
+        // The delayed fields that remain minted are not referenced by the base values in the
+        // shared map (the ids of the installed patched values are untracked), and hence not by
+        // the output either. They are removed regardless of the status, which covers all the
+        // discarded executions (speculative aborts, halts, aborts and invariant errors).
+        sync_view.rollback_minted_delayed_fields()?;
+        if read_set.is_exchange_size_threshold_exceeded() {
+            // Values above the exchange size threshold are not scanned for the written delayed
+            // fields. This is an expected fallback behavior, no alert is required.
+            debug!(
+                "[Execution] At txn {}, exchange size threshold exceeded",
+                idx_to_execute
+            );
+            return Err(PanicOr::Or(
+                ParallelBlockExecutionError::ExchangeSizeThresholdExceeded,
+            ));
+        }
+        if snapshot_consistency_checks {
+            Self::report_snapshot_inconsistencies(
+                idx_to_execute,
+                &read_set,
+                last_input_output,
+                sync_view.counter_labels(),
+            );
+        }
 
         // For tracking whether the recent execution wrote outside of the previous write/delta set.
         let mut updates_outside = false;
@@ -297,6 +366,9 @@ where
                 ParallelBlockExecutionError::ModulePathReadWriteError,
             ));
         }
+        if let Some(prev_read_set) = prev_read_set {
+            captured_reads_pool.recycle(prev_read_set);
+        }
         Ok(updates_outside)
     }
 
@@ -323,13 +395,125 @@ where
         // (i.e. not re-execute unless some other part of the validation fails or
         // until commit, but mark as estimates).
 
-        // TODO: validate modules when there is no r/w fallback.
         Ok(
             read_set.validate_data_reads(versioned_cache.data(), idx_to_validate)
-                && read_set.validate_group_reads(versioned_cache.group_data(), idx_to_validate),
+                && read_set.validate_group_reads(versioned_cache.group_data(), idx_to_validate)
+                && read_set.validate_module_reads(versioned_cache.modules(), idx_to_validate),
         )
     }
 
+    /// Logs the description of the data read that failed the validation of the transaction,
+    /// up to MAX_VALIDATION_FAILURE_LOGS_PER_BLOCK times per block.
+    fn log_validation_failure(
+        txn_idx: TxnIndex,
+        incarnation: Incarnation,
+        last_input_output: &TxnLastInputOutput<T, E::Output, E::Error>,
+        versioned_cache: &MVHashMap<T::Key, T::Tag, T::Value, X, T::Identifier>,
+        num_validation_failure_logs: &AtomicUsize,
+    ) {
+        if num_validation_failure_logs.fetch_add(1, Ordering::Relaxed)
+            >= MAX_VALIDATION_FAILURE_LOGS_PER_BLOCK
+        {
+            return;
+        }
+
+        let read_set = last_input_output
+            .read_set(txn_idx)
+            .expect("[BlockSTM]: Prior read-set must be recorded");
+        if let Some(description) =
+            read_set.describe_invalid_data_read(versioned_cache.data(), txn_idx)
+        {
+            debug!(
+                "[BlockSTM]: Validation of txn {} (incarnation {}) failed, {}",
+                txn_idx, incarnation, description
+            );
+        }
+    }
+
+    // The keys written by a transaction are those of its latest recorded output, which may
+    // belong to a different incarnation than the version read (the check is diagnostic).
+    fn report_snapshot_inconsistencies(
+        txn_idx: TxnIndex,
+        read_set: &CapturedReads<T>,
+        last_input_output: &TxnLastInputOutput<T, E::Output, E::Error>,
+        counter_labels: &ViewCounterLabels,
+    ) {
+        let writes_by_txn = |writer: TxnIndex| {
+            last_input_output
+                .modified_keys(writer)
+                .map_or(HashSet::new(), |keys| keys.map(|(key, _)| key).collect())
+        };
+        for report in read_set.check_snapshot_consistency(&writes_by_txn) {
+            counters::SNAPSHOT_INCONSISTENCY_COUNT
+                .with_label_values(&counter_labels.values())
+                .inc();
+            debug!(
+                "[BlockSTM]: Txn {} read {:?} at version {:?}, but {:?} (also written by txn {}) at version {:?}",
+                txn_idx,
+                report.key,
+                report.version,
+                report.stale_key,
+                report.version.0,
+                report.stale_version
+            );
+        }
+    }
+
+    // Called for a committed transaction: all lower transactions are committed, hence the
+    // group contents at txn_idx are final. Expensive (serializes every group the size of which
+    // was read), for canaries only.
+    fn report_group_size_discrepancies(
+        txn_idx: TxnIndex,
+        last_input_output: &TxnLastInputOutput<T, E::Output, E::Error>,
+        versioned_cache: &MVHashMap<T::Key, T::Tag, T::Value, X, T::Identifier>,
+        counter_labels: &ViewCounterLabels,
+    ) {
+        let Some(read_set) = last_input_output.read_set(txn_idx) else {
+            return;
+        };
+        let recompute_size = |group_key: &T::Key| {
+            let members = versioned_cache
+                .group_data()
+                .fetch_latest_group(group_key, txn_idx)
+                .ok()?;
+            group_contents_size::<T>(members).ok()
+        };
+        for discrepancy in read_set.check_group_size_reads(&recompute_size) {
+            counters::GROUP_SIZE_DISCREPANCY_COUNT
+                .with_label_values(&counter_labels.values())
+                .inc();
+            alert!(
+                "[BlockSTM] Post-commit group size validation failed for txn {}, {}",
+                txn_idx,
+                discrepancy
+            );
+        }
+    }
+
+    // Starts the execution of the transaction with the still valid reads of its prior
+    // incarnation if reuse_prior_reads is set, and otherwise with recycled (empty) reads.
+    fn with_initial_reads<'a>(
+        &self,
+        parallel_state: ParallelState<'a, T, X>,
+        txn_idx: TxnIndex,
+        versioned_cache: &MVHashMap<T::Key, T::Tag, T::Value, X, T::Identifier>,
+        last_input_output: &TxnLastInputOutput<T, E::Output, E::Error>,
+        captured_reads_pool: &CapturedReadsPool<T>,
+    ) -> ParallelState<'a, T, X> {
+        let prior_reads = self
+            .config
+            .local
+            .opt_in
+            .reuse_prior_reads
+            .then(|| last_input_output.read_set(txn_idx))
+            .flatten();
+        match prior_reads {
+            Some(prior_reads) => parallel_state
+                .with_prior_reads(prior_reads.into_validated_subset(versioned_cache, txn_idx)),
+            None => parallel_state.with_recycled_reads(captured_reads_pool.acquire()),
+        }
+    }
+
     fn update_transaction_on_abort(
         txn_idx: TxnIndex,
         last_input_output: &TxnLastInputOutput<T, E::Output, E::Error>,
@@ -391,13 +575,28 @@ where
         txn_idx: TxnIndex,
         versioned_cache: &MVHashMap<T::Key, T::Tag, T::Value, X, T::Identifier>,
         last_input_output: &TxnLastInputOutput<T, E::Output, E::Error>,
+        counter_labels: &ViewCounterLabels,
     ) -> Result<bool, PanicError> {
         let read_set = last_input_output
             .read_set(txn_idx)
             .expect("Read set must be recorded");
 
-        let mut execution_still_valid =
-            read_set.validate_delayed_field_reads(versioned_cache.delayed_fields(), txn_idx)?;
+        let mut execution_still_valid = match read_set
+            .validate_delayed_field_reads_batched(versioned_cache.delayed_fields(), txn_idx)?
+        {
+            DelayedFieldValidation::Valid => true,
+            DelayedFieldValidation::SpeculativeFailure => false,
+            DelayedFieldValidation::Invalid(id) => {
+                counters::DELAYED_FIELD_VALIDATION_FAILURES
+                    .with_label_values(&counter_labels.values())
+                    .inc();
+                debug!(
+                    "[BlockSTM]: Delayed field {:?} read by txn {} is invalid at commit",
+                    id, txn_idx
+                );
+                false
+            },
+        };
 
         if execution_still_valid {
             if let Some(delayed_field_ids) = last_input_output.delayed_field_keys(txn_idx) {
@@ -438,13 +637,24 @@ where
         base_view: &S,
         start_shared_counter: u32,
         shared_counter: &AtomicU32,
+        key_interner: &KeyInterner<T::Key>,
+        tag_layout_registry: Option<&TagLayoutRegistry<T::Tag>>,
+        layout_mismatch_observer: Option<&LayoutMismatchObserver>,
         executor: &E,
         block: &[T],
+        view_health: &mut ViewHealthSummary,
+        captured_reads_pool: &CapturedReadsPool<T>,
     ) -> Result<(), PanicOr<ParallelBlockExecutionError>> {
         let mut block_limit_processor = shared_commit_state.acquire();
+        let counter_labels = ViewCounterLabels::new(true, self.config.local.shard_id);
 
         while let Some((txn_idx, incarnation)) = scheduler.try_commit() {
-            if !Self::validate_commit_ready(txn_idx, versioned_cache, last_input_output)? {
+            if !Self::validate_commit_ready(
+                txn_idx,
+                versioned_cache,
+                last_input_output,
+                &counter_labels,
+            )? {
                 // Transaction needs to be re-executed, one final time.
 
                 Self::update_transaction_on_abort(txn_idx, last_input_output, versioned_cache);
@@ -460,12 +670,27 @@ where
                     versioned_cache,
                     executor,
                     base_view,
-                    ParallelState::new(
+                    self.with_initial_reads(
+                        ParallelState::new(
+                            versioned_cache,
+                            scheduler,
+                            start_shared_counter,
+                            shared_counter,
+                            key_interner,
+                            tag_layout_registry,
+                            layout_mismatch_observer,
+                            ParallelStateConfig::new(&self.config.local),
+                        ),
+                        txn_idx,
                         versioned_cache,
-                        scheduler,
-                        start_shared_counter,
-                        shared_counter,
+                        last_input_output,
+                        captured_reads_pool,
                     ),
+                    self.config.local.opt_in.snapshot_consistency_checks,
+                    self.config.local.opt_in.lift_aggregator_v1_values,
+                    view_health,
+                    captured_reads_pool,
+                    self.mapping_factory.as_ref(),
                 )?;
 
                 scheduler.finish_execution_during_commit(txn_idx)?;
@@ -473,8 +698,13 @@ where
                 let validation_result =
                     Self::validate(txn_idx, last_input_output, versioned_cache)?;
                 if !validation_result
-                    || !Self::validate_commit_ready(txn_idx, versioned_cache, last_input_output)
-                        .unwrap_or(false)
+                    || !Self::validate_commit_ready(
+                        txn_idx,
+                        versioned_cache,
+                        last_input_output,
+                        &counter_labels,
+                    )
+                    .unwrap_or(false)
                 {
                     return Err(code_invariant_error(format!(
                         "Validation after re-execution failed for {} txn, validate() = {}",
@@ -642,6 +872,9 @@ where
         scheduler: &Scheduler,
         start_shared_counter: u32,
         shared_counter: &AtomicU32,
+        key_interner: &KeyInterner<T::Key>,
+        tag_layout_registry: Option<&TagLayoutRegistry<T::Tag>>,
+        layout_mismatch_observer: Option<&LayoutMismatchObserver>,
         last_input_output: &TxnLastInputOutput<T, E::Output, E::Error>,
         base_view: &S,
         final_results: &ExplicitSyncWrapper<Vec<E::Output>>,
@@ -651,8 +884,15 @@ where
             scheduler,
             start_shared_counter,
             shared_counter,
-        );
-        let latest_view = LatestView::new(base_view, ViewState::Sync(parallel_state), txn_idx);
+            key_interner,
+            tag_layout_registry,
+            layout_mismatch_observer,
+            ParallelStateConfig::new(&self.config.local),
+        )
+        // The materialization reads the committed values, and is never validated.
+        .with_capture_disabled();
+        let latest_view = LatestView::new(base_view, ViewState::Sync(parallel_state), txn_idx)
+            .with_mapping_factory(self.mapping_factory.as_ref());
         let finalized_groups = last_input_output.take_finalized_group(txn_idx);
         let materialized_finalized_groups =
             map_id_to_values_in_group_writes(finalized_groups, &latest_view)?;
@@ -705,6 +945,15 @@ where
             }
         }
 
+        if self.config.local.opt_in.post_commit_group_size_validation {
+            Self::report_group_size_discrepancies(
+                txn_idx,
+                last_input_output,
+                versioned_cache,
+                &ViewCounterLabels::new(true, self.config.local.shard_id),
+            );
+        }
+
         let mut final_results = final_results.acquire();
         match last_input_output.take_output(txn_idx) {
             ExecutionStatus::Success(t) | ExecutionStatus::SkipRest(t) => {
@@ -730,16 +979,25 @@ where
         base_view: &S,
         start_shared_counter: u32,
         shared_counter: &AtomicU32,
+        key_interner: &KeyInterner<T::Key>,
+        tag_layout_registry: Option<&TagLayoutRegistry<T::Tag>>,
+        layout_mismatch_observer: Option<&LayoutMismatchObserver>,
         shared_commit_state: &ExplicitSyncWrapper<BlockGasLimitProcessor<T>>,
         final_results: &ExplicitSyncWrapper<Vec<E::Output>>,
+        shared_view_health: &Mutex<ViewHealthSummary>,
+        num_validation_failure_logs: &AtomicUsize,
     ) -> Result<(), PanicOr<ParallelBlockExecutionError>> {
         // Make executor for each task. TODO: fast concurrent executor.
+        // The health of the views is accumulated locally, and merged once per worker.
         let init_timer = VM_INIT_SECONDS.start_timer();
         let executor = E::init(*executor_arguments);
         drop(init_timer);
 
         let _timer = WORK_WITH_TASK_SECONDS.start_timer();
         let mut scheduler_task = SchedulerTask::Retry;
+        let mut view_health = ViewHealthSummary::default();
+        // The captured reads of the replaced incarnations, reused by the executions.
+        let captured_reads_pool = CapturedReadsPool::new();
 
         let drain_commit_queue = || -> Result<(), PanicError> {
             while let Ok(txn_idx) = scheduler.pop_from_commit_queue() {
@@ -749,6 +1007,9 @@ where
                     scheduler,
                     start_shared_counter,
                     shared_counter,
+                    key_interner,
+                    tag_layout_registry,
+                    layout_mismatch_observer,
                     last_input_output,
                     base_view,
                     final_results,
@@ -769,8 +1030,13 @@ where
                     base_view,
                     start_shared_counter,
                     shared_counter,
+                    key_interner,
+                    tag_layout_registry,
+                    layout_mismatch_observer,
                     &executor,
                     block,
+                    &mut view_health,
+                    &captured_reads_pool,
                 )?;
                 scheduler.queueing_commits_mark_done();
             }
@@ -780,6 +1046,15 @@ where
             scheduler_task = match scheduler_task {
                 SchedulerTask::ValidationTask(txn_idx, incarnation, wave) => {
                     let valid = Self::validate(txn_idx, last_input_output, versioned_cache)?;
+                    if !valid {
+                        Self::log_validation_failure(
+                            txn_idx,
+                            incarnation,
+                            last_input_output,
+                            versioned_cache,
+                            num_validation_failure_logs,
+                        );
+                    }
                     Self::update_on_validation(
                         txn_idx,
                         incarnation,
@@ -803,12 +1078,27 @@ where
                         versioned_cache,
                         &executor,
                         base_view,
-                        ParallelState::new(
+                        self.with_initial_reads(
+                            ParallelState::new(
+                                versioned_cache,
+                                scheduler,
+                                start_shared_counter,
+                                shared_counter,
+                                key_interner,
+                                tag_layout_registry,
+                                layout_mismatch_observer,
+                                ParallelStateConfig::new(&self.config.local),
+                            ),
+                            txn_idx,
                             versioned_cache,
-                            scheduler,
-                            start_shared_counter,
-                            shared_counter,
+                            last_input_output,
+                            &captured_reads_pool,
                         ),
+                        self.config.local.opt_in.snapshot_consistency_checks,
+                        self.config.local.opt_in.lift_aggregator_v1_values,
+                        &mut view_health,
+                        &captured_reads_pool,
+                        self.mapping_factory.as_ref(),
                     )?;
                     scheduler.finish_execution(txn_idx, incarnation, updates_outside)?
                 },
@@ -828,6 +1118,7 @@ where
                 SchedulerTask::Retry => scheduler.next_task(),
                 SchedulerTask::Done => {
                     drain_commit_queue()?;
+                    shared_view_health.lock().merge(&view_health);
                     break Ok(());
                 },
             }
@@ -853,6 +1144,20 @@ where
         let versioned_cache = MVHashMap::new();
         let start_shared_counter = gen_id_start_value(false);
         let shared_counter = AtomicU32::new(start_shared_counter);
+        // Keys of the captured reads are interned per block, shared by all incarnations.
+        let key_interner = KeyInterner::new();
+        let tag_layout_registry = self
+            .config
+            .local
+            .opt_in
+            .eager_group_member_patching
+            .then(TagLayoutRegistry::new);
+        let layout_mismatch_observer = self
+            .config
+            .local
+            .opt_in
+            .layout_mismatch_telemetry
+            .then(LayoutMismatchObserver::new);
 
         if signature_verified_block.is_empty() {
             return Ok(BlockOutput::new(vec![]));
@@ -877,7 +1182,15 @@ where
         let num_txns = num_txns as u32;
 
         let last_input_output = TxnLastInputOutput::new(num_txns);
-        let scheduler = Scheduler::new(num_txns);
+        let mut scheduler = Scheduler::new(num_txns);
+        if let Some(timeout_ms) = self.config.local.opt_in.dependency_wait_timeout_ms {
+            scheduler = scheduler.with_dependency_wait_timeout(Duration::from_millis(timeout_ms));
+        }
+        if let Some(spin_count) = self.config.local.opt_in.dependency_wait_spin_count {
+            scheduler = scheduler.with_dependency_wait_spin_count(spin_count);
+        }
+        let shared_view_health = Mutex::new(ViewHealthSummary::default());
+        let num_validation_failure_logs = AtomicUsize::new(0);
 
         let timer = RAYON_EXECUTION_SECONDS.start_timer();
         self.executor_thread_pool.scope(|s| {
@@ -892,8 +1205,13 @@ where
                         base_view,
                         start_shared_counter,
                         &shared_counter,
+                        &key_interner,
+                        tag_layout_registry.as_ref(),
+                        layout_mismatch_observer.as_ref(),
                         &shared_commit_state,
                         &final_results,
+                        &shared_view_health,
+                        &num_validation_failure_logs,
                     ) {
                         // If there are multiple errors, they all get logged:
                         // ModulePathReadWriteError and FatalVMErrorvariant is logged at construction,
@@ -912,9 +1230,22 @@ where
         drop(timer);
 
         counters::update_state_counters(versioned_cache.stats(), true);
+        shared_view_health
+            .into_inner()
+            .finish_update_counters_and_log_info(&ViewCounterLabels::new(
+                true,
+                self.config.local.shard_id,
+            ));
 
         // Explicit async drops.
-        DEFAULT_DROPPER.schedule_drop((last_input_output, scheduler, versioned_cache));
+        DEFAULT_DROPPER.schedule_drop((
+            last_input_output,
+            scheduler,
+            versioned_cache,
+            key_interner,
+            tag_layout_registry,
+            layout_mismatch_observer,
+        ));
 
         // TODO add block end info to output.
         // block_limit_processor.is_block_limit_reached();
@@ -1013,7 +1344,20 @@ where
         let start_counter = gen_id_start_value(true);
         let counter = RefCell::new(start_counter);
         let unsync_map = UnsyncMap::new();
+        let tag_layout_registry = self
+            .config
+            .local
+            .opt_in
+            .eager_group_member_patching
+            .then(TagLayoutRegistry::new);
+        let layout_mismatch_observer = self
+            .config
+            .local
+            .opt_in
+            .layout_mismatch_telemetry
+            .then(LayoutMismatchObserver::new);
         let mut ret = Vec::with_capacity(num_txns);
+        let mut view_health = ViewHealthSummary::default();
         let mut block_limit_processor = BlockGasLimitProcessor::<T>::new(
             self.config.onchain.block_gas_limit_type.clone(),
             num_txns,
@@ -1022,14 +1366,50 @@ where
         let last_input_output: TxnLastInputOutput<T, E::Output, E::Error> =
             TxnLastInputOutput::new(num_txns as TxnIndex);
 
+        // A single sequential state is reset and lent to the view of every transaction.
+        let sequential_state = SequentialState::new(
+            &unsync_map,
+            start_counter,
+            &counter,
+            self.config.local.opt_in.group_size_consistency_checks,
+            tag_layout_registry.as_ref(),
+            layout_mismatch_observer.as_ref(),
+            self.config.local.shard_id,
+        );
+
         for (idx, txn) in signature_verified_block.iter().enumerate() {
+            sequential_state.reset();
             let latest_view = LatestView::<T, S, X>::new(
                 base_view,
-                ViewState::Unsync(SequentialState::new(&unsync_map, start_counter, &counter)),
+                ViewState::Unsync(SequentialStateRef::Borrowed(&sequential_state)),
                 idx as TxnIndex,
-            );
+            )
+            .with_mapping_factory(self.mapping_factory.as_ref())
+            .with_lifted_aggregator_v1_values(self.config.local.opt_in.lift_aggregator_v1_values);
             let res = executor.execute_transaction(&latest_view, txn, idx as TxnIndex);
+            counters::update_txn_read_counters(
+                &latest_view.read_summary(),
+                latest_view.counter_labels(),
+            );
+            view_health.merge(&latest_view.health_fragment());
             let must_skip = matches!(res, ExecutionStatus::SkipRest(_));
+            if !matches!(
+                res,
+                ExecutionStatus::Success(_) | ExecutionStatus::SkipRest(_)
+            ) {
+                // The execution failed, restore the base values it set in the unsync map (which
+                // also removes the delayed fields it generated).
+                if let Err(err) = latest_view
+                    .take_sequential_write_journal()
+                    .restore(&unsync_map)
+                {
+                    alert!(
+                        "Sequential execution failed to restore base values of transaction {}: {:?}",
+                        idx as TxnIndex,
+                        err
+                    );
+                }
+            }
             match res {
                 ExecutionStatus::Abort(err) => {
                     if let Some(commit_hook) = &self.transaction_commit_hook {
@@ -1085,7 +1465,7 @@ where
                                 } as u64
                         });
 
-                    let sequential_reads = latest_view.take_sequential_reads();
+                    let sequential_reads = latest_view.take_sequential_reads()?;
                     let read_write_summary = self
                         .config
                         .onchain
@@ -1276,6 +1656,10 @@ where
         ret.resize_with(num_txns, E::Output::skip_output);
 
         counters::update_state_counters(unsync_map.stats(), false);
+        view_health.finish_update_counters_and_log_info(&ViewCounterLabels::new(
+            false,
+            self.config.local.shard_id,
+        ));
 
         // TODO add block end info to output.
         // block_limit_processor.is_block_limit_reached();
@@ -223,18 +223,12 @@ pub(crate) fn map_id_to_values_events<
             if let Some(layout) = layout {
                 let event_data = event.get_event_data();
                 latest_view
-                    .replace_identifiers_with_values(&Bytes::from(event_data.to_vec()), &layout)
+                    .exchange_event_payload(&Bytes::from(event_data.to_vec()), &layout)
                     .map(|(bytes, _)| {
                         let mut patched_event = event;
                         patched_event.set_event_data(bytes.to_vec());
                         patched_event
                     })
-                    .map_err(|_| {
-                        code_invariant_error(format!(
-                            "Failed to replace identifiers with values in an event {:?}",
-                            layout
-                        ))
-                    })
             } else {
                 Ok(event)
             }
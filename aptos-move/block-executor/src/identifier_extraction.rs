@@ -0,0 +1,194 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{anyhow, bail, ensure};
+use move_binary_format::file_format_common::bcs_size_of_byte_array;
+use move_core_types::{account_address::AccountAddress, value::MoveTypeLayout};
+use move_vm_types::{
+    delayed_values::{
+        delayed_field_id::{DelayedFieldID, ExtractWidth},
+        derived_string_snapshot::is_derived_string_struct_layout,
+    },
+    value_serde::MAX_DELAYED_FIELDS_PER_RESOURCE,
+};
+use std::collections::HashSet;
+
+/// Returns the identifiers in the bytes serialized with the layout, where the delayed field
+/// values were exchanged with identifiers. The bytes are traversed according to the layout
+/// without constructing a Move value, and are accepted (or rejected) exactly as when they are
+/// deserialized with deserialize_and_allow_delayed_values and scanned for the identifiers.
+pub(crate) fn find_identifiers_in_bytes(
+    bytes: &[u8],
+    layout: &MoveTypeLayout,
+) -> anyhow::Result<HashSet<DelayedFieldID>> {
+    let mut reader = BcsReader { bytes };
+    let mut identifiers = HashSet::new();
+    reader.visit(layout, &mut identifiers)?;
+    ensure!(reader.bytes.is_empty(), "Remaining input after the value");
+    Ok(identifiers)
+}
+
+// Reads the bytes serialized with BCS, checking the encoding the same way as the BCS
+// deserializer does.
+struct BcsReader<'b> {
+    bytes: &'b [u8],
+}
+
+impl<'b> BcsReader<'b> {
+    fn next_bytes(&mut self, len: usize) -> anyhow::Result<&'b [u8]> {
+        ensure!(self.bytes.len() >= len, "Unexpected end of input");
+        let (next, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(next)
+    }
+
+    fn next_array<const N: usize>(&mut self) -> anyhow::Result<[u8; N]> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.next_bytes(N)?);
+        Ok(array)
+    }
+
+    // Only canonical ULEB128 encodings of lengths that fit into u32 are accepted, and the
+    // lengths are bounded by the maximum sequence length.
+    fn next_length(&mut self) -> anyhow::Result<usize> {
+        let mut value: u64 = 0;
+        for shift in (0..32).step_by(7) {
+            let byte = self.next_bytes(1)?[0];
+            let digit = byte & 0x7f;
+            value |= u64::from(digit) << shift;
+            if digit == byte {
+                ensure!(shift == 0 || digit != 0, "Non-canonical ULEB128 encoding");
+                let len = u32::try_from(value)
+                    .map_err(|_| anyhow!("Integer overflow during ULEB128 decoding"))?
+                    as usize;
+                ensure!(
+                    len <= bcs::MAX_SEQUENCE_LENGTH,
+                    "Exceeded the maximum sequence length: {}",
+                    len
+                );
+                return Ok(len);
+            }
+        }
+        bail!("Integer overflow during ULEB128 decoding")
+    }
+
+    fn next_byte_array(&mut self) -> anyhow::Result<&'b [u8]> {
+        let len = self.next_length()?;
+        self.next_bytes(len)
+    }
+
+    fn visit(
+        &mut self,
+        layout: &MoveTypeLayout,
+        identifiers: &mut HashSet<DelayedFieldID>,
+    ) -> anyhow::Result<()> {
+        use MoveTypeLayout as L;
+
+        match layout {
+            // Primitive types are skipped, but booleans are checked to be encoded canonically.
+            L::Bool => {
+                let byte = self.next_bytes(1)?[0];
+                ensure!(byte <= 1, "Invalid boolean encoding: {}", byte);
+            },
+            L::U8 => {
+                self.next_bytes(1)?;
+            },
+            L::U16 => {
+                self.next_bytes(2)?;
+            },
+            L::U32 => {
+                self.next_bytes(4)?;
+            },
+            L::U64 => {
+                self.next_bytes(8)?;
+            },
+            L::U128 => {
+                self.next_bytes(16)?;
+            },
+            L::U256 => {
+                self.next_bytes(32)?;
+            },
+            L::Address | L::Signer => {
+                self.next_bytes(AccountAddress::LENGTH)?;
+            },
+
+            L::Struct(struct_layout) => {
+                for field_layout in struct_layout.fields() {
+                    self.visit(field_layout, identifiers)?;
+                }
+            },
+
+            // Bytes are skipped as a whole, instead of byte by byte.
+            L::Vector(element_layout) => match element_layout.as_ref() {
+                L::U8 => {
+                    self.next_byte_array()?;
+                },
+                element_layout => {
+                    let len = self.next_length()?;
+                    for _ in 0..len {
+                        self.visit(element_layout, identifiers)?;
+                    }
+                },
+            },
+
+            L::Native(kind, layout) => {
+                let id = self.next_identifier(layout).map_err(|e| {
+                    anyhow!(
+                        "Identifier extraction failed for {:?} with layout {}: {}",
+                        kind,
+                        layout,
+                        e
+                    )
+                })?;
+                ensure!(
+                    identifiers.insert(id),
+                    "Duplicated identifier {:?} in the value",
+                    id
+                );
+                ensure!(
+                    identifiers.len() <= MAX_DELAYED_FIELDS_PER_RESOURCE,
+                    "Too many delayed fields in the value"
+                );
+            },
+        }
+        Ok(())
+    }
+
+    // Mirrors DelayedFieldID::try_from_move_value, reading the identifier from the serialized
+    // value of the native layout instead.
+    fn next_identifier(&mut self, layout: &MoveTypeLayout) -> anyhow::Result<DelayedFieldID> {
+        let (id, width) = match layout {
+            MoveTypeLayout::U64 => (
+                DelayedFieldID::from(u64::from_le_bytes(self.next_array()?)),
+                8,
+            ),
+            MoveTypeLayout::U128 => {
+                let value = u64::try_from(u128::from_le_bytes(self.next_array()?))
+                    .map_err(|_| anyhow!("Cannot cast u128 into u64"))?;
+                (DelayedFieldID::from(value), 16)
+            },
+            layout if is_derived_string_struct_layout(layout) => {
+                // The struct contains a string with the identifier, and the padding.
+                let bytes = self.next_byte_array()?;
+                let padding = self.next_byte_array()?;
+                let id = std::str::from_utf8(bytes)
+                    .ok()
+                    .and_then(|s| s.parse::<u64>().ok())
+                    .ok_or_else(|| anyhow!("Unable to parse the identifier string"))?;
+                (
+                    DelayedFieldID::from(id),
+                    bcs_size_of_byte_array(bytes.len()) + bcs_size_of_byte_array(padding.len()),
+                )
+            },
+            _ => bail!("Unsupported layout of an identifier"),
+        };
+
+        ensure!(
+            id.extract_width() as usize == width,
+            "Extracted identifier has a wrong width: id={:?}, width={}",
+            id,
+            width
+        );
+        Ok(id)
+    }
+}
@@ -0,0 +1,84 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use dashmap::{mapref::entry::Entry, DashMap};
+use std::{hash::Hash, sync::Arc};
+
+/// Hash-conses the keys accessed during a block execution, so that all reads of the same key
+/// (by different transactions and incarnations) share a single allocation, as opposed to
+/// cloning the key for every captured read. Keys like StateKey embed vectors, making clones
+/// a measurable cost across all the reads in a block.
+pub(crate) struct KeyInterner<K: Hash + Eq> {
+    keys: DashMap<Arc<K>, ()>,
+}
+
+impl<K: Hash + Eq + Clone> KeyInterner<K> {
+    pub(crate) fn new() -> Self {
+        Self {
+            keys: DashMap::new(),
+        }
+    }
+
+    /// Returns the shared pointer to the key, allocating it only on the first access.
+    pub(crate) fn intern(&self, key: &K) -> Arc<K> {
+        if let Some(entry) = self.keys.get(key) {
+            return entry.key().clone();
+        }
+
+        // The key may have been concurrently interned since the look-up above.
+        match self.keys.entry(Arc::new(key.clone())) {
+            Entry::Occupied(entry) => entry.key().clone(),
+            Entry::Vacant(entry) => {
+                let interned = entry.key().clone();
+                entry.insert(());
+                interned
+            },
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn num_keys(&self) -> usize {
+        self.keys.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rayon::prelude::*;
+
+    #[test]
+    fn intern_same_key() {
+        let interner = KeyInterner::new();
+        let key = vec![1_u8; 100];
+
+        let first = interner.intern(&key);
+        let second = interner.intern(&key.clone());
+        assert!(Arc::ptr_eq(&first, &second));
+        // Held by the interner, first and second.
+        assert_eq!(Arc::strong_count(&first), 3);
+
+        let other = interner.intern(&vec![2_u8; 100]);
+        assert!(!Arc::ptr_eq(&first, &other));
+        assert_eq!(Arc::strong_count(&other), 2);
+        assert_eq!(interner.num_keys(), 2);
+
+        drop(second);
+        assert_eq!(Arc::strong_count(&first), 2);
+    }
+
+    #[test]
+    fn intern_concurrently() {
+        let interner = KeyInterner::new();
+        let interned: Vec<Arc<u64>> = (0..1000_u64)
+            .into_par_iter()
+            .map(|i| interner.intern(&(i % 10)))
+            .collect();
+
+        assert_eq!(interner.num_keys(), 10);
+        for (i, key) in interned.iter().enumerate() {
+            assert_eq!(**key, i as u64 % 10);
+            assert!(Arc::ptr_eq(key, &interner.intern(&(i as u64 % 10))));
+        }
+    }
+}
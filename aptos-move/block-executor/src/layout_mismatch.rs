@@ -0,0 +1,73 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::counters::{self, ViewCounterLabels};
+use aptos_logger::{error, sample, sample::SampleRate};
+use move_core_types::value::MoveTypeLayout;
+use std::{fmt::Debug, sync::Arc, time::Duration};
+
+/// Compares the layouts supplied with the value reads against the layouts stored with the
+/// exchanged values, logging and counting the mismatches. Observe-only: the reads are served
+/// unchanged.
+pub(crate) struct LayoutMismatchObserver;
+
+impl LayoutMismatchObserver {
+    pub(crate) fn new() -> Self {
+        Self
+    }
+
+    /// Returns true if the layouts are structurally equal, and otherwise reports the mismatch.
+    pub(crate) fn observe<K: Debug>(
+        &self,
+        key: &K,
+        supplied_layout: &MoveTypeLayout,
+        stored_layout: &Arc<MoveTypeLayout>,
+        counter_labels: &ViewCounterLabels,
+    ) -> bool {
+        let layouts_match = std::ptr::eq(supplied_layout, stored_layout.as_ref())
+            || supplied_layout == stored_layout.as_ref();
+
+        if !layouts_match {
+            counters::LAYOUT_MISMATCH_COUNT
+                .with_label_values(&counter_labels.values())
+                .inc();
+            sample!(
+                SampleRate::Duration(Duration::from_secs(15)),
+                error!(
+                    "Layout supplied for the read of {:?} does not match the stored layout: {:?} vs {:?}",
+                    key, supplied_layout, stored_layout
+                )
+            );
+        }
+        layouts_match
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn observe_structurally() {
+        let observer = LayoutMismatchObserver::new();
+        let counter_labels = ViewCounterLabels::new(true, Some(7));
+        let stored_layout = Arc::new(MoveTypeLayout::U64);
+        let supplied_layout = MoveTypeLayout::U64;
+        let other_layout = MoveTypeLayout::U128;
+        let mismatch_count = || {
+            counters::LAYOUT_MISMATCH_COUNT
+                .with_label_values(&counter_labels.values())
+                .get()
+        };
+        let initial_mismatch_count = mismatch_count();
+
+        assert!(observer.observe(&1, stored_layout.as_ref(), &stored_layout, &counter_labels));
+        assert!(observer.observe(&1, &supplied_layout, &stored_layout, &counter_labels));
+        assert!(observer.observe(&2, &supplied_layout, &stored_layout, &counter_labels));
+        assert_eq!(mismatch_count(), initial_mismatch_count);
+
+        assert!(!observer.observe(&1, &other_layout, &stored_layout, &counter_labels));
+        assert!(!observer.observe(&1, &other_layout, &stored_layout, &counter_labels));
+        assert_eq!(mismatch_count(), initial_mismatch_count + 2);
+    }
+}
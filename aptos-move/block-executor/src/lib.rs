@@ -145,15 +145,20 @@ pub mod errors;
 pub mod executor;
 mod executor_utilities;
 pub mod explicit_sync_wrapper;
+mod identifier_extraction;
+mod key_interner;
+mod layout_mismatch;
 mod limit_processor;
 #[cfg(any(test, feature = "fuzzing"))]
 pub mod proptest_types;
 mod scheduler;
+mod tag_layout_registry;
 pub mod task;
 pub mod txn_commit_hook;
 pub mod txn_last_input_output;
 pub mod types;
 #[cfg(test)]
 mod unit_tests;
-mod value_exchange;
+pub mod value_exchange;
 pub mod view;
+mod view_health;
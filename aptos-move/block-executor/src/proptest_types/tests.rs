@@ -8,16 +8,16 @@ use crate::{
     proptest_types::{
         baseline::BaselineOutput,
         types::{
-            DeltaDataView, EmptyDataView, KeyType, MockEvent, MockOutput, MockTask,
-            MockTransaction, NonEmptyGroupDataView, TransactionGen, TransactionGenParams,
-            MAX_GAS_PER_TXN,
+            assert_state_value_round_trip, DeltaDataView, EmptyDataView, KeyType, MockEvent,
+            MockOutput, MockTask, MockTransaction, NonEmptyGroupDataView, TransactionGen,
+            TransactionGenParams, ValueType, MAX_GAS_PER_TXN,
         },
     },
     txn_commit_hook::NoOpTransactionCommitHook,
 };
 use aptos_types::{
     block_executor::config::BlockExecutorConfig, contract_event::TransactionEvent,
-    executable::ExecutableTestType,
+    executable::ExecutableTestType, write_set::WriteOp,
 };
 use claims::{assert_matches, assert_ok};
 use num_cpus;
@@ -577,6 +577,12 @@ fn non_empty_group(
     }
 }
 
+#[test]
+fn state_value_round_trip() {
+    assert_state_value_round_trip::<ValueType>();
+    assert_state_value_round_trip::<WriteOp>();
+}
+
 #[test]
 fn dynamic_read_writes() {
     dynamic_read_writes_with_block_gas_limit(3000, None);
@@ -592,6 +598,56 @@ fn deltas_resolver() {
     deltas_resolver_with_block_gas_limit(1000, None);
 }
 
+#[test]
+fn deltas_writes_mixed_with_reused_prior_reads() {
+    let mut runner = TestRunner::default();
+
+    // A small universe, so that the transactions are re-executed.
+    let universe = vec(any::<[u8; 32]>(), 10)
+        .new_tree(&mut runner)
+        .expect("creating a new value should succeed")
+        .current();
+    let transaction_gen = vec(
+        any_with::<TransactionGen<[u8; 32]>>(TransactionGenParams::new_dynamic()),
+        1000,
+    )
+    .new_tree(&mut runner)
+    .expect("creating a new value should succeed")
+    .current();
+
+    // Do not allow deletions as resolver can't apply delta to a deleted aggregator.
+    let transactions: Vec<_> = transaction_gen
+        .into_iter()
+        .map(|txn_gen| txn_gen.materialize_with_deltas(&universe, 15, false))
+        .collect();
+
+    let data_view = DeltaDataView::<KeyType<[u8; 32]>> {
+        phantom: PhantomData,
+    };
+
+    let executor_thread_pool = Arc::new(
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(num_cpus::get())
+            .build()
+            .unwrap(),
+    );
+
+    let mut config = BlockExecutorConfig::new_no_block_limit(num_cpus::get());
+    config.local.opt_in.reuse_prior_reads = true;
+    for _ in 0..20 {
+        let output = BlockExecutor::<
+            MockTransaction<KeyType<[u8; 32]>, MockEvent>,
+            MockTask<KeyType<[u8; 32]>, MockEvent>,
+            DeltaDataView<KeyType<[u8; 32]>>,
+            NoOpTransactionCommitHook<MockOutput<KeyType<[u8; 32]>, MockEvent>, usize>,
+            ExecutableTestType,
+        >::new(config.clone(), executor_thread_pool.clone(), None)
+        .execute_transactions_parallel((), &transactions, &data_view);
+
+        BaselineOutput::generate(&transactions, None).assert_parallel_output(&output);
+    }
+}
+
 #[test]
 fn dynamic_read_writes_contended() {
     dynamic_read_writes_contended_with_block_gas_limit(1000, None);
@@ -37,6 +37,7 @@ use move_vm_types::delayed_values::delayed_field_id::DelayedFieldID;
 use once_cell::sync::OnceCell;
 use proptest::{arbitrary::Arbitrary, collection::vec, prelude::*, proptest, sample::Index};
 use proptest_derive::Arbitrary;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::{
     collections::{hash_map::DefaultHasher, BTreeMap, BTreeSet, HashMap, HashSet},
     convert::TryInto,
@@ -142,7 +143,7 @@ where
 // Generation of transactions
 ///////////////////////////////////////////////////////////////////////////
 
-#[derive(Clone, Copy, Hash, Debug, PartialEq, PartialOrd, Ord, Eq)]
+#[derive(Clone, Copy, Hash, Debug, PartialEq, PartialOrd, Ord, Eq, Serialize, Deserialize)]
 pub(crate) struct KeyType<K: Hash + Clone + Debug + PartialOrd + Ord + Eq>(
     /// Wrapping the types used for testing to add ModulePath trait implementation (below).
     pub K,
@@ -190,6 +191,20 @@ impl Clone for ValueType {
     }
 }
 
+// Serialized as the (bytes, metadata, kind) triple, as the kind is behind a sync wrapper.
+impl Serialize for ValueType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (&self.bytes, &self.metadata, self.write_op_kind()).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ValueType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (bytes, metadata, kind) = Deserialize::deserialize(deserializer)?;
+        Ok(ValueType::new(bytes, metadata, kind))
+    }
+}
+
 impl Arbitrary for ValueType {
     type Parameters = ();
     type Strategy = BoxedStrategy<Self>;
@@ -894,10 +909,7 @@ where
                 for (key, inner_ops) in behavior.group_writes.iter() {
                     let mut new_inner_ops = HashMap::new();
                     for (tag, inner_op) in inner_ops.iter() {
-                        let exists = view
-                            .get_resource_from_group(key, tag, None)
-                            .unwrap()
-                            .is_some();
+                        let exists = view.resource_exists_in_group(key, tag).unwrap();
 
                         // inner op is either deletion or creation.
                         assert!(!inner_op.is_modification());
@@ -980,6 +992,29 @@ pub(crate) fn raw_metadata(v: u64) -> StateValueMetadata {
     StateValueMetadata::legacy(v, &CurrentTimeMicroseconds { microseconds: v })
 }
 
+/// Checks that converting state values to V and back preserves the bytes and the metadata,
+/// which the block executor relies on when it creates sentinel ops from storage values (e.g.
+/// for the members of resource groups). Can be run against any TransactionWrite implementation.
+#[cfg(test)]
+pub(crate) fn assert_state_value_round_trip<V: TransactionWrite>() {
+    let state_values = [
+        StateValue::new_legacy(Bytes::new()),
+        StateValue::new_legacy(Bytes::from(vec![1, 2, 3])),
+        StateValue::new_with_metadata(Bytes::from(vec![5; 100]), raw_metadata(5)),
+    ];
+
+    for state_value in state_values {
+        let op = V::from_state_value(Some(state_value.clone()));
+        assert_eq!(op.bytes(), Some(state_value.bytes()));
+        assert_eq!(op.extract_raw_bytes().as_ref(), Some(state_value.bytes()));
+        assert_eq!(op.as_state_value(), Some(state_value));
+    }
+
+    let deletion = V::from_state_value(None);
+    assert!(deletion.is_deletion());
+    assert_eq!(deletion.as_state_value(), None);
+}
+
 #[derive(Debug)]
 pub(crate) struct MockOutput<K, E> {
     pub(crate) writes: Vec<(K, ValueType)>,
@@ -16,6 +16,7 @@ use std::{
         atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
         Arc, Condvar,
     },
+    time::Duration,
 };
 
 const TXN_IDX_MASK: u64 = (1 << 32) - 1;
@@ -252,6 +253,18 @@ pub trait TWaitForDependency {
         txn_idx: TxnIndex,
         dep_txn_idx: TxnIndex,
     ) -> Result<DependencyResult, PanicError>;
+
+    /// Time to wait on an unresolved dependency before giving up, if any (by default, the wait
+    /// is not bounded).
+    fn dependency_wait_timeout(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Number of times to poll the status of an unresolved dependency before blocking (by
+    /// default, the status is not polled).
+    fn dependency_wait_spin_count(&self) -> usize {
+        0
+    }
 }
 
 pub struct Scheduler {
@@ -300,6 +313,10 @@ pub struct Scheduler {
     queueing_commits_lock: CachePadded<ArmedLock>,
 
     commit_queue: ConcurrentQueue<u32>,
+
+    dependency_wait_timeout: Option<Duration>,
+
+    dependency_wait_spin_count: usize,
 }
 
 /// Public Interfaces for the Scheduler
@@ -328,9 +345,28 @@ impl Scheduler {
             has_halted: CachePadded::new(AtomicBool::new(false)),
             queueing_commits_lock: CachePadded::new(ArmedLock::new()),
             commit_queue: ConcurrentQueue::<u32>::bounded(num_txns as usize),
+            dependency_wait_timeout: None,
+            dependency_wait_spin_count: 0,
         }
     }
 
+    /// Sets the time to wait on an unresolved dependency, after which the waiting transaction
+    /// halts its speculative execution (by default, the wait is not bounded). Dependencies are
+    /// resolved much sooner in normal operation, so a timeout indicates that the block execution
+    /// would otherwise hang (e.g. due to a bug).
+    pub fn with_dependency_wait_timeout(mut self, dependency_wait_timeout: Duration) -> Self {
+        self.dependency_wait_timeout = Some(dependency_wait_timeout);
+        self
+    }
+
+    /// Sets the number of times to poll the status of an unresolved dependency before blocking
+    /// on it (by default, the status is not polled). Short dependencies are often resolved in
+    /// the meantime, which avoids parking (and waking up) the waiting thread.
+    pub fn with_dependency_wait_spin_count(mut self, dependency_wait_spin_count: usize) -> Self {
+        self.dependency_wait_spin_count = dependency_wait_spin_count;
+        self
+    }
+
     pub fn num_txns(&self) -> TxnIndex {
         self.num_txns
     }
@@ -703,6 +739,14 @@ impl TWaitForDependency for Scheduler {
 
         Ok(DependencyResult::Dependency(dep_condvar))
     }
+
+    fn dependency_wait_timeout(&self) -> Option<Duration> {
+        self.dependency_wait_timeout
+    }
+
+    fn dependency_wait_spin_count(&self) -> usize {
+        self.dependency_wait_spin_count
+    }
 }
 
 /// Private functions of the Scheduler
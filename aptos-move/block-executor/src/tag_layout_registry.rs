@@ -0,0 +1,58 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use dashmap::DashMap;
+use move_core_types::value::MoveTypeLayout;
+use std::{hash::Hash, sync::Arc};
+
+/// Records the layouts of resource group members (by tag), as observed by the reads during
+/// a block execution. Used for eager patching of group members: when a group is initialized
+/// from storage, the members with a known layout can be exchanged up front, instead of by
+/// the first read of each tag.
+pub(crate) struct TagLayoutRegistry<T: Hash + Eq> {
+    // None layout is recorded for tags that were read without a layout (no delayed fields).
+    layouts: DashMap<T, Option<Arc<MoveTypeLayout>>>,
+}
+
+impl<T: Hash + Eq + Clone> TagLayoutRegistry<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            layouts: DashMap::new(),
+        }
+    }
+
+    /// Records the layout of the tag, unless it was previously recorded: the layout of a
+    /// tag is determined by its type, and hence may not change during the block.
+    pub(crate) fn record(&self, tag: &T, maybe_layout: Option<&MoveTypeLayout>) {
+        if !self.layouts.contains_key(tag) {
+            self.layouts
+                .entry(tag.clone())
+                .or_insert_with(|| maybe_layout.cloned().map(Arc::new));
+        }
+    }
+
+    /// Returns the recorded layout of the tag, or None if the tag has not been recorded.
+    pub(crate) fn get(&self, tag: &T) -> Option<Option<Arc<MoveTypeLayout>>> {
+        self.layouts.get(tag).map(|layout| layout.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use claims::{assert_none, assert_some_eq};
+
+    #[test]
+    fn record_once() {
+        let registry = TagLayoutRegistry::new();
+        assert_none!(registry.get(&1_u32));
+
+        registry.record(&1, Some(&MoveTypeLayout::U64));
+        registry.record(&1, Some(&MoveTypeLayout::U128));
+        registry.record(&2, None);
+
+        assert_some_eq!(registry.get(&1), Some(Arc::new(MoveTypeLayout::U64)));
+        assert_some_eq!(registry.get(&2), None);
+        assert_none!(registry.get(&3));
+    }
+}
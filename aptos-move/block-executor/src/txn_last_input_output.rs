@@ -141,9 +141,10 @@ impl<T: Transaction, O: TransactionOutput<Txn = T>, E: Debug + Send + Clone>
             | ExecutionStatus::DelayedFieldsCodeInvariantError(_) => BTreeMap::new(),
         };
 
-        if self
-            .check_and_append_module_rw_conflict(input.module_reads.iter(), written_modules.keys())
-        {
+        if self.check_and_append_module_rw_conflict(
+            input.module_reads.iter().map(|read| read.key.as_ref()),
+            written_modules.keys(),
+        ) {
             return false;
         }
 
@@ -1,7 +1,11 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::view::{LatestView, ViewState};
+use crate::{
+    counters::{self, ExchangeType, ViewCounterLabels},
+    identifier_extraction::find_identifiers_in_bytes,
+    view::{LatestView, ViewState},
+};
 use aptos_aggregator::{
     resolver::TDelayedFieldView,
     types::{code_invariant_error, DelayedFieldValue, ReadPosition},
@@ -16,14 +20,133 @@ use aptos_types::{
 };
 use bytes::Bytes;
 use move_binary_format::errors::PartialVMResult;
-use move_core_types::value::{IdentifierMappingKind, MoveTypeLayout};
+use move_core_types::value::{IdentifierMappingKind, MoveStructLayout, MoveTypeLayout};
 use move_vm_types::{
     delayed_values::delayed_field_id::{ExtractWidth, TryFromMoveValue},
-    value_serde::{deserialize_and_allow_delayed_values, ValueToIdentifierMapping},
-    value_traversal::find_identifiers_in_value,
+    value_serde::ValueToIdentifierMapping,
     values::Value,
 };
-use std::{cell::RefCell, collections::HashSet, sync::Arc};
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    hash::Hash,
+    sync::Arc,
+};
+
+/// Creates the mappings used by the view to exchange delayed field values with identifiers
+/// (when patching base values, and when replacing identifiers with values), allowing to
+/// experiment with alternative exchange policies. A custom mapping must uphold:
+///   1. An identifier returned by value_to_identifier must be registered, with the value it
+///      replaces, in the delayed field map of the view (see set_delayed_field_value). Other
+///      transactions, as well as materialization, resolve the identifiers via the map.
+///   2. Exchange preserves the serialized length: the identifier has the width of the value
+///      it replaces, and identifier_to_value returns a value of the identifier's width.
+pub trait ValueToIdentifierMappingFactory<
+    T: Transaction,
+    S: TStateView<Key = T::Key>,
+    X: Executable,
+>
+{
+    fn make_mapping<'a>(
+        &self,
+        view: &'a LatestView<'a, T, S, X>,
+        txn_idx: TxnIndex,
+    ) -> Box<dyn ValueToIdentifierMapping<Identifier = T::Identifier> + 'a>;
+
+    /// Creates the mapping used to replace the identifiers in a resource with values, given
+    /// the committed values of all identifiers in the resource (read in a single batch). By
+    /// default, the provided values are ignored, and the mapping reads the values one by one.
+    fn make_mapping_with_committed_values<'a>(
+        &self,
+        view: &'a LatestView<'a, T, S, X>,
+        txn_idx: TxnIndex,
+        committed_values: HashMap<T::Identifier, DelayedFieldValue>,
+    ) -> Box<dyn ValueToIdentifierMapping<Identifier = T::Identifier> + 'a> {
+        let _ = committed_values;
+        self.make_mapping(view, txn_idx)
+    }
+
+    /// Returns the layout according to which the values are exchanged. The values at the
+    /// native layouts that are replaced by their inner layouts are not exchanged, and remain
+    /// in place. The VM must not treat such values as delayed.
+    fn exchange_layout<'l>(&self, layout: &'l MoveTypeLayout) -> Cow<'l, MoveTypeLayout> {
+        Cow::Borrowed(layout)
+    }
+}
+
+/// Exchanges all delayed field values, via TemporaryValueToIdentifierMapping.
+pub struct DefaultValueToIdentifierMappingFactory;
+
+impl<T: Transaction, S: TStateView<Key = T::Key>, X: Executable>
+    ValueToIdentifierMappingFactory<T, S, X> for DefaultValueToIdentifierMappingFactory
+{
+    fn make_mapping<'a>(
+        &self,
+        view: &'a LatestView<'a, T, S, X>,
+        txn_idx: TxnIndex,
+    ) -> Box<dyn ValueToIdentifierMapping<Identifier = T::Identifier> + 'a> {
+        Box::new(TemporaryValueToIdentifierMapping::new(view, txn_idx))
+    }
+
+    fn make_mapping_with_committed_values<'a>(
+        &self,
+        view: &'a LatestView<'a, T, S, X>,
+        txn_idx: TxnIndex,
+        committed_values: HashMap<T::Identifier, DelayedFieldValue>,
+    ) -> Box<dyn ValueToIdentifierMapping<Identifier = T::Identifier> + 'a> {
+        Box::new(
+            TemporaryValueToIdentifierMapping::new(view, txn_idx)
+                .with_committed_values(committed_values),
+        )
+    }
+}
+
+/// Wraps a mapping, recording the delayed field identifiers that were touched when utilizing
+/// it to replace ids with values or values with ids.
+pub(crate) struct RecordingValueToIdentifierMapping<'a, I> {
+    mapping: Box<dyn ValueToIdentifierMapping<Identifier = I> + 'a>,
+    delayed_field_ids: RefCell<HashSet<I>>,
+}
+
+impl<'a, I: Hash + Eq + Copy> RecordingValueToIdentifierMapping<'a, I> {
+    pub fn new(mapping: Box<dyn ValueToIdentifierMapping<Identifier = I> + 'a>) -> Self {
+        Self {
+            mapping,
+            delayed_field_ids: RefCell::new(HashSet::new()),
+        }
+    }
+
+    pub fn into_inner(self) -> HashSet<I> {
+        self.delayed_field_ids.into_inner()
+    }
+}
+
+impl<'a, I: Hash + Eq + Copy> ValueToIdentifierMapping
+    for RecordingValueToIdentifierMapping<'a, I>
+{
+    type Identifier = I;
+
+    fn value_to_identifier(
+        &self,
+        kind: &IdentifierMappingKind,
+        layout: &MoveTypeLayout,
+        value: Value,
+    ) -> PartialVMResult<Self::Identifier> {
+        let id = self.mapping.value_to_identifier(kind, layout, value)?;
+        self.delayed_field_ids.borrow_mut().insert(id);
+        Ok(id)
+    }
+
+    fn identifier_to_value(
+        &self,
+        layout: &MoveTypeLayout,
+        identifier: Self::Identifier,
+    ) -> PartialVMResult<Value> {
+        self.delayed_field_ids.borrow_mut().insert(identifier);
+        self.mapping.identifier_to_value(layout, identifier)
+    }
+}
 
 pub(crate) struct TemporaryValueToIdentifierMapping<
     'a,
@@ -33,9 +156,9 @@ pub(crate) struct TemporaryValueToIdentifierMapping<
 > {
     latest_view: &'a LatestView<'a, T, S, X>,
     txn_idx: TxnIndex,
-    // These are the delayed field keys that were touched when utilizing this mapping
-    // to replace ids with values or values with ids
-    delayed_field_ids: RefCell<HashSet<T::Identifier>>,
+    // The committed values of the identifiers, if read ahead of replacing the identifiers
+    // with values. The values of other identifiers are read from the view.
+    committed_values: HashMap<T::Identifier, DelayedFieldValue>,
 }
 
 impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable>
@@ -45,16 +168,20 @@ impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable>
         Self {
             latest_view,
             txn_idx,
-            delayed_field_ids: RefCell::new(HashSet::new()),
+            committed_values: HashMap::new(),
         }
     }
 
-    fn generate_delayed_field_id(&self, width: u32) -> T::Identifier {
-        self.latest_view.generate_delayed_field_id(width)
+    pub fn with_committed_values(
+        mut self,
+        committed_values: HashMap<T::Identifier, DelayedFieldValue>,
+    ) -> Self {
+        self.committed_values = committed_values;
+        self
     }
 
-    pub fn into_inner(self) -> HashSet<T::Identifier> {
-        self.delayed_field_ids.into_inner()
+    fn generate_delayed_field_id(&self, width: u32) -> Result<T::Identifier, PanicError> {
+        self.latest_view.generate_delayed_field_id(width)
     }
 }
 
@@ -73,12 +200,11 @@ impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> ValueToIden
         value: Value,
     ) -> PartialVMResult<Self::Identifier> {
         let (base_value, width) = DelayedFieldValue::try_from_move_value(layout, value, kind)?;
-        let id = self.generate_delayed_field_id(width);
+        let id = self.generate_delayed_field_id(width)?;
         match &self.latest_view.latest_view {
             ViewState::Sync(state) => state.set_delayed_field_value(id, base_value),
             ViewState::Unsync(state) => state.set_delayed_field_value(id, base_value),
         };
-        self.delayed_field_ids.borrow_mut().insert(id);
         Ok(id)
     }
 
@@ -87,7 +213,12 @@ impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> ValueToIden
         layout: &MoveTypeLayout,
         identifier: Self::Identifier,
     ) -> PartialVMResult<Value> {
-        self.delayed_field_ids.borrow_mut().insert(identifier);
+        if let Some(delayed_field) = self.committed_values.get(&identifier) {
+            return delayed_field
+                .clone()
+                .try_into_move_value(layout, identifier.extract_width());
+        }
+
         let delayed_field = match &self.latest_view.latest_view {
             ViewState::Sync(state) => state
                 .versioned_map
@@ -106,35 +237,148 @@ impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> ValueToIden
     }
 }
 
+/// Describes whether the delayed field values of a resource read by a transaction were
+/// exchanged with identifiers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExchangeStatus<I> {
+    /// The resource was not read as a value with a layout (or does not exist).
+    NotApplicable,
+    /// The resource was read with a layout, but no values were exchanged (e.g. the layout
+    /// had no delayed fields, or the exchange layout of the mapping factory retained none).
+    LayoutHadNoDelayedFields,
+    /// The values were exchanged with the given identifiers.
+    Exchanged { ids: BTreeSet<I> },
+    /// The resource was read, but the view is not capable of the delayed field optimization,
+    /// so the value was read as is and nothing was exchanged.
+    SkippedCapabilityOff,
+    /// The resource was read with a layout, but the value exceeds the exchange size threshold,
+    /// so it is not scanned for the delayed fields written by the transaction.
+    SkippedQuarantined,
+}
+
+impl<I: Ord> ExchangeStatus<I> {
+    /// Returns the status of an exchanged value (None for deletions) read with the layout.
+    pub(crate) fn from_exchanged_value<T: Transaction<Identifier = I>>(
+        value: &T::Value,
+        exchange_layout: &MoveTypeLayout,
+        counter_labels: &ViewCounterLabels,
+    ) -> Result<Self, PanicError> {
+        let Some(bytes) = value.bytes() else {
+            return Ok(ExchangeStatus::NotApplicable);
+        };
+
+        let ids: BTreeSet<_> =
+            extract_identifiers_from_value::<T>(bytes, exchange_layout, counter_labels)
+                .map_err(|e| {
+                    code_invariant_error(format!("Identifier extraction failed with {:?}", e))
+                })?
+                .into_iter()
+                .collect();
+        Ok(if ids.is_empty() {
+            ExchangeStatus::LayoutHadNoDelayedFields
+        } else {
+            ExchangeStatus::Exchanged { ids }
+        })
+    }
+}
+
+/// Returns true if the layout contains a delayed field (i.e. a native layout). Values read
+/// with a layout that does not contain delayed fields are exchanged as is, without the
+/// deserialization-serialization round-trip.
+pub(crate) fn layout_contains_delayed_field(layout: &MoveTypeLayout) -> bool {
+    use MoveTypeLayout::*;
+
+    match layout {
+        Native(_, _) => true,
+        Vector(element_layout) => layout_contains_delayed_field(element_layout),
+        Struct(MoveStructLayout::Runtime(field_layouts)) => {
+            field_layouts.iter().any(layout_contains_delayed_field)
+        },
+        Struct(MoveStructLayout::WithFields(fields))
+        | Struct(MoveStructLayout::WithTypes { fields, .. }) => fields
+            .iter()
+            .any(|field| layout_contains_delayed_field(&field.layout)),
+        Bool | U8 | U16 | U32 | U64 | U128 | U256 | Address | Signer => false,
+    }
+}
+
+/// Memoizes layout_contains_delayed_field by the pointer of the layout, so that the values
+/// read with a layout without delayed fields are not traversed when scanned for the ids.
+/// The layouts are shared by the reads of the same type, so most scans hit the cache.
+pub(crate) struct DelayedFieldLayoutCache {
+    // The layout is held by the entry, so that its address is not reused (by a different
+    // layout) while the entry exists.
+    layouts: RefCell<HashMap<usize, (Arc<MoveTypeLayout>, bool)>>,
+}
+
+impl DelayedFieldLayoutCache {
+    pub(crate) fn new() -> Self {
+        Self {
+            layouts: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn contains_delayed_field(&self, layout: &Arc<MoveTypeLayout>) -> bool {
+        self.layouts
+            .borrow_mut()
+            .entry(Arc::as_ptr(layout) as usize)
+            .or_insert_with(|| (layout.clone(), layout_contains_delayed_field(layout)))
+            .1
+    }
+
+    #[cfg(test)]
+    pub(crate) fn num_layouts(&self) -> usize {
+        self.layouts.borrow().len()
+    }
+}
+
 // Given bytes, where values were already exchanged with identifiers,
-// return a list of identifiers present in it.
-fn extract_identifiers_from_value<T: Transaction>(
+// return a list of identifiers present in it. The bytes are traversed
+// according to the layout, without deserializing the value.
+pub(crate) fn extract_identifiers_from_value<T: Transaction>(
     bytes: &Bytes,
     layout: &MoveTypeLayout,
+    counter_labels: &ViewCounterLabels,
 ) -> anyhow::Result<HashSet<T::Identifier>> {
-    // TODO[agg_v2](optimize): this performs 2 traversals of a value:
-    //   1) deserialize,
-    //   2) find identifiers to populate the set.
-    //   See if can cache identifiers in advance, or combine it with
-    //   deserialization.
-    let value = deserialize_and_allow_delayed_values(bytes, layout)
-        .ok_or_else(|| anyhow::anyhow!("Failed to deserialize resource during id replacement"))?;
-
-    let mut identifiers = HashSet::new();
-    find_identifiers_in_value(&value, &mut identifiers)?;
+    let [mode, shard] = counter_labels.values();
+    let _timer = counters::VALUE_EXCHANGE_SECONDS
+        .with_label_values(&[mode, shard, ExchangeType::IDENTIFIER_EXTRACTION])
+        .start_timer();
+    counters::VALUE_EXCHANGE_BYTES
+        .with_label_values(&[mode, shard, ExchangeType::IDENTIFIER_EXTRACTION])
+        .observe(bytes.len() as f64);
+
+    let identifiers = find_identifiers_in_bytes(bytes, layout)
+        .map_err(|e| anyhow::anyhow!("Failed to extract identifiers from resource with {:?}", e))?;
     // TODO[agg_v2](cleanup): ugly way of converting delayed ids to generic type params.
-    Ok(identifiers.into_iter().map(T::Identifier::from).collect())
+    Ok(identifiers
+        .into_iter()
+        .map(|id| T::Identifier::from(id.as_u64()))
+        .collect())
 }
 
 // Deletion returns a PanicError.
+// The identifiers extracted from the value are added to scanned_ids. Values read with a
+// layout without delayed fields do not need exchange, and are not traversed.
 pub(crate) fn does_value_need_exchange<T: Transaction>(
     value: &T::Value,
-    layout: &MoveTypeLayout,
+    layout: &Arc<MoveTypeLayout>,
     delayed_write_set_ids: &HashSet<T::Identifier>,
+    scanned_ids: &mut BTreeSet<T::Identifier>,
+    layout_cache: &DelayedFieldLayoutCache,
+    counter_labels: &ViewCounterLabels,
 ) -> Result<bool, PanicError> {
     if let Some(bytes) = value.bytes() {
-        extract_identifiers_from_value::<T>(bytes, layout)
-            .map(|identifiers_in_read| !delayed_write_set_ids.is_disjoint(&identifiers_in_read))
+        if !layout_cache.contains_delayed_field(layout) {
+            return Ok(false);
+        }
+
+        extract_identifiers_from_value::<T>(bytes, layout, counter_labels)
+            .map(|identifiers_in_read| {
+                let needs_exchange = !delayed_write_set_ids.is_disjoint(&identifiers_in_read);
+                scanned_ids.extend(identifiers_in_read);
+                needs_exchange
+            })
             .map_err(|e| code_invariant_error(format!("Identifier extraction failed with {:?}", e)))
     } else {
         // Deletion returns an error.
@@ -150,11 +394,22 @@ pub(crate) fn filter_value_for_exchange<T: Transaction>(
     layout: &Arc<MoveTypeLayout>,
     delayed_write_set_ids: &HashSet<T::Identifier>,
     key: &T::Key,
+    scanned_ids: &mut BTreeSet<T::Identifier>,
+    layout_cache: &DelayedFieldLayoutCache,
+    counter_labels: &ViewCounterLabels,
 ) -> Option<Result<(T::Key, (StateValueMetadata, u64, Arc<MoveTypeLayout>)), PanicError>> {
     if value.is_deletion() {
         None
     } else {
-        does_value_need_exchange::<T>(value, layout, delayed_write_set_ids).map_or_else(
+        does_value_need_exchange::<T>(
+            value,
+            layout,
+            delayed_write_set_ids,
+            scanned_ids,
+            layout_cache,
+            counter_labels,
+        )
+        .map_or_else(
             |e| Some(Err(e)),
             |needs_exchange| {
                 needs_exchange.then(|| {
@@ -171,3 +426,153 @@ pub(crate) fn filter_value_for_exchange<T: Transaction>(
         )
     }
 }
+
+/// Resources read by a transaction that need exchange (see get_reads_needing_exchange), and
+/// the keys of the values read with a layout that were not scanned for delayed fields, as they
+/// are larger than the size threshold. Whether the latter need exchange is left to the caller
+/// to decide (e.g. by falling back to the sequential execution).
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct ReadsNeedingExchange<K: Ord> {
+    pub(crate) reads: BTreeMap<K, (StateValueMetadata, u64, Arc<MoveTypeLayout>)>,
+    pub(crate) fallback_keys: Vec<K>,
+}
+
+impl<K: Ord> Default for ReadsNeedingExchange<K> {
+    fn default() -> Self {
+        Self {
+            reads: BTreeMap::new(),
+            fallback_keys: Vec::new(),
+        }
+    }
+}
+
+impl<K: Ord + Clone> ReadsNeedingExchange<K> {
+    /// Adds the value read at the key if it needs exchange. A value larger than the size
+    /// threshold is not scanned (which requires traversing its bytes), and only its key is added
+    /// to the fallback keys. If no delayed fields were written, no value needs exchange.
+    pub(crate) fn add<T: Transaction<Key = K>>(
+        &mut self,
+        value: &T::Value,
+        layout: &Arc<MoveTypeLayout>,
+        delayed_write_set_ids: &HashSet<T::Identifier>,
+        key: &K,
+        scanned_ids: &mut BTreeSet<T::Identifier>,
+        size_threshold: Option<usize>,
+        layout_cache: &DelayedFieldLayoutCache,
+        counter_labels: &ViewCounterLabels,
+    ) -> Result<(), PanicError> {
+        let exceeds_threshold = !delayed_write_set_ids.is_empty()
+            && size_threshold.is_some_and(|threshold| {
+                value.bytes().is_some_and(|bytes| bytes.len() > threshold)
+            });
+
+        if exceeds_threshold {
+            self.fallback_keys.push(key.clone());
+        } else if let Some((key, entry)) = filter_value_for_exchange::<T>(
+            value,
+            layout,
+            delayed_write_set_ids,
+            key,
+            scanned_ids,
+            layout_cache,
+            counter_labels,
+        )
+        .transpose()?
+        {
+            self.reads.insert(key, entry);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::proptest_types::types::{KeyType, MockEvent, MockTransaction, ValueType};
+    use aptos_types::state_store::state_value::StateValue;
+    use claims::{assert_err, assert_ok_eq};
+    use move_core_types::{identifier::Identifier, value::MoveFieldLayout};
+    use move_vm_types::delayed_values::delayed_field_id::DelayedFieldID;
+
+    #[test]
+    fn layout_contains_delayed_field_nested() {
+        let aggregator_layout = MoveTypeLayout::Native(
+            IdentifierMappingKind::Aggregator,
+            Box::new(MoveTypeLayout::U64),
+        );
+        let nested = |layout: MoveTypeLayout| {
+            MoveTypeLayout::Vector(Box::new(MoveTypeLayout::Struct(MoveStructLayout::Runtime(
+                vec![MoveTypeLayout::U8, MoveTypeLayout::Vector(Box::new(layout))],
+            ))))
+        };
+
+        assert!(layout_contains_delayed_field(&aggregator_layout));
+        assert!(layout_contains_delayed_field(&nested(
+            aggregator_layout.clone()
+        )));
+        assert!(!layout_contains_delayed_field(&MoveTypeLayout::U128));
+        assert!(!layout_contains_delayed_field(&nested(
+            MoveTypeLayout::Address
+        )));
+
+        let decorated = |layout: MoveTypeLayout| {
+            MoveTypeLayout::Struct(MoveStructLayout::WithFields(vec![MoveFieldLayout::new(
+                Identifier::new("field").unwrap(),
+                layout,
+            )]))
+        };
+        assert!(layout_contains_delayed_field(&decorated(aggregator_layout)));
+        assert!(!layout_contains_delayed_field(&decorated(
+            MoveTypeLayout::U64
+        )));
+    }
+
+    #[test]
+    fn value_scan_skipped_without_delayed_fields() {
+        type TestTxn = MockTransaction<KeyType<u32>, MockEvent>;
+
+        let cache = DelayedFieldLayoutCache::new();
+        let u64_layout = Arc::new(MoveTypeLayout::U64);
+        let aggregator_layout = Arc::new(MoveTypeLayout::Native(
+            IdentifierMappingKind::Aggregator,
+            Box::new(MoveTypeLayout::U64),
+        ));
+        // Not a valid serialization of a u64 value.
+        let value =
+            ValueType::from_state_value(Some(StateValue::new_legacy(Bytes::from(vec![1_u8; 3]))));
+        let delayed_write_set_ids = HashSet::from([DelayedFieldID::new_with_width(1, 8)]);
+        let mut scanned_ids = BTreeSet::new();
+        let counter_labels = ViewCounterLabels::new(true, None);
+
+        // The value is not traversed, as its layout does not contain delayed fields.
+        assert_ok_eq!(
+            does_value_need_exchange::<TestTxn>(
+                &value,
+                &u64_layout,
+                &delayed_write_set_ids,
+                &mut scanned_ids,
+                &cache,
+                &counter_labels,
+            ),
+            false
+        );
+        assert_err!(does_value_need_exchange::<TestTxn>(
+            &value,
+            &aggregator_layout,
+            &delayed_write_set_ids,
+            &mut scanned_ids,
+            &cache,
+            &counter_labels,
+        ));
+        assert!(scanned_ids.is_empty());
+        assert_eq!(cache.num_layouts(), 2);
+
+        // Memoized by the pointer of the layout (equal layouts behind other pointers are
+        // recorded separately).
+        assert!(!cache.contains_delayed_field(&u64_layout));
+        assert!(cache.contains_delayed_field(&aggregator_layout));
+        assert_eq!(cache.num_layouts(), 2);
+        assert!(!cache.contains_delayed_field(&Arc::new(MoveTypeLayout::U64)));
+        assert_eq!(cache.num_layouts(), 3);
+    }
+}
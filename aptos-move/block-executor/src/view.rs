@@ -1,18 +1,27 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-#[cfg(test)]
-use crate::types::InputOutputKey;
 use crate::{
     captured_reads::{
-        CapturedReads, DataRead, DelayedFieldRead, DelayedFieldReadKind, GroupRead, ReadKind,
-        UnsyncReadSet,
+        CapturedRead, CapturedReads, DataRead, DelayedFieldRead, DelayedFieldReadKind, GroupRead,
+        ModuleRead, ReadKind, ReadSummary, UnsyncReadSet,
     },
-    counters,
+    counters::{self, ExchangeType, ViewCounterLabels},
+    key_interner::KeyInterner,
+    layout_mismatch::LayoutMismatchObserver,
     scheduler::{DependencyResult, DependencyStatus, Scheduler, TWaitForDependency},
+    tag_layout_registry::TagLayoutRegistry,
     value_exchange::{
-        does_value_need_exchange, filter_value_for_exchange, TemporaryValueToIdentifierMapping,
+        does_value_need_exchange, extract_identifiers_from_value, layout_contains_delayed_field,
+        DefaultValueToIdentifierMappingFactory, DelayedFieldLayoutCache, ExchangeStatus,
+        ReadsNeedingExchange, RecordingValueToIdentifierMapping, ValueToIdentifierMappingFactory,
     },
+    view_health::{SpeculativeHaltReason, ViewHealthSummary},
+};
+#[cfg(any(test, feature = "serde"))]
+use crate::{
+    captured_reads::{ModuleReadVersion, TakenReads},
+    types::InputOutputKey,
 };
 use aptos_aggregator::{
     bounded_math::{ok_overflow, BoundedMath, SignedU128},
@@ -20,22 +29,27 @@ use aptos_aggregator::{
     delta_math::DeltaHistory,
     resolver::{TAggregatorV1View, TDelayedFieldView},
     types::{
-        code_invariant_error, expect_ok, DelayedFieldValue, DelayedFieldsSpeculativeError, PanicOr,
-        ReadPosition,
+        code_invariant_error, expect_ok, DelayedFieldValue, DelayedFieldValueKind,
+        DelayedFieldsSpeculativeError, PanicOr, ReadPosition,
     },
 };
 use aptos_logger::error;
+#[cfg(any(test, feature = "serde"))]
+use aptos_mvhashmap::utils::module_hash;
 use aptos_mvhashmap::{
     types::{
         GroupReadResult, MVDataError, MVDataOutput, MVDelayedFieldsError, MVGroupError,
         MVModulesError, MVModulesOutput, StorageVersion, TxnIndex, UnknownOrLayout,
-        UnsyncGroupError, ValueWithLayout,
+        UnsyncGroupError, ValueWithLayout, Version,
     },
     unsync_map::UnsyncMap,
     versioned_delayed_fields::TVersionedDelayedFieldView,
     MVHashMap,
 };
+#[cfg(any(test, feature = "serde"))]
+use aptos_types::executable::ExecutableDescriptor;
 use aptos_types::{
+    block_executor::config::BlockExecutorLocalConfig,
     delayed_fields::PanicError,
     executable::{Executable, ModulePath},
     state_store::{
@@ -52,52 +66,76 @@ use aptos_vm_types::resolver::{
     ResourceGroupSize, StateStorageView, TModuleView, TResourceGroupView, TResourceView,
 };
 use bytes::Bytes;
-use claims::assert_ok;
-use move_binary_format::errors::{PartialVMError, PartialVMResult};
-use move_core_types::{value::MoveTypeLayout, vm_status::StatusCode};
+use move_binary_format::{
+    errors::{PartialVMError, PartialVMResult},
+    file_format_common::read_uleb128_as_u64,
+};
+use move_core_types::{
+    value::{IdentifierMappingKind, MoveStructLayout, MoveTypeLayout},
+    vm_status::{sub_status::unexpected_deserialization_error, StatusCode},
+};
 use move_vm_types::{
-    delayed_values::delayed_field_id::ExtractUniqueIndex,
+    delayed_values::delayed_field_id::{DelayedFieldID, ExtractUniqueIndex, ExtractWidth},
     value_serde::{
         deserialize_and_allow_delayed_values, deserialize_and_replace_values_with_ids,
         serialize_and_allow_delayed_values, serialize_and_replace_ids_with_values,
     },
 };
+use rayon::prelude::*;
 use std::{
     cell::RefCell,
-    collections::{BTreeMap, HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     fmt::Debug,
+    io::Cursor,
+    ops::Deref,
     sync::{
         atomic::{AtomicU32, Ordering},
         Arc,
     },
+    time::{Duration, Instant},
 };
 
 /// A struct which describes the result of the read from the proxy. The client
 /// can interpret these types to further resolve the reads.
 #[derive(Debug)]
-pub(crate) enum ReadResult {
+pub enum ReadResult {
     Value(Option<StateValue>, Option<Arc<MoveTypeLayout>>),
     Metadata(Option<StateValueMetadata>),
+    // Size of the value in bytes, None if the value does not exist.
+    Size(Option<u64>),
     Exists(bool),
     Uninitialized,
-    // Must halt the execution of the calling transaction. This might be because
-    // there was an inconsistency in observed speculative state, or dependency
-    // waiting indicated that the parallel execution had been halted. The String
-    // parameter provides more context (error description / message).
+    // Aggregator (v1) deltas were found, but no base value to apply them to. The caller
+    // must set the base value of the aggregator from storage and read again.
+    Unresolved,
+    // The read must halt the speculative execution of the transaction, with the message.
+    // Only returned by the public reads (see public::LatestView::read_resource), as the
+    // reads of the view return the halts as errors.
     HaltSpeculativeExecution(String),
 }
 
 impl ReadResult {
-    fn from_data_read<V: TransactionWrite>(data: DataRead<V>) -> Self {
-        match data {
+    /// Returns an error for a value read captured by the hash, which does not retain the
+    /// value, and must be served by refetching the value from the versioned map.
+    fn from_data_read<V: TransactionWrite>(data: DataRead<V>) -> Result<Self, PanicError> {
+        Ok(match data {
             DataRead::Versioned(_, v, layout) => ReadResult::Value(v.as_state_value(), layout),
+            DataRead::Hashed(version, _) => {
+                return Err(code_invariant_error(format!(
+                    "Hashed value read (at version {:?}) can not be served without a refetch",
+                    version
+                )));
+            },
             DataRead::Resolved(v) => {
                 // TODO[agg_v1](cleanup): Move AggV1 to Delayed fields, and then handle the layout if needed
                 ReadResult::Value(Some(StateValue::new_legacy(serialize(&v).into())), None)
             },
             DataRead::Metadata(maybe_metadata) => ReadResult::Metadata(maybe_metadata),
+            DataRead::Size(maybe_metadata_and_size) => {
+                ReadResult::Size(maybe_metadata_and_size.map(|(_, size)| size))
+            },
             DataRead::Exists(exists) => ReadResult::Exists(exists),
-        }
+        })
     }
 
     fn from_value_with_layout<V: TransactionWrite>(
@@ -113,6 +151,10 @@ impl ReadResult {
             | (ValueWithLayout::RawFromStorage(v), ReadKind::Metadata) => {
                 Some(ReadResult::Metadata(v.as_state_value_metadata()))
             },
+            (ValueWithLayout::Exchanged(v, _), ReadKind::Size)
+            | (ValueWithLayout::RawFromStorage(v), ReadKind::Size) => Some(ReadResult::Size(
+                (!v.is_deletion()).then(|| v.bytes().map_or(0, |bytes| bytes.len() as u64)),
+            )),
             (ValueWithLayout::Exchanged(v, _), ReadKind::Exists)
             | (ValueWithLayout::RawFromStorage(v), ReadKind::Exists) => {
                 Some(ReadResult::Exists(!v.is_deletion()))
@@ -120,11 +162,68 @@ impl ReadResult {
         }
     }
 
-    pub fn into_value(self) -> Option<StateValue> {
-        if let ReadResult::Value(v, _layout) = self {
-            v
-        } else {
-            unreachable!("Read result must be Value kind")
+    fn kind_mismatch_error(&self, kind: &str) -> PanicError {
+        code_invariant_error(format!(
+            "Read result must be {} kind, found {:?}",
+            kind, self
+        ))
+    }
+
+    pub fn into_value(self) -> Result<Option<StateValue>, PanicError> {
+        match self {
+            ReadResult::Value(v, _layout) => Ok(v),
+            other => Err(other.kind_mismatch_error("Value")),
+        }
+    }
+
+    /// Returns the value, or None if the result is not of the Value kind.
+    pub fn try_into_value(self) -> Option<Option<StateValue>> {
+        match self {
+            ReadResult::Value(v, _layout) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the metadata (None if the value does not exist), or None if the result is
+    /// not of the Metadata kind.
+    pub fn into_metadata(self) -> Option<Option<StateValueMetadata>> {
+        match self {
+            ReadResult::Metadata(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn into_metadata_checked(self) -> Result<Option<StateValueMetadata>, PanicError> {
+        match self {
+            ReadResult::Metadata(v) => Ok(v),
+            other => Err(other.kind_mismatch_error("Metadata")),
+        }
+    }
+
+    pub fn into_size(self) -> Result<Option<u64>, PanicError> {
+        match self {
+            ReadResult::Size(v) => Ok(v),
+            other => Err(other.kind_mismatch_error("Size")),
+        }
+    }
+
+    /// Returns true if the result is of the Exists kind, and the value exists.
+    pub fn into_exists(self) -> bool {
+        matches!(self, ReadResult::Exists(true))
+    }
+
+    pub(crate) fn into_exists_checked(self) -> Result<bool, PanicError> {
+        match self {
+            ReadResult::Exists(v) => Ok(v),
+            other => Err(other.kind_mismatch_error("Exists")),
+        }
+    }
+
+    /// Returns the message if the read must halt the speculative execution.
+    pub fn as_halt_message(&self) -> Option<&str> {
+        match self {
+            ReadResult::HaltSpeculativeExecution(message) => Some(message),
+            _ => None,
         }
     }
 }
@@ -139,20 +238,409 @@ trait ResourceState<T: Transaction> {
         target_kind: ReadKind,
         layout: UnknownOrLayout,
         patch_base_value: &dyn Fn(&T::Value, Option<&MoveTypeLayout>) -> PartialVMResult<T::Value>,
-    ) -> ReadResult;
+    ) -> Result<ReadResult, ViewError>;
+
+    /// Returns true if the base value of the key was set.
+    fn has_base_value(&self, key: &T::Key) -> bool;
+
+    /// Returns true if the base value of the key was exchanged with a layout, i.e. read as a
+    /// resource value containing delayed fields.
+    fn has_exchanged_base_value_with_layout(&self, key: &T::Key) -> bool;
+
+    /// Reads the keys (each with the layout of its read) by kind, returning the results in the
+    /// order of the keys. By default, the keys are read one by one.
+    fn read_cached_data_by_kind_multi(
+        &self,
+        txn_idx: TxnIndex,
+        keys: &[(&T::Key, UnknownOrLayout)],
+        target_kind: ReadKind,
+        patch_base_value: &dyn Fn(&T::Value, Option<&MoveTypeLayout>) -> PartialVMResult<T::Value>,
+    ) -> Result<Vec<ReadResult>, ViewError> {
+        keys.iter()
+            .map(|(key, layout)| {
+                self.read_cached_data_by_kind(
+                    txn_idx,
+                    key,
+                    target_kind.clone(),
+                    layout.clone(),
+                    patch_base_value,
+                )
+            })
+            .collect()
+    }
+}
+
+/// Errors of the reads of resources and resource groups from the state. Converted to
+/// PartialVMError at the boundary of the resource (and resource group) view traits.
+#[derive(Debug)]
+enum ViewError {
+    // Must halt the execution of the calling transaction. This might be because there was
+    // an inconsistency in observed speculative state, or dependency waiting indicated that
+    // the parallel execution had been halted (or timed out). The String parameter provides
+    // more context (error description / message).
+    SpeculativeAbort(SpeculativeHaltReason, String),
+    // Reading from the base view (storage) failed.
+    StorageError(String),
+    // Errors of (de)serialization, e.g. of the tags of the group members, or of patching
+    // the base values.
+    SerializationError(PartialVMError),
+    CodeInvariant(PanicError),
+}
+
+impl ViewError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            // The VM does not log the speculative errors, so no error is logged when the
+            // speculative logging is flushed after the parallel execution is halted.
+            ViewError::SpeculativeAbort(_, _) => StatusCode::SPECULATIVE_EXECUTION_ABORT_ERROR,
+            ViewError::StorageError(_) => StatusCode::STORAGE_ERROR,
+            ViewError::SerializationError(err) => err.major_status(),
+            ViewError::CodeInvariant(_) => StatusCode::DELAYED_MATERIALIZATION_CODE_INVARIANT_ERROR,
+        }
+    }
+}
+
+impl From<PartialVMError> for ViewError {
+    fn from(err: PartialVMError) -> Self {
+        ViewError::SerializationError(err)
+    }
+}
+
+impl From<PanicError> for ViewError {
+    fn from(err: PanicError) -> Self {
+        ViewError::CodeInvariant(err)
+    }
+}
+
+impl From<ViewError> for PartialVMError {
+    fn from(err: ViewError) -> Self {
+        let status_code = err.status_code();
+        match err {
+            ViewError::SpeculativeAbort(_, message) | ViewError::StorageError(message) => {
+                PartialVMError::new(status_code).with_message(message)
+            },
+            ViewError::SerializationError(err) => err,
+            ViewError::CodeInvariant(err) => err.into(),
+        }
+    }
+}
+
+// Returns the error halting the speculative execution of the transaction for the given reason,
+// and counts the halt per reason. The halts observed by the dependency waits are recorded in the
+// health of the view by the waits, and the other halts are recorded here.
+fn speculative_abort(
+    reason: SpeculativeHaltReason,
+    message: String,
+    counter_labels: &ViewCounterLabels,
+    health: &RefCell<ViewHealthSummary>,
+) -> ViewError {
+    let [mode, shard] = counter_labels.values();
+    counters::SPECULATIVE_HALT_COUNT
+        .with_label_values(&[mode, shard, reason.as_str()])
+        .inc();
+    if !matches!(
+        reason,
+        SpeculativeHaltReason::ExecutionHalted | SpeculativeHaltReason::DependencyTimeout
+    ) {
+        health.borrow_mut().record_speculative_halt(reason);
+    }
+    ViewError::SpeculativeAbort(reason, message)
 }
 
 trait ResourceGroupState<T: Transaction> {
     fn set_raw_group_base_values(&self, group_key: T::Key, base_values: Vec<(T::Tag, T::Value)>);
 
+    /// Returns true if the key was initialized (or written) as a resource group.
+    fn contains_group(&self, group_key: &T::Key) -> bool;
+
+    /// Sets the size of the group at the storage version, computed without the group members.
+    fn set_raw_group_base_size(&self, group_key: T::Key, base_size: ResourceGroupSize);
+
+    /// Returns true if the exchanged value was installed, i.e. the member was not already
+    /// exchanged (e.g. by a concurrent reader).
+    fn update_tagged_base_value_with_layout(
+        &self,
+        group_key: T::Key,
+        resource_tag: T::Tag,
+        value: T::Value,
+        layout: Option<Arc<MoveTypeLayout>>,
+    ) -> bool;
+
+    /// Provided when group members are patched eagerly (at the group initialization).
+    fn tag_layout_registry(&self) -> Option<&TagLayoutRegistry<T::Tag>>;
+
+    /// Reads the group member by kind: ReadKind::Value returns GroupReadResult::Value (with
+    /// the base value patched according to the layout), while ReadKind::Exists returns
+    /// GroupReadResult::Exists, without patching the base value.
     fn read_cached_group_tagged_data(
         &self,
         txn_idx: TxnIndex,
         group_key: &T::Key,
         resource_tag: &T::Tag,
+        target_kind: ReadKind,
         maybe_layout: Option<&MoveTypeLayout>,
         patch_base_value: &dyn Fn(&T::Value, Option<&MoveTypeLayout>) -> PartialVMResult<T::Value>,
-    ) -> PartialVMResult<GroupReadResult>;
+    ) -> Result<GroupReadResult, ViewError>;
+}
+
+fn group_read_result_from_data_read<V: TransactionWrite>(
+    data_read: DataRead<V>,
+) -> Result<GroupReadResult, ViewError> {
+    match data_read {
+        DataRead::Versioned(_, v, layout) => {
+            Ok(GroupReadResult::Value(v.extract_raw_bytes(), layout))
+        },
+        DataRead::Exists(exists) => Ok(GroupReadResult::Exists(exists)),
+        DataRead::Hashed(_, _)
+        | DataRead::Metadata(_)
+        | DataRead::Size(_)
+        | DataRead::Resolved(_) => Err(code_invariant_error(format!(
+            "Unexpected read of a group member {:?}",
+            data_read.get_kind()
+        ))
+        .into()),
+    }
+}
+
+/// Computes the size of the group from its contents, i.e. the size of the serialized
+/// BTreeMap of (existing) group members. Returns the size with the tags of the existing
+/// members, in order.
+pub(crate) fn group_contents_size<T: Transaction>(
+    members: impl IntoIterator<Item = (T::Tag, ValueWithLayout<T::Value>)>,
+) -> Result<(u64, Vec<T::Tag>), String> {
+    let group: BTreeMap<T::Tag, Bytes> = members
+        .into_iter()
+        .filter_map(|(tag, value)| {
+            value
+                .extract_value_no_layout()
+                .bytes()
+                .map(|bytes| (tag, bytes.clone()))
+        })
+        .collect();
+
+    // An empty group has size 0 (as it does not exist), rather than the size of an empty map.
+    let contents_size = if group.is_empty() {
+        0
+    } else {
+        bcs::serialized_size(&group)
+            .map_err(|e| format!("Group contents serialization error {:?}", e))? as u64
+    };
+    Ok((contents_size, group.into_keys().collect()))
+}
+
+/// Compares the provided group size with the size computed from the group contents.
+/// Returns a description of the mismatch, if any.
+fn check_group_size<T: Transaction>(
+    group_size: ResourceGroupSize,
+    members: impl IntoIterator<Item = (T::Tag, ValueWithLayout<T::Value>)>,
+) -> Result<(), String> {
+    let (contents_size, tags) = group_contents_size::<T>(members)?;
+    if group_size.get() == contents_size {
+        Ok(())
+    } else {
+        Err(format!(
+            "Group size {:?} (= {}) does not match the serialized size {} of {} group members",
+            group_size,
+            group_size.get(),
+            contents_size,
+            tags.len(),
+        ))
+    }
+}
+
+/// Used when group size consistency checks are enabled: inconsistencies are not execution
+/// failures, but are counted and alerted on. Returns true if the group size is consistent.
+fn report_group_size_inconsistency<T: Transaction>(
+    group_key: &T::Key,
+    group_size: ResourceGroupSize,
+    members: impl IntoIterator<Item = (T::Tag, ValueWithLayout<T::Value>)>,
+    counter_labels: &ViewCounterLabels,
+) -> bool {
+    match check_group_size::<T>(group_size, members) {
+        Ok(()) => true,
+        Err(msg) => {
+            counters::GROUP_SIZE_CONSISTENCY_ERROR_COUNT
+                .with_label_values(&counter_labels.values())
+                .inc();
+            alert!(
+                "[BlockSTM] Group size consistency check failed for group {:?}: {}",
+                group_key,
+                msg
+            );
+            false
+        },
+    }
+}
+
+/// When provided the observer, compares the layout supplied with a value read against the
+/// layout stored with the exchanged value (observe-only, the read is served unchanged).
+fn observe_layout_mismatch<K: Debug, V>(
+    layout_mismatch_observer: Option<&LayoutMismatchObserver>,
+    key: &K,
+    target_kind: &ReadKind,
+    layout: &UnknownOrLayout,
+    value: &ValueWithLayout<V>,
+    counter_labels: &ViewCounterLabels,
+) {
+    if let (
+        Some(observer),
+        ReadKind::Value,
+        UnknownOrLayout::Known(Some(supplied_layout)),
+        ValueWithLayout::Exchanged(_, Some(stored_layout)),
+    ) = (layout_mismatch_observer, target_kind, layout, value)
+    {
+        observer.observe(key, supplied_layout, stored_layout, counter_labels);
+    }
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Returns the structural hash of the layout, i.e. equal layouts have equal fingerprints, even
+/// if built independently. The fingerprint is the FNV-1a hash of the tags of the layout in
+/// pre-order, hence it is stable across processes and toolchains. The fingerprints may
+/// collide: equal fingerprints must be confirmed by the structural comparison.
+pub(crate) fn layout_fingerprint(layout: &MoveTypeLayout) -> u64 {
+    let mut hash = FNV_OFFSET_BASIS;
+    hash_layout_tags(layout, &mut hash);
+    hash
+}
+
+fn fnv_hash_bytes(bytes: &[u8], hash: &mut u64) {
+    for byte in bytes {
+        *hash ^= u64::from(*byte);
+        *hash = hash.wrapping_mul(FNV_PRIME);
+    }
+}
+
+// The number of fields and the length of the names are hashed before them, so that the
+// encoding of the traversal is unambiguous. The struct tags of the decorated layouts are not
+// hashed, as they are confirmed by the structural comparison.
+fn hash_layout_tags(layout: &MoveTypeLayout, hash: &mut u64) {
+    use MoveTypeLayout as L;
+
+    match layout {
+        L::Bool => fnv_hash_bytes(&[0], hash),
+        L::U8 => fnv_hash_bytes(&[1], hash),
+        L::U16 => fnv_hash_bytes(&[2], hash),
+        L::U32 => fnv_hash_bytes(&[3], hash),
+        L::U64 => fnv_hash_bytes(&[4], hash),
+        L::U128 => fnv_hash_bytes(&[5], hash),
+        L::U256 => fnv_hash_bytes(&[6], hash),
+        L::Address => fnv_hash_bytes(&[7], hash),
+        L::Signer => fnv_hash_bytes(&[8], hash),
+        L::Vector(element_layout) => {
+            fnv_hash_bytes(&[9], hash);
+            hash_layout_tags(element_layout, hash);
+        },
+        L::Struct(MoveStructLayout::Runtime(field_layouts)) => {
+            fnv_hash_bytes(&[10], hash);
+            fnv_hash_bytes(&(field_layouts.len() as u64).to_le_bytes(), hash);
+            for field_layout in field_layouts {
+                hash_layout_tags(field_layout, hash);
+            }
+        },
+        L::Struct(MoveStructLayout::WithFields(fields))
+        | L::Struct(MoveStructLayout::WithTypes { fields, .. }) => {
+            fnv_hash_bytes(&[11], hash);
+            fnv_hash_bytes(&(fields.len() as u64).to_le_bytes(), hash);
+            for field in fields {
+                let name = field.name.as_bytes();
+                fnv_hash_bytes(&(name.len() as u64).to_le_bytes(), hash);
+                fnv_hash_bytes(name, hash);
+                hash_layout_tags(&field.layout, hash);
+            }
+        },
+        L::Native(kind, layout) => {
+            let kind_tag = match kind {
+                IdentifierMappingKind::Aggregator => 0,
+                IdentifierMappingKind::Snapshot => 1,
+                IdentifierMappingKind::DerivedString => 2,
+            };
+            fnv_hash_bytes(&[12, kind_tag], hash);
+            hash_layout_tags(layout, hash);
+        },
+    }
+}
+
+/// Memoizes layout_fingerprint by the pointer of the layout, so that the layouts shared by
+/// the reads of the same type are traversed once per view.
+pub(crate) struct LayoutFingerprintCache {
+    // The layout is held by the entry, so that its address is not reused (by a different
+    // layout) while the entry exists.
+    fingerprints: RefCell<HashMap<usize, (Arc<MoveTypeLayout>, u64)>>,
+    #[cfg(test)]
+    num_fingerprint_computations: RefCell<usize>,
+}
+
+impl LayoutFingerprintCache {
+    pub(crate) fn new() -> Self {
+        Self {
+            fingerprints: RefCell::new(HashMap::new()),
+            #[cfg(test)]
+            num_fingerprint_computations: RefCell::new(0),
+        }
+    }
+
+    pub(crate) fn fingerprint(&self, layout: &Arc<MoveTypeLayout>) -> u64 {
+        self.fingerprints
+            .borrow_mut()
+            .entry(Arc::as_ptr(layout) as usize)
+            .or_insert_with(|| {
+                #[cfg(test)]
+                {
+                    *self.num_fingerprint_computations.borrow_mut() += 1;
+                }
+                (layout.clone(), layout_fingerprint(layout))
+            })
+            .1
+    }
+
+    /// Returns true if the layouts are structurally equal. The layouts with different
+    /// fingerprints are never traversed, and equal fingerprints fall back to the structural
+    /// comparison (in case of a collision).
+    pub(crate) fn layouts_equal(
+        &self,
+        layout: &Arc<MoveTypeLayout>,
+        other_layout: &Arc<MoveTypeLayout>,
+    ) -> bool {
+        Arc::ptr_eq(layout, other_layout)
+            || (self.fingerprint(layout) == self.fingerprint(other_layout)
+                && layout == other_layout)
+    }
+}
+
+/// Counts of the fetches performed by a view, reported in the ReadSummary.
+#[derive(Default)]
+struct FetchCounts {
+    num_captured_read_hits: usize,
+    num_map_fetches: usize,
+    num_base_view_fetches: usize,
+}
+
+/// Opt-in checks and tuning of the parallel state (see BlockExecutorOptInConfig), and the id
+/// of the shard executing the block.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct ParallelStateConfig {
+    pub(crate) group_size_consistency_checks: bool,
+    pub(crate) hashed_value_read_threshold: Option<usize>,
+    pub(crate) exchange_size_threshold: Option<usize>,
+    pub(crate) history_base_revalidation: bool,
+    pub(crate) dependency_wait_retry_limit: Option<usize>,
+    pub(crate) shard_id: Option<usize>,
+}
+
+impl ParallelStateConfig {
+    pub(crate) fn new(local_config: &BlockExecutorLocalConfig) -> Self {
+        Self {
+            group_size_consistency_checks: local_config.opt_in.group_size_consistency_checks,
+            hashed_value_read_threshold: local_config.opt_in.hashed_value_read_threshold,
+            exchange_size_threshold: local_config.opt_in.exchange_size_threshold,
+            history_base_revalidation: local_config.opt_in.history_base_revalidation,
+            dependency_wait_retry_limit: local_config.opt_in.dependency_wait_retry_limit,
+            shard_id: local_config.shard_id,
+        }
+    }
 }
 
 pub(crate) struct ParallelState<'a, T: Transaction, X: Executable> {
@@ -161,6 +649,83 @@ pub(crate) struct ParallelState<'a, T: Transaction, X: Executable> {
     start_counter: u32,
     counter: &'a AtomicU32,
     captured_reads: RefCell<CapturedReads<T>>,
+    // If set, the reads are served, but not recorded (e.g. when the execution is only used
+    // for simulation or gas estimation, and hence never validated).
+    capture_disabled: bool,
+    key_interner: &'a KeyInterner<T::Key>,
+    group_size_consistency_checks: bool,
+    tag_layout_registry: Option<&'a TagLayoutRegistry<T::Tag>>,
+    layout_mismatch_observer: Option<&'a LayoutMismatchObserver>,
+    hashed_value_read_threshold: Option<usize>,
+    exchange_size_threshold: Option<usize>,
+    // If set, the base of a captured aggregator history is re-validated against the latest
+    // committed value when the history is used (see delayed_field_try_add_delta_outcome_impl).
+    history_base_revalidation: bool,
+    // If set, bounds the number of the dependency waits of a single read, after which the
+    // execution is halted (see check_dependency_wait_retries).
+    dependency_wait_retry_limit: Option<usize>,
+    counter_labels: ViewCounterLabels,
+    // Ids generated by the execution (when replacing values with identifiers), with the
+    // base values installed, and not referenced by any base value in the shared map.
+    minted_delayed_field_ids: RefCell<HashSet<T::Identifier>>,
+    // Ids generated by the last patch of a base value, untracked from the minted ids once
+    // the patched value is installed (see confirm_patched_base_value_impl).
+    patched_delayed_field_ids: RefCell<Vec<T::Identifier>>,
+    // Ids contained in the read values that were scanned for exchange.
+    scanned_delayed_field_ids: RefCell<BTreeSet<T::Identifier>>,
+    fetch_counts: RefCell<FetchCounts>,
+    health: RefCell<ViewHealthSummary>,
+}
+
+/// Removes the delayed fields generated by an execution (that is being discarded), which
+/// are not referenced by the base values in the shared map. The id counter is not rewound,
+/// as the later ids may have been concurrently generated by other executions: ids only need
+/// to be unique, and gaps in the range of generated ids are allowed.
+fn rollback_minted_delayed_fields_impl<T: Transaction>(
+    minted_delayed_field_ids: &RefCell<HashSet<T::Identifier>>,
+    versioned_delayed_fields: &dyn TVersionedDelayedFieldView<T::Identifier>,
+) -> Result<(), PanicError> {
+    for id in minted_delayed_field_ids.take() {
+        versioned_delayed_fields.remove_base_value(&id)?;
+    }
+    Ok(())
+}
+
+/// Untracks the ids generated by the last patch of a base value, if the patched value was
+/// installed as the base value (which then references the ids). Otherwise, the base value
+/// exchanged by a concurrent reader was kept, and the ids remain minted, to be removed if
+/// the execution is discarded.
+fn confirm_patched_base_value_impl<T: Transaction>(
+    patched_delayed_field_ids: &RefCell<Vec<T::Identifier>>,
+    minted_delayed_field_ids: &RefCell<HashSet<T::Identifier>>,
+    installed: bool,
+) {
+    let patched_ids = patched_delayed_field_ids.take();
+    if installed {
+        let mut minted_delayed_field_ids = minted_delayed_field_ids.borrow_mut();
+        for id in patched_ids {
+            minted_delayed_field_ids.remove(&id);
+        }
+    }
+}
+
+/// Reads the committed values of the given delayed fields with a single (batched) read,
+/// e.g. to replace all identifiers in a resource with values without accessing the map
+/// for each identifier.
+fn read_committed_delayed_field_values_impl<T: Transaction>(
+    versioned_delayed_fields: &dyn TVersionedDelayedFieldView<T::Identifier>,
+    ids: &[T::Identifier],
+    txn_idx: TxnIndex,
+) -> Result<HashMap<T::Identifier, DelayedFieldValue>, PanicError> {
+    let values = versioned_delayed_fields
+        .read_latest_committed_values(ids, txn_idx, ReadPosition::AfterCurrentTxn)
+        .map_err(|e| {
+            code_invariant_error(format!(
+                "Committed values for delayed fields {:?} must exist, got {:?}",
+                ids, e
+            ))
+        })?;
+    Ok(ids.iter().copied().zip(values).collect())
 }
 
 fn get_delayed_field_value_impl<T: Transaction>(
@@ -169,6 +734,8 @@ fn get_delayed_field_value_impl<T: Transaction>(
     wait_for: &dyn TWaitForDependency,
     id: &T::Identifier,
     txn_idx: TxnIndex,
+    counter_labels: &ViewCounterLabels,
+    health: &RefCell<ViewHealthSummary>,
 ) -> Result<DelayedFieldValue, PanicOr<DelayedFieldsSpeculativeError>> {
     // We expect only DelayedFieldReadKind::Value (which is set from this function),
     // to be a "full materialized/aggregated" read, and so we don't use the value
@@ -204,9 +771,12 @@ fn get_delayed_field_value_impl<T: Transaction>(
                 return Ok(value);
             },
             Err(PanicOr::Or(MVDelayedFieldsError::Dependency(dep_idx))) => {
-                if !wait_for_dependency(wait_for, txn_idx, dep_idx)? {
-                    // TODO[agg_v2](cleanup): think of correct return type
-                    return Err(PanicOr::Or(DelayedFieldsSpeculativeError::InconsistentRead));
+                match wait_for_dependency(wait_for, txn_idx, dep_idx, counter_labels, health)? {
+                    DependencyWaitResult::Resolved => {},
+                    DependencyWaitResult::ExecutionHalted | DependencyWaitResult::TimedOut(_) => {
+                        // TODO[agg_v2](cleanup): think of correct return type
+                        return Err(PanicOr::Or(DelayedFieldsSpeculativeError::InconsistentRead));
+                    },
                 }
             },
             Err(e) => {
@@ -220,6 +790,124 @@ fn get_delayed_field_value_impl<T: Transaction>(
     }
 }
 
+/// Returns whether the delayed field exists, without resolving its value. Captures the
+/// (weakest) Exists read, so that only the existence is validated, unless a stronger read
+/// of the same delayed field is (or gets) captured.
+fn delayed_field_exists_impl<T: Transaction>(
+    captured_reads: &RefCell<CapturedReads<T>>,
+    versioned_delayed_fields: &dyn TVersionedDelayedFieldView<T::Identifier>,
+    wait_for: &dyn TWaitForDependency,
+    id: &T::Identifier,
+    txn_idx: TxnIndex,
+    counter_labels: &ViewCounterLabels,
+    health: &RefCell<ViewHealthSummary>,
+) -> Result<bool, PanicOr<DelayedFieldsSpeculativeError>> {
+    // Any captured read implies existence, except for an Exists read that has not found it.
+    let delayed_read = captured_reads
+        .borrow()
+        .get_delayed_field_by_kind(id, DelayedFieldReadKind::Exists);
+    if let Some(data) = delayed_read {
+        return Ok(!matches!(data, DelayedFieldRead::Exists { exists: false }));
+    }
+
+    loop {
+        match versioned_delayed_fields.exists(id, txn_idx) {
+            Ok(exists) => {
+                captured_reads.borrow_mut().capture_delayed_field_read(
+                    *id,
+                    false,
+                    DelayedFieldRead::Exists { exists },
+                )?;
+                return Ok(exists);
+            },
+            Err(MVDelayedFieldsError::Dependency(dep_idx)) => {
+                match wait_for_dependency(wait_for, txn_idx, dep_idx, counter_labels, health)? {
+                    DependencyWaitResult::Resolved => {},
+                    DependencyWaitResult::ExecutionHalted | DependencyWaitResult::TimedOut(_) => {
+                        return Err(PanicOr::Or(DelayedFieldsSpeculativeError::InconsistentRead));
+                    },
+                }
+            },
+            Err(e) => {
+                let e = PanicOr::Or(e);
+                captured_reads
+                    .borrow_mut()
+                    .capture_delayed_field_read_error(&e);
+                return Err(e.map_non_panic(|_| DelayedFieldsSpeculativeError::InconsistentRead));
+            },
+        }
+    }
+}
+
+/// Returns the latest committed value of the delayed field, as of the read position, i.e.
+/// without the pending changes of the lower transactions. Captures a Committed read, which
+/// (as all delayed field reads) is validated against the committed value when the
+/// transaction is committed. During the execution, the transaction itself is not committed,
+/// so both read positions observe the same value.
+fn get_delayed_field_latest_committed_value_impl<T: Transaction>(
+    captured_reads: &RefCell<CapturedReads<T>>,
+    versioned_delayed_fields: &dyn TVersionedDelayedFieldView<T::Identifier>,
+    wait_for: &dyn TWaitForDependency,
+    id: &T::Identifier,
+    read_position: ReadPosition,
+    txn_idx: TxnIndex,
+    counter_labels: &ViewCounterLabels,
+    health: &RefCell<ViewHealthSummary>,
+) -> Result<DelayedFieldValue, PanicOr<DelayedFieldsSpeculativeError>> {
+    // A captured Value read is returned as well, as it must match the committed value for
+    // the transaction to commit.
+    let delayed_read = captured_reads
+        .borrow()
+        .get_delayed_field_by_kind(id, DelayedFieldReadKind::Committed);
+    match delayed_read {
+        Some(DelayedFieldRead::Value { value } | DelayedFieldRead::Committed { value }) => {
+            return Ok(value)
+        },
+        Some(read) => {
+            let err = code_invariant_error(format!(
+                "Committed DelayedField read returned non-value result {:?}",
+                read
+            ))
+            .into();
+            captured_reads
+                .borrow_mut()
+                .capture_delayed_field_read_error(&err);
+            return Err(err);
+        },
+        None => {},
+    }
+
+    loop {
+        match versioned_delayed_fields.read_latest_committed_value(id, txn_idx, read_position) {
+            Ok(value) => {
+                captured_reads.borrow_mut().capture_delayed_field_read(
+                    *id,
+                    false,
+                    DelayedFieldRead::Committed {
+                        value: value.clone(),
+                    },
+                )?;
+                return Ok(value);
+            },
+            Err(MVDelayedFieldsError::Dependency(dep_idx)) => {
+                match wait_for_dependency(wait_for, txn_idx, dep_idx, counter_labels, health)? {
+                    DependencyWaitResult::Resolved => {},
+                    DependencyWaitResult::ExecutionHalted | DependencyWaitResult::TimedOut(_) => {
+                        return Err(PanicOr::Or(DelayedFieldsSpeculativeError::InconsistentRead));
+                    },
+                }
+            },
+            Err(e) => {
+                let e = PanicOr::Or(e);
+                captured_reads
+                    .borrow_mut()
+                    .capture_delayed_field_read_error(&e);
+                return Err(e.map_non_panic(|_| DelayedFieldsSpeculativeError::InconsistentRead));
+            },
+        }
+    }
+}
+
 fn compute_delayed_field_try_add_delta_outcome_from_history(
     base_delta: &SignedU128,
     delta: &SignedU128,
@@ -268,6 +956,28 @@ fn compute_delayed_field_try_add_delta_outcome_from_history(
     }))
 }
 
+/// Extracts the aggregator value of the delayed field, with the error providing the id and the
+/// actual kind of the value otherwise (e.g. a try_add on a snapshot).
+fn expect_aggregator_value<I: ExtractUniqueIndex + ExtractWidth>(
+    id: &I,
+    value: DelayedFieldValue,
+    counter_labels: &ViewCounterLabels,
+) -> Result<u128, PanicOr<DelayedFieldsSpeculativeError>> {
+    match value {
+        DelayedFieldValue::Aggregator(value) => Ok(value),
+        DelayedFieldValue::Snapshot(_) | DelayedFieldValue::Derived(_) => {
+            counters::DELAYED_FIELD_TYPE_MISMATCH_COUNT
+                .with_label_values(&counter_labels.values())
+                .inc();
+            Err(PanicOr::Or(DelayedFieldsSpeculativeError::WrongKind {
+                id: DelayedFieldID::new_with_width(id.extract_unique_index(), id.extract_width()),
+                expected: DelayedFieldValueKind::Aggregator,
+                found: value.kind(),
+            }))
+        },
+    }
+}
+
 fn compute_delayed_field_try_add_delta_outcome_first_time(
     delta: &SignedU128,
     max_value: u128,
@@ -299,6 +1009,28 @@ fn compute_delayed_field_try_add_delta_outcome_first_time(
         inner_aggregator_value: base_aggregator_value,
     }))
 }
+
+// Reads the latest committed value of the delayed field before the transaction, i.e. the
+// value the deltas of the transaction apply to. No transaction precedes the transaction 0, so
+// its read is of the base value (installed via set_base_value, e.g. when the value was
+// exchanged from storage), rather than relying on the versioned map to resolve the empty
+// range of the committed versions to the base value.
+fn read_committed_value_before_txn<K>(
+    versioned_delayed_fields: &dyn TVersionedDelayedFieldView<K>,
+    id: &K,
+    txn_idx: TxnIndex,
+) -> Result<DelayedFieldValue, MVDelayedFieldsError> {
+    if txn_idx == 0 {
+        versioned_delayed_fields.read_base_value(id)
+    } else {
+        versioned_delayed_fields.read_latest_committed_value(
+            id,
+            txn_idx,
+            ReadPosition::BeforeCurrentTxn,
+        )
+    }
+}
+
 // TODO[agg_v2](cleanup): see about the split with CapturedReads,
 // and whether anything should be moved there.
 fn delayed_field_try_add_delta_outcome_impl<T: Transaction>(
@@ -310,23 +1042,39 @@ fn delayed_field_try_add_delta_outcome_impl<T: Transaction>(
     delta: &SignedU128,
     max_value: u128,
     txn_idx: TxnIndex,
+    history_base_revalidation: bool,
+    counter_labels: &ViewCounterLabels,
+    health: &RefCell<ViewHealthSummary>,
 ) -> Result<bool, PanicOr<DelayedFieldsSpeculativeError>> {
     // No need to record or check or try, if input value exceeds the bound.
     if delta.abs() > max_value {
         return Ok(false);
     }
 
+    // A value of a different kind is recorded, so that the validation fails (and the cause
+    // is not lost in a generic error).
+    let aggregator_value = |value| {
+        expect_aggregator_value(id, value, counter_labels).map_err(|err| {
+            captured_reads
+                .borrow_mut()
+                .capture_delayed_field_read_error(&err);
+            err
+        })
+    };
+
     let delayed_read = captured_reads
         .borrow()
         .get_delayed_field_by_kind(id, DelayedFieldReadKind::HistoryBounded);
     match delayed_read {
-        Some(DelayedFieldRead::Value { value }) => {
+        Some(DelayedFieldRead::Value { value } | DelayedFieldRead::Committed { value }) => {
             let math = BoundedMath::new(max_value);
-            let before = expect_ok(
-                math.unsigned_add_delta(value.clone().into_aggregator_value()?, base_delta),
-            )?;
+            let before = expect_ok(math.unsigned_add_delta(aggregator_value(value)?, base_delta))?;
             Ok(math.unsigned_add_delta(before, delta).is_ok())
         },
+        Some(DelayedFieldRead::Exists { .. }) => Err(code_invariant_error(
+            "HistoryBounded DelayedField read returned an Exists read",
+        )
+        .into()),
         Some(DelayedFieldRead::HistoryBounded {
             restriction: history,
             max_value: before_max_value,
@@ -338,6 +1086,27 @@ fn delayed_field_try_add_delta_outcome_impl<T: Transaction>(
                 );
             }
 
+            // The history is relative to the committed value read by the first try_add. If
+            // the value was since re-committed (e.g. a lower transaction was re-executed),
+            // the read is going to fail the validation, hence the execution can be stopped
+            // early. The check is skipped if the value cannot be read without waiting.
+            if history_base_revalidation {
+                if let Ok(committed_value) =
+                    read_committed_value_before_txn(versioned_delayed_fields, id, txn_idx)
+                {
+                    if aggregator_value(committed_value)? != inner_aggregator_value {
+                        captured_reads.borrow_mut().mark_failure();
+                        return Err(PanicOr::Or(DelayedFieldsSpeculativeError::InconsistentRead));
+                    }
+                }
+            }
+
+            // A zero delta (e.g. try_add(agg, 0) used as a bound check) succeeds on top of the
+            // prior successful deltas, whose cumulative delta is already in the history.
+            if delta.is_zero() {
+                return Ok(true);
+            }
+
             let (result, udpated_delayed_read) =
                 compute_delayed_field_try_add_delta_outcome_from_history(
                     base_delta,
@@ -355,7 +1124,10 @@ fn delayed_field_try_add_delta_outcome_impl<T: Transaction>(
             Ok(result)
         },
         None => {
-            if !base_delta.is_zero() {
+            // When the capture is disabled, the history of the prior try_add calls is not
+            // recorded, and the outcome is computed from the committed value instead.
+            let capture_disabled = captured_reads.borrow().is_capture_disabled();
+            if !base_delta.is_zero() && !capture_disabled {
                 return Err(code_invariant_error(
                     "Passed-in delta is not zero, but CapturedReads has no record",
                 )
@@ -363,26 +1135,41 @@ fn delayed_field_try_add_delta_outcome_impl<T: Transaction>(
             }
 
             let last_committed_value = loop {
-                match versioned_delayed_fields.read_latest_committed_value(
-                    id,
-                    txn_idx,
-                    ReadPosition::BeforeCurrentTxn,
-                ) {
+                match read_committed_value_before_txn(versioned_delayed_fields, id, txn_idx) {
                     Ok(v) => break v,
                     Err(MVDelayedFieldsError::Dependency(dep_idx)) => {
-                        if !wait_for_dependency(wait_for, txn_idx, dep_idx)? {
-                            // TODO[agg_v2](cleanup): think of correct return type
-                            return Err(PanicOr::Or(
-                                DelayedFieldsSpeculativeError::InconsistentRead,
-                            ));
+                        match wait_for_dependency(
+                            wait_for,
+                            txn_idx,
+                            dep_idx,
+                            counter_labels,
+                            health,
+                        )? {
+                            DependencyWaitResult::Resolved => {},
+                            DependencyWaitResult::ExecutionHalted
+                            | DependencyWaitResult::TimedOut(_) => {
+                                // TODO[agg_v2](cleanup): think of correct return type
+                                return Err(PanicOr::Or(
+                                    DelayedFieldsSpeculativeError::InconsistentRead,
+                                ));
+                            },
                         }
                     },
                     Err(_) => {
                         return Err(PanicOr::Or(DelayedFieldsSpeculativeError::InconsistentRead))
                     },
                 };
+            };
+            let last_committed_value = aggregator_value(last_committed_value)?;
+
+            if capture_disabled {
+                let math = BoundedMath::new(max_value);
+                // The committed value may have changed since the prior try_add calls.
+                let before = math
+                    .unsigned_add_delta(last_committed_value, base_delta)
+                    .map_err(|_| PanicOr::Or(DelayedFieldsSpeculativeError::InconsistentRead))?;
+                return Ok(math.unsigned_add_delta(before, delta).is_ok());
             }
-            .into_aggregator_value()?;
 
             let (result, new_delayed_read) =
                 compute_delayed_field_try_add_delta_outcome_first_time(
@@ -399,17 +1186,128 @@ fn delayed_field_try_add_delta_outcome_impl<T: Transaction>(
     }
 }
 
+// The try_add calls of the batch update the captured histories in place, and the prior
+// reads are restored if the commit policy is not met. The reads of the delayed fields
+// with a failed try_add are kept (also with the updates of the preceding try_add calls on
+// the same field), as the rejection of the batch depends on them: for a monotone policy,
+// the batch remains rejected as long as the failures are validated. Errors are propagated
+// without a rollback, as the incarnation fails anyway.
+fn delayed_field_try_add_deltas_with_policy_impl<T: Transaction>(
+    captured_reads: &RefCell<CapturedReads<T>>,
+    versioned_delayed_fields: &dyn TVersionedDelayedFieldView<T::Identifier>,
+    wait_for: &dyn TWaitForDependency,
+    deltas: &[(T::Identifier, SignedU128, SignedU128, u128)],
+    commit_policy: &dyn Fn(&[bool]) -> bool,
+    txn_idx: TxnIndex,
+    history_base_revalidation: bool,
+    counter_labels: &ViewCounterLabels,
+    health: &RefCell<ViewHealthSummary>,
+) -> Result<Vec<bool>, PanicOr<DelayedFieldsSpeculativeError>> {
+    let mut prior_reads: HashMap<T::Identifier, Option<DelayedFieldRead>> = HashMap::new();
+    let mut outcomes = Vec::with_capacity(deltas.len());
+    for (id, base_delta, delta, max_value) in deltas {
+        prior_reads
+            .entry(*id)
+            .or_insert_with(|| captured_reads.borrow().get_delayed_field_read(id).cloned());
+        outcomes.push(delayed_field_try_add_delta_outcome_impl(
+            captured_reads,
+            versioned_delayed_fields,
+            wait_for,
+            id,
+            base_delta,
+            delta,
+            *max_value,
+            txn_idx,
+            history_base_revalidation,
+            counter_labels,
+            health,
+        )?);
+    }
+
+    if !commit_policy(&outcomes) {
+        let failed_ids: HashSet<_> = deltas
+            .iter()
+            .zip(outcomes.iter())
+            .filter_map(|((id, _, _, _), outcome)| (!outcome).then_some(*id))
+            .collect();
+        let mut captured_reads = captured_reads.borrow_mut();
+        for (id, prior_read) in prior_reads {
+            if !failed_ids.contains(&id) {
+                captured_reads.restore_delayed_field_read(id, prior_read);
+            }
+        }
+    }
+    Ok(outcomes)
+}
+
+/// Outcome of waiting on a read dependency.
+#[derive(Debug, PartialEq, Eq)]
+enum DependencyWaitResult {
+    // The dependency is resolved, and it is safe to continue.
+    Resolved,
+    // The parallel execution has been halted.
+    ExecutionHalted,
+    // The dependency was not resolved within the (provided) timeout.
+    TimedOut(Duration),
+}
+
+fn dependency_wait_timeout_message(dep_idx: TxnIndex, timeout: Duration) -> String {
+    format!(
+        "Dependency wait timeout: transaction {} not resolved within {:?}",
+        dep_idx, timeout
+    )
+}
+
 // txn_idx is estimated to have a r/w dependency on dep_idx.
-// Returns after the dependency has been resolved, the returned indicator is true if
-// it is safe to continue, and false if the execution has been halted.
+// Returns after the dependency has been resolved, the execution has been halted, or the
+// timeout of waiting on the dependency has expired. The wait (and the halt) is recorded
+// in the health of the view.
 fn wait_for_dependency(
     wait_for: &dyn TWaitForDependency,
     txn_idx: TxnIndex,
     dep_idx: TxnIndex,
-) -> Result<bool, PanicError> {
+    counter_labels: &ViewCounterLabels,
+    health: &RefCell<ViewHealthSummary>,
+) -> Result<DependencyWaitResult, PanicError> {
+    let start = Instant::now();
+    let ret = wait_for_dependency_impl(wait_for, txn_idx, dep_idx, counter_labels);
+
+    let mut health = health.borrow_mut();
+    health.num_dependency_waits += 1;
+    health.dependency_wait_time += start.elapsed();
+    match ret {
+        Ok(DependencyWaitResult::ExecutionHalted) => {
+            health.record_speculative_halt(SpeculativeHaltReason::ExecutionHalted)
+        },
+        Ok(DependencyWaitResult::TimedOut(_)) => {
+            health.record_speculative_halt(SpeculativeHaltReason::DependencyTimeout)
+        },
+        Ok(DependencyWaitResult::Resolved) | Err(_) => {},
+    }
+    ret
+}
+
+fn wait_for_dependency_impl(
+    wait_for: &dyn TWaitForDependency,
+    txn_idx: TxnIndex,
+    dep_idx: TxnIndex,
+    counter_labels: &ViewCounterLabels,
+) -> Result<DependencyWaitResult, PanicError> {
     match wait_for.wait_for_dependency(txn_idx, dep_idx)? {
         DependencyResult::Dependency(dep_condition) => {
-            let _timer = counters::DEPENDENCY_WAIT_SECONDS.start_timer();
+            let (lock, cvar) = &*dep_condition;
+            // Short dependencies are often resolved within a few polls of the status, which
+            // is cheaper than blocking on the condition variable. Only blocking is timed.
+            for _ in 0..wait_for.dependency_wait_spin_count() {
+                if let Some(result) = dependency_wait_result(&lock.lock()) {
+                    return Ok(result);
+                }
+                std::hint::spin_loop();
+            }
+
+            let _timer = counters::DEPENDENCY_WAIT_SECONDS
+                .with_label_values(&counter_labels.values())
+                .start_timer();
             // Wait on a condition variable corresponding to the encountered
             // read dependency. Once the dep_idx finishes re-execution, scheduler
             // will mark the dependency as resolved, and then the txn_idx will be
@@ -424,16 +1322,50 @@ fn wait_for_dependency(
             // thread that aborted dep_idx was alive, and again, since lower txns
             // than txn_idx are not blocked, so the execution of dep_idx will
             // eventually finish and lead to unblocking txn_idx, contradiction.
-            let (lock, cvar) = &*dep_condition;
+            //
+            // The (opt-in) timeout guards against the above not holding (e.g. due to a bug),
+            // and only fires if the status is still unresolved (re-checked after every wake-up).
+            let deadline = wait_for
+                .dependency_wait_timeout()
+                .and_then(|timeout| Some((timeout, Instant::now().checked_add(timeout)?)));
             let mut dep_resolved = lock.lock();
             while matches!(*dep_resolved, DependencyStatus::Unresolved) {
-                dep_resolved = cvar.wait(dep_resolved).unwrap();
+                dep_resolved = match deadline {
+                    Some((timeout, deadline)) => {
+                        let now = Instant::now();
+                        if now >= deadline {
+                            counters::DEPENDENCY_WAIT_TIMEOUT_COUNT
+                                .with_label_values(&counter_labels.values())
+                                .inc();
+                            error!(
+                                "Transaction {} timed out waiting on a dependency on transaction {}",
+                                txn_idx, dep_idx
+                            );
+                            return Ok(DependencyWaitResult::TimedOut(timeout));
+                        }
+                        cvar.wait_timeout(dep_resolved, deadline - now).unwrap().0
+                    },
+                    None => cvar.wait(dep_resolved).unwrap(),
+                };
             }
             // dep resolved status is either resolved or execution halted.
-            Ok(matches!(*dep_resolved, DependencyStatus::Resolved))
+            Ok(match *dep_resolved {
+                DependencyStatus::Resolved => DependencyWaitResult::Resolved,
+                _ => DependencyWaitResult::ExecutionHalted,
+            })
         },
-        DependencyResult::ExecutionHalted => Ok(false),
-        DependencyResult::Resolved => Ok(true),
+        DependencyResult::ExecutionHalted => Ok(DependencyWaitResult::ExecutionHalted),
+        DependencyResult::Resolved => Ok(DependencyWaitResult::Resolved),
+    }
+}
+
+// Returns the result of waiting on a dependency with the given status, if it is no longer
+// unresolved.
+fn dependency_wait_result(status: &DependencyStatus) -> Option<DependencyWaitResult> {
+    match status {
+        DependencyStatus::Unresolved => None,
+        DependencyStatus::Resolved => Some(DependencyWaitResult::Resolved),
+        DependencyStatus::ExecutionHalted => Some(DependencyWaitResult::ExecutionHalted),
     }
 }
 
@@ -443,48 +1375,160 @@ impl<'a, T: Transaction, X: Executable> ParallelState<'a, T, X> {
         shared_scheduler: &'a Scheduler,
         start_shared_counter: u32,
         shared_counter: &'a AtomicU32,
+        key_interner: &'a KeyInterner<T::Key>,
+        tag_layout_registry: Option<&'a TagLayoutRegistry<T::Tag>>,
+        layout_mismatch_observer: Option<&'a LayoutMismatchObserver>,
+        config: ParallelStateConfig,
     ) -> Self {
+        let ParallelStateConfig {
+            group_size_consistency_checks,
+            hashed_value_read_threshold,
+            exchange_size_threshold,
+            history_base_revalidation,
+            dependency_wait_retry_limit,
+            shard_id,
+        } = config;
         Self {
             versioned_map: shared_map,
             scheduler: shared_scheduler,
             start_counter: start_shared_counter,
             counter: shared_counter,
             captured_reads: RefCell::new(CapturedReads::new()),
+            capture_disabled: false,
+            key_interner,
+            group_size_consistency_checks,
+            tag_layout_registry,
+            layout_mismatch_observer,
+            hashed_value_read_threshold,
+            exchange_size_threshold,
+            history_base_revalidation,
+            dependency_wait_retry_limit,
+            counter_labels: ViewCounterLabels::new(true, shard_id),
+            minted_delayed_field_ids: RefCell::new(HashSet::new()),
+            patched_delayed_field_ids: RefCell::new(Vec::new()),
+            scanned_delayed_field_ids: RefCell::new(BTreeSet::new()),
+            fetch_counts: RefCell::new(FetchCounts::default()),
+            health: RefCell::new(ViewHealthSummary::default()),
+        }
+    }
+
+    /// Disables capturing the reads (the taken reads are empty), for the views that are never
+    /// validated, e.g. the views materializing the outputs of the committed transactions.
+    pub(crate) fn with_capture_disabled(mut self) -> Self {
+        self.capture_disabled = true;
+        self.captured_reads.get_mut().disable_capture();
+        self
+    }
+
+    /// Replaces the (empty) captured reads with the reads of a prior execution, e.g. taken
+    /// from the CapturedReadsPool of the worker, so that their allocations are reused.
+    pub(crate) fn with_recycled_reads(mut self, mut reads: CapturedReads<T>) -> Self {
+        reads.clear();
+        if self.capture_disabled {
+            reads.disable_capture();
         }
+        *self.captured_reads.get_mut() = reads;
+        self
+    }
+
+    /// Replaces the (empty) captured reads with the still valid reads of a prior incarnation
+    /// (see CapturedReads::into_validated_subset), which are then served without fetching
+    /// from the shared map.
+    pub(crate) fn with_prior_reads(mut self, prior_reads: CapturedReads<T>) -> Self {
+        *self.captured_reads.get_mut() = prior_reads;
+        self
     }
 
     pub(crate) fn set_delayed_field_value(&self, id: T::Identifier, base_value: DelayedFieldValue) {
         self.versioned_map
             .delayed_fields()
-            .set_base_value(id, base_value)
+            .set_base_value(id, base_value);
+        self.minted_delayed_field_ids.borrow_mut().insert(id);
+        self.health.borrow_mut().num_minted_delayed_field_ids += 1;
+    }
+
+    fn confirm_patched_base_value(&self, installed: bool) {
+        confirm_patched_base_value_impl::<T>(
+            &self.patched_delayed_field_ids,
+            &self.minted_delayed_field_ids,
+            installed,
+        );
+    }
+
+    fn speculative_abort(&self, reason: SpeculativeHaltReason, message: String) -> ViewError {
+        speculative_abort(reason, message, &self.counter_labels, &self.health)
+    }
+
+    fn execution_halted(&self) -> ViewError {
+        self.speculative_abort(
+            SpeculativeHaltReason::ExecutionHalted,
+            "Interrupted as block execution was halted".to_string(),
+        )
+    }
+
+    /// Called before each wait on a read dependency of the key (the read is re-fetched after
+    /// the wait). Halts the execution if the read already waited as many times as allowed.
+    fn check_dependency_wait_retries(
+        &self,
+        num_waits: &mut usize,
+        key: &T::Key,
+        dep_idx: TxnIndex,
+    ) -> Result<(), ViewError> {
+        if let Some(limit) = self.dependency_wait_retry_limit {
+            if *num_waits >= limit {
+                self.captured_reads.borrow_mut().mark_failure();
+                return Err(self.speculative_abort(
+                    SpeculativeHaltReason::DependencyWaitLimit,
+                    format!(
+                        "Read of {:?} exceeded the limit of {} dependency waits (dependency on \
+                         transaction {})",
+                        key, limit, dep_idx
+                    ),
+                ));
+            }
+        }
+        *num_waits += 1;
+        Ok(())
     }
 
-    // TODO: Actually fill in the logic to record fetched executables, etc.
     fn fetch_module(
         &self,
         key: &T::Key,
         txn_idx: TxnIndex,
     ) -> anyhow::Result<MVModulesOutput<T::Value, X>, MVModulesError> {
-        // Record for the R/W path intersection fallback for modules.
-        self.captured_reads
-            .borrow_mut()
-            .module_reads
-            .push(key.clone());
+        // Record the version of the fetched module for validation (the key is also used for
+        // the R/W path intersection fallback for modules).
+        let fetched_module = self
+            .versioned_map
+            .modules()
+            .fetch_module_with_version(key, txn_idx);
+        if let Err(err) =
+            self.captured_reads
+                .borrow_mut()
+                .capture_module_read(ModuleRead::from_fetched_module(
+                    self.key_interner.intern(key),
+                    &fetched_module,
+                ))
+        {
+            // The use is marked as incorrect, leading to the sequential execution fallback.
+            error!("Module read of {:?} not captured: {:?}", key, err);
+        }
 
-        self.versioned_map.modules().fetch_module(key, txn_idx)
+        fetched_module.map(|(_, output)| output)
     }
 
     fn read_group_size(
         &self,
         group_key: &T::Key,
         txn_idx: TxnIndex,
-    ) -> PartialVMResult<GroupReadResult> {
+    ) -> Result<GroupReadResult, ViewError> {
         use MVGroupError::*;
 
         if let Some(group_size) = self.captured_reads.borrow().group_size(group_key) {
             return Ok(GroupReadResult::Size(group_size));
         }
 
+        let mut num_dependency_waits = 0;
         loop {
             match self
                 .versioned_map
@@ -492,12 +1536,24 @@ impl<'a, T: Transaction, X: Executable> ParallelState<'a, T, X> {
                 .get_group_size(group_key, txn_idx)
             {
                 Ok(group_size) => {
-                    assert_ok!(
-                        self.captured_reads
+                    if !self.capture_disabled
+                        && self
+                            .captured_reads
                             .borrow_mut()
-                            .capture_group_size(group_key.clone(), group_size),
-                        "Group size may not be inconsistent: must be recorded once"
-                    );
+                            .capture_group_size(self.key_interner.intern(group_key), group_size)
+                            .is_err()
+                    {
+                        // A different size was recorded by the same execution.
+                        return Err(self.speculative_abort(
+                            SpeculativeHaltReason::InconsistentRead,
+                            "Inconsistency in group size reads (must be due to speculation)"
+                                .to_string(),
+                        ));
+                    }
+
+                    if self.group_size_consistency_checks {
+                        self.check_group_size_consistency(group_key, txn_idx, group_size);
+                    }
 
                     return Ok(GroupReadResult::Size(group_size));
                 },
@@ -508,19 +1564,125 @@ impl<'a, T: Transaction, X: Executable> ParallelState<'a, T, X> {
                     unreachable!("Reading group size does not require a specific tag look-up");
                 },
                 Err(Dependency(dep_idx)) => {
-                    if !wait_for_dependency(self.scheduler, txn_idx, dep_idx)? {
-                        return Err(PartialVMError::new(
-                            StatusCode::SPECULATIVE_EXECUTION_ABORT_ERROR,
-                        )
-                        .with_message("Interrupted as block execution was halted".to_string()));
+                    self.check_dependency_wait_retries(
+                        &mut num_dependency_waits,
+                        group_key,
+                        dep_idx,
+                    )?;
+                    match wait_for_dependency(
+                        self.scheduler,
+                        txn_idx,
+                        dep_idx,
+                        &self.counter_labels,
+                        &self.health,
+                    )? {
+                        DependencyWaitResult::Resolved => {},
+                        DependencyWaitResult::ExecutionHalted => {
+                            return Err(self.execution_halted());
+                        },
+                        DependencyWaitResult::TimedOut(timeout) => {
+                            return Err(self.speculative_abort(
+                                SpeculativeHaltReason::DependencyTimeout,
+                                dependency_wait_timeout_message(dep_idx, timeout),
+                            ));
+                        },
+                    }
+                },
+                Err(TagSerializationError(e)) => {
+                    return Err(ViewError::SerializationError(e));
+                },
+            }
+        }
+    }
+
+    /// Returns the tags of the existing members of the group, or None if the group contents
+    /// are not initialized. The tags are not captured: the caller reads the group size first,
+    /// which validates the set of members.
+    fn read_group_tags(
+        &self,
+        group_key: &T::Key,
+        txn_idx: TxnIndex,
+    ) -> Result<Option<Vec<T::Tag>>, ViewError> {
+        use MVGroupError::*;
+
+        let mut num_dependency_waits = 0;
+        loop {
+            match self
+                .versioned_map
+                .group_data()
+                .fetch_latest_group(group_key, txn_idx)
+            {
+                Ok(members) => {
+                    return Ok(Some(members.into_iter().map(|(tag, _)| tag).collect()));
+                },
+                Err(Uninitialized) => {
+                    return Ok(None);
+                },
+                Err(TagNotFound) => {
+                    unreachable!("Reading group tags does not require a specific tag look-up");
+                },
+                Err(Dependency(dep_idx)) => {
+                    self.check_dependency_wait_retries(
+                        &mut num_dependency_waits,
+                        group_key,
+                        dep_idx,
+                    )?;
+                    match wait_for_dependency(
+                        self.scheduler,
+                        txn_idx,
+                        dep_idx,
+                        &self.counter_labels,
+                        &self.health,
+                    )? {
+                        DependencyWaitResult::Resolved => {},
+                        DependencyWaitResult::ExecutionHalted => {
+                            return Err(self.execution_halted());
+                        },
+                        DependencyWaitResult::TimedOut(timeout) => {
+                            return Err(self.speculative_abort(
+                                SpeculativeHaltReason::DependencyTimeout,
+                                dependency_wait_timeout_message(dep_idx, timeout),
+                            ));
+                        },
                     }
                 },
                 Err(TagSerializationError(e)) => {
-                    return Err(e);
+                    return Err(ViewError::SerializationError(e));
                 },
             }
         }
     }
+
+    fn check_group_size_consistency(
+        &self,
+        group_key: &T::Key,
+        txn_idx: TxnIndex,
+        group_size: ResourceGroupSize,
+    ) {
+        // The group may be concurrently modified between the size and the contents reads (e.g.
+        // by a re-execution of a lower transaction). To avoid false positives, the check is
+        // performed only if the size is unchanged after the contents are read. Otherwise, the
+        // reading transaction is going to fail validation regardless.
+        if let Ok(members) = self
+            .versioned_map
+            .group_data()
+            .fetch_latest_group(group_key, txn_idx)
+        {
+            if self
+                .versioned_map
+                .group_data()
+                .get_group_size(group_key, txn_idx)
+                == Ok(group_size)
+            {
+                report_group_size_inconsistency::<T>(
+                    group_key,
+                    group_size,
+                    members,
+                    &self.counter_labels,
+                );
+            }
+        }
+    }
 }
 
 impl<'a, T: Transaction, X: Executable> ResourceState<T> for ParallelState<'a, T, X> {
@@ -528,6 +1690,22 @@ impl<'a, T: Transaction, X: Executable> ResourceState<T> for ParallelState<'a, T
         self.versioned_map.data().set_base_value(key, value);
     }
 
+    fn has_base_value(&self, key: &T::Key) -> bool {
+        // Only the base value is fetched at index 0.
+        self.versioned_map.data().fetch_data(key, 0).is_ok()
+    }
+
+    fn has_exchanged_base_value_with_layout(&self, key: &T::Key) -> bool {
+        // Only the base value is fetched at index 0.
+        matches!(
+            self.versioned_map.data().fetch_data(key, 0),
+            Ok(MVDataOutput::Versioned(
+                _,
+                ValueWithLayout::Exchanged(_, Some(_))
+            ))
+        )
+    }
+
     /// Captures a read from the VM execution, but not unresolved deltas, as in this case it is the
     /// callers responsibility to set the aggregator's base value and call fetch_data again.
     fn read_cached_data_by_kind(
@@ -537,48 +1715,84 @@ impl<'a, T: Transaction, X: Executable> ResourceState<T> for ParallelState<'a, T
         target_kind: ReadKind,
         layout: UnknownOrLayout,
         patch_base_value: &dyn Fn(&T::Value, Option<&MoveTypeLayout>) -> PartialVMResult<T::Value>,
-    ) -> ReadResult {
+    ) -> Result<ReadResult, ViewError> {
         use MVDataError::*;
         use MVDataOutput::*;
 
-        if let Some(data) = self
-            .captured_reads
-            .borrow()
-            .get_by_kind(key, None, target_kind.clone())
-        {
-            return ReadResult::from_data_read(data);
-        }
+        // A value read captured by the hash is served by refetching the value, which must be
+        // consistent with the captured read (and is not captured again).
+        let captured_read =
+            self.captured_reads
+                .borrow()
+                .get_by_kind(key, None, target_kind.clone());
+        let hashed_read = match captured_read {
+            Some(DataRead::Hashed(version, hashed_value)) => Some((version, hashed_value)),
+            Some(data) => {
+                self.fetch_counts.borrow_mut().num_captured_read_hits += 1;
+                return Ok(ReadResult::from_data_read(data)?);
+            },
+            None => None,
+        };
 
+        let mut num_dependency_waits = 0;
         loop {
+            self.fetch_counts.borrow_mut().num_map_fetches += 1;
             match self.versioned_map.data().fetch_data(key, txn_idx) {
                 Ok(Versioned(version, value)) => {
+                    observe_layout_mismatch(
+                        self.layout_mismatch_observer,
+                        key,
+                        &target_kind,
+                        &layout,
+                        &value,
+                        &self.counter_labels,
+                    );
                     // If we have a known layout, upgrade RawFromStorage value to Exchanged.
                     if let UnknownOrLayout::Known(layout) = layout {
                         if let ValueWithLayout::RawFromStorage(v) = value {
                             assert_eq!(version, Err(StorageVersion), "Fetched resource has unknown layout but the version is not Err(StorageVersion)");
                             match patch_base_value(v.as_ref(), layout) {
                                 Ok(patched_value) => {
-                                    self.versioned_map.data().set_base_value(
+                                    let installed = self.versioned_map.data().set_base_value(
                                         key.clone(),
                                         ValueWithLayout::Exchanged(
                                             Arc::new(patched_value),
                                             layout.cloned().map(Arc::new),
                                         ),
                                     );
-                                    // Refetch in case a concurrent change went through.
+                                    // The ids of the patch are referenced by the shared map
+                                    // only if the patched value was installed.
+                                    self.confirm_patched_base_value(installed);
+                                    // Refetch in case a concurrent change went through:
+                                    // set_base_value only upgrades RawFromStorage to
+                                    // Exchanged, keeping a value exchanged by a concurrent
+                                    // reader, so the refetch never serves RawFromStorage
+                                    // and all readers serve the same exchanged base value
+                                    // (see test_concurrent_base_value_exchange).
                                     continue;
                                 },
                                 Err(e) => {
                                     error!("Couldn't patch value from versioned map: {}", e);
                                     self.captured_reads.borrow_mut().mark_incorrect_use();
-                                    return ReadResult::HaltSpeculativeExecution(
+                                    return Err(self.speculative_abort(
+                                        SpeculativeHaltReason::PatchFailure,
                                         "Couldn't patch value from versioned map".to_string(),
-                                    );
+                                    ));
                                 },
                             }
                         }
                     }
 
+                    if let Some((_, hashed_value)) = &hashed_read {
+                        if !hashed_value.matches(&value) {
+                            self.captured_reads.borrow_mut().mark_failure();
+                            return Err(self.speculative_abort(
+                                SpeculativeHaltReason::InconsistentRead,
+                                "Inconsistency in reads (must be due to speculation)".to_string(),
+                            ));
+                        }
+                    }
+
                     let data_read = match DataRead::from_value_with_layout(version, value)
                         .downcast(target_kind)
                     {
@@ -586,69 +1800,108 @@ impl<'a, T: Transaction, X: Executable> ResourceState<T> for ParallelState<'a, T
                         None => {
                             error!("Couldn't downcast value from versioned map");
                             self.captured_reads.borrow_mut().mark_incorrect_use();
-                            return ReadResult::HaltSpeculativeExecution(
+                            return Err(self.speculative_abort(
+                                SpeculativeHaltReason::DowncastFailure,
                                 "Couldn't downcast value from versioned map".to_string(),
-                            );
+                            ));
                         },
                     };
 
-                    if self
-                        .captured_reads
-                        .borrow_mut()
-                        .capture_read(key.clone(), None, data_read.clone())
-                        .is_err()
+                    if hashed_read.is_some() {
+                        return Ok(ReadResult::from_data_read(data_read)?);
+                    }
+
+                    // Large values may be captured by the hash, but the read is served the
+                    // actual value.
+                    let captured_read = match self.hashed_value_read_threshold {
+                        Some(threshold) => data_read.clone().hashed_if_larger_than(threshold),
+                        None => data_read.clone(),
+                    };
+                    if !self.capture_disabled
+                        && self
+                            .captured_reads
+                            .borrow_mut()
+                            .capture_read(self.key_interner.intern(key), None, captured_read)
+                            .is_err()
                     {
                         // Inconsistency in recorded reads.
-                        return ReadResult::HaltSpeculativeExecution(
+                        return Err(self.speculative_abort(
+                            SpeculativeHaltReason::InconsistentRead,
                             "Inconsistency in reads (must be due to speculation)".to_string(),
-                        );
+                        ));
                     }
 
-                    return ReadResult::from_data_read(data_read);
+                    return Ok(ReadResult::from_data_read(data_read)?);
+                },
+                Ok(Resolved(_)) if hashed_read.is_some() => {
+                    // The hashed value read was not a resolved aggregator v1 value.
+                    self.captured_reads.borrow_mut().mark_failure();
+                    return Err(self.speculative_abort(
+                        SpeculativeHaltReason::InconsistentRead,
+                        "Inconsistency in reads (must be due to speculation)".to_string(),
+                    ));
                 },
                 Ok(Resolved(value)) => {
                     let data_read = DataRead::Resolved(value)
                         .downcast(target_kind)
                         .expect("Downcast from Resolved must succeed");
 
-                    if self
-                        .captured_reads
-                        .borrow_mut()
-                        .capture_read(key.clone(), None, data_read.clone())
-                        .is_err()
+                    if !self.capture_disabled
+                        && self
+                            .captured_reads
+                            .borrow_mut()
+                            .capture_read(self.key_interner.intern(key), None, data_read.clone())
+                            .is_err()
                     {
                         // Inconsistency in recorded reads.
-                        return ReadResult::HaltSpeculativeExecution(
+                        return Err(self.speculative_abort(
+                            SpeculativeHaltReason::InconsistentRead,
                             "Inconsistency in reads (must be due to speculation)".to_string(),
-                        );
+                        ));
                     }
 
-                    return ReadResult::from_data_read(data_read);
+                    return Ok(ReadResult::from_data_read(data_read)?);
                 },
-                Err(Uninitialized) | Err(Unresolved(_)) => {
+                Err(Uninitialized) => {
                     // The underlying assumption here for not recording anything about the read is
                     // that the caller is expected to initialize the contents and serve the reads
                     // solely via the 'fetch_read' interface. Thus, the later, successful read,
                     // will make the needed recordings.
-                    return ReadResult::Uninitialized;
+                    return Ok(ReadResult::Uninitialized);
+                },
+                Err(Unresolved(_)) => {
+                    // Same as above, the read is recorded after the base value is set.
+                    return Ok(ReadResult::Unresolved);
                 },
                 Err(Dependency(dep_idx)) => {
-                    match wait_for_dependency(self.scheduler, txn_idx, dep_idx) {
+                    self.check_dependency_wait_retries(&mut num_dependency_waits, key, dep_idx)?;
+                    match wait_for_dependency(
+                        self.scheduler,
+                        txn_idx,
+                        dep_idx,
+                        &self.counter_labels,
+                        &self.health,
+                    ) {
                         Err(e) => {
                             error!("Error {:?} in wait for dependency", e);
                             self.captured_reads.borrow_mut().mark_incorrect_use();
-                            return ReadResult::HaltSpeculativeExecution(format!(
-                                "Error {:?} in wait for dependency",
-                                e
+                            return Err(self.speculative_abort(
+                                SpeculativeHaltReason::DependencyWaitError,
+                                format!("Error {:?} in wait for dependency", e),
                             ));
                         },
-                        Ok(false) => {
+                        Ok(DependencyWaitResult::ExecutionHalted) => {
                             self.captured_reads.borrow_mut().mark_failure();
-                            return ReadResult::HaltSpeculativeExecution(
-                                "Interrupted as block execution was halted".to_string(),
-                            );
+                            return Err(self.execution_halted());
+                        },
+                        Ok(DependencyWaitResult::TimedOut(timeout)) => {
+                            self.captured_reads.borrow_mut().mark_failure();
+                            return Err(self.speculative_abort(
+                                SpeculativeHaltReason::DependencyTimeout,
+                                dependency_wait_timeout_message(dep_idx, timeout),
+                            ));
                         },
-                        Ok(true) => {
+                        Ok(DependencyWaitResult::Resolved) => {
                             //dependency resolved
                         },
                     }
@@ -656,13 +1909,48 @@ impl<'a, T: Transaction, X: Executable> ResourceState<T> for ParallelState<'a, T
                 Err(DeltaApplicationFailure) => {
                     // AggregatorV1 may have delta application failure due to speculation.
                     self.captured_reads.borrow_mut().mark_failure();
-                    return ReadResult::HaltSpeculativeExecution(
+                    return Err(self.speculative_abort(
+                        SpeculativeHaltReason::DeltaApplicationFailure,
                         "Delta application failure (must be speculative)".to_string(),
-                    );
+                    ));
                 },
             };
         }
     }
+
+    /// The captured reads of all the keys are looked up under a single borrow, and only the
+    /// keys without a captured read (or captured by the hash) are fetched from the map.
+    fn read_cached_data_by_kind_multi(
+        &self,
+        txn_idx: TxnIndex,
+        keys: &[(&T::Key, UnknownOrLayout)],
+        target_kind: ReadKind,
+        patch_base_value: &dyn Fn(&T::Value, Option<&MoveTypeLayout>) -> PartialVMResult<T::Value>,
+    ) -> Result<Vec<ReadResult>, ViewError> {
+        let captured_reads: Vec<_> = {
+            let captured_reads = self.captured_reads.borrow();
+            keys.iter()
+                .map(|(key, _)| captured_reads.get_by_kind(key, None, target_kind.clone()))
+                .collect()
+        };
+
+        keys.iter()
+            .zip(captured_reads)
+            .map(|((key, layout), captured_read)| match captured_read {
+                Some(DataRead::Hashed(_, _)) | None => self.read_cached_data_by_kind(
+                    txn_idx,
+                    key,
+                    target_kind.clone(),
+                    layout.clone(),
+                    patch_base_value,
+                ),
+                Some(data) => {
+                    self.fetch_counts.borrow_mut().num_captured_read_hits += 1;
+                    Ok(ReadResult::from_data_read(data)?)
+                },
+            })
+            .collect()
+    }
 }
 
 impl<'a, T: Transaction, X: Executable> ResourceGroupState<T> for ParallelState<'a, T, X> {
@@ -672,99 +1960,215 @@ impl<'a, T: Transaction, X: Executable> ResourceGroupState<T> for ParallelState<
             .set_raw_base_values(group_key.clone(), base_values);
     }
 
+    fn contains_group(&self, group_key: &T::Key) -> bool {
+        self.versioned_map.group_data().contains_group(group_key)
+    }
+
+    fn set_raw_group_base_size(&self, group_key: T::Key, base_size: ResourceGroupSize) {
+        self.versioned_map
+            .group_data()
+            .set_raw_base_size(group_key, base_size);
+    }
+
+    fn update_tagged_base_value_with_layout(
+        &self,
+        group_key: T::Key,
+        resource_tag: T::Tag,
+        value: T::Value,
+        layout: Option<Arc<MoveTypeLayout>>,
+    ) -> bool {
+        self.versioned_map
+            .group_data()
+            .update_tagged_base_value_with_layout(group_key, resource_tag, value, layout)
+    }
+
+    fn tag_layout_registry(&self) -> Option<&TagLayoutRegistry<T::Tag>> {
+        self.tag_layout_registry
+    }
+
     fn read_cached_group_tagged_data(
         &self,
         txn_idx: TxnIndex,
         group_key: &T::Key,
         resource_tag: &T::Tag,
+        target_kind: ReadKind,
         maybe_layout: Option<&MoveTypeLayout>,
         patch_base_value: &dyn Fn(&T::Value, Option<&MoveTypeLayout>) -> PartialVMResult<T::Value>,
-    ) -> PartialVMResult<GroupReadResult> {
+    ) -> Result<GroupReadResult, ViewError> {
         use MVGroupError::*;
 
-        if let Some(DataRead::Versioned(_, v, layout)) =
-            self.captured_reads
-                .borrow()
-                .get_by_kind(group_key, Some(resource_tag), ReadKind::Value)
-        {
-            return Ok(GroupReadResult::Value(v.extract_raw_bytes(), layout));
+        if let Some(data_read) = self.captured_reads.borrow().get_by_kind(
+            group_key,
+            Some(resource_tag),
+            target_kind.clone(),
+        ) {
+            self.fetch_counts.borrow_mut().num_captured_read_hits += 1;
+            return group_read_result_from_data_read(data_read);
         }
 
+        let mut num_dependency_waits = 0;
         loop {
-            match self.versioned_map.group_data().fetch_tagged_data(
+            self.fetch_counts.borrow_mut().num_map_fetches += 1;
+            let data_read = match self.versioned_map.group_data().fetch_tagged_data(
                 group_key,
                 resource_tag,
                 txn_idx,
             ) {
                 Ok((version, value_with_layout)) => {
                     // If we have a known layout, upgrade RawFromStorage value to Exchanged.
-                    match value_with_layout {
-                        ValueWithLayout::RawFromStorage(v) => {
-                            let patched_value = patch_base_value(v.as_ref(), maybe_layout)?;
-                            self.versioned_map
-                                .group_data()
-                                .update_tagged_base_value_with_layout(
-                                    group_key.clone(),
-                                    resource_tag.clone(),
-                                    patched_value,
-                                    maybe_layout.cloned().map(Arc::new),
-                                );
-                            // Re-fetch in case a concurrent change went through.
-                            continue;
-                        },
-                        ValueWithLayout::Exchanged(value, layout) => {
-                            let data_read =
-                                DataRead::Versioned(version, value.clone(), layout.clone());
-                            assert_ok!(
-                                self.captured_reads.borrow_mut().capture_read(
-                                    group_key.clone(),
-                                    Some(resource_tag.clone()),
-                                    data_read
-                                ),
-                                "Resource read in group recorded once: may not be inconsistent"
+                    // Existence is determined without the layout.
+                    if let (ValueWithLayout::RawFromStorage(v), ReadKind::Value) =
+                        (&value_with_layout, &target_kind)
+                    {
+                        let patched_value = patch_base_value(v.as_ref(), maybe_layout)?;
+                        let installed = self
+                            .versioned_map
+                            .group_data()
+                            .update_tagged_base_value_with_layout(
+                                group_key.clone(),
+                                resource_tag.clone(),
+                                patched_value,
+                                maybe_layout.cloned().map(Arc::new),
                             );
-                            return Ok(GroupReadResult::Value(
-                                value.extract_raw_bytes(),
-                                layout.clone(),
-                            ));
-                        },
+                        self.confirm_patched_base_value(installed);
+                        // Re-fetch in case a concurrent change went through.
+                        continue;
                     }
+
+                    DataRead::from_value_with_layout(version, value_with_layout)
                 },
                 Err(Uninitialized) => {
                     return Ok(GroupReadResult::Uninitialized);
                 },
                 Err(TagNotFound) => {
-                    let data_read = DataRead::Versioned(
+                    // A sentinel (deletion) read is captured, which the validation compares
+                    // against if the tag is still not found (for value and existence reads).
+                    DataRead::Versioned(
                         Err(StorageVersion),
-                        Arc::<T::Value>::new(TransactionWrite::from_state_value(None)),
+                        Arc::<T::Value>::new(sentinel_op_from_state_value(
+                            None,
+                            &self.counter_labels,
+                        )),
                         None,
-                    );
-                    assert_ok!(
-                        self.captured_reads.borrow_mut().capture_read(
-                            group_key.clone(),
-                            Some(resource_tag.clone()),
-                            data_read
-                        ),
-                        "Resource read in group recorded once: may not be inconsistent"
-                    );
-
-                    return Ok(GroupReadResult::Value(None, None));
+                    )
                 },
                 Err(Dependency(dep_idx)) => {
-                    if !wait_for_dependency(self.scheduler, txn_idx, dep_idx)? {
-                        // TODO[agg_v2](cleanup): consider changing from PartialVMResult<GroupReadResult> to GroupReadResult
-                        // like in ReadResult for resources.
-                        return Err(PartialVMError::new(
-                            StatusCode::SPECULATIVE_EXECUTION_ABORT_ERROR,
-                        )
-                        .with_message("Interrupted as block execution was halted".to_string()));
+                    self.check_dependency_wait_retries(
+                        &mut num_dependency_waits,
+                        group_key,
+                        dep_idx,
+                    )?;
+                    match wait_for_dependency(
+                        self.scheduler,
+                        txn_idx,
+                        dep_idx,
+                        &self.counter_labels,
+                        &self.health,
+                    )? {
+                        DependencyWaitResult::Resolved => {},
+                        DependencyWaitResult::ExecutionHalted => {
+                            return Err(self.execution_halted());
+                        },
+                        DependencyWaitResult::TimedOut(timeout) => {
+                            return Err(self.speculative_abort(
+                                SpeculativeHaltReason::DependencyTimeout,
+                                dependency_wait_timeout_message(dep_idx, timeout),
+                            ));
+                        },
                     }
+                    continue;
                 },
                 Err(TagSerializationError(_)) => {
                     unreachable!("Reading a resource does not require tag serialization");
                 },
+            };
+
+            let data_read = data_read.downcast(target_kind.clone()).ok_or_else(|| {
+                code_invariant_error(format!(
+                    "Couldn't downcast the read of a group member to {:?}",
+                    target_kind
+                ))
+            })?;
+            // A previously captured read of a lower kind (existence) may be inconsistent
+            // with the new read, due to speculation.
+            if !self.capture_disabled
+                && self
+                    .captured_reads
+                    .borrow_mut()
+                    .capture_read(
+                        self.key_interner.intern(group_key),
+                        Some(resource_tag.clone()),
+                        data_read.clone(),
+                    )
+                    .is_err()
+            {
+                return Err(self.speculative_abort(
+                    SpeculativeHaltReason::InconsistentRead,
+                    "Inconsistency in group reads (must be due to speculation)".to_string(),
+                ));
+            }
+            return group_read_result_from_data_read(data_read);
+        }
+    }
+}
+
+/// A base value set in the unsync map by the sequential view during an execution (e.g. when
+/// the value is patched), with the value it replaced, if any.
+#[derive(Debug)]
+pub(crate) enum UnsyncWriteJournalEntry<T: Transaction> {
+    Resource {
+        key: T::Key,
+        prior: Option<ValueWithLayout<T::Value>>,
+    },
+    GroupMember {
+        group_key: T::Key,
+        tag: T::Tag,
+        prior: Option<ValueWithLayout<T::Value>>,
+    },
+    DelayedField {
+        id: T::Identifier,
+        prior: Option<DelayedFieldValue>,
+    },
+}
+
+/// The base values set in the unsync map by an execution, in order. Allows to restore the
+/// unsync map to the state prior to the execution (e.g. when the execution is discarded).
+/// The initializations of the groups from storage are not journaled (the base contents of
+/// a group are the same regardless of the execution).
+#[derive(Debug)]
+pub(crate) struct UnsyncWriteJournal<T: Transaction> {
+    entries: Vec<UnsyncWriteJournalEntry<T>>,
+}
+
+impl<T: Transaction> UnsyncWriteJournal<T> {
+    #[cfg(test)]
+    pub(crate) fn entries(&self) -> &[UnsyncWriteJournalEntry<T>] {
+        &self.entries
+    }
+
+    /// Restores the prior values, in the reverse order of the writes. The base values of the
+    /// delayed fields generated by the execution are also removed, hence the journal should
+    /// not be restored in addition to rolling back the minted delayed fields.
+    pub(crate) fn restore<X: Executable>(
+        self,
+        unsync_map: &UnsyncMap<T::Key, T::Tag, T::Value, X, T::Identifier>,
+    ) -> Result<(), PanicError> {
+        for entry in self.entries.into_iter().rev() {
+            match entry {
+                UnsyncWriteJournalEntry::Resource { key, prior } => {
+                    unsync_map.restore_base_value(key, prior)
+                },
+                UnsyncWriteJournalEntry::GroupMember {
+                    group_key,
+                    tag,
+                    prior,
+                } => unsync_map.restore_tagged_base_value(&group_key, tag, prior)?,
+                UnsyncWriteJournalEntry::DelayedField { id, prior } => {
+                    unsync_map.restore_base_delayed_field(id, prior)?
+                },
             }
         }
+        Ok(())
     }
 }
 
@@ -774,6 +2178,25 @@ pub(crate) struct SequentialState<'a, T: Transaction, X: Executable> {
     pub(crate) start_counter: u32,
     pub(crate) counter: &'a RefCell<u32>,
     pub(crate) incorrect_use: RefCell<bool>,
+    group_size_consistency_checks: bool,
+    tag_layout_registry: Option<&'a TagLayoutRegistry<T::Tag>>,
+    layout_mismatch_observer: Option<&'a LayoutMismatchObserver>,
+    counter_labels: ViewCounterLabels,
+    // Ids generated by the execution, not referenced by any base value in the unsync map.
+    minted_delayed_field_ids: RefCell<HashSet<T::Identifier>>,
+    // Ids generated by the last patch of a base value (see confirm_patched_base_value_impl).
+    patched_delayed_field_ids: RefCell<Vec<T::Identifier>>,
+    // Ids contained in the read values that were scanned for exchange.
+    scanned_delayed_field_ids: RefCell<BTreeSet<T::Identifier>>,
+    // Group sizes computed by the view (the unsync map may only change between executions,
+    // except for the base values of the groups, which invalidate the cached sizes).
+    group_sizes: RefCell<HashMap<T::Key, ResourceGroupSize>>,
+    fetch_counts: RefCell<FetchCounts>,
+    health: RefCell<ViewHealthSummary>,
+    // Base values set in the unsync map by the execution, with the values they replaced.
+    write_journal: RefCell<Vec<UnsyncWriteJournalEntry<T>>>,
+    #[cfg(test)]
+    num_group_size_computations: RefCell<usize>,
 }
 
 impl<'a, T: Transaction, X: Executable> SequentialState<'a, T, X> {
@@ -781,6 +2204,10 @@ impl<'a, T: Transaction, X: Executable> SequentialState<'a, T, X> {
         unsync_map: &'a UnsyncMap<T::Key, T::Tag, T::Value, X, T::Identifier>,
         start_counter: u32,
         counter: &'a RefCell<u32>,
+        group_size_consistency_checks: bool,
+        tag_layout_registry: Option<&'a TagLayoutRegistry<T::Tag>>,
+        layout_mismatch_observer: Option<&'a LayoutMismatchObserver>,
+        shard_id: Option<usize>,
     ) -> Self {
         Self {
             unsync_map,
@@ -788,33 +2215,170 @@ impl<'a, T: Transaction, X: Executable> SequentialState<'a, T, X> {
             start_counter,
             counter,
             incorrect_use: RefCell::new(false),
+            group_size_consistency_checks,
+            tag_layout_registry,
+            layout_mismatch_observer,
+            counter_labels: ViewCounterLabels::new(false, shard_id),
+            minted_delayed_field_ids: RefCell::new(HashSet::new()),
+            patched_delayed_field_ids: RefCell::new(Vec::new()),
+            scanned_delayed_field_ids: RefCell::new(BTreeSet::new()),
+            group_sizes: RefCell::new(HashMap::new()),
+            fetch_counts: RefCell::new(FetchCounts::default()),
+            health: RefCell::new(ViewHealthSummary::default()),
+            write_journal: RefCell::new(Vec::new()),
+            #[cfg(test)]
+            num_group_size_computations: RefCell::new(0),
         }
     }
 
-    pub(crate) fn set_delayed_field_value(&self, id: T::Identifier, base_value: DelayedFieldValue) {
-        self.unsync_map.set_base_delayed_field(id, base_value)
+    /// Resets the per-execution state in place, so that the state (and its allocations) can be
+    /// reused by the execution of the next transaction. The unsync map and the counters are
+    /// left untouched.
+    pub(crate) fn reset(&self) {
+        self.read_set.borrow_mut().clear();
+        *self.incorrect_use.borrow_mut() = false;
+        self.minted_delayed_field_ids.borrow_mut().clear();
+        self.patched_delayed_field_ids.borrow_mut().clear();
+        self.scanned_delayed_field_ids.borrow_mut().clear();
+        self.group_sizes.borrow_mut().clear();
+        *self.fetch_counts.borrow_mut() = FetchCounts::default();
+        *self.health.borrow_mut() = ViewHealthSummary::default();
+        self.write_journal.borrow_mut().clear();
     }
 
-    pub(crate) fn read_delayed_field(&self, id: T::Identifier) -> Option<DelayedFieldValue> {
-        self.unsync_map.fetch_delayed_field(&id)
+    fn speculative_abort(&self, reason: SpeculativeHaltReason, message: String) -> ViewError {
+        speculative_abort(reason, message, &self.counter_labels, &self.health)
     }
-}
 
-impl<'a, T: Transaction, X: Executable> ResourceState<T> for SequentialState<'a, T, X> {
-    fn set_base_value(&self, key: T::Key, value: ValueWithLayout<T::Value>) {
-        self.unsync_map.set_base_value(key, value);
-    }
+    fn read_group_size(&self, group_key: &T::Key) -> Result<GroupReadResult, ViewError> {
+        if let Some(group_size) = self.group_sizes.borrow().get(group_key) {
+            return Ok(GroupReadResult::Size(*group_size));
+        }
 
-    fn read_cached_data_by_kind(
-        &self,
-        _txn_idx: TxnIndex,
-        key: &T::Key,
-        target_kind: ReadKind,
-        layout: UnknownOrLayout,
-        patch_base_value: &dyn Fn(&T::Value, Option<&MoveTypeLayout>) -> PartialVMResult<T::Value>,
-    ) -> ReadResult {
+        let group_read = self
+            .unsync_map
+            .get_group_size(group_key)
+            .map_err(ViewError::SerializationError)?;
+        #[cfg(test)]
+        {
+            if let GroupReadResult::Size(_) = &group_read {
+                *self.num_group_size_computations.borrow_mut() += 1;
+            }
+        }
+
+        if self.group_size_consistency_checks {
+            if let (GroupReadResult::Size(group_size), Some(members)) =
+                (&group_read, self.unsync_map.fetch_group_data(group_key))
+            {
+                report_group_size_inconsistency::<T>(
+                    group_key,
+                    *group_size,
+                    members
+                        .into_iter()
+                        .map(|(tag, value)| ((*tag).clone(), value)),
+                    &self.counter_labels,
+                );
+            }
+        }
+
+        if let GroupReadResult::Size(group_size) = &group_read {
+            self.group_sizes
+                .borrow_mut()
+                .insert(group_key.clone(), *group_size);
+        }
+        Ok(group_read)
+    }
+
+    // Deleted members are removed from the group cache, so all the tags are of existing members.
+    fn read_group_tags(&self, group_key: &T::Key) -> Option<Vec<T::Tag>> {
+        self.unsync_map
+            .fetch_group_data(group_key)
+            .map(|members| members.into_iter().map(|(tag, _)| (*tag).clone()).collect())
+    }
+
+    pub(crate) fn set_delayed_field_value(&self, id: T::Identifier, base_value: DelayedFieldValue) {
+        self.write_journal
+            .borrow_mut()
+            .push(UnsyncWriteJournalEntry::DelayedField {
+                id,
+                prior: self.unsync_map.fetch_delayed_field(&id),
+            });
+        self.unsync_map.set_base_delayed_field(id, base_value);
+        self.minted_delayed_field_ids.borrow_mut().insert(id);
+        self.health.borrow_mut().num_minted_delayed_field_ids += 1;
+    }
+
+    fn confirm_patched_base_value(&self, installed: bool) {
+        confirm_patched_base_value_impl::<T>(
+            &self.patched_delayed_field_ids,
+            &self.minted_delayed_field_ids,
+            installed,
+        );
+    }
+
+    pub(crate) fn read_delayed_field(&self, id: T::Identifier) -> Option<DelayedFieldValue> {
+        self.unsync_map.fetch_delayed_field(&id)
+    }
+}
+
+/// The sequential state of a view, owned by the view, or borrowed from the sequential executor,
+/// which reuses a single state across the transactions of the block (see SequentialState::reset).
+pub(crate) enum SequentialStateRef<'a, T: Transaction, X: Executable> {
+    Owned(SequentialState<'a, T, X>),
+    Borrowed(&'a SequentialState<'a, T, X>),
+}
+
+impl<'a, T: Transaction, X: Executable> Deref for SequentialStateRef<'a, T, X> {
+    type Target = SequentialState<'a, T, X>;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            SequentialStateRef::Owned(state) => state,
+            SequentialStateRef::Borrowed(state) => state,
+        }
+    }
+}
+
+impl<'a, T: Transaction, X: Executable> ResourceState<T> for SequentialState<'a, T, X> {
+    fn set_base_value(&self, key: T::Key, value: ValueWithLayout<T::Value>) {
+        self.write_journal
+            .borrow_mut()
+            .push(UnsyncWriteJournalEntry::Resource {
+                key: key.clone(),
+                prior: self.unsync_map.fetch_data(&key),
+            });
+        self.unsync_map.set_base_value(key, value);
+    }
+
+    fn has_base_value(&self, key: &T::Key) -> bool {
+        // The writes replace the base value, and are also considered.
+        self.unsync_map.fetch_data(key).is_some()
+    }
+
+    fn has_exchanged_base_value_with_layout(&self, key: &T::Key) -> bool {
+        // The writes replace the base value, and are also considered.
+        self.unsync_map.fetch_exchanged_data(key).is_some()
+    }
+
+    fn read_cached_data_by_kind(
+        &self,
+        _txn_idx: TxnIndex,
+        key: &T::Key,
+        target_kind: ReadKind,
+        layout: UnknownOrLayout,
+        patch_base_value: &dyn Fn(&T::Value, Option<&MoveTypeLayout>) -> PartialVMResult<T::Value>,
+    ) -> Result<ReadResult, ViewError> {
+        self.fetch_counts.borrow_mut().num_map_fetches += 1;
         match self.unsync_map.fetch_data(key) {
             Some(mut value) => {
+                observe_layout_mismatch(
+                    self.layout_mismatch_observer,
+                    key,
+                    &target_kind,
+                    &layout,
+                    &value,
+                    &self.counter_labels,
+                );
                 // If we have a known layout, upgrade RawFromStorage value to Exchanged.
                 if let UnknownOrLayout::Known(layout) = layout {
                     if let ValueWithLayout::RawFromStorage(v) = value {
@@ -824,8 +2388,8 @@ impl<'a, T: Transaction, X: Executable> ResourceState<T> for SequentialState<'a,
                                     Arc::new(patched_value.clone()),
                                     layout.cloned().map(Arc::new),
                                 );
-                                self.unsync_map
-                                    .set_base_value(key.clone(), exchanged_value.clone());
+                                self.set_base_value(key.clone(), exchanged_value.clone());
+                                self.confirm_patched_base_value(true);
 
                                 // sequential execution doesn't need to worry about concurrent change going through.
                                 value = exchanged_value;
@@ -836,9 +2400,10 @@ impl<'a, T: Transaction, X: Executable> ResourceState<T> for SequentialState<'a,
                                 //               resources and groups.
                                 *self.incorrect_use.borrow_mut() = true;
                                 error!("Unsync map couldn't patch base value");
-                                return ReadResult::HaltSpeculativeExecution(
+                                return Err(self.speculative_abort(
+                                    SpeculativeHaltReason::PatchFailure,
                                     "Unsync map couldn't patch base value".to_string(),
-                                );
+                                ));
                             },
                         }
                     }
@@ -852,52 +2417,109 @@ impl<'a, T: Transaction, X: Executable> ResourceState<T> for SequentialState<'a,
                             .insert(key.clone());
                     }
 
-                    ret
+                    Ok(ret)
                 } else {
                     *self.incorrect_use.borrow_mut() = true;
                     error!(
                         "Unsync map has RawFromStorage value type, while we are requesting value"
                     );
-                    ReadResult::HaltSpeculativeExecution(
+                    Err(self.speculative_abort(
+                        SpeculativeHaltReason::DowncastFailure,
                         "Unsync map has RawFromStorage value type, while we are requesting value"
                             .to_string(),
-                    )
+                    ))
                 }
             },
-            None => ReadResult::Uninitialized,
+            None => Ok(ReadResult::Uninitialized),
         }
     }
 }
 
 impl<'a, T: Transaction, X: Executable> ResourceGroupState<T> for SequentialState<'a, T, X> {
     fn set_raw_group_base_values(&self, group_key: T::Key, base_values: Vec<(T::Tag, T::Value)>) {
+        self.group_sizes.borrow_mut().remove(&group_key);
         self.unsync_map
             .set_group_base_values(group_key.clone(), base_values);
     }
 
+    fn contains_group(&self, group_key: &T::Key) -> bool {
+        self.unsync_map.contains_group(group_key)
+    }
+
+    fn set_raw_group_base_size(&self, group_key: T::Key, base_size: ResourceGroupSize) {
+        self.unsync_map.set_group_base_size(group_key, base_size);
+    }
+
+    fn update_tagged_base_value_with_layout(
+        &self,
+        group_key: T::Key,
+        resource_tag: T::Tag,
+        value: T::Value,
+        layout: Option<Arc<MoveTypeLayout>>,
+    ) -> bool {
+        self.group_sizes.borrow_mut().remove(&group_key);
+        self.write_journal
+            .borrow_mut()
+            .push(UnsyncWriteJournalEntry::GroupMember {
+                group_key: group_key.clone(),
+                tag: resource_tag.clone(),
+                prior: self
+                    .unsync_map
+                    .fetch_group_tagged_data(&group_key, &resource_tag)
+                    .ok(),
+            });
+        self.unsync_map.update_tagged_base_value_with_layout(
+            group_key,
+            resource_tag,
+            value,
+            layout,
+        );
+        // The unsync map is not concurrently exchanged.
+        true
+    }
+
+    fn tag_layout_registry(&self) -> Option<&TagLayoutRegistry<T::Tag>> {
+        self.tag_layout_registry
+    }
+
     fn read_cached_group_tagged_data(
         &self,
         _txn_idx: TxnIndex,
         group_key: &T::Key,
         resource_tag: &T::Tag,
+        target_kind: ReadKind,
         maybe_layout: Option<&MoveTypeLayout>,
         patch_base_value: &dyn Fn(&T::Value, Option<&MoveTypeLayout>) -> PartialVMResult<T::Value>,
-    ) -> PartialVMResult<GroupReadResult> {
+    ) -> Result<GroupReadResult, ViewError> {
+        self.fetch_counts.borrow_mut().num_map_fetches += 1;
         match self
             .unsync_map
             .fetch_group_tagged_data(group_key, resource_tag)
         {
+            Ok(ValueWithLayout::RawFromStorage(v) | ValueWithLayout::Exchanged(v, _))
+                if target_kind == ReadKind::Exists =>
+            {
+                // Existence is determined without the layout (no need to patch).
+                self.read_set
+                    .borrow_mut()
+                    .group_reads
+                    .entry(group_key.clone())
+                    .or_default()
+                    .insert(resource_tag.clone());
+                Ok(GroupReadResult::Exists(!v.is_deletion()))
+            },
             Ok(mut value) => {
                 // If we have a known layout, upgrade RawFromStorage value to Exchanged.
                 if let ValueWithLayout::RawFromStorage(v) = value {
                     let patched_value = patch_base_value(v.as_ref(), maybe_layout)?;
                     let maybe_layout = maybe_layout.cloned().map(Arc::new);
-                    self.unsync_map.update_tagged_base_value_with_layout(
+                    let installed = self.update_tagged_base_value_with_layout(
                         group_key.clone(),
                         resource_tag.clone(),
                         patched_value.clone(),
                         maybe_layout.clone(),
                     );
+                    self.confirm_patched_base_value(installed);
 
                     // Sequential execution doesn't need to worry about concurrent change going through.
                     value = ValueWithLayout::Exchanged(Arc::new(patched_value), maybe_layout);
@@ -928,7 +2550,10 @@ impl<'a, T: Transaction, X: Executable> ResourceGroupState<T> for SequentialStat
                     .entry(group_key.clone())
                     .or_default()
                     .insert(resource_tag.clone());
-                Ok(GroupReadResult::Value(None, None))
+                Ok(match target_kind {
+                    ReadKind::Exists => GroupReadResult::Exists(false),
+                    _ => GroupReadResult::Value(None, None),
+                })
             },
         }
     }
@@ -936,34 +2561,171 @@ impl<'a, T: Transaction, X: Executable> ResourceGroupState<T> for SequentialStat
 
 pub(crate) enum ViewState<'a, T: Transaction, X: Executable> {
     Sync(ParallelState<'a, T, X>),
-    Unsync(SequentialState<'a, T, X>),
+    Unsync(SequentialStateRef<'a, T, X>),
 }
 
 impl<'a, T: Transaction, X: Executable> ViewState<'a, T, X> {
+    fn patched_delayed_field_ids(&self) -> &RefCell<Vec<T::Identifier>> {
+        match self {
+            ViewState::Sync(state) => &state.patched_delayed_field_ids,
+            ViewState::Unsync(state) => &state.patched_delayed_field_ids,
+        }
+    }
+
+    fn confirm_patched_base_value(&self, installed: bool) {
+        match self {
+            ViewState::Sync(state) => state.confirm_patched_base_value(installed),
+            ViewState::Unsync(state) => state.confirm_patched_base_value(installed),
+        }
+    }
+
+    fn scanned_delayed_field_ids(&self) -> &RefCell<BTreeSet<T::Identifier>> {
+        match self {
+            ViewState::Sync(state) => &state.scanned_delayed_field_ids,
+            ViewState::Unsync(state) => &state.scanned_delayed_field_ids,
+        }
+    }
+
+    fn fetch_counts(&self) -> &RefCell<FetchCounts> {
+        match self {
+            ViewState::Sync(state) => &state.fetch_counts,
+            ViewState::Unsync(state) => &state.fetch_counts,
+        }
+    }
+
+    fn health(&self) -> &RefCell<ViewHealthSummary> {
+        match self {
+            ViewState::Sync(state) => &state.health,
+            ViewState::Unsync(state) => &state.health,
+        }
+    }
+
+    fn counter_labels(&self) -> &ViewCounterLabels {
+        match self {
+            ViewState::Sync(state) => &state.counter_labels,
+            ViewState::Unsync(state) => &state.counter_labels,
+        }
+    }
+
     fn get_resource_state(&self) -> &dyn ResourceState<T> {
         match self {
             ViewState::Sync(state) => state,
-            ViewState::Unsync(state) => state,
+            ViewState::Unsync(state) => &**state,
         }
     }
 
     fn get_resource_group_state(&self) -> &dyn ResourceGroupState<T> {
         match self {
             ViewState::Sync(state) => state,
-            ViewState::Unsync(state) => state,
+            ViewState::Unsync(state) => &**state,
         }
     }
 }
 
+/// Creates a sentinel op from the (storage) state value, and verifies that the conversion
+/// preserves the bytes and the metadata, as otherwise the base value would be silently
+/// corrupted. An inconsistency panics in debug builds, and is counted otherwise.
+fn sentinel_op_from_state_value<V: TransactionWrite>(
+    maybe_state_value: Option<StateValue>,
+    counter_labels: &ViewCounterLabels,
+) -> V {
+    let op = V::from_state_value(maybe_state_value.clone());
+
+    let round_trip = op.as_state_value();
+    if round_trip != maybe_state_value {
+        counters::SENTINEL_OP_CONVERSION_ERROR_COUNT
+            .with_label_values(&counter_labels.values())
+            .inc();
+        error!(
+            "Sentinel op conversion did not preserve the state value: {:?} converted to {:?}",
+            maybe_state_value, round_trip
+        );
+        debug_assert!(
+            false,
+            "Sentinel op conversion must preserve the state value"
+        );
+    }
+
+    op
+}
+
+fn not_a_resource_group_error<K: Debug>(state_key: &K) -> PartialVMError {
+    PartialVMError::new(StatusCode::UNEXPECTED_DESERIALIZATION_ERROR)
+        .with_message(format!("Key {:?} is not a resource group", state_key))
+}
+
+// The number of leading bytes of a corrupt resource group that are included in the error.
+const CORRUPT_GROUP_BYTES_PREFIX_LEN: usize = 16;
+
+fn corrupt_resource_group_error<K: Debug>(
+    group_key: &K,
+    bytes: &[u8],
+    err: bcs::Error,
+) -> PartialVMError {
+    PartialVMError::new(StatusCode::UNEXPECTED_DESERIALIZATION_ERROR).with_message(format!(
+        "Failed to deserialize the resource group at {:?} ({} bytes, starting with {:?}): {:?}",
+        group_key,
+        bytes.len(),
+        &bytes[..bytes.len().min(CORRUPT_GROUP_BYTES_PREFIX_LEN)],
+        err
+    ))
+}
+
+/// Computes the size of the group from its serialized bytes (BTreeMap of the group members)
+/// without deserializing the members: the bytes are the ULEB128-encoded number of members,
+/// followed by the serialized members. Returns None if the number can not be decoded.
+fn raw_group_size(bytes: &[u8]) -> Option<ResourceGroupSize> {
+    let mut cursor = Cursor::new(bytes);
+    let num_tagged_resources = read_uleb128_as_u64(&mut cursor).ok()?;
+    Some(ResourceGroupSize::Combined {
+        num_tagged_resources: usize::try_from(num_tagged_resources).ok()?,
+        all_tagged_resources_size: bytes.len() as u64 - cursor.position(),
+    })
+}
+
+/// Describes where a resolved aggregator V1 value was read from, providing the information
+/// needed to construct the write that replaces it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VersionInfo {
+    /// Value was read from the entry written at the given version (Err(StorageVersion) if
+    /// it was the pre-block storage value), with the metadata of that state item.
+    Versioned(Version, StateValueMetadata),
+    /// Value was resolved by applying the aggregator deltas to the latest written value.
+    /// In this case, the read validates only as long as the same deltas resolve to the same
+    /// value, and thus no metadata is available.
+    Resolved,
+    /// Value was read during sequential execution, where it is not versioned.
+    Unversioned(StateValueMetadata),
+}
+
+// The indices of the delayed field identifiers at or above the bound are reserved, and are
+// never generated (nor valid). In the parallel setting, the counter is incremented before the
+// index is checked against the bound, so the reserved range absorbs the increments of the
+// generations that fail after the identifiers are exhausted (each halting the execution),
+// ensuring that the u32 counter never wraps around and produces colliding identifiers.
+pub(crate) const DELAYED_FIELD_ID_INDEX_BOUND: u32 = u32::MAX - (1 << 20);
+
 /// A struct that represents a single block execution worker thread's view into the state,
 /// some of which (in Sync case) might be shared with other workers / threads. By implementing
 /// all necessary traits, LatestView is provided to the VM and used to intercept the reads.
 /// In the Sync case, also records captured reads for later validation. latest_txn_idx
 /// must be set according to the latest transaction that the worker was / is executing.
-pub(crate) struct LatestView<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> {
+pub struct LatestView<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> {
     base_view: &'a S,
     pub(crate) latest_view: ViewState<'a, T, X>,
     txn_idx: TxnIndex,
+    mapping_factory: &'a dyn ValueToIdentifierMappingFactory<T, S, X>,
+    // If set, the aggregator V1 values are read with the u128 layout, i.e. through the same
+    // exchange path as the resources with delayed fields.
+    lift_aggregator_v1_values: bool,
+    // Whether the layouts of the reads contain delayed fields, memoized for the scans of the
+    // reads needing exchange.
+    delayed_field_layouts: DelayedFieldLayoutCache,
+    // Fingerprints of the layouts, memoized for the identity checks of the layouts.
+    layout_fingerprints: LayoutFingerprintCache,
+    // If unset, the view reports that it is not capable of the delayed field optimization,
+    // and the VM reads the resources as is.
+    delayed_field_optimization_capable: bool,
 }
 
 impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> LatestView<'a, T, S, X> {
@@ -976,9 +2738,42 @@ impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> LatestView<
             base_view,
             latest_view,
             txn_idx,
+            mapping_factory: &DefaultValueToIdentifierMappingFactory,
+            lift_aggregator_v1_values: false,
+            delayed_field_layouts: DelayedFieldLayoutCache::new(),
+            layout_fingerprints: LayoutFingerprintCache::new(),
+            delayed_field_optimization_capable: true,
         }
     }
 
+    /// Replaces the factory of the mappings used to exchange delayed field values with
+    /// identifiers (by default, all delayed field values are exchanged), e.g. with the factory
+    /// configured on the BlockExecutor (see BlockExecutor::with_mapping_factory).
+    pub(crate) fn with_mapping_factory(
+        mut self,
+        mapping_factory: &'a dyn ValueToIdentifierMappingFactory<T, S, X>,
+    ) -> Self {
+        self.mapping_factory = mapping_factory;
+        self
+    }
+
+    /// If set, reads the aggregator V1 values with the u128 layout (by default, they are read
+    /// without a layout), so that the old and the new aggregator flows can be cross-tested.
+    pub(crate) fn with_lifted_aggregator_v1_values(
+        mut self,
+        lift_aggregator_v1_values: bool,
+    ) -> Self {
+        self.lift_aggregator_v1_values = lift_aggregator_v1_values;
+        self
+    }
+
+    /// Reports the view as not capable of the delayed field optimization, so that the
+    /// resources are read without exchanging the delayed field values.
+    pub fn with_delayed_field_optimization_disabled(mut self) -> Self {
+        self.delayed_field_optimization_capable = false;
+        self
+    }
+
     #[cfg(test)]
     fn get_read_summary(&self) -> HashSet<InputOutputKey<T::Key, T::Tag, T::Identifier>> {
         match &self.latest_view {
@@ -987,142 +2782,722 @@ impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> LatestView<
         }
     }
 
-    /// Drains the parallel captured reads.
-    pub(crate) fn take_parallel_reads(&self) -> CapturedReads<T> {
+    /// Returns the fingerprint of the layout (see layout_fingerprint), memoized by the view.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub(crate) fn layout_fingerprint(&self, layout: &Arc<MoveTypeLayout>) -> u64 {
+        self.layout_fingerprints.fingerprint(layout)
+    }
+
+    /// Returns true if the layouts are structurally equal, comparing the memoized fingerprints
+    /// before the layouts.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub(crate) fn layouts_equal(
+        &self,
+        layout: &Arc<MoveTypeLayout>,
+        other_layout: &Arc<MoveTypeLayout>,
+    ) -> bool {
+        self.layout_fingerprints.layouts_equal(layout, other_layout)
+    }
+
+    /// Returns the label values of the counters updated by the view.
+    pub(crate) fn counter_labels(&self) -> &ViewCounterLabels {
+        self.latest_view.counter_labels()
+    }
+
+    /// Returns the statistics of the reads performed by the execution so far. Must be called
+    /// before the reads are taken.
+    pub(crate) fn read_summary(&self) -> ReadSummary {
+        let read_summary = match &self.latest_view {
+            ViewState::Sync(state) => state.captured_reads.borrow().read_summary(),
+            ViewState::Unsync(state) => state.read_set.borrow().read_summary(),
+        };
+        let fetch_counts = self.latest_view.fetch_counts().borrow();
+        ReadSummary {
+            num_captured_read_hits: fetch_counts.num_captured_read_hits,
+            num_map_fetches: fetch_counts.num_map_fetches,
+            num_base_view_fetches: fetch_counts.num_base_view_fetches,
+            ..read_summary
+        }
+    }
+
+    /// Returns the health of the view, to be merged into the summary of the block. Must be
+    /// called before the reads are taken (which also takes the failure flags).
+    pub(crate) fn health_fragment(&self) -> ViewHealthSummary {
+        let mut fragment = self.latest_view.health().borrow().clone();
+        fragment.num_storage_fetches = self
+            .latest_view
+            .fetch_counts()
+            .borrow()
+            .num_base_view_fetches;
+        if self.is_incorrect_use() {
+            fragment.num_incorrect_uses += 1;
+        }
+        // The halts of the view are recorded with their reasons, and otherwise the speculative
+        // failure was observed by an inconsistent read (e.g. of a delayed field).
+        if let ViewState::Sync(state) = &self.latest_view {
+            if state.captured_reads.borrow().is_speculative_failure()
+                && fragment.total_speculative_halts() == 0
+            {
+                fragment.record_speculative_halt(SpeculativeHaltReason::InconsistentRead);
+            }
+        }
+        fragment
+    }
+
+    /// Drains the parallel captured reads. The reads may only be taken once, and the reads
+    /// captured afterwards are an incorrect use (as they would not be validated).
+    pub(crate) fn take_parallel_reads(&self) -> Result<CapturedReads<T>, PanicError> {
         match &self.latest_view {
-            ViewState::Sync(state) => state.captured_reads.take(),
+            ViewState::Sync(state) => state.captured_reads.borrow_mut().take(),
             ViewState::Unsync(_) => {
                 unreachable!("Take reads called in sequential setting (not captured)")
             },
         }
     }
 
-    /// Drains the unsync read set.
-    pub(crate) fn take_sequential_reads(&self) -> UnsyncReadSet<T> {
+    /// Returns the journal of the base values set in the unsync map by the sequential execution
+    /// (see UnsyncWriteJournal::restore), leaving the journal empty.
+    pub(crate) fn take_sequential_write_journal(&self) -> UnsyncWriteJournal<T> {
         match &self.latest_view {
             ViewState::Sync(_) => {
-                unreachable!("Take unsync reads called in parallel setting")
+                unreachable!("Take write journal called in parallel setting (not recorded)")
+            },
+            ViewState::Unsync(state) => UnsyncWriteJournal {
+                entries: state.write_journal.take(),
             },
-            ViewState::Unsync(state) => state.read_set.take(),
         }
     }
 
-    fn mark_incorrect_use(&self) {
+    /// Removes the base values of the delayed fields generated by the execution, which are not
+    /// referenced by the (patched) base values. Called when the execution is discarded.
+    pub(crate) fn rollback_minted_delayed_fields(&self) -> Result<(), PanicError> {
         match &self.latest_view {
-            ViewState::Sync(state) => state.captured_reads.borrow_mut().mark_incorrect_use(),
-            ViewState::Unsync(state) => *state.incorrect_use.borrow_mut() = true,
+            ViewState::Sync(state) => rollback_minted_delayed_fields_impl(
+                &state.minted_delayed_field_ids,
+                state.versioned_map.delayed_fields(),
+            ),
+            ViewState::Unsync(state) => {
+                for id in state.minted_delayed_field_ids.take() {
+                    state.unsync_map.remove_base_delayed_field(&id)?;
+                }
+                Ok(())
+            },
         }
     }
 
-    pub fn is_incorrect_use(&self) -> bool {
+    /// Returns the committed values of the given delayed fields, read in a single batch when
+    /// executing in parallel.
+    fn read_committed_delayed_field_values(
+        &self,
+        ids: &[T::Identifier],
+    ) -> Result<HashMap<T::Identifier, DelayedFieldValue>, PanicError> {
         match &self.latest_view {
-            ViewState::Sync(state) => state.captured_reads.borrow().is_incorrect_use(),
-            ViewState::Unsync(state) => *state.incorrect_use.borrow(),
+            ViewState::Sync(state) => read_committed_delayed_field_values_impl::<T>(
+                state.versioned_map.delayed_fields(),
+                ids,
+                self.txn_idx,
+            ),
+            ViewState::Unsync(state) => ids
+                .iter()
+                .map(|id| {
+                    state
+                        .read_delayed_field(*id)
+                        .map(|value| (*id, value))
+                        .ok_or_else(|| {
+                            code_invariant_error(format!(
+                                "Delayed field value for {:?} must exist in sequential execution",
+                                id
+                            ))
+                        })
+                })
+                .collect(),
         }
     }
 
-    fn get_raw_base_value(&self, state_key: &T::Key) -> PartialVMResult<Option<StateValue>> {
-        let ret = self.base_view.get_state_value(state_key).map_err(|e| {
-            PartialVMError::new(StatusCode::STORAGE_ERROR).with_message(format!(
-                "Unexpected storage error for {:?}: {:?}",
-                state_key, e
-            ))
-        });
-
-        if ret.is_err() {
-            // Even speculatively, reading from base view should not return an error.
-            // Thus, this critical error log and count does not need to be buffered.
-            let log_context = AdapterLogSchema::new(self.base_view.id(), self.txn_idx as usize);
-            alert!(
-                log_context,
-                "[VM, StateView] Error getting data from storage for {:?}",
-                state_key
-            );
-            self.mark_incorrect_use();
+    /// Returns the latest committed value of the delayed field (e.g. for the epilogue), as
+    /// opposed to get_delayed_field_value that includes the pending changes of the lower
+    /// transactions. In sequential execution, all lower transactions are committed.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub(crate) fn get_delayed_field_latest_committed_value(
+        &self,
+        id: &T::Identifier,
+        read_position: ReadPosition,
+    ) -> Result<DelayedFieldValue, PanicOr<DelayedFieldsSpeculativeError>> {
+        match &self.latest_view {
+            ViewState::Sync(state) => get_delayed_field_latest_committed_value_impl(
+                &state.captured_reads,
+                state.versioned_map.delayed_fields(),
+                state.scheduler,
+                id,
+                read_position,
+                self.txn_idx,
+                &state.counter_labels,
+                &state.health,
+            ),
+            ViewState::Unsync(state) => {
+                state.read_set.borrow_mut().delayed_field_reads.insert(*id);
+                Ok(state.unsync_map.fetch_delayed_field(id).ok_or_else(|| {
+                    code_invariant_error(format!(
+                        "DelayedField {:?} not found in sequential execution",
+                        id
+                    ))
+                })?)
+            },
         }
-
-        ret.map_err(Into::into)
     }
 
-    fn patch_base_value(
+    /// Returns whether the delayed field exists. When executing in parallel, only the
+    /// existence is captured (and validated), e.g. not the deltas applied by earlier
+    /// transactions.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub(crate) fn delayed_field_exists(
         &self,
-        value: &T::Value,
-        layout: Option<&MoveTypeLayout>,
-    ) -> PartialVMResult<T::Value> {
-        let maybe_patched = match (value.as_state_value(), layout) {
-            (Some(state_value), Some(layout)) => {
-                let res = self.replace_values_with_identifiers(state_value, layout);
-                match res {
-                    Ok((value, _)) => Some(value),
-                    Err(err) => {
-                        let log_context =
-                            AdapterLogSchema::new(self.base_view.id(), self.txn_idx as usize);
-                        alert!(
-                            log_context,
-                            "[VM, ResourceView] Error during value to id replacement: {}",
-                            err
-                        );
-                        self.mark_incorrect_use();
-                        return Err(PartialVMError::new(
-                            StatusCode::DELAYED_MATERIALIZATION_CODE_INVARIANT_ERROR,
-                        )
-                        .with_message(format!("{}", err)));
-                    },
-                }
+        id: &T::Identifier,
+    ) -> Result<bool, PanicOr<DelayedFieldsSpeculativeError>> {
+        match &self.latest_view {
+            ViewState::Sync(state) => delayed_field_exists_impl(
+                &state.captured_reads,
+                state.versioned_map.delayed_fields(),
+                state.scheduler,
+                id,
+                self.txn_idx,
+                &state.counter_labels,
+                &state.health,
+            ),
+            ViewState::Unsync(state) => {
+                state.read_set.borrow_mut().delayed_field_reads.insert(*id);
+                Ok(state.read_delayed_field(*id).is_some())
             },
-            (state_value, _) => state_value,
-        };
-        Ok(TransactionWrite::from_state_value(maybe_patched))
+        }
     }
 
-    /// Given a state value, performs deserialization-serialization round-trip
-    /// to replace any aggregator / snapshot values.
-    fn replace_values_with_identifiers(
-        &self,
-        state_value: StateValue,
-        layout: &MoveTypeLayout,
-    ) -> anyhow::Result<(StateValue, HashSet<T::Identifier>)> {
-        let mapping = TemporaryValueToIdentifierMapping::new(self, self.txn_idx);
-        state_value
-            .map_bytes(|bytes| {
-                // This call will replace all occurrences of aggregator / snapshot
-                // values with unique identifiers with the same type layout.
-                // The values are stored in aggregators multi-version data structure,
-                // see the actual trait implementation for more details.
-                let patched_value =
-                    deserialize_and_replace_values_with_ids(bytes.as_ref(), layout, &mapping)
-                        .ok_or_else(|| {
-                            anyhow::anyhow!("Failed to deserialize resource during id replacement")
-                        })?;
-                serialize_and_allow_delayed_values(&patched_value, layout)?
-                    .ok_or_else(|| {
-                        anyhow::anyhow!(
-                            "Failed to serialize value {} after id replacement",
-                            patched_value
-                        )
-                    })
-                    .map(|b| b.into())
-            })
-            .map(|v| (v, mapping.into_inner()))
+    /// Returns the ids of the delayed fields observed by the execution so far: read by value
+    /// or by try_add, or contained in the read values that were scanned for exchange. Values
+    /// that have not been scanned are not inspected. Must be called before the reads are taken.
+    pub(crate) fn captured_delayed_field_ids(&self) -> BTreeSet<T::Identifier> {
+        let mut ids = self
+            .latest_view
+            .scanned_delayed_field_ids()
+            .borrow()
+            .clone();
+        match &self.latest_view {
+            ViewState::Sync(state) => ids.extend(
+                state
+                    .captured_reads
+                    .borrow()
+                    .delayed_field_read_ids()
+                    .copied(),
+            ),
+            ViewState::Unsync(state) => {
+                ids.extend(state.read_set.borrow().delayed_field_reads.iter().copied())
+            },
+        }
+        ids
+    }
+
+    /// Returns whether the delayed field values of the resource at the key, as read by the
+    /// execution so far, were exchanged with identifiers. Does not record the ids as scanned.
+    pub fn exchange_status(
+        &self,
+        key: &T::Key,
+    ) -> Result<ExchangeStatus<T::Identifier>, PanicError> {
+        let (maybe_value_with_layout, exchange_size_threshold) = match &self.latest_view {
+            ViewState::Sync(state) => (
+                match state
+                    .captured_reads
+                    .borrow()
+                    .get_by_kind(key, None, ReadKind::Value)
+                {
+                    Some(DataRead::Versioned(_, value, maybe_layout)) => {
+                        Some((value, maybe_layout))
+                    },
+                    _ => None,
+                },
+                state.exchange_size_threshold,
+            ),
+            ViewState::Unsync(state) => (
+                if state.read_set.borrow().resource_reads.contains(key) {
+                    match state.unsync_map.fetch_data(key) {
+                        Some(ValueWithLayout::Exchanged(value, maybe_layout)) => {
+                            Some((value, maybe_layout))
+                        },
+                        Some(ValueWithLayout::RawFromStorage(value)) => Some((value, None)),
+                        None => None,
+                    }
+                } else {
+                    None
+                },
+                None,
+            ),
+        };
+
+        let Some((value, maybe_layout)) = maybe_value_with_layout else {
+            return Ok(ExchangeStatus::NotApplicable);
+        };
+        let Some(bytes) = value.bytes() else {
+            return Ok(ExchangeStatus::NotApplicable);
+        };
+        if !self.is_delayed_field_optimization_capable() {
+            return Ok(ExchangeStatus::SkippedCapabilityOff);
+        }
+        let Some(layout) = maybe_layout else {
+            return Ok(ExchangeStatus::NotApplicable);
+        };
+        // Mirrors ReadsNeedingExchange::add, which does not scan the values above the
+        // threshold (the execution falls back if they may contain the written ids).
+        if exchange_size_threshold.is_some_and(|threshold| bytes.len() > threshold) {
+            return Ok(ExchangeStatus::SkippedQuarantined);
+        }
+
+        ExchangeStatus::from_exchanged_value::<T>(
+            value.as_ref(),
+            &self.mapping_factory.exchange_layout(&layout),
+            self.latest_view.counter_labels(),
+        )
+    }
+
+    /// Drains the unsync read set, which may only be taken once.
+    pub(crate) fn take_sequential_reads(&self) -> Result<UnsyncReadSet<T>, PanicError> {
+        match &self.latest_view {
+            ViewState::Sync(_) => {
+                unreachable!("Take unsync reads called in parallel setting")
+            },
+            ViewState::Unsync(state) => state.read_set.borrow_mut().take(),
+        }
+    }
+
+    /// Takes the reads of the view in either setting, as a snapshot that can be serialized
+    /// (e.g. to replay the execution). As with the setting-specific takes, the reads can be
+    /// taken only once.
+    #[cfg(any(test, feature = "serde"))]
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub(crate) fn take_reads(&self) -> Result<TakenReads<T>, PanicError> {
+        Ok(match &self.latest_view {
+            ViewState::Sync(state) => {
+                TakenReads::Parallel(state.captured_reads.borrow_mut().take()?)
+            },
+            ViewState::Unsync(state) => TakenReads::Sequential(state.read_set.borrow_mut().take()?),
+        })
+    }
+
+    /// Calls the provided function on each captured read at a key that satisfies the predicate.
+    /// Only reads captured in the parallel setting can be iterated.
+    pub(crate) fn for_each_read_matching(
+        &self,
+        pred: impl Fn(&T::Key) -> bool,
+        f: impl FnMut(CapturedRead<'_, T>),
+    ) {
+        match &self.latest_view {
+            ViewState::Sync(state) => state
+                .captured_reads
+                .borrow()
+                .iter_reads_matching(pred)
+                .for_each(f),
+            ViewState::Unsync(_) => {
+                unreachable!("Iterating captured reads in sequential setting (not captured)")
+            },
+        }
+    }
+
+    /// Returns the keys of all reads (resources, groups and modules) satisfying the predicate.
+    pub(crate) fn read_keys_matching(&self, pred: impl Fn(&T::Key) -> bool) -> HashSet<T::Key> {
+        match &self.latest_view {
+            ViewState::Sync(state) => state
+                .captured_reads
+                .borrow()
+                .read_keys_matching(pred)
+                .into_iter()
+                .cloned()
+                .collect(),
+            ViewState::Unsync(state) => {
+                let read_set = state.read_set.borrow();
+                read_set
+                    .resource_reads
+                    .iter()
+                    .chain(read_set.group_reads.keys())
+                    .chain(read_set.module_reads.iter())
+                    .filter(|key| pred(key))
+                    .cloned()
+                    .collect()
+            },
+        }
+    }
+
+    /// Returns the keys of the resource groups read by the execution so far (e.g. to
+    /// re-validate only the groups that the transaction depended on).
+    // Not used by the executor, which validates all captured group reads.
+    #[allow(unused)]
+    pub(crate) fn group_keys_read(&self) -> HashSet<T::Key> {
+        match &self.latest_view {
+            ViewState::Sync(state) => state
+                .captured_reads
+                .borrow()
+                .group_keys()
+                .cloned()
+                .collect(),
+            ViewState::Unsync(state) => state
+                .read_set
+                .borrow()
+                .group_reads
+                .keys()
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Returns the keys of the modules read by the execution so far (including the modules
+    /// that were not found), each once and in no particular order.
+    pub fn modules_read(&self) -> Vec<T::Key> {
+        let keys: HashSet<T::Key> = match &self.latest_view {
+            ViewState::Sync(state) => state
+                .captured_reads
+                .borrow()
+                .module_keys()
+                .cloned()
+                .collect(),
+            ViewState::Unsync(state) => state.read_set.borrow().module_reads.clone(),
+        };
+        keys.into_iter().collect()
+    }
+
+    fn mark_incorrect_use(&self) {
+        match &self.latest_view {
+            ViewState::Sync(state) => state.captured_reads.borrow_mut().mark_incorrect_use(),
+            ViewState::Unsync(state) => *state.incorrect_use.borrow_mut() = true,
+        }
+    }
+
+    // A read result of an unexpected kind is a code invariant violation: the use is marked
+    // as incorrect, and the execution halted (similar to ViewError::SpeculativeAbort).
+    fn read_kind_mismatch_error(&self, err: PanicError) -> PartialVMError {
+        self.mark_incorrect_use();
+        PartialVMError::new(StatusCode::SPECULATIVE_EXECUTION_ABORT_ERROR)
+            .with_message(err.to_string())
+    }
+
+    // A key read both as a plain resource with delayed fields and as a resource group within
+    // the block (e.g. when a resource is migrated into a group) would be exchanged in one of
+    // the two disjoint representations, and served inconsistently in the other. Whether the
+    // parallel execution observes the flip depends on the schedule, so the use is marked as
+    // incorrect (falling back to the sequential execution). The sequential execution observes
+    // the flip deterministically, and fails the transaction with a dedicated error.
+    fn representation_flip_error(&self, state_key: &T::Key, to_group: bool) -> PartialVMError {
+        let direction = if to_group {
+            "resource_to_group"
+        } else {
+            "group_to_resource"
+        };
+        let [mode, shard] = self.latest_view.counter_labels().values();
+        counters::RESOURCE_GROUP_REPRESENTATION_FLIP_COUNT
+            .with_label_values(&[mode, shard, direction])
+            .inc();
+
+        let message = format!(
+            "Key {:?} flipped between a resource and a resource group ({})",
+            state_key, direction
+        );
+        match &self.latest_view {
+            ViewState::Sync(_) => {
+                self.mark_incorrect_use();
+                PartialVMError::new(StatusCode::SPECULATIVE_EXECUTION_ABORT_ERROR)
+                    .with_message(message)
+            },
+            ViewState::Unsync(_) => {
+                PartialVMError::new(StatusCode::UNEXPECTED_DESERIALIZATION_ERROR)
+                    .with_sub_status(
+                        unexpected_deserialization_error::ERESOURCE_GROUP_REPRESENTATION_FLIP,
+                    )
+                    .with_message(message)
+            },
+        }
+    }
+
+    pub fn is_incorrect_use(&self) -> bool {
+        match &self.latest_view {
+            ViewState::Sync(state) => state.captured_reads.borrow().is_incorrect_use(),
+            ViewState::Unsync(state) => *state.incorrect_use.borrow(),
+        }
+    }
+
+    /// Resolves the value of aggregator V1 at the given key, when the caller is going to replace
+    /// it with a new write. The read is captured before returning (in the parallel setting),
+    /// s.t. the subsequent write of the transaction does not affect what gets validated: in
+    /// particular, when the value was resolved from deltas, the captured read is validated by
+    /// resolving the same delta chain from the entries of the lower transactions.
+    pub fn resolve_aggregator_v1_for_update(
+        &self,
+        state_key: &T::Key,
+    ) -> PartialVMResult<(u128, VersionInfo)> {
+        let state_value = self
+            .get_aggregator_v1_state_value(state_key)?
+            .ok_or_else(|| {
+                PartialVMError::new(StatusCode::SPECULATIVE_EXECUTION_ABORT_ERROR)
+                    .with_message("Cannot resolve deleted aggregator for update".to_string())
+            })?;
+        let value: u128 = bcs::from_bytes(state_value.bytes()).map_err(|e| {
+            PartialVMError::new(StatusCode::UNEXPECTED_DESERIALIZATION_ERROR)
+                .with_message(format!("Failed to deserialize aggregator value: {:?}", e))
+        })?;
+
+        let version_info = match &self.latest_view {
+            ViewState::Sync(state) => {
+                match state
+                    .captured_reads
+                    .borrow()
+                    .get_by_kind(state_key, None, ReadKind::Value)
+                {
+                    Some(DataRead::Versioned(version, _, _))
+                    | Some(DataRead::Hashed(version, _)) => {
+                        VersionInfo::Versioned(version, state_value.into_metadata())
+                    },
+                    Some(DataRead::Resolved(_)) => VersionInfo::Resolved,
+                    Some(DataRead::Metadata(_))
+                    | Some(DataRead::Size(_))
+                    | Some(DataRead::Exists(_))
+                    | None => {
+                        return Err(code_invariant_error(
+                            "Aggregator V1 value read must be captured",
+                        )
+                        .into());
+                    },
+                }
+            },
+            ViewState::Unsync(_) => VersionInfo::Unversioned(state_value.into_metadata()),
+        };
+
+        Ok((value, version_info))
+    }
+
+    fn get_raw_base_value(&self, state_key: &T::Key) -> Result<Option<StateValue>, ViewError> {
+        self.record_base_view_fetch(state_key, self.base_view.get_state_value(state_key))
+    }
+
+    /// Records the result of a fetch from the base view (storage).
+    fn record_base_view_fetch(
+        &self,
+        state_key: &T::Key,
+        fetched: Result<Option<StateValue>, StateviewError>,
+    ) -> Result<Option<StateValue>, ViewError> {
+        self.latest_view
+            .fetch_counts()
+            .borrow_mut()
+            .num_base_view_fetches += 1;
+        let ret = fetched.map_err(|e| {
+            ViewError::StorageError(format!(
+                "Unexpected storage error for {:?}: {:?}",
+                state_key, e
+            ))
+        });
+        if let Ok(Some(state_value)) = &ret {
+            self.latest_view.health().borrow_mut().storage_fetch_bytes +=
+                state_value.bytes().len() as u64;
+        }
+
+        if ret.is_err() {
+            // Even speculatively, reading from base view should not return an error.
+            // Thus, this critical error log and count does not need to be buffered.
+            let log_context = AdapterLogSchema::new(self.base_view.id(), self.txn_idx as usize);
+            alert!(
+                log_context,
+                "[VM, StateView] Error getting data from storage for {:?}",
+                state_key
+            );
+            self.mark_incorrect_use();
+        }
+
+        ret
+    }
+
+    fn patch_base_value(
+        &self,
+        value: &T::Value,
+        layout: Option<&MoveTypeLayout>,
+    ) -> PartialVMResult<T::Value> {
+        let maybe_patched = match (value.as_state_value(), layout) {
+            (Some(state_value), Some(layout)) => {
+                let res = self.replace_values_with_identifiers(state_value, layout);
+                match res {
+                    Ok((value, minted_ids)) => {
+                        // The patched value is installed as a base value by the caller, and
+                        // the ids may then be read by other transactions (even if the execution
+                        // is discarded). As a concurrent reader may install its value first,
+                        // the ids remain minted until the caller confirms the installation
+                        // (see confirm_patched_base_value_impl). Ids generated by a failed
+                        // replacement remain minted.
+                        self.latest_view
+                            .patched_delayed_field_ids()
+                            .borrow_mut()
+                            .extend(minted_ids);
+                        Some(value)
+                    },
+                    Err(err) => {
+                        let log_context =
+                            AdapterLogSchema::new(self.base_view.id(), self.txn_idx as usize);
+                        alert!(
+                            log_context,
+                            "[VM, ResourceView] Error during value to id replacement: {}",
+                            err
+                        );
+                        self.mark_incorrect_use();
+                        return Err(PartialVMError::new(
+                            StatusCode::DELAYED_MATERIALIZATION_CODE_INVARIANT_ERROR,
+                        )
+                        .with_message(format!("{}", err)));
+                    },
+                }
+            },
+            (state_value, _) => state_value,
+        };
+        Ok(TransactionWrite::from_state_value(maybe_patched))
+    }
+
+    /// Replaces the identifiers of the delayed fields in the event payload with the values,
+    /// returning the materialized payload and the exchanged ids. The ids are also recorded
+    /// with the scanned ids, and hence returned by captured_delayed_field_ids.
+    pub(crate) fn exchange_event_payload(
+        &self,
+        bytes: &Bytes,
+        layout: &MoveTypeLayout,
+    ) -> Result<(Bytes, BTreeSet<T::Identifier>), PanicError> {
+        let (materialized_bytes, ids) = self
+            .replace_identifiers_with_values(bytes, layout)
+            .map_err(|err| {
+                code_invariant_error(format!(
+                    "Failed to replace identifiers with values in an event {:?}: {}",
+                    layout, err
+                ))
+            })?;
+        let ids: BTreeSet<_> = ids.into_iter().collect();
+        self.latest_view
+            .scanned_delayed_field_ids()
+            .borrow_mut()
+            .extend(ids.iter().copied());
+        Ok((materialized_bytes, ids))
+    }
+
+    /// Returns the bytes of the group member (None for deletions), with the identifiers
+    /// of the delayed fields replaced with the values.
+    fn materialized_group_member_bytes(
+        &self,
+        value: ValueWithLayout<T::Value>,
+    ) -> anyhow::Result<Option<Bytes>> {
+        Ok(match value {
+            ValueWithLayout::RawFromStorage(value) | ValueWithLayout::Exchanged(value, None) => {
+                value.extract_raw_bytes()
+            },
+            ValueWithLayout::Exchanged(value, Some(layout)) => match value.extract_raw_bytes() {
+                Some(bytes) => Some(self.replace_identifiers_with_values(&bytes, &layout)?.0),
+                None => None,
+            },
+        })
+    }
+
+    /// Given a state value, performs deserialization-serialization round-trip
+    /// to replace any aggregator / snapshot values. The round-trip is skipped
+    /// if the (exchange) layout does not contain delayed fields.
+    fn replace_values_with_identifiers(
+        &self,
+        state_value: StateValue,
+        layout: &MoveTypeLayout,
+    ) -> anyhow::Result<(StateValue, HashSet<T::Identifier>)> {
+        let layout = self.mapping_factory.exchange_layout(layout);
+        if !layout_contains_delayed_field(&layout) {
+            return Ok((state_value, HashSet::new()));
+        }
+        {
+            let mut health = self.latest_view.health().borrow_mut();
+            health.num_exchanges += 1;
+            health.exchange_bytes += state_value.bytes().len() as u64;
+        }
+        let [mode, shard] = self.latest_view.counter_labels().values();
+        let _timer = counters::VALUE_EXCHANGE_SECONDS
+            .with_label_values(&[mode, shard, ExchangeType::VALUES_TO_IDENTIFIERS])
+            .start_timer();
+        counters::VALUE_EXCHANGE_BYTES
+            .with_label_values(&[mode, shard, ExchangeType::VALUES_TO_IDENTIFIERS])
+            .observe(state_value.bytes().len() as f64);
+
+        let mapping = RecordingValueToIdentifierMapping::new(
+            self.mapping_factory.make_mapping(self, self.txn_idx),
+        );
+        // The bytes of the state value may be shared (e.g. with the raw base value in the
+        // multi-versioned map), and must never be modified in place: the exchange reads a
+        // cloned handle of the bytes, and the patched bytes are a new buffer.
+        let original_bytes = state_value.bytes().clone();
+        // This call will replace all occurrences of aggregator / snapshot
+        // values with unique identifiers with the same type layout.
+        // The values are stored in aggregators multi-version data structure,
+        // see the actual trait implementation for more details.
+        let patched_value =
+            deserialize_and_replace_values_with_ids(original_bytes.as_ref(), &layout, &mapping)
+                .ok_or_else(|| {
+                    anyhow::anyhow!("Failed to deserialize resource during id replacement")
+                })?;
+        let patched_bytes: Bytes = serialize_and_allow_delayed_values(&patched_value, &layout)?
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Failed to serialize value {} after id replacement",
+                    patched_value
+                )
+            })?
+            .into();
+
+        let identifiers = mapping.into_inner();
+        counters::VALUE_EXCHANGE_GENERATED_IDENTIFIERS
+            .with_label_values(&[mode, shard])
+            .observe(identifiers.len() as f64);
+        Ok((
+            StateValue::new_with_metadata(patched_bytes, state_value.into_metadata()),
+            identifiers,
+        ))
     }
 
     /// Given a state value, performs deserialization-serialization round-trip
-    /// to replace any aggregator / snapshot values.
+    /// to replace any aggregator / snapshot values. The round-trip is skipped
+    /// if the (exchange) layout does not contain delayed fields.
     pub(crate) fn replace_identifiers_with_values(
         &self,
         bytes: &Bytes,
         layout: &MoveTypeLayout,
     ) -> anyhow::Result<(Bytes, HashSet<T::Identifier>)> {
+        let layout = self.mapping_factory.exchange_layout(layout);
+        if !layout_contains_delayed_field(&layout) {
+            return Ok((bytes.clone(), HashSet::new()));
+        }
+        let counter_labels = self.latest_view.counter_labels();
+        let [mode, shard] = counter_labels.values();
+        let _timer = counters::VALUE_EXCHANGE_SECONDS
+            .with_label_values(&[mode, shard, ExchangeType::IDENTIFIERS_TO_VALUES])
+            .start_timer();
+        counters::VALUE_EXCHANGE_BYTES
+            .with_label_values(&[mode, shard, ExchangeType::IDENTIFIERS_TO_VALUES])
+            .observe(bytes.len() as f64);
+
+        // Read the values of all identifiers in the resource ahead of the replacement, so
+        // that the mapping does not access the delayed fields for every identifier.
+        let ids: Vec<_> = extract_identifiers_from_value::<T>(bytes, &layout, counter_labels)?
+            .into_iter()
+            .collect();
+        let committed_values = self
+            .read_committed_delayed_field_values(&ids)
+            .map_err(|e| anyhow::anyhow!(e.to_string()))?;
+
         // This call will replace all occurrences of aggregator / snapshot
         // identifiers with values with the same type layout.
-        let value = deserialize_and_allow_delayed_values(bytes, layout).ok_or_else(|| {
+        let value = deserialize_and_allow_delayed_values(bytes, &layout).ok_or_else(|| {
             anyhow::anyhow!(
                 "Failed to deserialize resource during id replacement: {:?}",
                 bytes
             )
         })?;
-        let mapping = TemporaryValueToIdentifierMapping::new(self, self.txn_idx);
-        let patched_bytes = serialize_and_replace_ids_with_values(&value, layout, &mapping)
+        let mapping = RecordingValueToIdentifierMapping::new(
+            self.mapping_factory.make_mapping_with_committed_values(
+                self,
+                self.txn_idx,
+                committed_values,
+            ),
+        );
+        let patched_bytes = serialize_and_replace_ids_with_values(&value, &layout, &mapping)
             .ok_or_else(|| anyhow::anyhow!("Failed to serialize resource during id replacement"))?
             .into();
         Ok((patched_bytes, mapping.into_inner()))
@@ -1134,31 +3509,36 @@ impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> LatestView<
         unsync_map: &UnsyncMap<T::Key, T::Tag, T::Value, X, T::Identifier>,
         delayed_write_set_ids: &HashSet<T::Identifier>,
         skip: &HashSet<T::Key>,
-    ) -> Result<BTreeMap<T::Key, (StateValueMetadata, u64, Arc<MoveTypeLayout>)>, PanicError> {
-        read_set
-            .iter()
-            .filter_map(|key| {
-                if skip.contains(key) {
-                    return None;
-                }
+        size_threshold: Option<usize>,
+    ) -> Result<ReadsNeedingExchange<T::Key>, PanicError> {
+        let mut reads_needing_exchange = ReadsNeedingExchange::default();
+        for key in read_set {
+            if skip.contains(key) {
+                continue;
+            }
 
-                match unsync_map.fetch_data(key) {
-                    Some(ValueWithLayout::Exchanged(value, Some(layout))) => {
-                        filter_value_for_exchange::<T>(
-                            value.as_ref(),
-                            &layout,
-                            delayed_write_set_ids,
-                            key,
-                        )
-                    },
-                    Some(ValueWithLayout::Exchanged(_, None)) => None,
-                    Some(ValueWithLayout::RawFromStorage(_)) => Some(Err(code_invariant_error(
+            match unsync_map.fetch_data(key) {
+                Some(ValueWithLayout::Exchanged(value, Some(layout))) => {
+                    reads_needing_exchange.add::<T>(
+                        value.as_ref(),
+                        &layout,
+                        delayed_write_set_ids,
+                        key,
+                        &mut self.latest_view.scanned_delayed_field_ids().borrow_mut(),
+                        size_threshold,
+                        &self.delayed_field_layouts,
+                        self.latest_view.counter_labels(),
+                    )?;
+                },
+                Some(ValueWithLayout::Exchanged(_, None)) | None => {},
+                Some(ValueWithLayout::RawFromStorage(_)) => {
+                    return Err(code_invariant_error(
                         "Cannot exchange value that was not exchanged before",
-                    ))),
-                    None => None,
-                }
-            })
-            .collect()
+                    ));
+                },
+            }
+        }
+        Ok(reads_needing_exchange)
     }
 
     fn get_group_reads_needing_exchange_parallel(
@@ -1167,52 +3547,52 @@ impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> LatestView<
         delayed_write_set_ids: &HashSet<T::Identifier>,
         skip: &HashSet<T::Key>,
     ) -> PartialVMResult<BTreeMap<T::Key, (StateValueMetadata, u64)>> {
-        let reads_with_delayed_fields = parallel_state
+        // Only the keys of the groups needing exchange are collected while the captured reads
+        // are borrowed (the group reads are inspected by reference). The borrow is released
+        // before reading the metadata and the sizes of the groups, which capture reads.
+        let mut keys_needing_exchange = Vec::new();
+        for (key, group_read) in parallel_state
             .captured_reads
             .borrow()
             .get_group_read_values_with_delayed_fields(skip)
-            .map(|(k, v)| (k.clone(), v.clone()))
-            .collect::<Vec<_>>();
-
-        reads_with_delayed_fields
-            .into_iter()
-            .map(|(key, group_read)| -> PartialVMResult<_> {
-                let GroupRead { inner_reads, .. } = group_read;
-
-                // TODO[agg_v2](clean-up): Once ids can be extracted without possible failure,
-                // the following is just an any call on iterator (same for resource reads).
-                let mut resources_needing_delayed_field_exchange = false;
-                for data_read in inner_reads.values() {
-                    if let DataRead::Versioned(_version, value, Some(layout)) = data_read {
-                        let needs_exchange = does_value_need_exchange::<T>(
-                            value,
-                            layout.as_ref(),
-                            delayed_write_set_ids,
-                        )
-                        .map_err(PartialVMError::from)?;
+        {
+            // TODO[agg_v2](clean-up): Once ids can be extracted without possible failure,
+            // the following is just an any call on iterator (same for resource reads).
+            for data_read in group_read.inner_reads.values() {
+                if let DataRead::Versioned(_version, value, Some(layout)) = data_read {
+                    let needs_exchange = does_value_need_exchange::<T>(
+                        value,
+                        layout,
+                        delayed_write_set_ids,
+                        &mut self.latest_view.scanned_delayed_field_ids().borrow_mut(),
+                        &self.delayed_field_layouts,
+                        &parallel_state.counter_labels,
+                    )
+                    .map_err(PartialVMError::from)?;
 
-                        if needs_exchange {
-                            resources_needing_delayed_field_exchange = true;
-                            break;
-                        }
+                    if needs_exchange {
+                        keys_needing_exchange.push(key.clone());
+                        break;
                     }
                 }
-                if !resources_needing_delayed_field_exchange {
-                    return Ok(None);
-                }
+            }
+        }
 
+        keys_needing_exchange
+            .into_iter()
+            .map(|key| -> PartialVMResult<_> {
                 match self.get_resource_state_value_metadata(&key)? {
                     Some(metadata) => match parallel_state.read_group_size(&key, self.txn_idx)? {
                         GroupReadResult::Size(group_size) => {
-                            Ok(Some((key, (metadata, group_size.get()))))
-                        },
-                        GroupReadResult::Value(_, _) | GroupReadResult::Uninitialized => {
-                            Err(code_invariant_error(format!(
-                                "Cannot compute metadata op size for the group read {:?}",
-                                key
-                            ))
-                            .into())
+                            Ok((key, (metadata, group_size.get())))
                         },
+                        GroupReadResult::Value(_, _)
+                        | GroupReadResult::Exists(_)
+                        | GroupReadResult::Uninitialized => Err(code_invariant_error(format!(
+                            "Cannot compute metadata op size for the group read {:?}",
+                            key
+                        ))
+                        .into()),
                     },
                     None => Err(code_invariant_error(format!(
                         "Metadata op not present for the group read {:?}",
@@ -1221,7 +3601,6 @@ impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> LatestView<
                     .into()),
                 }
             })
-            .flat_map(Result::transpose)
             .collect()
     }
 
@@ -1246,8 +3625,11 @@ impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> LatestView<
                             {
                                 let needs_exchange = does_value_need_exchange::<T>(
                                     &value,
-                                    layout.as_ref(),
+                                    &layout,
                                     delayed_write_set_ids,
+                                    &mut self.latest_view.scanned_delayed_field_ids().borrow_mut(),
+                                    &self.delayed_field_layouts,
+                                    self.latest_view.counter_labels(),
                                 )?;
                                 if needs_exchange {
                                     resources_needing_delayed_field_exchange = true;
@@ -1264,7 +3646,7 @@ impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> LatestView<
                             GroupReadResult::Size(group_size) => {
                                 Ok(Some((key.clone(), (metadata, group_size.get()))))
                             },
-                            GroupReadResult::Value(_, _) => {
+                            GroupReadResult::Value(_, _) | GroupReadResult::Exists(_) => {
                                 unreachable!(
                                     "get_group_size cannot return GroupReadResult::Value type"
                                 )
@@ -1294,22 +3676,15 @@ impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> LatestView<
         state_key: &T::Key,
         layout: UnknownOrLayout,
         kind: ReadKind,
-    ) -> PartialVMResult<ReadResult> {
+    ) -> Result<ReadResult, ViewError> {
         debug_assert!(
             state_key.module_path().is_none(),
             "Reading a module {:?} using ResourceView",
             state_key,
         );
 
-        let layout = if self.is_delayed_field_optimization_capable() {
-            layout
-        } else {
-            match layout {
-                UnknownOrLayout::Known(_) => UnknownOrLayout::Known(None),
-                UnknownOrLayout::Unknown => UnknownOrLayout::Unknown,
-            }
-        };
-
+        let layout = self.delayed_field_capable_layout(layout);
+        self.check_no_group_with_layout(state_key, &layout)?;
         let state = self.latest_view.get_resource_state();
 
         let mut ret = state.read_cached_data_by_kind(
@@ -1318,14 +3693,9 @@ impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> LatestView<
             kind.clone(),
             layout.clone(),
             &|value, layout| self.patch_base_value(value, layout),
-        );
-        if matches!(ret, ReadResult::Uninitialized) {
-            let from_storage =
-                TransactionWrite::from_state_value(self.get_raw_base_value(state_key)?);
-            state.set_base_value(
-                state_key.clone(),
-                ValueWithLayout::RawFromStorage(Arc::new(from_storage)),
-            );
+        )?;
+        if let Some(base_value) = self.base_value_from_storage(state_key, &ret)? {
+            state.set_base_value(state_key.clone(), base_value);
 
             // In case of concurrent storage fetches, we cannot use our value,
             // but need to fetch it from versioned_map again.
@@ -1335,60 +3705,342 @@ impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> LatestView<
                 kind,
                 layout.clone(),
                 &|value, layout| self.patch_base_value(value, layout),
-            );
+            )?;
         }
 
-        match ret {
-            // ExecutionHalted indicates that the parallel execution is halted.
-            // The read should return immediately and log the error.
-            // For now we use SPECULATIVE_EXECUTION_ABORT_ERROR as the VM
-            // will not log the speculative error,
-            // so no actual error will be logged once the execution is halted and
-            // the speculative logging is flushed.
-            ReadResult::HaltSpeculativeExecution(msg) => Err(PartialVMError::new(
-                StatusCode::SPECULATIVE_EXECUTION_ABORT_ERROR,
-            )
-            .with_message(msg)),
-            ReadResult::Uninitialized => Err(code_invariant_error(
-                "base value must already be recorded in the MV data structure",
-            )
+        Self::check_base_value_recorded(ret)
+    }
+
+    /// Reads the values of the resources (each with the layout of its read), returning the
+    /// values in the order of the keys. The reads are performed in a batch: the captured reads
+    /// are looked up once, and the base values of all the keys that were not initialized are
+    /// fetched from storage and set in a single pass, before these keys are read again. Each
+    /// read is captured (and validated) as a single read. The errors are PartialVMErrors.
+    pub fn get_resource_state_values(
+        &self,
+        keys: &[(T::Key, Option<&MoveTypeLayout>)],
+    ) -> anyhow::Result<Vec<Option<StateValue>>> {
+        Ok(self.get_resource_state_values_impl(keys)?)
+    }
+
+    fn get_resource_state_values_impl(
+        &self,
+        keys: &[(T::Key, Option<&MoveTypeLayout>)],
+    ) -> PartialVMResult<Vec<Option<StateValue>>> {
+        let keys: Vec<_> = keys
+            .iter()
+            .map(|(state_key, maybe_layout)| {
+                debug_assert!(
+                    state_key.module_path().is_none(),
+                    "Reading a module {:?} using ResourceView",
+                    state_key,
+                );
+                let layout =
+                    self.delayed_field_capable_layout(UnknownOrLayout::Known(*maybe_layout));
+                self.check_no_group_with_layout(state_key, &layout)?;
+                Ok((state_key, layout))
+            })
+            .collect::<PartialVMResult<_>>()?;
+        let state = self.latest_view.get_resource_state();
+        let patch_base_value = |value: &T::Value, layout: Option<&MoveTypeLayout>| {
+            self.patch_base_value(value, layout)
+        };
+
+        let mut results = state.read_cached_data_by_kind_multi(
+            self.txn_idx,
+            &keys,
+            ReadKind::Value,
+            &patch_base_value,
+        )?;
+
+        let mut uninitialized_indices = Vec::new();
+        for (idx, ((state_key, _), result)) in keys.iter().zip(&results).enumerate() {
+            if let Some(base_value) = self.base_value_from_storage(state_key, result)? {
+                state.set_base_value((*state_key).clone(), base_value);
+                uninitialized_indices.push(idx);
+            }
+        }
+        if !uninitialized_indices.is_empty() {
+            // As for a single read, the keys are read again after setting the base values.
+            let uninitialized_keys: Vec<_> = uninitialized_indices
+                .iter()
+                .map(|idx| keys[*idx].clone())
+                .collect();
+            let reads = state.read_cached_data_by_kind_multi(
+                self.txn_idx,
+                &uninitialized_keys,
+                ReadKind::Value,
+                &patch_base_value,
+            )?;
+            for (idx, read) in uninitialized_indices.into_iter().zip(reads) {
+                results[idx] = read;
+            }
+        }
+
+        results
+            .into_iter()
+            .map(|result| {
+                Self::check_base_value_recorded(result)?
+                    .into_value()
+                    .map_err(|err| self.read_kind_mismatch_error(err))
+            })
+            .collect()
+    }
+
+    /// Reads the value of the resource along with its metadata, in a single read at the Value
+    /// kind. The metadata is derived from the value read, which is captured (and hence also
+    /// validates the metadata).
+    // Not used by the executor, as the VM reads the values and the metadata separately.
+    #[allow(unused)]
+    pub(crate) fn get_resource_state_value_and_metadata(
+        &self,
+        state_key: &T::Key,
+        maybe_layout: Option<&MoveTypeLayout>,
+    ) -> PartialVMResult<(Option<StateValue>, Option<StateValueMetadata>)> {
+        let value = self
+            .get_resource_state_value_impl(
+                state_key,
+                UnknownOrLayout::Known(maybe_layout),
+                ReadKind::Value,
+            )?
+            .into_value()
+            .map_err(|err| self.read_kind_mismatch_error(err))?;
+        let metadata = value.clone().map(StateValue::into_metadata);
+        Ok((value, metadata))
+    }
+
+    /// Sets the base values of the keys (that do not have a base value yet) ahead of their
+    /// reads, fetching the values from storage concurrently. The values are set as raw, and
+    /// are patched by the first read with a layout, so that the reads of the prefetched keys
+    /// are served from the (multi-versioned or unsync) map.
+    // Not used by the executor, as the read sets of the transactions are not known upfront.
+    #[allow(unused)]
+    pub(crate) fn prefetch_base_values(&self, keys: &[T::Key]) -> PartialVMResult<()>
+    where
+        S: Sync,
+    {
+        let state = self.latest_view.get_resource_state();
+        let keys: Vec<_> = keys
+            .iter()
+            .filter(|key| {
+                debug_assert!(
+                    key.module_path().is_none(),
+                    "Prefetching a module {:?} using ResourceView",
+                    key,
+                );
+                !state.has_base_value(key)
+            })
+            .collect();
+        let base_view = self.base_view;
+        let fetched: Vec<_> = keys
+            .par_iter()
+            .map(|key| base_view.get_state_value(key))
+            .collect();
+
+        for (key, fetched) in keys.into_iter().zip(fetched) {
+            let from_storage =
+                TransactionWrite::from_state_value(self.record_base_view_fetch(key, fetched)?);
+            state.set_base_value(
+                key.clone(),
+                ValueWithLayout::RawFromStorage(Arc::new(from_storage)),
+            );
+        }
+        Ok(())
+    }
+
+    /// Layouts are not used if the view is not capable of the delayed field optimization.
+    fn delayed_field_capable_layout<'l>(&self, layout: UnknownOrLayout<'l>) -> UnknownOrLayout<'l> {
+        if self.is_delayed_field_optimization_capable() {
+            layout
+        } else {
+            match layout {
+                UnknownOrLayout::Known(_) => UnknownOrLayout::Known(None),
+                UnknownOrLayout::Unknown => UnknownOrLayout::Unknown,
+            }
+        }
+    }
+
+    /// Returns the base value (from storage) to set if the read did not find one, or None.
+    fn base_value_from_storage(
+        &self,
+        state_key: &T::Key,
+        read_result: &ReadResult,
+    ) -> Result<Option<ValueWithLayout<T::Value>>, ViewError> {
+        Ok(match read_result {
+            ReadResult::Uninitialized => {
+                let from_storage =
+                    TransactionWrite::from_state_value(self.get_raw_base_value(state_key)?);
+                Some(ValueWithLayout::RawFromStorage(Arc::new(from_storage)))
+            },
+            ReadResult::Unresolved => {
+                counters::AGGREGATOR_V1_UNRESOLVED_READ_COUNT
+                    .with_label_values(&self.latest_view.counter_labels().values())
+                    .inc();
+                // Deltas are only recorded for aggregator (v1) values, which are serialized
+                // u128s and do not contain delayed fields. Hence, the base value from storage
+                // is set as exchanged, with no layout.
+                let from_storage =
+                    TransactionWrite::from_state_value(self.get_raw_base_value(state_key)?);
+                Some(ValueWithLayout::Exchanged(Arc::new(from_storage), None))
+            },
+            _ => None,
+        })
+    }
+
+    fn check_base_value_recorded(read_result: ReadResult) -> Result<ReadResult, ViewError> {
+        match read_result {
+            ReadResult::Uninitialized | ReadResult::Unresolved => Err(code_invariant_error(
+                "base value must already be recorded in the MV data structure",
+            )
             .into()),
-            ReadResult::Exists(_) | ReadResult::Metadata(_) | ReadResult::Value(_, _) => Ok(ret),
+            ReadResult::Exists(_)
+            | ReadResult::Metadata(_)
+            | ReadResult::Size(_)
+            | ReadResult::Value(_, _)
+            | ReadResult::HaltSpeculativeExecution(_) => Ok(read_result),
+        }
+    }
+
+    /// Returns true if the key was already read as a plain resource by the transaction.
+    fn is_known_resource_key(&self, state_key: &T::Key) -> bool {
+        match &self.latest_view {
+            ViewState::Sync(state) => state.captured_reads.borrow().is_resource_read(state_key),
+            ViewState::Unsync(state) => state.read_set.borrow().resource_reads.contains(state_key),
+        }
+    }
+
+    /// Sets the size of the group at the storage version, computed from the raw group bytes,
+    /// so that the group size can be read without initializing the group contents. Returns
+    /// false if the size could not be computed, and the contents must be initialized instead.
+    fn initialize_mvhashmap_base_group_size(&self, group_key: &T::Key) -> PartialVMResult<bool> {
+        if self.is_known_resource_key(group_key) {
+            // Initializing the contents reports the misuse of the resource group API.
+            return Ok(false);
+        }
+        self.check_no_resource_with_layout(group_key)?;
+
+        let base_size = match self.get_raw_base_value(group_key)? {
+            Some(state_value) => match raw_group_size(state_value.bytes()) {
+                Some(base_size) => base_size,
+                None => return Ok(false),
+            },
+            None => ResourceGroupSize::zero_combined(),
+        };
+        self.latest_view
+            .get_resource_group_state()
+            .set_raw_group_base_size(group_key.clone(), base_size);
+        Ok(true)
+    }
+
+    /// Fails if the key, read as a resource with delayed fields, was previously initialized (or
+    /// written) as a resource group.
+    fn check_no_group_with_layout(
+        &self,
+        state_key: &T::Key,
+        layout: &UnknownOrLayout,
+    ) -> PartialVMResult<()> {
+        if matches!(layout, UnknownOrLayout::Known(Some(_)))
+            && self
+                .latest_view
+                .get_resource_group_state()
+                .contains_group(state_key)
+        {
+            return Err(self.representation_flip_error(state_key, false));
+        }
+        Ok(())
+    }
+
+    /// Fails if the key, about to be initialized as a resource group, was previously read (or,
+    /// sequentially, written) as a resource with delayed fields.
+    fn check_no_resource_with_layout(&self, group_key: &T::Key) -> PartialVMResult<()> {
+        if self
+            .latest_view
+            .get_resource_state()
+            .has_exchanged_base_value_with_layout(group_key)
+        {
+            return Err(self.representation_flip_error(group_key, true));
         }
+        Ok(())
     }
 
     fn initialize_mvhashmap_base_group_contents(&self, group_key: &T::Key) -> PartialVMResult<()> {
-        let (base_group, metadata_op): (BTreeMap<T::Tag, Bytes>, _) =
-            match self.get_raw_base_value(group_key)? {
-                Some(state_value) => (
-                    bcs::from_bytes(state_value.bytes()).map_err(|e| {
-                        PartialVMError::new(StatusCode::UNEXPECTED_DESERIALIZATION_ERROR)
-                            .with_message(format!(
-                                "Failed to deserialize the resource group at {:? }: {:?}",
-                                group_key, e
-                            ))
-                    })?,
-                    TransactionWrite::from_state_value(Some(state_value)),
-                ),
-                None => (BTreeMap::new(), TransactionWrite::from_state_value(None)),
-            };
-        let base_group_sentinel_ops = base_group
+        self.check_no_resource_with_layout(group_key)?;
+        let counter_labels = self.latest_view.counter_labels();
+        let (base_group, metadata_op): (BTreeMap<T::Tag, Bytes>, _) = match self
+            .get_raw_base_value(group_key)?
+        {
+            Some(state_value) => (
+                bcs::from_bytes(state_value.bytes()).map_err(|e| {
+                    if self.is_known_resource_key(group_key) {
+                        // Resource group API is used on a plain resource. This is an error
+                        // of the caller, and the view remains usable for resource reads.
+                        not_a_resource_group_error(group_key)
+                    } else {
+                        // The group in storage is corrupt, which is not due to speculation.
+                        let err = corrupt_resource_group_error(group_key, state_value.bytes(), e);
+                        let log_context =
+                            AdapterLogSchema::new(self.base_view.id(), self.txn_idx as usize);
+                        alert!(log_context, "[VM, ResourceGroupView] {:?}", err);
+                        self.mark_incorrect_use();
+                        err
+                    }
+                })?,
+                sentinel_op_from_state_value(Some(state_value), counter_labels),
+            ),
+            None => (
+                BTreeMap::new(),
+                sentinel_op_from_state_value(None, counter_labels),
+            ),
+        };
+        let base_group_sentinel_ops: Vec<(T::Tag, T::Value)> = base_group
             .into_iter()
             .map(|(t, bytes)| {
                 (
                     t,
-                    TransactionWrite::from_state_value(Some(StateValue::new_legacy(bytes))),
+                    sentinel_op_from_state_value(
+                        Some(StateValue::new_legacy(bytes)),
+                        counter_labels,
+                    ),
                 )
             })
             .collect();
 
-        self.latest_view
-            .get_resource_group_state()
-            .set_raw_group_base_values(group_key.clone(), base_group_sentinel_ops);
+        let resource_group_state = self.latest_view.get_resource_group_state();
+        // When patching eagerly, members with a known layout are exchanged up front, so that
+        // the reads do not need to patch the base values (one at a time).
+        let members_with_layout: Vec<_> = match resource_group_state.tag_layout_registry() {
+            Some(registry) => base_group_sentinel_ops
+                .iter()
+                .filter_map(|(tag, value)| {
+                    registry
+                        .get(tag)
+                        .map(|layout| (tag.clone(), value.clone(), layout))
+                })
+                .collect(),
+            None => vec![],
+        };
+
+        resource_group_state.set_raw_group_base_values(group_key.clone(), base_group_sentinel_ops);
+        for (tag, value, layout) in members_with_layout {
+            // Each member is installed right after it is patched, so that the ids of the patch
+            // are confirmed against its installation.
+            let patched_value = self.patch_base_value(&value, layout.as_deref())?;
+            let installed = resource_group_state.update_tagged_base_value_with_layout(
+                group_key.clone(),
+                tag,
+                patched_value,
+                layout,
+            );
+            self.latest_view.confirm_patched_base_value(installed);
+        }
         self.latest_view.get_resource_state().set_base_value(
             group_key.clone(),
             ValueWithLayout::RawFromStorage(Arc::new(metadata_op)),
         );
+        self.latest_view
+            .health()
+            .borrow_mut()
+            .num_group_initializations += 1;
         Ok(())
     }
 }
@@ -1408,33 +4060,30 @@ impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> TResourceVi
             state_key,
             UnknownOrLayout::Known(maybe_layout),
             ReadKind::Value,
-        )
-        .map(|res| res.into_value())
+        )?
+        .into_value()
+        .map_err(|err| self.read_kind_mismatch_error(err))
     }
 
     fn get_resource_state_value_metadata(
         &self,
         state_key: &Self::Key,
     ) -> PartialVMResult<Option<StateValueMetadata>> {
-        self.get_resource_state_value_impl(state_key, UnknownOrLayout::Unknown, ReadKind::Metadata)
-            .map(|res| {
-                if let ReadResult::Metadata(v) = res {
-                    v
-                } else {
-                    unreachable!("Read result must be Metadata kind")
-                }
-            })
+        self.get_resource_state_value_impl(state_key, UnknownOrLayout::Unknown, ReadKind::Metadata)?
+            .into_metadata_checked()
+            .map_err(|err| self.read_kind_mismatch_error(err))
+    }
+
+    fn get_resource_state_value_size(&self, state_key: &Self::Key) -> PartialVMResult<Option<u64>> {
+        self.get_resource_state_value_impl(state_key, UnknownOrLayout::Unknown, ReadKind::Size)?
+            .into_size()
+            .map_err(|err| self.read_kind_mismatch_error(err))
     }
 
     fn resource_exists(&self, state_key: &Self::Key) -> PartialVMResult<bool> {
-        self.get_resource_state_value_impl(state_key, UnknownOrLayout::Unknown, ReadKind::Exists)
-            .map(|res| {
-                if let ReadResult::Exists(v) = res {
-                    v
-                } else {
-                    unreachable!("Read result must be Exists kind")
-                }
-            })
+        self.get_resource_state_value_impl(state_key, UnknownOrLayout::Unknown, ReadKind::Exists)?
+            .into_exists_checked()
+            .map_err(|err| self.read_kind_mismatch_error(err))
     }
 }
 
@@ -1449,23 +4098,55 @@ impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> TResourceGr
         &self,
         group_key: &Self::GroupKey,
     ) -> PartialVMResult<ResourceGroupSize> {
-        let mut group_read = match &self.latest_view {
-            ViewState::Sync(state) => state.read_group_size(group_key, self.txn_idx)?,
-            ViewState::Unsync(state) => state.unsync_map.get_group_size(group_key)?,
+        let read_group_size = || match &self.latest_view {
+            ViewState::Sync(state) => state.read_group_size(group_key, self.txn_idx),
+            ViewState::Unsync(state) => state.read_group_size(group_key),
         };
+        let mut group_read = read_group_size()?;
+
+        // The size of the group at the storage version does not require deserializing the
+        // group contents. They are still initialized if a lower transaction wrote to the
+        // group, as the size then depends on the base sizes of the written members.
+        if matches!(group_read, GroupReadResult::Uninitialized)
+            && self.initialize_mvhashmap_base_group_size(group_key)?
+        {
+            group_read = read_group_size()?;
+        }
 
         if matches!(group_read, GroupReadResult::Uninitialized) {
             self.initialize_mvhashmap_base_group_contents(group_key)?;
-
-            group_read = match &self.latest_view {
-                ViewState::Sync(state) => state.read_group_size(group_key, self.txn_idx)?,
-                ViewState::Unsync(state) => state.unsync_map.get_group_size(group_key)?,
-            }
+            group_read = read_group_size()?;
         };
 
         Ok(group_read.into_size())
     }
 
+    fn group_tags(&self, group_key: &Self::GroupKey) -> PartialVMResult<Vec<Self::ResourceTag>> {
+        // The (captured) group size read validates the set of members that the tags are
+        // collected from, and also waits on the dependencies if the group was written.
+        self.resource_group_size(group_key)?;
+
+        let read_group_tags = || match &self.latest_view {
+            ViewState::Sync(state) => state.read_group_tags(group_key, self.txn_idx),
+            ViewState::Unsync(state) => Ok(state.read_group_tags(group_key)),
+        };
+        // Group size may have been computed from the raw base size only.
+        let mut maybe_tags = read_group_tags()?;
+        if maybe_tags.is_none() {
+            self.initialize_mvhashmap_base_group_contents(group_key)?;
+            maybe_tags = read_group_tags()?;
+        }
+
+        let mut tags = maybe_tags.ok_or_else(|| {
+            PartialVMError::from(code_invariant_error(format!(
+                "Group contents of {:?} must be initialized",
+                group_key
+            )))
+        })?;
+        tags.sort();
+        Ok(tags)
+    }
+
     fn get_resource_from_group(
         &self,
         group_key: &Self::GroupKey,
@@ -1473,6 +4154,13 @@ impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> TResourceGr
         maybe_layout: Option<&Self::Layout>,
     ) -> PartialVMResult<Option<Bytes>> {
         let maybe_layout = maybe_layout.filter(|_| self.is_delayed_field_optimization_capable());
+        if let Some(registry) = self
+            .latest_view
+            .get_resource_group_state()
+            .tag_layout_registry()
+        {
+            registry.record(resource_tag, maybe_layout);
+        }
 
         let mut group_read = self
             .latest_view
@@ -1481,6 +4169,7 @@ impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> TResourceGr
                 self.txn_idx,
                 group_key,
                 resource_tag,
+                ReadKind::Value,
                 maybe_layout,
                 &|value, layout| self.patch_base_value(value, layout),
             )?;
@@ -1495,6 +4184,7 @@ impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> TResourceGr
                     self.txn_idx,
                     group_key,
                     resource_tag,
+                    ReadKind::Value,
                     maybe_layout,
                     &|value, layout| self.patch_base_value(value, layout),
                 )?;
@@ -1503,26 +4193,178 @@ impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> TResourceGr
         Ok(group_read.into_value().0)
     }
 
+    fn get_resources_from_group(
+        &self,
+        group_key: &Self::GroupKey,
+        resource_tags: &[Self::ResourceTag],
+        maybe_layouts: &[Option<&Self::Layout>],
+    ) -> PartialVMResult<Vec<Option<Bytes>>> {
+        if resource_tags.len() != maybe_layouts.len() {
+            return Err(code_invariant_error(format!(
+                "Number of layouts ({}) does not match the number of tags ({})",
+                maybe_layouts.len(),
+                resource_tags.len()
+            ))
+            .into());
+        }
+
+        // Each read is captured as by get_resource_from_group, but the group is initialized
+        // at most once, by the first read that observes it uninitialized.
+        let resource_group_state = self.latest_view.get_resource_group_state();
+        let mut group_initialized = false;
+        resource_tags
+            .iter()
+            .zip(maybe_layouts)
+            .map(|(resource_tag, maybe_layout)| {
+                let maybe_layout =
+                    maybe_layout.filter(|_| self.is_delayed_field_optimization_capable());
+                if let Some(registry) = resource_group_state.tag_layout_registry() {
+                    registry.record(resource_tag, maybe_layout);
+                }
+                let read_value = || {
+                    resource_group_state.read_cached_group_tagged_data(
+                        self.txn_idx,
+                        group_key,
+                        resource_tag,
+                        ReadKind::Value,
+                        maybe_layout,
+                        &|value, layout| self.patch_base_value(value, layout),
+                    )
+                };
+
+                let mut group_read = read_value()?;
+                if matches!(group_read, GroupReadResult::Uninitialized) {
+                    if group_initialized {
+                        return Err(code_invariant_error(format!(
+                            "Group {:?} uninitialized after initialization",
+                            group_key
+                        ))
+                        .into());
+                    }
+                    self.initialize_mvhashmap_base_group_contents(group_key)?;
+                    group_initialized = true;
+                    group_read = read_value()?;
+                }
+
+                Ok(group_read.into_value().0)
+            })
+            .collect()
+    }
+
+    fn get_resource_from_group_with_group_size(
+        &self,
+        group_key: &Self::GroupKey,
+        resource_tag: &Self::ResourceTag,
+        maybe_layout: Option<&Self::Layout>,
+    ) -> PartialVMResult<(Option<Bytes>, ResourceGroupSize)> {
+        // The size is read first, waiting on the dependencies of all the tags (if the group
+        // was written by lower transactions), so that the value read then does not need to.
+        // Both reads are captured, and the validation is the same as for separate reads.
+        let group_size = self.resource_group_size(group_key)?;
+        let maybe_bytes = self.get_resource_from_group(group_key, resource_tag, maybe_layout)?;
+        Ok((maybe_bytes, group_size))
+    }
+
     fn resource_size_in_group(
         &self,
-        _group_key: &Self::GroupKey,
-        _resource_tag: &Self::ResourceTag,
+        group_key: &Self::GroupKey,
+        resource_tag: &Self::ResourceTag,
     ) -> PartialVMResult<usize> {
-        unimplemented!("Currently resolved by ResourceGroupAdapter");
+        // The value read is captured, so that the validation observes the same size. There
+        // is no size-only lookup of a member in the group data, but the group is initialized
+        // only if needed (i.e. if the read returns Uninitialized).
+        //
+        // The exchange preserves the sizes of the values, so the layout is not required for
+        // the size. However, reading with the recorded layout of the tag (if any) ensures the
+        // base value is never installed with a different layout than by the value reads.
+        // Extracted bytes share the underlying buffer (no copy).
+        let resource_group_state = self.latest_view.get_resource_group_state();
+        let maybe_layout = resource_group_state
+            .tag_layout_registry()
+            .filter(|_| self.is_delayed_field_optimization_capable())
+            .and_then(|registry| registry.get(resource_tag))
+            .flatten();
+        let read_value = || {
+            resource_group_state.read_cached_group_tagged_data(
+                self.txn_idx,
+                group_key,
+                resource_tag,
+                ReadKind::Value,
+                maybe_layout.as_deref(),
+                &|value, layout| self.patch_base_value(value, layout),
+            )
+        };
+
+        let mut group_read = read_value()?;
+        if matches!(group_read, GroupReadResult::Uninitialized) {
+            self.initialize_mvhashmap_base_group_contents(group_key)?;
+            group_read = read_value()?;
+        };
+
+        Ok(group_read.into_value().0.map_or(0, |bytes| bytes.len()))
     }
 
     fn resource_exists_in_group(
         &self,
-        _group_key: &Self::GroupKey,
-        _resource_tag: &Self::ResourceTag,
+        group_key: &Self::GroupKey,
+        resource_tag: &Self::ResourceTag,
     ) -> PartialVMResult<bool> {
-        unimplemented!("Currently resolved by ResourceGroupAdapter");
+        let read_exists = || {
+            self.latest_view
+                .get_resource_group_state()
+                .read_cached_group_tagged_data(
+                    self.txn_idx,
+                    group_key,
+                    resource_tag,
+                    ReadKind::Exists,
+                    None,
+                    &|value, layout| self.patch_base_value(value, layout),
+                )
+        };
+
+        let mut group_read = read_exists()?;
+        if matches!(group_read, GroupReadResult::Uninitialized) {
+            self.initialize_mvhashmap_base_group_contents(group_key)?;
+            group_read = read_exists()?;
+        };
+
+        Ok(group_read.into_exists())
     }
 
     fn release_group_cache(
         &self,
     ) -> Option<HashMap<Self::GroupKey, BTreeMap<Self::ResourceTag, Bytes>>> {
-        unimplemented!("Currently resolved by ResourceGroupAdapter");
+        match &self.latest_view {
+            // Group contents in the multi-versioned map are shared among the transactions.
+            ViewState::Sync(_) => None,
+            ViewState::Unsync(state) => state
+                .unsync_map
+                .fetch_all_group_data()
+                .into_iter()
+                .map(|(group_key, members)| {
+                    let contents = members
+                        .into_iter()
+                        .flat_map(|(tag, value)| {
+                            self.materialized_group_member_bytes(value)
+                                .transpose()
+                                .map(|bytes| bytes.map(|bytes| (tag, bytes)))
+                        })
+                        .collect::<anyhow::Result<BTreeMap<_, _>>>()?;
+                    Ok((group_key, contents))
+                })
+                .collect::<anyhow::Result<HashMap<_, _>>>()
+                .map_err(|err| {
+                    let log_context =
+                        AdapterLogSchema::new(self.base_view.id(), self.txn_idx as usize);
+                    alert!(
+                        log_context,
+                        "[VM, ResourceView] Error during group cache release: {}",
+                        err
+                    );
+                    self.mark_incorrect_use();
+                })
+                .ok(),
+        }
     }
 
     fn is_resource_groups_split_in_change_set_capable(&self) -> bool {
@@ -1558,7 +4400,7 @@ impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> TModuleView
                         // because parallel execution will fall back to sequential anyway.
                         Ok(None)
                     },
-                    Err(NotFound) => self.get_raw_base_value(state_key),
+                    Err(NotFound) => Ok(self.get_raw_base_value(state_key)?),
                 }
             },
             ViewState::Unsync(state) => {
@@ -1568,7 +4410,7 @@ impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> TModuleView
                     .module_reads
                     .insert(state_key.clone());
                 state.unsync_map.fetch_module_data(state_key).map_or_else(
-                    || self.get_raw_base_value(state_key),
+                    || Ok(self.get_raw_base_value(state_key)?),
                     |v| Ok(v.as_state_value()),
                 )
             },
@@ -1597,12 +4439,11 @@ impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> TAggregator
         &self,
         state_key: &Self::Identifier,
     ) -> PartialVMResult<Option<StateValue>> {
-        // TODO[agg_v1](cleanup):
-        // Integrate aggregators V1. That is, we can lift the u128 value
-        // from the state item by passing the right layout here. This can
-        // be useful for cross-testing the old and the new flows.
-        // self.get_resource_state_value(state_key, Some(&MoveTypeLayout::U128))
-        self.get_resource_state_value(state_key, None)
+        // TODO[agg_v1](cleanup): always lift the u128 value once aggregators V1 are integrated.
+        let maybe_layout = self
+            .lift_aggregator_v1_values
+            .then_some(&MoveTypeLayout::U128);
+        self.get_resource_state_value(state_key, maybe_layout)
     }
 }
 
@@ -1614,10 +4455,7 @@ impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> TDelayedFie
     type ResourceKey = T::Key;
 
     fn is_delayed_field_optimization_capable(&self) -> bool {
-        match &self.latest_view {
-            ViewState::Sync(_) => true,
-            ViewState::Unsync(_) => true,
-        }
+        self.delayed_field_optimization_capable
     }
 
     fn get_delayed_field_value(
@@ -1631,6 +4469,8 @@ impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> TDelayedFie
                 state.scheduler,
                 id,
                 self.txn_idx,
+                &state.counter_labels,
+                &state.health,
             ),
             ViewState::Unsync(state) => {
                 state.read_set.borrow_mut().delayed_field_reads.insert(*id);
@@ -1658,15 +4498,19 @@ impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> TDelayedFie
                 delta,
                 max_value,
                 self.txn_idx,
+                state.history_base_revalidation,
+                &state.counter_labels,
+                &state.health,
             ),
             ViewState::Unsync(state) => {
+                state.read_set.borrow_mut().delayed_field_reads.insert(*id);
                 // No speculation in sequential execution, just evaluate directly
                 let value = state.unsync_map
                     .fetch_delayed_field(id)
                     .ok_or_else(|| {
                         code_invariant_error(format!("DelayedField {:?} not found in delayed_field_try_add_delta_outcome in sequential execution", id))
-                    })?
-                    .into_aggregator_value()?;
+                    })?;
+                let value = expect_aggregator_value(id, value, &state.counter_labels)?;
                 let math = BoundedMath::new(max_value);
                 let before = expect_ok(math.unsigned_add_delta(value, base_delta))?;
                 if math.unsigned_add_delta(before, delta).is_err() {
@@ -1678,18 +4522,60 @@ impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> TDelayedFie
         }
     }
 
-    fn generate_delayed_field_id(&self, width: u32) -> Self::Identifier {
+    fn try_add_deltas_with_policy(
+        &self,
+        deltas: &[(Self::Identifier, SignedU128, SignedU128, u128)],
+        commit_policy: &dyn Fn(&[bool]) -> bool,
+    ) -> Result<Vec<bool>, PanicOr<DelayedFieldsSpeculativeError>> {
+        match &self.latest_view {
+            ViewState::Sync(state) => delayed_field_try_add_deltas_with_policy_impl(
+                &state.captured_reads,
+                state.versioned_map.delayed_fields(),
+                state.scheduler,
+                deltas,
+                commit_policy,
+                self.txn_idx,
+                state.history_base_revalidation,
+                &state.counter_labels,
+                &state.health,
+            ),
+            // No histories are captured in sequential execution, the outcomes are evaluated
+            // directly.
+            ViewState::Unsync(_) => deltas
+                .iter()
+                .map(|(id, base_delta, delta, max_value)| {
+                    self.delayed_field_try_add_delta_outcome(id, base_delta, delta, *max_value)
+                })
+                .collect(),
+        }
+    }
+
+    fn generate_delayed_field_id(&self, width: u32) -> Result<Self::Identifier, PanicError> {
+        // The counter is never incremented past the bound, so it cannot wrap.
         let index = match &self.latest_view {
-            ViewState::Sync(state) => state.counter.fetch_add(1, Ordering::SeqCst),
+            ViewState::Sync(state) => state
+                .counter
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |id| {
+                    (id < DELAYED_FIELD_ID_INDEX_BOUND).then_some(id + 1)
+                })
+                .unwrap_or_else(|id| id),
             ViewState::Unsync(state) => {
                 let mut counter = state.counter.borrow_mut();
                 let id = *counter;
-                *counter += 1;
+                if id < DELAYED_FIELD_ID_INDEX_BOUND {
+                    *counter += 1;
+                }
                 id
             },
         };
 
-        (index, width).into()
+        if index >= DELAYED_FIELD_ID_INDEX_BOUND {
+            return Err(code_invariant_error(format!(
+                "Delayed field ids exhausted: index {} reached the bound {}",
+                index, DELAYED_FIELD_ID_INDEX_BOUND
+            )));
+        }
+        Ok((index, width).into())
     }
 
     fn validate_delayed_field_id(&self, id: &Self::Identifier) -> Result<(), PanicError> {
@@ -1705,8 +4591,12 @@ impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> TDelayedFie
         };
 
         // We read the counter to create an identifier from it, and only after
-        // increment. So its value must be < the current value.
-        if unique_index < start_counter || unique_index >= current_counter {
+        // increment. So its value must be < the current value. Identifiers with the
+        // reserved indices are never generated.
+        if unique_index < start_counter
+            || unique_index >= current_counter
+            || unique_index >= DELAYED_FIELD_ID_INDEX_BOUND
+        {
             return Err(code_invariant_error(format!(
                 "Invalid delayed field id: {:?} with index: {} (started from {} and reached {})",
                 id, unique_index, start_counter, current_counter
@@ -1723,21 +4613,43 @@ impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> TDelayedFie
         BTreeMap<Self::ResourceKey, (StateValueMetadata, u64, Arc<MoveTypeLayout>)>,
         PanicError,
     > {
-        match &self.latest_view {
-            ViewState::Sync(state) => state
-                .captured_reads
-                .borrow()
-                .get_read_values_with_delayed_fields(delayed_write_set_ids, skip),
+        let reads_needing_exchange = match &self.latest_view {
+            ViewState::Sync(state) => {
+                let reads_needing_exchange = state
+                    .captured_reads
+                    .borrow()
+                    .get_read_values_with_delayed_fields(
+                        delayed_write_set_ids,
+                        skip,
+                        &mut state.scanned_delayed_field_ids.borrow_mut(),
+                        state.exchange_size_threshold,
+                        &self.delayed_field_layouts,
+                        &state.counter_labels,
+                    )?;
+                if !reads_needing_exchange.fallback_keys.is_empty() {
+                    // Large values are not scanned in the parallel execution. This is not an
+                    // invariant violation: the reads are marked, and the executor discards the
+                    // output and falls back to the sequential execution.
+                    state
+                        .captured_reads
+                        .borrow_mut()
+                        .mark_exchange_size_threshold_exceeded();
+                }
+                reads_needing_exchange
+            },
             ViewState::Unsync(state) => {
+                // Sequential execution is the fallback, hence all values are scanned.
                 let read_set = state.read_set.borrow();
                 self.get_reads_needing_exchange_sequential(
                     &read_set.resource_reads,
                     state.unsync_map,
                     delayed_write_set_ids,
                     skip,
-                )
+                    None,
+                )?
             },
-        }
+        };
+        Ok(reads_needing_exchange.reads)
     }
 
     fn get_group_reads_needing_exchange(
@@ -1762,60 +4674,355 @@ impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> TDelayedFie
     }
 }
 
-#[cfg(test)]
-mod test {
-    //
-    // TODO[agg_v2](tests): Refactor the tests, and include resource groups in testing
-    //
-    use super::*;
-    use crate::{
-        captured_reads::{CapturedReads, DelayedFieldRead, DelayedFieldReadKind},
-        proptest_types::types::{KeyType, MockEvent, ValueType},
-        scheduler::{DependencyResult, Scheduler, TWaitForDependency},
-        view::{delayed_field_try_add_delta_outcome_impl, get_delayed_field_value_impl, ViewState},
-    };
-    use aptos_aggregator::{
-        bounded_math::{BoundedMath, SignedU128},
-        delta_math::DeltaHistory,
-        types::{DelayedFieldValue, DelayedFieldsSpeculativeError, PanicOr, ReadPosition},
-    };
-    use aptos_mvhashmap::{
-        types::{MVDelayedFieldsError, TxnIndex},
-        unsync_map::UnsyncMap,
-        versioned_delayed_fields::TVersionedDelayedFieldView,
-        MVHashMap,
-    };
-    use aptos_types::{
-        executable::Executable,
-        state_store::{
-            errors::StateviewError, state_storage_usage::StateStorageUsage,
-            state_value::StateValue, TStateView,
-        },
-        transaction::BlockExecutableTransaction,
-        write_set::TransactionWrite,
+/// Replays the captured reads of the transaction at txn_idx against the base view, through a
+/// fresh sequential view (with an empty unsync map), and returns the first read that diverged,
+/// or None if all the reads still match. Unlike the validation, which compares the versions
+/// in the multi-versioned data-structure, the reads are compared by content:
+/// - data reads (of resources and group members) by the kind of the read (DataRead::matches_value),
+/// - group sizes by the size of the group in the base view,
+/// - modules published in the block by the hash of the module in the base view.
+///
+/// Modules read from storage are not compared, as only the version is captured. Delayed field
+/// reads are not replayed either, as the identifiers are minted during the block execution
+/// (the values containing them were exchanged, and are compared by metadata and size).
+///
+/// Data reads are replayed in the order of the keys, then group reads and then module reads
+/// (in the order of capture), hence the reported divergence is deterministic. The reads of an
+/// execution that observed a speculative failure or an incorrect use are incomplete, and may
+/// not be replayed (an error is returned).
+#[cfg(any(test, feature = "serde"))]
+#[cfg_attr(not(test), allow(dead_code))]
+pub(crate) fn validate_captured_reads_against<T, S, X>(
+    reads: &CapturedReads<T>,
+    base_view: &S,
+    txn_idx: TxnIndex,
+) -> Result<Option<InputOutputKey<T::Key, T::Tag, T::Identifier>>, PanicError>
+where
+    T: Transaction,
+    S: TStateView<Key = T::Key>,
+    X: Executable,
+{
+    if reads.is_speculative_failure() || reads.is_incorrect_use() {
+        return Err(code_invariant_error(format!(
+            "Replaying incomplete captured reads of txn {}",
+            txn_idx
+        )));
+    }
+
+    let unsync_map = UnsyncMap::new();
+    let counter = RefCell::new(0);
+    let view = LatestView::<T, S, X>::new(
+        base_view,
+        ViewState::Unsync(SequentialStateRef::Owned(SequentialState::new(
+            &unsync_map,
+            0,
+            &counter,
+            false,
+            None,
+            None,
+            None,
+        ))),
+        txn_idx,
+    );
+
+    for (key, read) in reads.sorted_data_reads() {
+        let matches = view
+            .get_resource_state_value(key, None)
+            .is_ok_and(|maybe_state_value| {
+                read.matches_value(&TransactionWrite::from_state_value(maybe_state_value))
+            });
+        if !matches {
+            return Ok(Some(InputOutputKey::Resource(key.clone())));
+        }
+    }
+
+    for (key, group) in reads.sorted_group_reads() {
+        if let Some(size) = group.collected_size {
+            if view.resource_group_size(key).ok() != Some(size) {
+                return Ok(Some(InputOutputKey::Resource(key.clone())));
+            }
+        }
+
+        let mut inner_reads: Vec<_> = group.inner_reads.iter().collect();
+        inner_reads.sort_by(|(a, _), (b, _)| a.cmp(b));
+        for (tag, read) in inner_reads {
+            // Group members are stored without metadata (as in the base group contents).
+            let matches = view
+                .get_resource_from_group(key, tag, None)
+                .is_ok_and(|maybe_bytes| {
+                    read.matches_value(&TransactionWrite::from_state_value(
+                        maybe_bytes.map(StateValue::new_legacy),
+                    ))
+                });
+            if !matches {
+                return Ok(Some(InputOutputKey::Group(key.clone(), tag.clone())));
+            }
+        }
+    }
+
+    for read in &reads.module_reads {
+        let matches = match (read.version, &read.executable_descriptor) {
+            (ModuleReadVersion::Storage, _) => true,
+            (ModuleReadVersion::Published(_), Some(ExecutableDescriptor::Published(hash))) => view
+                .get_module_state_value(&read.key)
+                .is_ok_and(|maybe_state_value| {
+                    maybe_state_value.is_some_and(|state_value| {
+                        let module: T::Value =
+                            TransactionWrite::from_state_value(Some(state_value));
+                        module_hash(&module) == *hash
+                    })
+                }),
+            // The read of an estimate was never resolved.
+            _ => false,
+        };
+        if !matches {
+            return Ok(Some(InputOutputKey::Resource(read.key.as_ref().clone())));
+        }
+    }
+
+    Ok(None)
+}
+
+/// The surface of the views for the executors outside of this crate (e.g. the sharded
+/// executors), which drive a LatestView through the resolver traits that it implements
+/// (TResourceView, TDelayedFieldView, etc.). Only the items here are part of the API, the
+/// states of the views and their other methods may change without notice.
+///
+/// There is no public constructor of parallel views: their state borrows the Scheduler (that
+/// the reads wait on the dependencies with) and the KeyInterner of the block (that the captured
+/// reads are validated with), which are internal to BlockExecutor::execute_transactions_parallel.
+/// Exposing them would make the scheduling protocol part of the API, hence the executors outside
+/// of this crate drive sequential views only.
+///
+/// ```
+/// use aptos_block_executor::view::public::sequential_view;
+/// use aptos_mvhashmap::unsync_map::UnsyncMap;
+/// use aptos_types::{
+///     executable::ExecutableTestType,
+///     state_store::{
+///         in_memory_state_view::InMemoryStateView, state_key::StateKey, state_value::StateValue,
+///     },
+///     transaction::signature_verified_transaction::SignatureVerifiedTransaction,
+/// };
+/// use aptos_vm_types::resolver::TResourceView;
+/// use std::{cell::RefCell, collections::HashMap};
+///
+/// let key = StateKey::raw(b"key");
+/// let value = StateValue::new_legacy(vec![1, 2, 3].into());
+/// let base_view = InMemoryStateView::new(HashMap::from([(key.clone(), value.clone())]));
+/// let unsync_map = UnsyncMap::new();
+/// let counter = RefCell::new(0);
+///
+/// let view = sequential_view::<SignatureVerifiedTransaction, _, ExecutableTestType>(
+///     &base_view,
+///     &unsync_map,
+///     &counter,
+///     0,
+/// );
+/// assert_eq!(view.get_resource_state_value(&key, None).unwrap(), Some(value));
+/// assert_eq!(view.read_statistics().num_resource_reads, 1);
+/// assert_eq!(view.health_statistics().num_incorrect_uses, 0);
+/// ```
+pub mod public {
+    pub use super::{LatestView, ReadResult, VersionInfo};
+    use super::{SequentialState, SequentialStateRef, ViewError, ViewState};
+    pub use crate::captured_reads::ReadKind;
+    use aptos_mvhashmap::{
+        types::{TxnIndex, UnknownOrLayout},
+        unsync_map::UnsyncMap,
+    };
+    use aptos_types::{
+        executable::Executable, state_store::TStateView,
+        transaction::BlockExecutableTransaction as Transaction,
+    };
+    use move_binary_format::errors::{PartialVMError, PartialVMResult};
+    use move_core_types::value::MoveTypeLayout;
+    use std::{cell::RefCell, time::Duration};
+
+    /// Counts of the distinct keys read by the execution, and of the fetches of the values.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct ReadStatistics {
+        pub num_resource_reads: usize,
+        pub num_group_reads: usize,
+        pub num_module_reads: usize,
+        pub num_delayed_field_reads: usize,
+        /// Fetches of the values from the multi-versioned (or unsync) map.
+        pub num_map_fetches: usize,
+        /// Fetches of the values from the base view (storage).
+        pub num_base_view_fetches: usize,
+    }
+
+    /// Speculative halts, incorrect uses and dependency waits observed by the execution.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct HealthStatistics {
+        pub num_speculative_halts: usize,
+        pub num_incorrect_uses: usize,
+        pub num_dependency_waits: usize,
+        pub dependency_wait_time: Duration,
+    }
+
+    /// Creates the view for the sequential execution of the transaction at txn_idx, on top of
+    /// the values in the unsync map (i.e. as of after the lower transactions). The identifiers
+    /// of the delayed fields created by the execution are generated from the counter.
+    pub fn sequential_view<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable>(
+        base_view: &'a S,
+        unsync_map: &'a UnsyncMap<T::Key, T::Tag, T::Value, X, T::Identifier>,
+        counter: &'a RefCell<u32>,
+        txn_idx: TxnIndex,
+    ) -> LatestView<'a, T, S, X> {
+        let start_counter = *counter.borrow();
+        LatestView::new(
+            base_view,
+            ViewState::Unsync(SequentialStateRef::Owned(SequentialState::new(
+                unsync_map,
+                start_counter,
+                counter,
+                false,
+                None,
+                None,
+                None,
+            ))),
+            txn_idx,
+        )
+    }
+
+    impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> LatestView<'a, T, S, X> {
+        /// Reads the resource at the key by kind (the layout is only used by the value reads),
+        /// capturing the read as the resource view does. The speculative halts of the read are
+        /// returned as ReadResult::HaltSpeculativeExecution, and the other failures as errors.
+        pub fn read_resource(
+            &self,
+            state_key: &T::Key,
+            maybe_layout: Option<&MoveTypeLayout>,
+            kind: ReadKind,
+        ) -> PartialVMResult<ReadResult> {
+            let layout = match kind {
+                ReadKind::Value => UnknownOrLayout::Known(maybe_layout),
+                ReadKind::Exists | ReadKind::Metadata | ReadKind::Size => UnknownOrLayout::Unknown,
+            };
+            match self.get_resource_state_value_impl(state_key, layout, kind) {
+                Err(ViewError::SpeculativeAbort(_, message)) => {
+                    Ok(ReadResult::HaltSpeculativeExecution(message))
+                },
+                result => result.map_err(PartialVMError::from),
+            }
+        }
+
+        /// Returns the statistics of the reads performed by the execution so far.
+        pub fn read_statistics(&self) -> ReadStatistics {
+            let summary = self.read_summary();
+            ReadStatistics {
+                num_resource_reads: summary.num_resource_reads,
+                num_group_reads: summary.num_group_reads,
+                num_module_reads: summary.num_module_reads,
+                num_delayed_field_reads: summary.num_delayed_field_reads,
+                num_map_fetches: summary.num_map_fetches,
+                num_base_view_fetches: summary.num_base_view_fetches,
+            }
+        }
+
+        /// Returns the health of the execution so far.
+        pub fn health_statistics(&self) -> HealthStatistics {
+            let health = self.health_fragment();
+            HealthStatistics {
+                num_speculative_halts: health.total_speculative_halts(),
+                num_incorrect_uses: health.num_incorrect_uses,
+                num_dependency_waits: health.num_dependency_waits,
+                dependency_wait_time: health.dependency_wait_time,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    //
+    // TODO[agg_v2](tests): Refactor the tests, and include resource groups in testing
+    //
+    use super::*;
+    use crate::{
+        captured_reads::{CapturedReads, DelayedFieldRead, DelayedFieldReadKind},
+        proptest_types::types::{raw_metadata, KeyType, MockEvent, ValueType},
+        scheduler::{DependencyResult, Scheduler, SchedulerTask, TWaitForDependency},
+        value_exchange::extract_identifiers_from_value,
+        view::{delayed_field_try_add_delta_outcome_impl, get_delayed_field_value_impl, ViewState},
+        view_health::merge_view_health,
+    };
+    use aptos_aggregator::{
+        bounded_math::{BoundedMath, SignedU128},
+        delayed_change::{DelayedApplyEntry, DelayedEntry},
+        delta_change_set::DeltaOp,
+        delta_math::DeltaHistory,
+        types::{DelayedFieldValue, DelayedFieldsSpeculativeError, PanicOr, ReadPosition},
+    };
+    use aptos_mvhashmap::{
+        types::{MVDelayedFieldsError, TxnIndex},
+        unsync_map::UnsyncMap,
+        versioned_delayed_fields::TVersionedDelayedFieldView,
+        MVHashMap,
+    };
+    use aptos_types::{
+        executable::Executable,
+        on_chain_config::CurrentTimeMicroseconds,
+        state_store::{
+            errors::StateviewError, state_storage_usage::StateStorageUsage,
+            state_value::StateValue, TStateView,
+        },
+        transaction::BlockExecutableTransaction,
+        write_set::{TransactionWrite, WriteOpKind},
+    };
+    use aptos_vm_types::{
+        resolver::{TResourceGroupView, TResourceView},
+        resource_group_adapter::group_size_as_sum,
     };
-    use aptos_vm_types::resolver::TResourceView;
     use bytes::Bytes;
-    use claims::{assert_err_eq, assert_none, assert_ok_eq, assert_some_eq};
+    use claims::{
+        assert_err, assert_err_eq, assert_matches, assert_none, assert_ok, assert_ok_eq,
+        assert_some, assert_some_eq,
+    };
     use move_core_types::value::{IdentifierMappingKind, MoveStructLayout, MoveTypeLayout};
     use move_vm_types::{
         delayed_values::{
             delayed_field_id::DelayedFieldID,
             derived_string_snapshot::{bytes_and_width_to_derived_string_struct, to_utf8_bytes},
         },
+        value_serde::{deserialize_and_allow_delayed_values, ValueToIdentifierMapping},
+        value_traversal::find_identifiers_in_value,
         values::{Struct, Value},
     };
-    use std::{cell::RefCell, collections::HashMap, sync::atomic::AtomicU32};
+    use std::{borrow::Cow, cell::RefCell, collections::HashMap, sync::atomic::AtomicU32};
     use test_case::test_case;
 
     #[derive(Default)]
     pub struct FakeVersionedDelayedFieldView {
-        data: HashMap<DelayedFieldID, DelayedFieldValue>,
+        data: RefCell<HashMap<DelayedFieldID, DelayedFieldValue>>,
+        // The number of calls to read the committed value of a single id, and to read the
+        // committed values of a batch of ids, respectively.
+        num_committed_reads: RefCell<usize>,
+        num_batched_committed_reads: RefCell<usize>,
+        // The number of the following reads of a single committed value that return a
+        // dependency (on the transaction 0), before the value is read.
+        num_pending_dependencies: RefCell<usize>,
+        // The base values, kept apart from the (committed) values above, so that the reads
+        // before the first transaction can be told apart from the reads of committed values.
+        base_data: RefCell<HashMap<DelayedFieldID, DelayedFieldValue>>,
     }
 
     impl FakeVersionedDelayedFieldView {
         pub fn set_value(&mut self, id: DelayedFieldID, value: DelayedFieldValue) {
-            self.data.insert(id, value);
+            self.data.get_mut().insert(id, value);
+        }
+
+        pub fn contains(&self, id: &DelayedFieldID) -> bool {
+            self.data.borrow().contains_key(id)
+        }
+
+        pub fn set_pending_dependencies(&mut self, num_dependencies: usize) {
+            *self.num_pending_dependencies.get_mut() = num_dependencies;
+        }
+
+        pub fn set_base_value(&mut self, id: DelayedFieldID, value: DelayedFieldValue) {
+            self.base_data.get_mut().insert(id, value);
         }
     }
 
@@ -1826,6 +5033,7 @@ mod test {
             _txn_idx: TxnIndex,
         ) -> Result<DelayedFieldValue, PanicOr<MVDelayedFieldsError>> {
             self.data
+                .borrow()
                 .get(id)
                 .cloned()
                 .ok_or(PanicOr::Or(MVDelayedFieldsError::NotFound))
@@ -1837,11 +5045,58 @@ mod test {
             _current_txn_idx: TxnIndex,
             _read_position: ReadPosition,
         ) -> Result<DelayedFieldValue, MVDelayedFieldsError> {
+            *self.num_committed_reads.borrow_mut() += 1;
+            let mut num_pending_dependencies = self.num_pending_dependencies.borrow_mut();
+            if *num_pending_dependencies > 0 {
+                *num_pending_dependencies -= 1;
+                return Err(MVDelayedFieldsError::Dependency(0));
+            }
             self.data
+                .borrow()
+                .get(id)
+                .cloned()
+                .ok_or(MVDelayedFieldsError::NotFound)
+        }
+
+        fn read_base_value(
+            &self,
+            id: &DelayedFieldID,
+        ) -> Result<DelayedFieldValue, MVDelayedFieldsError> {
+            self.base_data
+                .borrow()
                 .get(id)
                 .cloned()
                 .ok_or(MVDelayedFieldsError::NotFound)
         }
+
+        fn read_latest_committed_values(
+            &self,
+            ids: &[DelayedFieldID],
+            _current_txn_idx: TxnIndex,
+            _read_position: ReadPosition,
+        ) -> Result<Vec<DelayedFieldValue>, MVDelayedFieldsError> {
+            *self.num_batched_committed_reads.borrow_mut() += 1;
+            let data = self.data.borrow();
+            ids.iter()
+                .map(|id| data.get(id).cloned().ok_or(MVDelayedFieldsError::NotFound))
+                .collect()
+        }
+
+        fn exists(
+            &self,
+            id: &DelayedFieldID,
+            _txn_idx: TxnIndex,
+        ) -> Result<bool, MVDelayedFieldsError> {
+            Ok(self.data.borrow().contains_key(id))
+        }
+
+        fn remove_base_value(&self, id: &DelayedFieldID) -> Result<(), PanicError> {
+            self.data
+                .borrow_mut()
+                .remove(id)
+                .map(|_| ())
+                .ok_or_else(|| code_invariant_error("Removing a missing delayed field"))
+        }
     }
 
     struct FakeWaitForDependency();
@@ -1871,11 +5126,303 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_try_add_delta_type_mismatch() {
+        let mut view = FakeVersionedDelayedFieldView::default();
+        let wait_for = FakeWaitForDependency();
+        let counter_labels = ViewCounterLabels::new(true, None);
+        let health = RefCell::new(ViewHealthSummary::default());
+        let id = DelayedFieldID::new_for_test_for_u64(600);
+        let snapshot_value = DelayedFieldValue::Snapshot(100);
+        view.set_value(id, snapshot_value.clone());
+
+        let assert_type_mismatch =
+            |result: Result<bool, PanicOr<DelayedFieldsSpeculativeError>>| {
+                assert_err_eq!(
+                    result,
+                    PanicOr::Or(DelayedFieldsSpeculativeError::WrongKind {
+                        id,
+                        expected: DelayedFieldValueKind::Aggregator,
+                        found: DelayedFieldValueKind::Snapshot,
+                    })
+                )
+            };
+        let sequential_labels = ViewCounterLabels::new(false, None);
+        let mismatch_count = |labels: &ViewCounterLabels| {
+            counters::DELAYED_FIELD_TYPE_MISMATCH_COUNT
+                .with_label_values(&labels.values())
+                .get()
+        };
+        let parallel_mismatch_count = mismatch_count(&counter_labels);
+        let sequential_mismatch_count = mismatch_count(&sequential_labels);
+
+        // Reading the value for the first time.
+        let captured_reads = RefCell::new(CapturedReads::<TestTransactionType>::new());
+        assert_type_mismatch(delayed_field_try_add_delta_outcome_impl(
+            &captured_reads,
+            &view,
+            &wait_for,
+            &id,
+            &SignedU128::Positive(0),
+            &SignedU128::Positive(10),
+            600,
+            1,
+            false,
+            &counter_labels,
+            &health,
+        ));
+        // The mismatch is recorded as a speculative failure, failing the validation.
+        assert!(captured_reads.borrow().is_speculative_failure());
+
+        // The value was already captured.
+        let captured_reads = RefCell::new(CapturedReads::<TestTransactionType>::new());
+        assert_ok!(captured_reads.borrow_mut().capture_delayed_field_read(
+            id,
+            false,
+            DelayedFieldRead::Value {
+                value: snapshot_value.clone(),
+            }
+        ));
+        assert_type_mismatch(delayed_field_try_add_delta_outcome_impl(
+            &captured_reads,
+            &view,
+            &wait_for,
+            &id,
+            &SignedU128::Positive(0),
+            &SignedU128::Positive(10),
+            600,
+            1,
+            false,
+            &counter_labels,
+            &health,
+        ));
+        assert!(captured_reads.borrow().is_speculative_failure());
+
+        // Sequential execution.
+        let holder = Holder::new(HashMap::new(), 1000);
+        holder
+            .unsync_map
+            .set_base_delayed_field(id, snapshot_value.clone());
+        let latest_view = create_sequential_latest_view(&holder);
+        assert_type_mismatch(latest_view.delayed_field_try_add_delta_outcome(
+            &id,
+            &SignedU128::Positive(0),
+            &SignedU128::Positive(10),
+            600,
+        ));
+
+        // Mismatches are counted under the labels of the execution mode.
+        assert!(mismatch_count(&counter_labels) >= parallel_mismatch_count + 2);
+        assert!(mismatch_count(&sequential_labels) > sequential_mismatch_count);
+    }
+
+    // Returns the same dependency (resolved by the test, if at all) on every wait.
+    struct FakeDependency {
+        dep_condvar: Arc<(
+            aptos_infallible::Mutex<DependencyStatus>,
+            std::sync::Condvar,
+        )>,
+        timeout: Option<Duration>,
+        spin_count: usize,
+    }
+
+    impl FakeDependency {
+        fn new(timeout: Option<Duration>) -> Self {
+            Self {
+                dep_condvar: Arc::new((
+                    aptos_infallible::Mutex::new(DependencyStatus::Unresolved),
+                    std::sync::Condvar::new(),
+                )),
+                timeout,
+                spin_count: 0,
+            }
+        }
+
+        fn with_spin_count(mut self, spin_count: usize) -> Self {
+            self.spin_count = spin_count;
+            self
+        }
+
+        // Sets the status of the dependency after a delay, from another thread.
+        fn set_status_after(
+            &self,
+            delay: Duration,
+            status: DependencyStatus,
+        ) -> std::thread::JoinHandle<()> {
+            let dep_condvar = self.dep_condvar.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(delay);
+                let (lock, cvar) = &*dep_condvar;
+                *lock.lock() = status;
+                cvar.notify_one();
+            })
+        }
+    }
+
+    impl TWaitForDependency for FakeDependency {
+        fn wait_for_dependency(
+            &self,
+            _txn_idx: TxnIndex,
+            _dep_txn_idx: TxnIndex,
+        ) -> Result<DependencyResult, PanicError> {
+            Ok(DependencyResult::Dependency(self.dep_condvar.clone()))
+        }
+
+        fn dependency_wait_timeout(&self) -> Option<Duration> {
+            self.timeout
+        }
+
+        fn dependency_wait_spin_count(&self) -> usize {
+            self.spin_count
+        }
+    }
+
+    #[test]
+    fn test_wait_for_dependency_timeout() {
+        let counter_labels = ViewCounterLabels::new(true, Some(3));
+        let timeout_count = || {
+            counters::DEPENDENCY_WAIT_TIMEOUT_COUNT
+                .with_label_values(&counter_labels.values())
+                .get()
+        };
+        let initial_timeout_count = timeout_count();
+        let timeout = Duration::from_millis(20);
+        let health = RefCell::new(ViewHealthSummary::default());
+
+        // The dependency is never resolved.
+        let wait_for = FakeDependency::new(Some(timeout));
+        assert_ok_eq!(
+            wait_for_dependency(&wait_for, 2, 1, &counter_labels, &health),
+            DependencyWaitResult::TimedOut(timeout)
+        );
+        assert!(timeout_count() > initial_timeout_count);
+
+        // A wake-up without resolving the dependency does not end the wait early.
+        let wait_for = FakeDependency::new(Some(timeout));
+        let start = Instant::now();
+        let handle = wait_for.set_status_after(Duration::ZERO, DependencyStatus::Unresolved);
+        assert_ok_eq!(
+            wait_for_dependency(&wait_for, 2, 1, &counter_labels, &health),
+            DependencyWaitResult::TimedOut(timeout)
+        );
+        assert!(start.elapsed() >= timeout);
+        handle.join().unwrap();
+
+        // The dependency is resolved (or the execution halted) before the timeout, or without
+        // a timeout.
+        for (status, expected, wait_timeout) in [
+            (
+                DependencyStatus::Resolved,
+                DependencyWaitResult::Resolved,
+                Some(Duration::from_secs(60)),
+            ),
+            (
+                DependencyStatus::Resolved,
+                DependencyWaitResult::Resolved,
+                None,
+            ),
+            (
+                DependencyStatus::ExecutionHalted,
+                DependencyWaitResult::ExecutionHalted,
+                Some(Duration::from_secs(60)),
+            ),
+        ] {
+            let wait_for = FakeDependency::new(wait_timeout);
+            let handle = wait_for.set_status_after(Duration::from_millis(10), status);
+            assert_ok_eq!(
+                wait_for_dependency(&wait_for, 2, 1, &counter_labels, &health),
+                expected
+            );
+            handle.join().unwrap();
+        }
+
+        // All the waits are recorded, with the timeouts and the halt.
+        let health = health.into_inner();
+        assert_eq!(health.num_dependency_waits, 5);
+        assert!(health.dependency_wait_time >= 2 * timeout);
+        assert_eq!(
+            health.num_speculative_halts(SpeculativeHaltReason::DependencyTimeout),
+            2
+        );
+        assert_eq!(
+            health.num_speculative_halts(SpeculativeHaltReason::ExecutionHalted),
+            1
+        );
+
+        assert_eq!(
+            dependency_wait_timeout_message(1, timeout),
+            "Dependency wait timeout: transaction 1 not resolved within 20ms"
+        );
+    }
+
+    #[test]
+    fn test_wait_for_dependency_spin() {
+        let counter_labels = ViewCounterLabels::new(true, Some(4));
+        let num_timed_waits = || {
+            counters::DEPENDENCY_WAIT_SECONDS
+                .with_label_values(&counter_labels.values())
+                .get_sample_count()
+        };
+        let health = RefCell::new(ViewHealthSummary::default());
+
+        // The dependency is resolved (or the execution halted) before the wait. It is then
+        // observed while polling, without blocking, hence the wait is not timed, unless the
+        // polling is disabled.
+        for expected in [
+            DependencyWaitResult::Resolved,
+            DependencyWaitResult::ExecutionHalted,
+        ] {
+            for (spin_count, num_blocking_waits) in [(64, 0), (0, 1)] {
+                let status = match expected {
+                    DependencyWaitResult::Resolved => DependencyStatus::Resolved,
+                    _ => DependencyStatus::ExecutionHalted,
+                };
+                let wait_for = FakeDependency::new(None).with_spin_count(spin_count);
+                wait_for
+                    .set_status_after(Duration::ZERO, status)
+                    .join()
+                    .unwrap();
+
+                let initial_num_timed_waits = num_timed_waits();
+                assert_ok_eq!(
+                    wait_for_dependency(&wait_for, 2, 1, &counter_labels, &health),
+                    expected
+                );
+                assert_eq!(
+                    num_timed_waits(),
+                    initial_num_timed_waits + num_blocking_waits
+                );
+            }
+        }
+
+        // An unresolved dependency is still waited on after polling.
+        let wait_for = FakeDependency::new(None).with_spin_count(1000);
+        let handle =
+            wait_for.set_status_after(Duration::from_millis(10), DependencyStatus::Resolved);
+        assert_ok_eq!(
+            wait_for_dependency(&wait_for, 2, 1, &counter_labels, &health),
+            DependencyWaitResult::Resolved
+        );
+        handle.join().unwrap();
+        assert_eq!(health.borrow().num_dependency_waits, 5);
+
+        // The status is only polled if configured.
+        assert_eq!(Scheduler::new(1).dependency_wait_spin_count(), 0);
+        assert_eq!(
+            Scheduler::new(1)
+                .with_dependency_wait_spin_count(64)
+                .dependency_wait_spin_count(),
+            64
+        );
+    }
+
     #[test]
     fn test_history_updates() {
         let mut view = FakeVersionedDelayedFieldView::default();
         let captured_reads = RefCell::new(CapturedReads::<TestTransactionType>::new());
         let wait_for = FakeWaitForDependency();
+        let counter_labels = ViewCounterLabels::new(true, None);
+        let health = RefCell::new(ViewHealthSummary::default());
         let id = DelayedFieldID::new_for_test_for_u64(600);
         let max_value = 600;
         let math = BoundedMath::new(max_value);
@@ -1897,7 +5444,10 @@ mod test {
                         base_value_ref,
                         &$delta,
                         max_value,
-                        txn_idx
+                        txn_idx,
+                        false,
+                        &counter_labels,
+                        &health
                     ),
                     $outcome
                 );
@@ -2011,10 +5561,79 @@ mod test {
     }
 
     #[test]
-    fn test_aggregator_overflows() {
+    fn test_first_try_add_reads_committed_value_before_txn() {
+        let mut view = FakeVersionedDelayedFieldView::default();
+        let wait_for = FakeWaitForDependency();
+        let counter_labels = ViewCounterLabels::new(true, None);
+        let health = RefCell::new(ViewHealthSummary::default());
+        let id = DelayedFieldID::new_for_test_for_u64(600);
+        let max_value = 120;
+        // The base value (e.g. from storage), and the value committed by the transaction 0.
+        let base_value = 100;
+        let committed_value = 110;
+        view.set_base_value(id, DelayedFieldValue::Aggregator(base_value));
+        view.set_value(id, DelayedFieldValue::Aggregator(committed_value));
+
+        // The transaction 0 applies its deltas to the base value, and the transaction 1 to the
+        // value committed by the transaction 0 (which the delta overflows).
+        let successful_history = DeltaHistory {
+            max_achieved_positive_delta: 15,
+            min_achieved_negative_delta: 0,
+            min_overflow_positive_delta: None,
+            max_underflow_negative_delta: None,
+        };
+        let overflown_history = DeltaHistory {
+            max_achieved_positive_delta: 0,
+            min_achieved_negative_delta: 0,
+            min_overflow_positive_delta: Some(15),
+            max_underflow_negative_delta: None,
+        };
+        for (txn_idx, inner_aggregator_value, outcome, restriction) in [
+            (0, base_value, true, successful_history),
+            (1, committed_value, false, overflown_history),
+        ] {
+            let captured_reads = RefCell::new(CapturedReads::<TestTransactionType>::new());
+            let num_committed_reads = *view.num_committed_reads.borrow();
+            assert_ok_eq!(
+                delayed_field_try_add_delta_outcome_impl(
+                    &captured_reads,
+                    &view,
+                    &wait_for,
+                    &id,
+                    &SignedU128::Positive(0),
+                    &SignedU128::Positive(15),
+                    max_value,
+                    txn_idx,
+                    false,
+                    &counter_labels,
+                    &health
+                ),
+                outcome
+            );
+            assert_some_eq!(
+                captured_reads
+                    .borrow()
+                    .get_delayed_field_by_kind(&id, DelayedFieldReadKind::HistoryBounded),
+                DelayedFieldRead::HistoryBounded {
+                    restriction,
+                    max_value,
+                    inner_aggregator_value,
+                }
+            );
+            // Only the transactions after the first one read the committed values.
+            assert_eq!(
+                *view.num_committed_reads.borrow() - num_committed_reads,
+                usize::from(txn_idx > 0)
+            );
+        }
+    }
+
+    #[test]
+    fn test_history_updates_with_zero_deltas() {
         let mut view = FakeVersionedDelayedFieldView::default();
-        let captured_reads = RefCell::new(CapturedReads::<TestTransactionType>::new());
         let wait_for = FakeWaitForDependency();
+        let counter_labels = ViewCounterLabels::new(true, None);
+        let health = RefCell::new(ViewHealthSummary::default());
         let id = DelayedFieldID::new_for_test_for_u64(600);
         let max_value = 600;
         let math = BoundedMath::new(max_value);
@@ -2022,13 +5641,277 @@ mod test {
         let storage_value = 100;
         view.set_value(id, DelayedFieldValue::Aggregator(storage_value));
 
-        let mut base_delta = SignedU128::Positive(0);
-        let base_value_ref = &mut base_delta;
+        // Returns the captured read after the deltas are applied in order (each
+        // try_add expected to succeed iff the delta is within the bounds).
+        let apply_deltas = |deltas: &[SignedU128]| {
+            let captured_reads = RefCell::new(CapturedReads::<TestTransactionType>::new());
+            let mut base_delta = SignedU128::Positive(0);
+            for delta in deltas {
+                let outcome = assert_ok!(delayed_field_try_add_delta_outcome_impl(
+                    &captured_reads,
+                    &view,
+                    &wait_for,
+                    &id,
+                    &base_delta,
+                    delta,
+                    max_value,
+                    txn_idx,
+                    false,
+                    &counter_labels,
+                    &health
+                ));
+                if delta.is_zero() {
+                    assert!(outcome);
+                }
+                if outcome {
+                    base_delta = math.signed_add(&base_delta, delta).unwrap();
+                }
+            }
+            captured_reads
+                .into_inner()
+                .get_delayed_field_by_kind(&id, DelayedFieldReadKind::HistoryBounded)
+        };
 
-        macro_rules! assert_try_add {
-            ($delta:expr, $outcome:expr) => {
-                assert_ok_eq!(
-                    delayed_field_try_add_delta_outcome_impl(
+        // The first zero delta is captured (as a success of 0), as the outcome depends on
+        // the committed value.
+        assert_some_eq!(
+            apply_deltas(&[SignedU128::Positive(0)]),
+            DelayedFieldRead::HistoryBounded {
+                restriction: DeltaHistory::new(),
+                max_value,
+                inner_aggregator_value: storage_value,
+            }
+        );
+
+        // The following zero deltas return early, and leave the history unchanged. Not a
+        // benchmark: the saving of the fast path (try_add(agg, 0) is a common bound check,
+        // which no longer clones and re-captures the read) is not measured here.
+        let deltas = [
+            SignedU128::Positive(300),
+            SignedU128::Positive(400),
+            SignedU128::Negative(350),
+            SignedU128::Positive(250),
+            SignedU128::Negative(150),
+        ];
+        let interleaved_deltas: Vec<_> = deltas
+            .iter()
+            .flat_map(|delta| [*delta, SignedU128::Positive(0), SignedU128::Negative(0)])
+            .collect();
+        let read = apply_deltas(&deltas);
+        assert_some_eq!(
+            &read,
+            &DelayedFieldRead::HistoryBounded {
+                restriction: DeltaHistory {
+                    max_achieved_positive_delta: 300,
+                    min_achieved_negative_delta: 50,
+                    min_overflow_positive_delta: Some(700),
+                    max_underflow_negative_delta: None,
+                },
+                max_value,
+                inner_aggregator_value: storage_value,
+            }
+        );
+        assert_eq!(apply_deltas(&interleaved_deltas), read);
+    }
+
+    #[test]
+    fn test_try_add_deltas_rollback() {
+        let mut view = FakeVersionedDelayedFieldView::default();
+        let captured_reads = RefCell::new(CapturedReads::<TestTransactionType>::new());
+        let wait_for = FakeWaitForDependency();
+        let counter_labels = ViewCounterLabels::new(true, None);
+        let health = RefCell::new(ViewHealthSummary::default());
+        let id_a = DelayedFieldID::new_for_test_for_u64(600);
+        let id_b = DelayedFieldID::new_for_test_for_u64(601);
+        let max_value = 600;
+        let txn_idx = 1;
+        view.set_value(id_a, DelayedFieldValue::Aggregator(100));
+        view.set_value(id_b, DelayedFieldValue::Aggregator(500));
+
+        let all_succeeded = |outcomes: &[bool]| outcomes.iter().all(|o| *o);
+        let history_of = |id: &DelayedFieldID, history: DeltaHistory| {
+            assert_some_eq!(
+                captured_reads
+                    .borrow()
+                    .get_delayed_field_by_kind(id, DelayedFieldReadKind::HistoryBounded),
+                DelayedFieldRead::HistoryBounded {
+                    restriction: history,
+                    max_value,
+                    inner_aggregator_value: if id == &id_a { 100 } else { 500 },
+                }
+            );
+        };
+
+        assert_ok_eq!(
+            delayed_field_try_add_delta_outcome_impl(
+                &captured_reads,
+                &view,
+                &wait_for,
+                &id_a,
+                &SignedU128::Positive(0),
+                &SignedU128::Positive(100),
+                max_value,
+                txn_idx,
+                false,
+                &counter_labels,
+                &health
+            ),
+            true
+        );
+        let history_a = DeltaHistory {
+            max_achieved_positive_delta: 100,
+            min_achieved_negative_delta: 0,
+            min_overflow_positive_delta: None,
+            max_underflow_negative_delta: None,
+        };
+        history_of(&id_a, history_a);
+
+        // The try_add on id_b fails, so the update of the id_a history is rolled back. The
+        // failure on id_b is kept, as the rejection of the batch depends on it.
+        let batch = [
+            (
+                id_a,
+                SignedU128::Positive(100),
+                SignedU128::Positive(200),
+                max_value,
+            ),
+            (
+                id_b,
+                SignedU128::Positive(0),
+                SignedU128::Positive(200),
+                max_value,
+            ),
+        ];
+        assert_ok_eq!(
+            delayed_field_try_add_deltas_with_policy_impl(
+                &captured_reads,
+                &view,
+                &wait_for,
+                &batch,
+                &all_succeeded,
+                txn_idx,
+                false,
+                &counter_labels,
+                &health
+            ),
+            vec![true, false]
+        );
+        history_of(&id_a, history_a);
+        let history_b = DeltaHistory {
+            max_achieved_positive_delta: 0,
+            min_achieved_negative_delta: 0,
+            min_overflow_positive_delta: Some(200),
+            max_underflow_negative_delta: None,
+        };
+        history_of(&id_b, history_b);
+
+        // With a policy that accepts the failure, the updates are kept.
+        assert_ok_eq!(
+            delayed_field_try_add_deltas_with_policy_impl(
+                &captured_reads,
+                &view,
+                &wait_for,
+                &batch,
+                &|outcomes: &[bool]| outcomes[0],
+                txn_idx,
+                false,
+                &counter_labels,
+                &health
+            ),
+            vec![true, false]
+        );
+        let history_a = DeltaHistory {
+            max_achieved_positive_delta: 300,
+            ..history_a
+        };
+        history_of(&id_a, history_a);
+        history_of(&id_b, history_b);
+    }
+
+    struct ResolvedDependency();
+
+    impl TWaitForDependency for ResolvedDependency {
+        fn wait_for_dependency(
+            &self,
+            _txn_idx: TxnIndex,
+            _dep_txn_idx: TxnIndex,
+        ) -> Result<DependencyResult, PanicError> {
+            Ok(DependencyResult::Resolved)
+        }
+    }
+
+    #[test]
+    fn test_committed_value_read_waits_for_dependency() {
+        let mut view = FakeVersionedDelayedFieldView::default();
+        let captured_reads = RefCell::new(CapturedReads::<TestTransactionType>::new());
+        let counter_labels = ViewCounterLabels::new(true, None);
+        let health = RefCell::new(ViewHealthSummary::default());
+        let id = DelayedFieldID::new_for_test_for_u64(600);
+        view.set_value(id, DelayedFieldValue::Aggregator(100));
+        view.set_pending_dependencies(2);
+
+        assert_ok_eq!(
+            get_delayed_field_latest_committed_value_impl(
+                &captured_reads,
+                &view,
+                &ResolvedDependency(),
+                &id,
+                ReadPosition::AfterCurrentTxn,
+                1,
+                &counter_labels,
+                &health
+            ),
+            DelayedFieldValue::Aggregator(100)
+        );
+        assert_eq!(health.borrow().num_dependency_waits, 2);
+        assert_some_eq!(
+            captured_reads
+                .borrow()
+                .get_delayed_field_by_kind(&id, DelayedFieldReadKind::Committed),
+            DelayedFieldRead::Committed {
+                value: DelayedFieldValue::Aggregator(100),
+            }
+        );
+
+        // The repeated read is served from the captured read.
+        assert_ok_eq!(
+            get_delayed_field_latest_committed_value_impl(
+                &captured_reads,
+                &view,
+                &FakeWaitForDependency(),
+                &id,
+                ReadPosition::AfterCurrentTxn,
+                1,
+                &counter_labels,
+                &health
+            ),
+            DelayedFieldValue::Aggregator(100)
+        );
+        assert_eq!(*view.num_committed_reads.borrow(), 3);
+        assert!(!captured_reads.borrow().is_incorrect_use());
+    }
+
+    #[test]
+    fn test_aggregator_overflows() {
+        let mut view = FakeVersionedDelayedFieldView::default();
+        let captured_reads = RefCell::new(CapturedReads::<TestTransactionType>::new());
+        let wait_for = FakeWaitForDependency();
+        let counter_labels = ViewCounterLabels::new(true, None);
+        let health = RefCell::new(ViewHealthSummary::default());
+        let id = DelayedFieldID::new_for_test_for_u64(600);
+        let max_value = 600;
+        let math = BoundedMath::new(max_value);
+        let txn_idx = 1;
+        let storage_value = 100;
+        view.set_value(id, DelayedFieldValue::Aggregator(storage_value));
+
+        let mut base_delta = SignedU128::Positive(0);
+        let base_value_ref = &mut base_delta;
+
+        macro_rules! assert_try_add {
+            ($delta:expr, $outcome:expr) => {
+                assert_ok_eq!(
+                    delayed_field_try_add_delta_outcome_impl(
                         &captured_reads,
                         &view,
                         &wait_for,
@@ -2036,7 +5919,10 @@ mod test {
                         base_value_ref,
                         &$delta,
                         max_value,
-                        txn_idx
+                        txn_idx,
+                        false,
+                        &counter_labels,
+                        &health
                     ),
                     $outcome
                 );
@@ -2154,6 +6040,8 @@ mod test {
         let mut view = FakeVersionedDelayedFieldView::default();
         let captured_reads = RefCell::new(CapturedReads::<TestTransactionType>::new());
         let wait_for = FakeWaitForDependency();
+        let counter_labels = ViewCounterLabels::new(true, None);
+        let health = RefCell::new(ViewHealthSummary::default());
         let id = DelayedFieldID::new_for_test_for_u64(600);
         let max_value = 600;
         let math = BoundedMath::new(max_value);
@@ -2175,7 +6063,10 @@ mod test {
                         base_value_ref,
                         &$delta,
                         max_value,
-                        txn_idx
+                        txn_idx,
+                        false,
+                        &counter_labels,
+                        &health
                     ),
                     $outcome
                 );
@@ -2293,6 +6184,8 @@ mod test {
         let mut view = FakeVersionedDelayedFieldView::default();
         let captured_reads = RefCell::new(CapturedReads::<TestTransactionType>::new());
         let wait_for = FakeWaitForDependency();
+        let counter_labels = ViewCounterLabels::new(true, None);
+        let health = RefCell::new(ViewHealthSummary::default());
         let id = DelayedFieldID::new_for_test_for_u64(600);
         let max_value = 600;
         let txn_idx = 1;
@@ -2308,7 +6201,10 @@ mod test {
                 &SignedU128::Positive(0),
                 &SignedU128::Positive(300),
                 max_value,
-                txn_idx
+                txn_idx,
+                false,
+                &counter_labels,
+                &health
             ),
             true
         );
@@ -2331,11 +6227,74 @@ mod test {
 
         view.set_value(id, DelayedFieldValue::Aggregator(400));
         assert_err_eq!(
-            get_delayed_field_value_impl(&captured_reads, &view, &wait_for, &id, txn_idx),
+            get_delayed_field_value_impl(
+                &captured_reads,
+                &view,
+                &wait_for,
+                &id,
+                txn_idx,
+                &counter_labels,
+                &health
+            ),
             PanicOr::Or(DelayedFieldsSpeculativeError::InconsistentRead),
         );
     }
 
+    #[test_case(false)]
+    #[test_case(true)]
+    fn test_history_base_revalidation(history_base_revalidation: bool) {
+        let mut view = FakeVersionedDelayedFieldView::default();
+        let captured_reads = RefCell::new(CapturedReads::<TestTransactionType>::new());
+        let wait_for = FakeWaitForDependency();
+        let counter_labels = ViewCounterLabels::new(true, None);
+        let health = RefCell::new(ViewHealthSummary::default());
+        let id = DelayedFieldID::new_for_test_for_u64(600);
+        let max_value = 600;
+        let txn_idx = 1;
+        view.set_value(id, DelayedFieldValue::Aggregator(200));
+
+        let try_add = |view: &FakeVersionedDelayedFieldView, base_delta, delta| {
+            delayed_field_try_add_delta_outcome_impl(
+                &captured_reads,
+                view,
+                &wait_for,
+                &id,
+                &SignedU128::Positive(base_delta),
+                &SignedU128::Positive(delta),
+                max_value,
+                txn_idx,
+                history_base_revalidation,
+                &counter_labels,
+                &health,
+            )
+        };
+
+        assert_ok_eq!(try_add(&view, 0, 300), true);
+        // The history is unchanged if the committed value is the same.
+        assert_ok_eq!(try_add(&view, 300, 50), true);
+
+        // The committed value changes between the try_add calls.
+        view.set_value(id, DelayedFieldValue::Aggregator(400));
+        if history_base_revalidation {
+            assert_err_eq!(
+                try_add(&view, 350, 50),
+                PanicOr::Or(DelayedFieldsSpeculativeError::InconsistentRead)
+            );
+            assert!(captured_reads.borrow().is_speculative_failure());
+        } else {
+            // The outcome is computed from the captured history (with the base of 200), and
+            // the inconsistency is only detected by the validation.
+            assert_ok_eq!(try_add(&view, 350, 50), true);
+            assert!(!captured_reads.borrow().is_speculative_failure());
+            assert_ok_eq!(
+                captured_reads
+                    .borrow()
+                    .validate_delayed_field_reads(&view, txn_idx),
+                false
+            );
+        }
+    }
+
     fn create_struct_layout(inner: MoveTypeLayout) -> MoveTypeLayout {
         MoveTypeLayout::Struct(MoveStructLayout::new(vec![inner]))
     }
@@ -2417,6 +6376,11 @@ mod test {
         StateValue::new_legacy(value.simple_serialize(layout).unwrap().into())
     }
 
+    // Exchanged bytes replace the contents of the original state value, keeping its metadata.
+    fn rebuild_state_value(original: &StateValue, new_bytes: Bytes) -> StateValue {
+        StateValue::new_with_metadata(new_bytes, original.clone().into_metadata())
+    }
+
     // TODO: Check how to import MockStateView from other tests
     // rather than rewriting it here again
     struct MockStateView {
@@ -2461,7 +6425,15 @@ mod test {
         let start_counter = 5;
         let latest_view = LatestView::<TestTransactionType, MockStateView, MockExecutable>::new(
             &base_view,
-            ViewState::Unsync(SequentialState::new(&unsync_map, start_counter, &counter)),
+            ViewState::Unsync(SequentialStateRef::Owned(SequentialState::new(
+                &unsync_map,
+                start_counter,
+                &counter,
+                false,
+                None,
+                None,
+                None,
+            ))),
             1,
         );
 
@@ -2488,7 +6460,10 @@ mod test {
         let (final_state_value, identifiers) = latest_view
             .replace_identifiers_with_values(patched_state_value.bytes(), &layout)
             .unwrap();
-        assert_eq!(state_value, StateValue::from(final_state_value.to_vec()));
+        assert_eq!(
+            state_value,
+            rebuild_state_value(&patched_state_value, final_state_value)
+        );
         assert!(
             identifiers.is_empty(),
             "No identifiers should have been replaced in this case"
@@ -2521,7 +6496,10 @@ mod test {
         let (final_state_value, identifiers) = latest_view
             .replace_identifiers_with_values(patched_state_value.bytes(), &layout)
             .unwrap();
-        assert_eq!(state_value, StateValue::from(final_state_value.to_vec()));
+        assert_eq!(
+            state_value,
+            rebuild_state_value(&patched_state_value, final_state_value)
+        );
         assert_eq!(
             identifiers.len(),
             1,
@@ -2585,7 +6563,10 @@ mod test {
         let (final_state_value, identifiers) = latest_view
             .replace_identifiers_with_values(patched_state_value.bytes(), &layout)
             .unwrap();
-        assert_eq!(state_value, StateValue::from(final_state_value.to_vec()));
+        assert_eq!(
+            state_value,
+            rebuild_state_value(&patched_state_value, final_state_value)
+        );
         assert_eq!(
             identifiers.len(),
             3,
@@ -2648,7 +6629,10 @@ mod test {
         let (final_state_value, identifiers2) = latest_view
             .replace_identifiers_with_values(patched_state_value.bytes(), &layout)
             .unwrap();
-        assert_eq!(state_value, StateValue::from(final_state_value.to_vec()));
+        assert_eq!(
+            state_value,
+            rebuild_state_value(&patched_state_value, final_state_value)
+        );
         assert_eq!(
             identifiers2.len(),
             3,
@@ -2710,7 +6694,10 @@ mod test {
         let (final_state_value, identifiers2) = latest_view
             .replace_identifiers_with_values(patched_state_value.bytes(), &layout)
             .unwrap();
-        assert_eq!(state_value, StateValue::from(final_state_value.to_vec()));
+        assert_eq!(
+            state_value,
+            rebuild_state_value(&patched_state_value, final_state_value)
+        );
         assert_eq!(
             identifiers2.len(),
             3,
@@ -2719,63 +6706,487 @@ mod test {
         assert_eq!(identifiers, identifiers2);
     }
 
-    struct Holder {
-        unsync_map: UnsyncMap<KeyType<u32>, u32, ValueType, MockExecutable, DelayedFieldID>,
-        counter: RefCell<u32>,
-        base_view: MockStateView,
+    // The reference extraction, which deserializes the value and then scans it.
+    fn extract_identifiers_by_deserialization(
+        bytes: &[u8],
+        layout: &MoveTypeLayout,
+    ) -> Option<HashSet<DelayedFieldID>> {
+        let value = deserialize_and_allow_delayed_values(bytes, layout)?;
+        let mut identifiers = HashSet::new();
+        find_identifiers_in_value(&value, &mut identifiers).ok()?;
+        Some(identifiers.into_iter().map(DelayedFieldID::from).collect())
     }
 
-    impl Holder {
-        fn new(data: HashMap<KeyType<u32>, StateValue>, start_counter: u32) -> Self {
-            let unsync_map = UnsyncMap::new();
-            let counter = RefCell::new(start_counter);
-            let base_view = MockStateView::new(data);
-            Self {
-                unsync_map,
-                counter,
-                base_view,
+    #[test]
+    fn test_identifier_extraction_matches_deserialization() {
+        let holder = Holder::new(HashMap::new(), 5);
+        let latest_view = create_sequential_latest_view(&holder);
+
+        let plain_layout = MoveTypeLayout::Struct(MoveStructLayout::new(vec![
+            MoveTypeLayout::U64,
+            MoveTypeLayout::U64,
+            MoveTypeLayout::U64,
+        ]));
+        let cases = vec![
+            (
+                plain_layout.clone(),
+                plain_layout,
+                Value::struct_(Struct::pack(vec![
+                    Value::u64(1),
+                    Value::u64(2),
+                    Value::u64(3),
+                ])),
+            ),
+            (
+                create_struct_layout(create_aggregator_storage_layout(MoveTypeLayout::U64)),
+                create_struct_layout(create_aggregator_layout_u64()),
+                create_struct_value(create_aggregator_value_u64(25, 30)),
+            ),
+            (
+                create_struct_layout(create_vector_layout(create_aggregator_storage_layout(
+                    MoveTypeLayout::U64,
+                ))),
+                create_struct_layout(create_vector_layout(create_aggregator_layout_u64())),
+                create_struct_value(create_vector_value(vec![
+                    create_aggregator_value_u64(20, 50),
+                    create_aggregator_value_u64(35, 65),
+                    create_aggregator_value_u64(0, 20),
+                ])),
+            ),
+            (
+                create_struct_layout(create_vector_layout(create_snapshot_storage_layout(
+                    MoveTypeLayout::U128,
+                ))),
+                create_struct_layout(create_vector_layout(create_snapshot_layout(
+                    MoveTypeLayout::U128,
+                ))),
+                create_struct_value(create_vector_value(vec![
+                    create_snapshot_value(Value::u128(20)),
+                    create_snapshot_value(Value::u128(35)),
+                    create_snapshot_value(Value::u128(0)),
+                ])),
+            ),
+            (
+                create_struct_layout(create_vector_layout(create_derived_string_storage_layout())),
+                create_struct_layout(create_vector_layout(create_derived_string_layout())),
+                create_struct_value(create_vector_value(vec![
+                    create_derived_value("hello", 60),
+                    create_derived_value("ab", 55),
+                    create_derived_value("c", 50),
+                ])),
+            ),
+        ];
+
+        for (storage_layout, layout, value) in cases {
+            let state_value = create_state_value(&value, &storage_layout);
+            let (patched_state_value, identifiers) = latest_view
+                .replace_values_with_identifiers(state_value.clone(), &layout)
+                .unwrap();
+            assert_ok_eq!(
+                extract_identifiers_from_value::<TestTransactionType>(
+                    patched_state_value.bytes(),
+                    &layout,
+                    latest_view.counter_labels(),
+                ),
+                identifiers
+            );
+
+            // Bytes that were not exchanged, truncated or extended are accepted (or rejected)
+            // by both extractions alike.
+            let patched_bytes = patched_state_value.bytes().clone();
+            let mut extended_bytes = patched_bytes.to_vec();
+            extended_bytes.push(0);
+            let candidates = vec![
+                patched_bytes.clone(),
+                state_value.bytes().clone(),
+                patched_bytes.slice(..patched_bytes.len() - 1),
+                Bytes::from(extended_bytes),
+            ];
+            for bytes in candidates {
+                assert_eq!(
+                    extract_identifiers_from_value::<TestTransactionType>(
+                        &bytes,
+                        &layout,
+                        latest_view.counter_labels()
+                    )
+                    .ok(),
+                    extract_identifiers_by_deserialization(&bytes, &layout),
+                );
             }
         }
     }
 
-    fn create_sequential_latest_view<'a>(
-        h: &'a Holder,
-    ) -> LatestView<'a, TestTransactionType, MockStateView, MockExecutable> {
-        let sequential_state: SequentialState<'a, TestTransactionType, MockExecutable> =
-            SequentialState::new(&h.unsync_map, *h.counter.borrow(), &h.counter);
+    #[test]
+    fn test_value_exchange_counters() {
+        let holder = Holder::new(HashMap::new(), 5);
+        let latest_view = create_sequential_latest_view(&holder);
+        let [mode, shard] = latest_view.counter_labels().values();
+        let seconds_count = |exchange_type| {
+            counters::VALUE_EXCHANGE_SECONDS
+                .with_label_values(&[mode, shard, exchange_type])
+                .get_sample_count()
+        };
+        let bytes_sum = |exchange_type| {
+            counters::VALUE_EXCHANGE_BYTES
+                .with_label_values(&[mode, shard, exchange_type])
+                .get_sample_sum()
+        };
+        let identifiers_sum = || {
+            counters::VALUE_EXCHANGE_GENERATED_IDENTIFIERS
+                .with_label_values(&[mode, shard])
+                .get_sample_sum()
+        };
 
-        LatestView::<'a, TestTransactionType, MockStateView, MockExecutable>::new(
-            &h.base_view,
-            ViewState::Unsync(sequential_state),
-            1,
-        )
-    }
+        let storage_layout =
+            create_struct_layout(create_aggregator_storage_layout(MoveTypeLayout::U64));
+        let layout = create_struct_layout(create_aggregator_layout_u64());
+        let value = create_struct_value(create_aggregator_value_u64(25, 30));
+        let state_value =
+            StateValue::new_legacy(value.simple_serialize(&storage_layout).unwrap().into());
+        let num_bytes = state_value.bytes().len() as f64;
 
-    struct ComparisonHolder {
-        start_counter: u32,
-        holder: Holder,
-        counter: AtomicU32,
-        base_view: MockStateView,
-        versioned_map: MVHashMap<KeyType<u32>, u32, ValueType, MockExecutable, DelayedFieldID>,
-        scheduler: Scheduler,
+        let initial_values_to_ids = seconds_count(ExchangeType::VALUES_TO_IDENTIFIERS);
+        let initial_values_to_ids_bytes = bytes_sum(ExchangeType::VALUES_TO_IDENTIFIERS);
+        let initial_identifiers = identifiers_sum();
+        let (patched_state_value, _) = latest_view
+            .replace_values_with_identifiers(state_value, &layout)
+            .unwrap();
+        assert!(seconds_count(ExchangeType::VALUES_TO_IDENTIFIERS) > initial_values_to_ids);
+        assert!(
+            bytes_sum(ExchangeType::VALUES_TO_IDENTIFIERS)
+                >= initial_values_to_ids_bytes + num_bytes
+        );
+        assert!(identifiers_sum() >= initial_identifiers + 1.0);
+
+        let initial_extractions = seconds_count(ExchangeType::IDENTIFIER_EXTRACTION);
+        let initial_extraction_bytes = bytes_sum(ExchangeType::IDENTIFIER_EXTRACTION);
+        let mut scanned_ids = BTreeSet::new();
+        assert_ok!(does_value_need_exchange::<TestTransactionType>(
+            &ValueType::from_state_value(Some(patched_state_value.clone())),
+            &Arc::new(layout.clone()),
+            &HashSet::new(),
+            &mut scanned_ids,
+            &DelayedFieldLayoutCache::new(),
+            latest_view.counter_labels(),
+        ));
+        assert_eq!(scanned_ids.len(), 1);
+        assert!(seconds_count(ExchangeType::IDENTIFIER_EXTRACTION) > initial_extractions);
+        assert!(
+            bytes_sum(ExchangeType::IDENTIFIER_EXTRACTION) >= initial_extraction_bytes + num_bytes
+        );
+
+        let initial_ids_to_values = seconds_count(ExchangeType::IDENTIFIERS_TO_VALUES);
+        let initial_ids_to_values_bytes = bytes_sum(ExchangeType::IDENTIFIERS_TO_VALUES);
+        assert_ok!(
+            latest_view.replace_identifiers_with_values(patched_state_value.bytes(), &layout)
+        );
+        assert!(seconds_count(ExchangeType::IDENTIFIERS_TO_VALUES) > initial_ids_to_values);
+        assert!(
+            bytes_sum(ExchangeType::IDENTIFIERS_TO_VALUES)
+                >= initial_ids_to_values_bytes + num_bytes
+        );
     }
 
-    impl ComparisonHolder {
-        fn new(data: HashMap<KeyType<u32>, StateValue>, start_counter: u32) -> Self {
-            let holder = Holder::new(data.clone(), start_counter);
-            let counter = AtomicU32::new(start_counter);
-            let base_view = MockStateView::new(data);
-            let versioned_map = MVHashMap::new();
-            let scheduler = Scheduler::new(30);
+    #[test]
+    fn test_id_value_exchange_leaves_original_unchanged() {
+        let holder = Holder::new(HashMap::new(), 5);
+        let latest_view = create_sequential_latest_view(&holder);
 
-            Self {
-                start_counter,
-                holder,
-                counter,
-                base_view,
-                versioned_map,
-                scheduler,
-            }
+        let storage_layout =
+            create_struct_layout(create_aggregator_storage_layout(MoveTypeLayout::U64));
+        let layout = create_struct_layout(create_aggregator_layout_u64());
+        let value = create_struct_value(create_aggregator_value_u64(25, 30));
+        let state_value =
+            StateValue::new_legacy(value.simple_serialize(&storage_layout).unwrap().into());
+        let original_ptr = state_value.bytes().as_ptr();
+        let original_content = state_value.bytes().to_vec();
+
+        // The state value passed to the exchange shares the bytes with the original.
+        let (patched_state_value, identifiers) = latest_view
+            .replace_values_with_identifiers(state_value.clone(), &layout)
+            .unwrap();
+        assert_eq!(identifiers.len(), 1);
+        assert_eq!(state_value.bytes().as_ptr(), original_ptr);
+        assert_eq!(state_value.bytes().as_ref(), original_content.as_slice());
+        assert_ne!(patched_state_value.bytes().as_ptr(), original_ptr);
+        assert_ne!(patched_state_value.bytes(), state_value.bytes());
+    }
+
+    #[test]
+    fn test_id_value_exchange_preserves_metadata() {
+        let holder = Holder::new(HashMap::new(), 5);
+        let latest_view = create_sequential_latest_view(&holder);
+
+        let storage_layout =
+            create_struct_layout(create_aggregator_storage_layout(MoveTypeLayout::U64));
+        let layout = create_struct_layout(create_aggregator_layout_u64());
+        let value = create_struct_value(create_aggregator_value_u64(25, 30));
+        let metadata = StateValueMetadata::new(5, 7, &CurrentTimeMicroseconds { microseconds: 11 });
+        let state_value = StateValue::new_with_metadata(
+            value.simple_serialize(&storage_layout).unwrap().into(),
+            metadata.clone(),
+        );
+
+        let (patched_state_value, identifiers) = latest_view
+            .replace_values_with_identifiers(state_value.clone(), &layout)
+            .unwrap();
+        assert_eq!(identifiers.len(), 1);
+        assert_ne!(patched_state_value.bytes(), state_value.bytes());
+        assert_eq!(patched_state_value.clone().into_metadata(), metadata);
+
+        // Full value -> id -> value round trip restores the original state value.
+        let (final_bytes, _) = latest_view
+            .replace_identifiers_with_values(patched_state_value.bytes(), &layout)
+            .unwrap();
+        let final_state_value = rebuild_state_value(&patched_state_value, final_bytes);
+        assert_eq!(final_state_value, state_value);
+        assert_eq!(final_state_value.into_metadata(), metadata);
+    }
+
+    // The identifier of a derived string snapshot is serialized at the width of the exchanged
+    // value, hence the patched bytes have the same length as the original, for any string.
+    #[test_case(0)]
+    #[test_case(1)]
+    #[test_case(31)]
+    #[test_case(32)]
+    #[test_case(255)]
+    fn test_derived_string_exchange_round_trip(len: usize) {
+        let holder = Holder::new(HashMap::new(), 5);
+        let latest_view = create_sequential_latest_view(&holder);
+
+        let storage_layout = create_struct_layout(create_derived_string_storage_layout());
+        let layout = create_struct_layout(create_derived_string_layout());
+        // Leaves room for the padding, and for the identifier (of at most 20 digits).
+        let width = (bcs::serialized_size(&vec![0u8; len]).unwrap() + 1).max(24);
+        let state_value = create_state_value(
+            &create_struct_value(create_derived_value("a".repeat(len), width)),
+            &storage_layout,
+        );
+        assert_eq!(state_value.size(), width);
+
+        let (patched_state_value, identifiers) = latest_view
+            .replace_values_with_identifiers(state_value.clone(), &layout)
+            .unwrap();
+        let id = DelayedFieldID::new_with_width(5, width as u32);
+        assert_eq!(identifiers, HashSet::from([id]));
+        assert_eq!(
+            patched_state_value,
+            create_state_value(
+                &create_struct_value(id.into_derived_string_struct().unwrap()),
+                &storage_layout
+            )
+        );
+        assert_eq!(patched_state_value.size(), state_value.size());
+
+        let (final_bytes, identifiers) = latest_view
+            .replace_identifiers_with_values(patched_state_value.bytes(), &layout)
+            .unwrap();
+        assert_eq!(identifiers, HashSet::from([id]));
+        assert_eq!(&final_bytes, state_value.bytes());
+    }
+
+    // Refuses to exchange snapshots: the snapshot values remain in place.
+    struct NoSnapshotExchangeFactory;
+
+    struct NoSnapshotExchangeMapping<'a> {
+        mapping: Box<dyn ValueToIdentifierMapping<Identifier = DelayedFieldID> + 'a>,
+    }
+
+    impl<'a> ValueToIdentifierMapping for NoSnapshotExchangeMapping<'a> {
+        type Identifier = DelayedFieldID;
+
+        fn value_to_identifier(
+            &self,
+            kind: &IdentifierMappingKind,
+            layout: &MoveTypeLayout,
+            value: Value,
+        ) -> PartialVMResult<Self::Identifier> {
+            if *kind == IdentifierMappingKind::Snapshot {
+                return Err(
+                    PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
+                        .with_message("Snapshots must not be exchanged".to_string()),
+                );
+            }
+            self.mapping.value_to_identifier(kind, layout, value)
+        }
+
+        fn identifier_to_value(
+            &self,
+            layout: &MoveTypeLayout,
+            identifier: Self::Identifier,
+        ) -> PartialVMResult<Value> {
+            self.mapping.identifier_to_value(layout, identifier)
+        }
+    }
+
+    fn strip_snapshot_layouts(layout: &MoveTypeLayout) -> MoveTypeLayout {
+        match layout {
+            MoveTypeLayout::Native(IdentifierMappingKind::Snapshot, inner) => {
+                strip_snapshot_layouts(inner)
+            },
+            MoveTypeLayout::Native(kind, inner) => {
+                MoveTypeLayout::Native(kind.clone(), Box::new(strip_snapshot_layouts(inner)))
+            },
+            MoveTypeLayout::Vector(inner) => {
+                MoveTypeLayout::Vector(Box::new(strip_snapshot_layouts(inner)))
+            },
+            MoveTypeLayout::Struct(MoveStructLayout::Runtime(fields)) => MoveTypeLayout::Struct(
+                MoveStructLayout::new(fields.iter().map(strip_snapshot_layouts).collect()),
+            ),
+            layout => layout.clone(),
+        }
+    }
+
+    impl ValueToIdentifierMappingFactory<TestTransactionType, MockStateView, MockExecutable>
+        for NoSnapshotExchangeFactory
+    {
+        fn make_mapping<'a>(
+            &self,
+            view: &'a LatestView<'a, TestTransactionType, MockStateView, MockExecutable>,
+            txn_idx: TxnIndex,
+        ) -> Box<dyn ValueToIdentifierMapping<Identifier = DelayedFieldID> + 'a> {
+            Box::new(NoSnapshotExchangeMapping {
+                mapping: DefaultValueToIdentifierMappingFactory.make_mapping(view, txn_idx),
+            })
+        }
+
+        fn exchange_layout<'l>(&self, layout: &'l MoveTypeLayout) -> Cow<'l, MoveTypeLayout> {
+            Cow::Owned(strip_snapshot_layouts(layout))
+        }
+    }
+
+    #[test]
+    fn test_custom_mapping_factory() {
+        let holder = Holder::new(HashMap::new(), 5);
+        let factory = NoSnapshotExchangeFactory;
+        let latest_view = create_sequential_latest_view(&holder).with_mapping_factory(&factory);
+
+        /*
+            layout = Struct {
+                agg: Aggregator<u64>,
+                snap: AggregatorSnapshot<u128>,
+            }
+        */
+        let storage_layout = MoveTypeLayout::Struct(MoveStructLayout::new(vec![
+            create_aggregator_storage_layout(MoveTypeLayout::U64),
+            create_snapshot_storage_layout(MoveTypeLayout::U128),
+        ]));
+        let layout = MoveTypeLayout::Struct(MoveStructLayout::new(vec![
+            create_aggregator_layout_u64(),
+            create_snapshot_layout(MoveTypeLayout::U128),
+        ]));
+        let value = Value::struct_(Struct::pack(vec![
+            create_aggregator_value_u64(25, 30),
+            create_snapshot_value(Value::u128(20)),
+        ]));
+        let state_value = create_state_value(&value, &storage_layout);
+
+        let (patched_state_value, identifiers) = latest_view
+            .replace_values_with_identifiers(state_value.clone(), &layout)
+            .unwrap();
+        let id = DelayedFieldID::new_with_width(5, 8);
+        assert_eq!(identifiers, HashSet::from([id]));
+        assert_eq!(*holder.counter.borrow(), 6);
+
+        // Only the aggregator is exchanged, the snapshot value passes through unchanged.
+        let patched_value = Value::struct_(Struct::pack(vec![
+            create_aggregator_value_u64(id.as_u64(), 30),
+            create_snapshot_value(Value::u128(20)),
+        ]));
+        assert_eq!(
+            patched_state_value,
+            create_state_value(&patched_value, &storage_layout)
+        );
+        assert_some_eq!(
+            holder.unsync_map.fetch_delayed_field(&id),
+            DelayedFieldValue::Aggregator(25)
+        );
+
+        let (final_bytes, identifiers) = latest_view
+            .replace_identifiers_with_values(patched_state_value.bytes(), &layout)
+            .unwrap();
+        assert_eq!(
+            state_value,
+            rebuild_state_value(&patched_state_value, final_bytes)
+        );
+        assert_eq!(identifiers, HashSet::from([id]));
+
+        // With the default factory, both values are exchanged.
+        let latest_view = create_sequential_latest_view(&holder);
+        let (_, identifiers) = latest_view
+            .replace_values_with_identifiers(state_value, &layout)
+            .unwrap();
+        assert_eq!(identifiers.len(), 2);
+    }
+
+    struct Holder {
+        unsync_map: UnsyncMap<KeyType<u32>, u32, ValueType, MockExecutable, DelayedFieldID>,
+        counter: RefCell<u32>,
+        base_view: MockStateView,
+    }
+
+    impl Holder {
+        fn new(data: HashMap<KeyType<u32>, StateValue>, start_counter: u32) -> Self {
+            let unsync_map = UnsyncMap::new();
+            let counter = RefCell::new(start_counter);
+            let base_view = MockStateView::new(data);
+            Self {
+                unsync_map,
+                counter,
+                base_view,
+            }
+        }
+    }
+
+    fn create_sequential_latest_view<'a>(
+        h: &'a Holder,
+    ) -> LatestView<'a, TestTransactionType, MockStateView, MockExecutable> {
+        let sequential_state: SequentialState<'a, TestTransactionType, MockExecutable> =
+            SequentialState::new(
+                &h.unsync_map,
+                *h.counter.borrow(),
+                &h.counter,
+                false,
+                None,
+                None,
+                None,
+            );
+
+        LatestView::<'a, TestTransactionType, MockStateView, MockExecutable>::new(
+            &h.base_view,
+            ViewState::Unsync(SequentialStateRef::Owned(sequential_state)),
+            1,
+        )
+    }
+
+    struct ComparisonHolder {
+        start_counter: u32,
+        holder: Holder,
+        counter: AtomicU32,
+        base_view: MockStateView,
+        versioned_map: MVHashMap<KeyType<u32>, u32, ValueType, MockExecutable, DelayedFieldID>,
+        scheduler: Scheduler,
+        key_interner: KeyInterner<KeyType<u32>>,
+    }
+
+    impl ComparisonHolder {
+        fn new(data: HashMap<KeyType<u32>, StateValue>, start_counter: u32) -> Self {
+            let holder = Holder::new(data.clone(), start_counter);
+            let counter = AtomicU32::new(start_counter);
+            let base_view = MockStateView::new(data);
+            let versioned_map = MVHashMap::new();
+            let scheduler = Scheduler::new(30);
+            let key_interner = KeyInterner::new();
+
+            Self {
+                start_counter,
+                holder,
+                counter,
+                base_view,
+                versioned_map,
+                scheduler,
+                key_interner,
+            }
         }
 
         fn new_view(&self) -> ViewsComparison<'_> {
@@ -2788,6 +7199,10 @@ mod test {
                         &self.scheduler,
                         self.start_counter,
                         &self.counter,
+                        &self.key_interner,
+                        None,
+                        None,
+                        ParallelStateConfig::default(),
                     )),
                     1,
                 );
@@ -2797,6 +7212,33 @@ mod test {
                 latest_view_par,
             }
         }
+
+        // Asserts that the base value stored for the key is the same (and as expected) in both
+        // the versioned map (of the parallel view) and the unsync map (of the sequential view).
+        fn assert_base_value_eq(
+            &self,
+            state_key: &KeyType<u32>,
+            expected_state_value: Option<StateValue>,
+            expected_maybe_layout: Option<MoveTypeLayout>,
+        ) {
+            let versioned_base_value = match self.versioned_map.data().fetch_data(state_key, 1) {
+                Ok(MVDataOutput::Versioned(Err(StorageVersion), value)) => Some(value),
+                Err(MVDataError::Uninitialized) => None,
+                _ => unreachable!("Only the base value may be stored"),
+            };
+            for fetched in [
+                versioned_base_value,
+                self.holder.unsync_map.fetch_data(state_key),
+            ] {
+                assert_fetch_eq(
+                    fetched,
+                    expected_state_value
+                        .clone()
+                        .map(|state_value| TransactionWrite::from_state_value(Some(state_value))),
+                    expected_maybe_layout.clone(),
+                );
+            }
+        }
     }
 
     struct ViewsComparison<'a> {
@@ -2859,6 +7301,20 @@ mod test {
             self.assert_res_eq(seq, par)
         }
 
+        fn get_resource_state_value_size(
+            &self,
+            state_key: &KeyType<u32>,
+        ) -> PartialVMResult<Option<u64>> {
+            let seq = self
+                .latest_view_seq
+                .get_resource_state_value_size(state_key);
+            let par = self
+                .latest_view_par
+                .get_resource_state_value_size(state_key);
+
+            self.assert_res_eq(seq, par)
+        }
+
         fn get_reads_needing_exchange(
             &self,
             delayed_write_set_ids: &HashSet<DelayedFieldID>,
@@ -2899,6 +7355,28 @@ mod test {
 
             self.assert_res_eq(seq, par)
         }
+
+        // Asserts that both views recorded a value read of the resource, i.e. in the captured
+        // reads of the parallel view (with a layout if expected), and the sequential read set.
+        fn assert_value_read_recorded(&self, state_key: &KeyType<u32>, with_layout: bool) {
+            match &self.latest_view_par.latest_view {
+                ViewState::Sync(state) => assert_matches!(
+                    state
+                        .captured_reads
+                        .borrow()
+                        .get_by_kind(state_key, None, ReadKind::Value),
+                    Some(DataRead::Versioned(Err(StorageVersion), _, layout))
+                        if layout.is_some() == with_layout
+                ),
+                ViewState::Unsync(_) => unreachable!("Parallel view has a sync state"),
+            }
+            match &self.latest_view_seq.latest_view {
+                ViewState::Unsync(state) => {
+                    assert!(state.read_set.borrow().resource_reads.contains(state_key))
+                },
+                ViewState::Sync(_) => unreachable!("Sequential view has an unsync state"),
+            }
+        }
     }
 
     #[test]
@@ -2974,14 +7452,8 @@ mod test {
             Some(state_value.clone())
         );
 
-        assert_fetch_eq(
-            holder
-                .holder
-                .unsync_map
-                .fetch_data(&KeyType::<u32>(1, false)),
-            Some(TransactionWrite::from_state_value(Some(state_value))),
-            None,
-        );
+        views.assert_value_read_recorded(&KeyType::<u32>(1, false), false);
+        holder.assert_base_value_eq(&KeyType::<u32>(1, false), Some(state_value), None);
     }
 
     #[test_case(Some(true))]
@@ -3024,14 +7496,12 @@ mod test {
             .get_reads_needing_exchange(&HashSet::from([id]), &HashSet::new())
             .unwrap()
             .contains_key(&KeyType(1, false)));
-        assert_fetch_eq(
-            holder
-                .holder
-                .unsync_map
-                .fetch_data(&KeyType::<u32>(1, false)),
-            Some(TransactionWrite::from_state_value(Some(
-                patched_state_value,
-            ))),
+        // The pre-read of the metadata or existence is upgraded to a value read, and the base
+        // value is exchanged (and stored with the layout) in both views.
+        views.assert_value_read_recorded(&KeyType::<u32>(1, false), true);
+        holder.assert_base_value_eq(
+            &KeyType::<u32>(1, false),
+            Some(patched_state_value),
             Some(layout),
         );
     }
@@ -3112,11 +7582,17 @@ mod test {
             DelayedFieldValue::Aggregator(25),
         );
 
-        let captured_reads = views.latest_view_par.take_parallel_reads();
+        let captured_reads = views.latest_view_par.take_parallel_reads().unwrap();
         assert!(captured_reads.validate_data_reads(holder.versioned_map.data(), 1));
         // TODO(aggr_v2): what's up with this test case?
-        let _read_set_with_delayed_fields =
-            captured_reads.get_read_values_with_delayed_fields(&HashSet::new(), &HashSet::new());
+        let _read_set_with_delayed_fields = captured_reads.get_read_values_with_delayed_fields(
+            &HashSet::new(),
+            &HashSet::new(),
+            &mut BTreeSet::new(),
+            None,
+            &DelayedFieldLayoutCache::new(),
+            &ViewCounterLabels::new(true, None),
+        );
 
         // TODO[agg_v2](test): This prints
         // read: (KeyType(4, false), Versioned(Err(StorageVersion), Some(Struct(Runtime([Struct(Runtime([Tagged(IdentifierMapping(Aggregator), U64), U64]))])))))
@@ -3129,4 +7605,4136 @@ mod test {
         // let data_read = DataRead::Versioned(Ok((1,0)), Arc::new(TransactionWrite::from_state_value(Some(state_value_4))), Some(Arc::new(layout)));
         // assert!(read_set_with_delayed_fields.any(|x| x == (&KeyType::<u32>(4, false), &data_read)));
     }
+
+    #[test]
+    fn test_sequential_write_journal() {
+        let storage_layout =
+            create_struct_layout(create_aggregator_storage_layout(MoveTypeLayout::U64));
+        let layout = create_struct_layout(create_aggregator_layout_u64());
+        let key = KeyType::<u32>(1, false);
+        let state_value = create_state_value(
+            &create_struct_value(create_aggregator_value_u64(25, 30)),
+            &storage_layout,
+        );
+        let data = HashMap::from([(key, state_value)]);
+
+        let start_counter = 1000;
+        let id = DelayedFieldID::new_with_width(start_counter, 8);
+        let holder = Holder::new(data, start_counter);
+
+        // A prior execution only checks the existence, setting the raw base value.
+        let latest_view = create_sequential_latest_view(&holder);
+        assert_ok_eq!(latest_view.resource_exists(&key), true);
+        assert_matches!(
+            latest_view.take_sequential_write_journal().entries(),
+            [UnsyncWriteJournalEntry::Resource { prior: None, .. }]
+        );
+        let raw_base_value = holder.unsync_map.fetch_data(&key);
+        assert_matches!(raw_base_value, Some(ValueWithLayout::RawFromStorage(_)));
+        let stats = holder.unsync_map.stats();
+
+        // The value read patches the base value, replacing the aggregator with an id.
+        let latest_view = create_sequential_latest_view(&holder);
+        assert_ok!(latest_view.get_resource_state_value(&key, Some(&layout)));
+        assert_matches!(
+            holder.unsync_map.fetch_data(&key),
+            Some(ValueWithLayout::Exchanged(_, Some(_)))
+        );
+        assert_some_eq!(
+            holder.unsync_map.fetch_delayed_field(&id),
+            DelayedFieldValue::Aggregator(25)
+        );
+
+        let journal = latest_view.take_sequential_write_journal();
+        assert_matches!(
+            journal.entries(),
+            [
+                UnsyncWriteJournalEntry::DelayedField { prior: None, .. },
+                UnsyncWriteJournalEntry::Resource { prior: Some(_), .. },
+            ]
+        );
+        // The journal is emptied when taken.
+        assert!(latest_view
+            .take_sequential_write_journal()
+            .entries()
+            .is_empty());
+
+        assert_ok!(journal.restore(&holder.unsync_map));
+        assert_eq!(holder.unsync_map.fetch_data(&key), raw_base_value);
+        assert_none!(holder.unsync_map.fetch_delayed_field(&id));
+        let restored_stats = holder.unsync_map.stats();
+        assert_eq!(restored_stats.num_resources, stats.num_resources);
+        assert_eq!(restored_stats.num_delayed_fields, stats.num_delayed_fields);
+        assert_eq!(
+            restored_stats.base_resources_size,
+            stats.base_resources_size
+        );
+        assert_eq!(
+            restored_stats.base_delayed_fields_size,
+            stats.base_delayed_fields_size
+        );
+    }
+
+    #[test]
+    fn test_group_size_consistency_check() {
+        let holder = ComparisonHolder::new(HashMap::new(), 1000);
+        let counter_labels = ViewCounterLabels::new(false, Some(1));
+        let error_count = || {
+            counters::GROUP_SIZE_CONSISTENCY_ERROR_COUNT
+                .with_label_values(&counter_labels.values())
+                .get()
+        };
+        let initial_error_count = error_count();
+        let group_key = KeyType::<u32>(1, false);
+        let members: Vec<(u32, ValueType)> = (0..4)
+            .map(|i| {
+                (
+                    i,
+                    ValueType::with_len_and_metadata((i as usize + 1) * 10, raw_metadata(1)),
+                )
+            })
+            .collect();
+        holder
+            .versioned_map
+            .group_data()
+            .set_raw_base_values(group_key, members.clone());
+        holder
+            .holder
+            .unsync_map
+            .set_group_base_values(group_key, members);
+
+        // Deleted tag 3 should not be accounted for as a group member.
+        let deletion = ValueType::with_len_and_metadata(0, raw_metadata(1));
+        holder
+            .versioned_map
+            .group_data()
+            .write(group_key, 0, 0, vec![(3, (deletion.clone(), None))]);
+        assert_ok!(holder
+            .holder
+            .unsync_map
+            .insert_group_op(&group_key, 3, deletion, None));
+
+        let parallel_state = ParallelState::<TestTransactionType, MockExecutable>::new(
+            &holder.versioned_map,
+            &holder.scheduler,
+            holder.start_counter,
+            &holder.counter,
+            &holder.key_interner,
+            None,
+            None,
+            ParallelStateConfig {
+                group_size_consistency_checks: true,
+                ..ParallelStateConfig::default()
+            },
+        );
+        let sequential_state = SequentialState::<TestTransactionType, MockExecutable>::new(
+            &holder.holder.unsync_map,
+            holder.start_counter,
+            &holder.holder.counter,
+            true,
+            None,
+            None,
+            None,
+        );
+
+        let group_size = parallel_state
+            .read_group_size(&group_key, 1)
+            .unwrap()
+            .into_size();
+        assert_eq!(
+            sequential_state
+                .read_group_size(&group_key)
+                .unwrap()
+                .into_size(),
+            group_size
+        );
+        let all_tagged_resources_size = match group_size {
+            ResourceGroupSize::Combined {
+                num_tagged_resources,
+                all_tagged_resources_size,
+            } => {
+                assert_eq!(num_tagged_resources, 3);
+                all_tagged_resources_size
+            },
+            ResourceGroupSize::Concrete(_) => unreachable!("Group size must be combined"),
+        };
+
+        let parallel_members = holder
+            .versioned_map
+            .group_data()
+            .fetch_latest_group(&group_key, 1)
+            .unwrap();
+        let sequential_members = || {
+            holder
+                .holder
+                .unsync_map
+                .fetch_group_data(&group_key)
+                .unwrap()
+                .into_iter()
+                .map(|(tag, value)| (*tag, value))
+        };
+        assert_eq!(parallel_members.len(), 3);
+        assert!(report_group_size_inconsistency::<TestTransactionType>(
+            &group_key,
+            group_size,
+            parallel_members.clone(),
+            &counter_labels
+        ));
+        assert!(report_group_size_inconsistency::<TestTransactionType>(
+            &group_key,
+            group_size,
+            sequential_members(),
+            &counter_labels
+        ));
+        // Concrete size is consistent with the size of the serialized group.
+        assert!(report_group_size_inconsistency::<TestTransactionType>(
+            &group_key,
+            ResourceGroupSize::Concrete(group_size.get()),
+            sequential_members(),
+            &counter_labels
+        ));
+
+        let inconsistent_sizes = [
+            ResourceGroupSize::Concrete(group_size.get() + 1),
+            ResourceGroupSize::Combined {
+                num_tagged_resources: 2,
+                all_tagged_resources_size,
+            },
+            ResourceGroupSize::Combined {
+                num_tagged_resources: 3,
+                all_tagged_resources_size: all_tagged_resources_size - 1,
+            },
+            ResourceGroupSize::zero_combined(),
+        ];
+        for size in inconsistent_sizes {
+            assert!(!report_group_size_inconsistency::<TestTransactionType>(
+                &group_key,
+                size,
+                parallel_members.clone(),
+                &counter_labels
+            ));
+            assert!(!report_group_size_inconsistency::<TestTransactionType>(
+                &group_key,
+                size,
+                sequential_members(),
+                &counter_labels
+            ));
+        }
+
+        // An empty group (e.g. with all members deleted) must have size 0.
+        assert!(report_group_size_inconsistency::<TestTransactionType>(
+            &group_key,
+            ResourceGroupSize::zero_combined(),
+            vec![],
+            &counter_labels
+        ));
+        assert!(!report_group_size_inconsistency::<TestTransactionType>(
+            &group_key,
+            ResourceGroupSize::Concrete(1),
+            vec![],
+            &counter_labels
+        ));
+        assert!(error_count() >= initial_error_count + 9);
+    }
+
+    #[test]
+    fn test_resolve_aggregator_v1_for_update() {
+        let key = KeyType::<u32>(1, false);
+        let other_key = KeyType::<u32>(2, false);
+        let aggregator_state_value = |v: u128| StateValue::new_legacy(serialize(&v).into());
+        let data = HashMap::from([
+            (key, aggregator_state_value(100)),
+            (other_key, aggregator_state_value(200)),
+        ]);
+
+        let holder = ComparisonHolder::new(data, 1000);
+        // Transaction 0 has a pending delta at key.
+        holder.versioned_map.data().add_delta(
+            key,
+            0,
+            DeltaOp::new(SignedU128::Positive(5), 1000, DeltaHistory::new()),
+        );
+
+        let views = holder.new_view();
+        assert_ok_eq!(
+            views.latest_view_par.resolve_aggregator_v1_for_update(&key),
+            (105, VersionInfo::Resolved)
+        );
+        assert_ok_eq!(
+            views
+                .latest_view_par
+                .resolve_aggregator_v1_for_update(&other_key),
+            (
+                200,
+                VersionInfo::Versioned(Err(StorageVersion), StateValueMetadata::none())
+            )
+        );
+        assert_ok_eq!(
+            views
+                .latest_view_seq
+                .resolve_aggregator_v1_for_update(&other_key),
+            (200, VersionInfo::Unversioned(StateValueMetadata::none()))
+        );
+
+        // Install the replacement writes of transaction 1: the captured reads must still validate.
+        let captured_reads = views.latest_view_par.take_parallel_reads().unwrap();
+        for (k, v) in [(key, 110), (other_key, 210)] {
+            holder.versioned_map.data().write(
+                k,
+                1,
+                0,
+                Arc::new(TransactionWrite::from_state_value(Some(
+                    aggregator_state_value(v),
+                ))),
+                None,
+            );
+        }
+        assert!(captured_reads.validate_data_reads(holder.versioned_map.data(), 1));
+
+        // Re-execution of transaction 0 with a different delta invalidates the resolved read.
+        holder.versioned_map.data().add_delta(
+            key,
+            0,
+            DeltaOp::new(SignedU128::Positive(6), 1000, DeltaHistory::new()),
+        );
+        assert!(!captured_reads.validate_data_reads(holder.versioned_map.data(), 1));
+    }
+
+    #[test]
+    fn test_lifted_aggregator_v1_values() {
+        let key = KeyType::<u32>(1, false);
+        let aggregator_state_value = StateValue::new_legacy(serialize(&100_u128).into());
+        let data = HashMap::from([(key, aggregator_state_value.clone())]);
+
+        // By default, the aggregator V1 values are read without a layout.
+        let holder = ComparisonHolder::new(data.clone(), 1000);
+        let views = holder.new_view();
+        assert_some_eq!(
+            views
+                .assert_res_eq(
+                    views.latest_view_seq.get_aggregator_v1_state_value(&key),
+                    views.latest_view_par.get_aggregator_v1_state_value(&key),
+                )
+                .unwrap(),
+            aggregator_state_value.clone()
+        );
+        for view in [&views.latest_view_par, &views.latest_view_seq] {
+            assert_ok_eq!(view.exchange_status(&key), ExchangeStatus::NotApplicable);
+        }
+
+        // Lifted values flow through the exchange path, with the same results.
+        let holder = ComparisonHolder::new(data, 1000);
+        let views = holder.new_view();
+        let views = ViewsComparison {
+            latest_view_seq: views.latest_view_seq.with_lifted_aggregator_v1_values(true),
+            latest_view_par: views.latest_view_par.with_lifted_aggregator_v1_values(true),
+        };
+        assert_some_eq!(
+            views
+                .assert_res_eq(
+                    views.latest_view_seq.get_aggregator_v1_state_value(&key),
+                    views.latest_view_par.get_aggregator_v1_state_value(&key),
+                )
+                .unwrap(),
+            aggregator_state_value
+        );
+        for view in [&views.latest_view_par, &views.latest_view_seq] {
+            assert_ok_eq!(
+                view.exchange_status(&key),
+                ExchangeStatus::LayoutHadNoDelayedFields
+            );
+        }
+        assert_ok_eq!(
+            views.latest_view_par.resolve_aggregator_v1_for_update(&key),
+            (
+                100,
+                VersionInfo::Versioned(Err(StorageVersion), StateValueMetadata::none())
+            )
+        );
+    }
+
+    #[test]
+    fn test_lifted_aggregator_v1_values_with_writes_and_deltas() {
+        let storage_key = KeyType::<u32>(1, false);
+        let written_key = KeyType::<u32>(2, false);
+        let delta_key = KeyType::<u32>(3, false);
+        let aggregator_state_value = |v: u128| StateValue::new_legacy(serialize(&v).into());
+        let data = HashMap::from([
+            (storage_key, aggregator_state_value(100)),
+            (written_key, aggregator_state_value(200)),
+            (delta_key, aggregator_state_value(300)),
+        ]);
+
+        let read_values = |lifted: bool| {
+            let holder = ComparisonHolder::new(data.clone(), 1000);
+            // Transaction 0 writes at written_key, and has a pending delta at delta_key.
+            holder.versioned_map.data().write(
+                written_key,
+                0,
+                0,
+                Arc::new(TransactionWrite::from_state_value(Some(
+                    aggregator_state_value(250),
+                ))),
+                None,
+            );
+            holder.versioned_map.data().add_delta(
+                delta_key,
+                0,
+                DeltaOp::new(SignedU128::Positive(5), 1000, DeltaHistory::new()),
+            );
+
+            let views = holder.new_view();
+            let view = views
+                .latest_view_par
+                .with_lifted_aggregator_v1_values(lifted);
+            let values: Vec<_> = [storage_key, written_key, delta_key]
+                .iter()
+                .map(|key| view.get_aggregator_v1_state_value(key).unwrap())
+                .collect();
+
+            // The u128 layout has no delayed fields, so no read ever needs exchange.
+            assert_ok_eq!(
+                view.get_reads_needing_exchange(
+                    &HashSet::from([DelayedFieldID::new_with_width(1000, 8)]),
+                    &HashSet::new()
+                ),
+                BTreeMap::new()
+            );
+            values
+        };
+
+        let expected_values = vec![
+            Some(aggregator_state_value(100)),
+            Some(aggregator_state_value(250)),
+            Some(aggregator_state_value(305)),
+        ];
+        assert_eq!(read_values(false), expected_values);
+        assert_eq!(read_values(true), expected_values);
+    }
+
+    #[test]
+    fn test_group_reads_needing_exchange_propagate_scan_errors() {
+        let group_key = KeyType::<u32>(1, false);
+        let layout = Arc::new(create_aggregator_layout_u64());
+        // Not a valid serialization of an aggregator (identifier) value.
+        let corrupt_value = ValueType::with_len_and_metadata(3, raw_metadata(1));
+        let delayed_write_set_ids = HashSet::from([DelayedFieldID::new_with_width(1000, 8)]);
+
+        let holder = ComparisonHolder::new(HashMap::new(), 1000);
+        holder
+            .holder
+            .unsync_map
+            .set_group_base_values(group_key, vec![]);
+        assert_ok!(holder.holder.unsync_map.insert_group_op(
+            &group_key,
+            1,
+            corrupt_value.clone(),
+            Some(layout.clone()),
+        ));
+
+        let views = holder.new_view();
+        let ViewState::Sync(parallel_state) = &views.latest_view_par.latest_view else {
+            unreachable!("Parallel view must have parallel state");
+        };
+        let ViewState::Unsync(sequential_state) = &views.latest_view_seq.latest_view else {
+            unreachable!("Sequential view must have sequential state");
+        };
+        assert_ok!(parallel_state.captured_reads.borrow_mut().capture_read(
+            group_key,
+            Some(1),
+            DataRead::Versioned(Err(StorageVersion), Arc::new(corrupt_value), Some(layout)),
+        ));
+        sequential_state
+            .read_set
+            .borrow_mut()
+            .group_reads
+            .entry(group_key)
+            .or_default()
+            .insert(1);
+
+        // The failed scans are reported as code invariant errors (instead of panicking the
+        // worker).
+        for view in [&views.latest_view_par, &views.latest_view_seq] {
+            let err = view
+                .get_group_reads_needing_exchange(&delayed_write_set_ids, &HashSet::new())
+                .unwrap_err();
+            assert_eq!(
+                err.major_status(),
+                StatusCode::DELAYED_MATERIALIZATION_CODE_INVARIANT_ERROR
+            );
+        }
+        // Skipped groups are not scanned.
+        for view in [&views.latest_view_par, &views.latest_view_seq] {
+            assert_ok_eq!(
+                view.get_group_reads_needing_exchange(
+                    &delayed_write_set_ids,
+                    &HashSet::from([group_key])
+                ),
+                BTreeMap::new()
+            );
+        }
+    }
+
+    #[test]
+    fn test_group_reads_needing_exchange_for_one_of_three_groups() {
+        let group_keys = [1, 2, 3].map(|k| KeyType::<u32>(k, false));
+        let layout = Arc::new(create_aggregator_layout_u64());
+        let storage_layout = create_aggregator_storage_layout(MoveTypeLayout::U64);
+        let written_id = DelayedFieldID::new_with_width(1000, 8);
+        let aggregator = |id: DelayedFieldID| {
+            let bytes = create_aggregator_value_u64(id.as_u64(), 30)
+                .simple_serialize(&storage_layout)
+                .unwrap();
+            ValueType::new(Some(bytes.into()), raw_metadata(1), WriteOpKind::Creation)
+        };
+        // Only the first group contains the written identifier. The member of the third group
+        // has no layout (hence no delayed fields).
+        let members = [
+            (aggregator(written_id), Some(layout.clone())),
+            (aggregator(DelayedFieldID::new_with_width(1001, 8)), Some(layout)),
+            (ValueType::with_len_and_metadata(10, raw_metadata(1)), None),
+        ];
+
+        let data = group_keys
+            .iter()
+            .map(|key| (*key, StateValue::new_legacy(vec![1].into())))
+            .collect();
+        let holder = ComparisonHolder::new(data, 1000);
+        for (key, (value, maybe_layout)) in group_keys.iter().zip(members.iter()) {
+            holder
+                .versioned_map
+                .group_data()
+                .set_raw_base_values(*key, vec![]);
+            holder.versioned_map.group_data().write(
+                *key,
+                0,
+                0,
+                vec![(1, (value.clone(), maybe_layout.clone()))],
+            );
+            holder.holder.unsync_map.set_group_base_values(*key, vec![]);
+            assert_ok!(holder.holder.unsync_map.insert_group_op(
+                key,
+                1,
+                value.clone(),
+                maybe_layout.clone()
+            ));
+        }
+
+        let views = holder.new_view();
+        let ViewState::Sync(parallel_state) = &views.latest_view_par.latest_view else {
+            unreachable!("Parallel view must have parallel state");
+        };
+        let ViewState::Unsync(sequential_state) = &views.latest_view_seq.latest_view else {
+            unreachable!("Sequential view must have sequential state");
+        };
+        for (key, (value, maybe_layout)) in group_keys.iter().zip(members) {
+            assert_ok!(parallel_state.captured_reads.borrow_mut().capture_read(
+                *key,
+                Some(1),
+                DataRead::Versioned(Ok((0, 0)), Arc::new(value), maybe_layout),
+            ));
+            sequential_state
+                .read_set
+                .borrow_mut()
+                .group_reads
+                .entry(*key)
+                .or_default()
+                .insert(1);
+        }
+
+        let delayed_write_set_ids = HashSet::from([written_id]);
+        let group_reads_needing_exchange = views
+            .assert_res_eq(
+                views
+                    .latest_view_seq
+                    .get_group_reads_needing_exchange(&delayed_write_set_ids, &HashSet::new()),
+                views
+                    .latest_view_par
+                    .get_group_reads_needing_exchange(&delayed_write_set_ids, &HashSet::new()),
+            )
+            .unwrap();
+        assert_eq!(
+            group_reads_needing_exchange.into_keys().collect::<Vec<_>>(),
+            vec![group_keys[0]]
+        );
+        // The group reads remain captured.
+        assert_eq!(
+            parallel_state
+                .captured_reads
+                .borrow()
+                .read_summary()
+                .num_group_reads,
+            3
+        );
+    }
+
+    // Serves a fixed read result, irrespective of the requested kind.
+    struct MockResourceState(RefCell<Option<ReadResult>>);
+
+    impl ResourceState<TestTransactionType> for MockResourceState {
+        fn set_base_value(&self, _key: KeyType<u32>, _value: ValueWithLayout<ValueType>) {}
+
+        fn has_base_value(&self, _key: &KeyType<u32>) -> bool {
+            false
+        }
+
+        fn has_exchanged_base_value_with_layout(&self, _key: &KeyType<u32>) -> bool {
+            false
+        }
+
+        fn read_cached_data_by_kind(
+            &self,
+            _txn_idx: TxnIndex,
+            _key: &KeyType<u32>,
+            _target_kind: ReadKind,
+            _layout: UnknownOrLayout,
+            _patch_base_value: &dyn Fn(
+                &ValueType,
+                Option<&MoveTypeLayout>,
+            ) -> PartialVMResult<ValueType>,
+        ) -> Result<ReadResult, ViewError> {
+            Ok(self.0.borrow_mut().take().unwrap())
+        }
+    }
+
+    #[test]
+    fn test_read_result_accessors() {
+        let state_value = create_state_value(&Value::u64(12321), &MoveTypeLayout::U64);
+        let value = || ReadResult::Value(Some(state_value.clone()), None);
+        assert_some_eq!(value().try_into_value(), Some(state_value.clone()));
+        assert_none!(value().into_metadata());
+        assert!(!value().into_exists());
+        assert_none!(value().as_halt_message());
+
+        assert_some_eq!(
+            ReadResult::Metadata(Some(state_value.clone().into_metadata())).into_metadata(),
+            Some(state_value.clone().into_metadata())
+        );
+        assert_some_eq!(ReadResult::Metadata(None).into_metadata(), None);
+        assert!(ReadResult::Exists(true).into_exists());
+        assert!(!ReadResult::Exists(false).into_exists());
+        assert_none!(ReadResult::Exists(true).try_into_value());
+
+        let halt = ReadResult::HaltSpeculativeExecution("halted".to_string());
+        assert_some_eq!(halt.as_halt_message(), "halted");
+        assert_none!(halt.try_into_value());
+    }
+
+    #[test]
+    fn test_public_read_resource() {
+        let key = KeyType::<u32>(1, false);
+        let estimate_key = KeyType::<u32>(2, false);
+        let state_value = create_state_value(&Value::u64(12321), &MoveTypeLayout::U64);
+        let holder = ComparisonHolder::new(HashMap::from([(key, state_value.clone())]), 1000);
+
+        // Txn 0 is executed, but its write is an estimate, hence reading it halts txn 1.
+        assert_matches!(
+            holder.scheduler.next_task(),
+            SchedulerTask::ExecutionTask(0, 0, _)
+        );
+        assert_ok!(holder.scheduler.finish_execution(0, 0, false));
+        let data = holder.versioned_map.data();
+        data.write(
+            estimate_key,
+            0,
+            0,
+            Arc::new(ValueType::with_len_and_metadata(10, raw_metadata(1))),
+            None,
+        );
+        data.mark_estimate(&estimate_key, 0);
+
+        let latest_view = LatestView::<TestTransactionType, MockStateView, MockExecutable>::new(
+            &holder.base_view,
+            ViewState::Sync(ParallelState::new(
+                &holder.versioned_map,
+                &holder.scheduler,
+                holder.start_counter,
+                &holder.counter,
+                &holder.key_interner,
+                None,
+                None,
+                ParallelStateConfig {
+                    dependency_wait_retry_limit: Some(0),
+                    ..ParallelStateConfig::default()
+                },
+            )),
+            1,
+        );
+
+        let read = latest_view
+            .read_resource(&key, Some(&MoveTypeLayout::U64), ReadKind::Value)
+            .unwrap();
+        assert_some_eq!(read.try_into_value(), Some(state_value));
+        assert!(latest_view
+            .read_resource(&key, None, ReadKind::Exists)
+            .unwrap()
+            .into_exists());
+        assert_eq!(
+            latest_view.get_read_summary(),
+            HashSet::from([InputOutputKey::Resource(key)])
+        );
+
+        let read = latest_view
+            .read_resource(&estimate_key, None, ReadKind::Value)
+            .unwrap();
+        assert_some!(read.as_halt_message());
+    }
+
+    #[test]
+    fn test_read_result_kind_mismatch() {
+        let key = KeyType::<u32>(1, false);
+        let read = |state: &MockResourceState, result: ReadResult, kind: ReadKind| {
+            *state.0.borrow_mut() = Some(result);
+            state
+                .read_cached_data_by_kind(1, &key, kind, UnknownOrLayout::Unknown, &|v, _| {
+                    Ok(v.clone())
+                })
+                .unwrap()
+        };
+        let state = MockResourceState(RefCell::new(None));
+
+        let err = read(&state, ReadResult::Exists(true), ReadKind::Value)
+            .into_value()
+            .unwrap_err();
+        assert!(err.to_string().contains("Read result must be Value kind"));
+        let err = read(&state, ReadResult::Value(None, None), ReadKind::Metadata)
+            .into_metadata_checked()
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Read result must be Metadata kind"));
+        let err = read(&state, ReadResult::Metadata(None), ReadKind::Exists)
+            .into_exists_checked()
+            .unwrap_err();
+        assert!(err.to_string().contains("Read result must be Exists kind"));
+
+        assert_ok_eq!(
+            read(&state, ReadResult::Exists(false), ReadKind::Exists).into_exists_checked(),
+            false
+        );
+        assert_ok_eq!(
+            read(&state, ReadResult::Metadata(None), ReadKind::Metadata).into_metadata_checked(),
+            None
+        );
+
+        // The mismatch halts the execution, and the use is marked as incorrect.
+        let holder = ComparisonHolder::new(HashMap::new(), 1000);
+        let views = holder.new_view();
+        for view in [&views.latest_view_seq, &views.latest_view_par] {
+            let err = read(&state, ReadResult::Exists(true), ReadKind::Metadata)
+                .into_metadata_checked()
+                .map_err(|err| view.read_kind_mismatch_error(err))
+                .unwrap_err();
+            assert_eq!(
+                err.major_status(),
+                StatusCode::SPECULATIVE_EXECUTION_ABORT_ERROR
+            );
+            assert!(view.is_incorrect_use());
+        }
+    }
+
+    #[test]
+    fn test_unresolved_aggregator_v1_read() {
+        let key = KeyType::<u32>(1, false);
+        let missing_key = KeyType::<u32>(2, false);
+        let aggregator_state_value = |v: u128| StateValue::new_legacy(serialize(&v).into());
+        let data = HashMap::from([(key, aggregator_state_value(100))]);
+
+        let holder = ComparisonHolder::new(data, 1000);
+        // Transaction 0 has pending deltas, and no base values are recorded.
+        for k in [key, missing_key] {
+            holder.versioned_map.data().add_delta(
+                k,
+                0,
+                DeltaOp::new(SignedU128::Positive(5), 1000, DeltaHistory::new()),
+            );
+        }
+
+        let views = holder.new_view();
+        let read_cached = |k: &KeyType<u32>| {
+            views
+                .latest_view_par
+                .latest_view
+                .get_resource_state()
+                .read_cached_data_by_kind(
+                    1,
+                    k,
+                    ReadKind::Value,
+                    UnknownOrLayout::Known(None),
+                    &|value, _| Ok(value.clone()),
+                )
+        };
+        assert_matches!(read_cached(&key), Ok(ReadResult::Unresolved));
+        assert_matches!(read_cached(&missing_key), Ok(ReadResult::Unresolved));
+        assert_matches!(
+            read_cached(&KeyType::<u32>(3, false)),
+            Ok(ReadResult::Uninitialized)
+        );
+
+        let unresolved_count = || {
+            counters::AGGREGATOR_V1_UNRESOLVED_READ_COUNT
+                .with_label_values(&ViewCounterLabels::new(true, None).values())
+                .get()
+        };
+        let initial_unresolved_count = unresolved_count();
+        assert_ok_eq!(
+            views.latest_view_par.get_resource_state_value(&key, None),
+            Some(aggregator_state_value(105))
+        );
+        // The base value from storage is set as exchanged, with no layout.
+        assert_matches!(
+            holder.versioned_map.data().fetch_data(&key, 0),
+            Ok(MVDataOutput::Versioned(
+                Err(StorageVersion),
+                ValueWithLayout::Exchanged(_, None)
+            ))
+        );
+
+        // The deltas are not applied to a missing base value (deletion).
+        assert_ok_eq!(
+            views
+                .latest_view_par
+                .get_resource_state_value(&missing_key, None),
+            None
+        );
+        assert!(unresolved_count() >= initial_unresolved_count + 2);
+        assert!(!views.latest_view_par.is_incorrect_use());
+    }
+
+    #[test]
+    fn test_resource_exists_in_group() {
+        let group_key = KeyType::<u32>(1, false);
+        let storage_layout =
+            create_struct_layout(create_aggregator_storage_layout(MoveTypeLayout::U64));
+        let layout = create_struct_layout(create_aggregator_layout_u64());
+        let aggregator_value = create_state_value(
+            &create_struct_value(create_aggregator_value_u64(25, 30)),
+            &storage_layout,
+        );
+        let group: BTreeMap<u32, Bytes> = BTreeMap::from([(1, aggregator_value.bytes().clone())]);
+        let data = HashMap::from([(
+            group_key,
+            StateValue::new_legacy(bcs::to_bytes(&group).unwrap().into()),
+        )]);
+
+        let holder = ComparisonHolder::new(data, 1000);
+        let views = holder.new_view();
+        for view in [&views.latest_view_par, &views.latest_view_seq] {
+            assert_ok_eq!(view.resource_exists_in_group(&group_key, &1), true);
+            assert_ok_eq!(view.resource_exists_in_group(&group_key, &2), false);
+        }
+        assert_eq!(
+            views.latest_view_par.get_read_summary(),
+            views.latest_view_seq.get_read_summary()
+        );
+
+        // Existence is determined without patching the base value.
+        assert_matches!(
+            holder
+                .versioned_map
+                .group_data()
+                .fetch_tagged_data(&group_key, &1, 1),
+            Ok((Err(StorageVersion), ValueWithLayout::RawFromStorage(_)))
+        );
+        assert_matches!(
+            holder
+                .holder
+                .unsync_map
+                .fetch_group_tagged_data(&group_key, &1),
+            Ok(ValueWithLayout::RawFromStorage(_))
+        );
+
+        // The value of an existing member may still be read (with the layout).
+        let id = DelayedFieldID::new_with_width(1000, 8);
+        let patched_aggregator_value = create_state_value(
+            &create_struct_value(create_aggregator_value_u64(id.as_u64(), 30)),
+            &storage_layout,
+        );
+        for view in [&views.latest_view_par, &views.latest_view_seq] {
+            assert_ok_eq!(
+                view.get_resource_from_group(&group_key, &1, Some(&layout)),
+                Some(patched_aggregator_value.bytes().clone())
+            );
+        }
+
+        let captured_reads = views.latest_view_par.take_parallel_reads().unwrap();
+        assert!(captured_reads.validate_group_reads(holder.versioned_map.group_data(), 1));
+
+        // Creating the member that was not found invalidates the (sentinel) existence read.
+        holder
+            .versioned_map
+            .group_data()
+            .write(group_key, 0, 0, vec![(
+                2,
+                (
+                    TransactionWrite::from_state_value(Some(create_state_value(
+                        &Value::u64(5),
+                        &MoveTypeLayout::U64,
+                    ))),
+                    None,
+                ),
+            )]);
+        assert!(!captured_reads.validate_group_reads(holder.versioned_map.group_data(), 1));
+    }
+
+    #[test]
+    fn test_group_probes_generate_no_identifiers() {
+        let group_key = KeyType::<u32>(1, false);
+        let storage_layout =
+            create_struct_layout(create_aggregator_storage_layout(MoveTypeLayout::U64));
+        let layout = create_struct_layout(create_aggregator_layout_u64());
+        let group: BTreeMap<u32, Bytes> = (1..=3)
+            .map(|tag| {
+                let aggregator_value = create_state_value(
+                    &create_struct_value(create_aggregator_value_u64(25 * tag as u64, 100)),
+                    &storage_layout,
+                );
+                (tag, aggregator_value.bytes().clone())
+            })
+            .collect();
+        let data = HashMap::from([(
+            group_key,
+            StateValue::new_legacy(bcs::to_bytes(&group).unwrap().into()),
+        )]);
+
+        let holder = ComparisonHolder::new(data, 1000);
+        let views = holder.new_view();
+        for view in [&views.latest_view_par, &views.latest_view_seq] {
+            for tag in 1..=3 {
+                assert_ok_eq!(view.resource_exists_in_group(&group_key, &tag), true);
+            }
+            // Without a recorded layout of the tag, the size is read without exchange.
+            assert_ok_eq!(view.resource_size_in_group(&group_key, &1), group[&1].len());
+        }
+
+        // The existence and size reads did not exchange the aggregators with identifiers.
+        assert_eq!(holder.counter.load(Ordering::SeqCst), 1000);
+        assert_eq!(*holder.holder.counter.borrow(), 1000);
+        for view in [&views.latest_view_par, &views.latest_view_seq] {
+            assert!(view.captured_delayed_field_ids().is_empty());
+        }
+
+        // A value read with the layout of a member that was not read before does.
+        for view in [&views.latest_view_par, &views.latest_view_seq] {
+            assert_ok!(view.get_resource_from_group(&group_key, &2, Some(&layout)));
+        }
+        assert_eq!(holder.counter.load(Ordering::SeqCst), 1001);
+        assert_eq!(*holder.holder.counter.borrow(), 1001);
+    }
+
+    #[test]
+    fn test_resource_size_and_exists_in_group_validation() {
+        let group_key = KeyType::<u32>(1, false);
+        let value_1 = create_state_value(&Value::u64(5), &MoveTypeLayout::U64);
+        let value_2 = create_state_value(&Value::u128(7), &MoveTypeLayout::U128);
+        let group: BTreeMap<u32, Bytes> =
+            BTreeMap::from([(1, value_1.bytes().clone()), (2, value_2.bytes().clone())]);
+        let data = HashMap::from([(
+            group_key,
+            StateValue::new_legacy(bcs::to_bytes(&group).unwrap().into()),
+        )]);
+
+        let holder = ComparisonHolder::new(data, 1000);
+        let views = holder.new_view();
+        for view in [&views.latest_view_par, &views.latest_view_seq] {
+            assert_ok_eq!(view.resource_exists_in_group(&group_key, &1), true);
+            assert_ok_eq!(
+                view.resource_size_in_group(&group_key, &2),
+                value_2.bytes().len()
+            );
+            assert_ok_eq!(view.resource_size_in_group(&group_key, &3), 0);
+        }
+        assert_eq!(
+            views.latest_view_par.get_read_summary(),
+            views.latest_view_seq.get_read_summary()
+        );
+
+        let captured_reads = views.latest_view_par.take_parallel_reads().unwrap();
+        assert!(captured_reads.validate_group_reads(holder.versioned_map.group_data(), 1));
+
+        // A lower transaction deleting the member invalidates the existence read.
+        holder
+            .versioned_map
+            .group_data()
+            .write(group_key, 0, 0, vec![(
+                1,
+                (TransactionWrite::from_state_value(None), None),
+            )]);
+        assert!(!captured_reads.validate_group_reads(holder.versioned_map.group_data(), 1));
+    }
+
+    #[test]
+    fn test_group_size_validation() {
+        let group_key = KeyType::<u32>(1, false);
+        let value_1 = create_state_value(&Value::u64(5), &MoveTypeLayout::U64);
+        let value_2 = create_state_value(&Value::u128(7), &MoveTypeLayout::U128);
+        let group: BTreeMap<u32, Bytes> =
+            BTreeMap::from([(1, value_1.bytes().clone()), (2, value_2.bytes().clone())]);
+        let data = HashMap::from([(
+            group_key,
+            StateValue::new_legacy(bcs::to_bytes(&group).unwrap().into()),
+        )]);
+
+        let holder = ComparisonHolder::new(data, 1000);
+        let views = holder.new_view();
+        let group_size = assert_ok!(views.latest_view_par.resource_group_size(&group_key));
+        assert_ok_eq!(
+            views.latest_view_seq.resource_group_size(&group_key),
+            group_size
+        );
+
+        // Only the size of the group was read.
+        let captured_reads = views.latest_view_par.take_parallel_reads().unwrap();
+        assert_some_eq!(captured_reads.group_size(&group_key), group_size);
+        assert!(captured_reads.validate_group_reads(holder.versioned_map.group_data(), 1));
+
+        // A lower transaction inserting a member invalidates the size read.
+        holder
+            .versioned_map
+            .group_data()
+            .write(group_key, 0, 0, vec![(
+                3,
+                (
+                    TransactionWrite::from_state_value(Some(value_1.clone())),
+                    None,
+                ),
+            )]);
+        assert!(!captured_reads.validate_group_reads(holder.versioned_map.group_data(), 1));
+    }
+
+    #[test]
+    fn test_group_metadata_change_validation() {
+        let group_key = KeyType::<u32>(1, false);
+        let value_1 = create_state_value(&Value::u64(5), &MoveTypeLayout::U64);
+        let value_2 = create_state_value(&Value::u128(7), &MoveTypeLayout::U128);
+        let group: BTreeMap<u32, Bytes> =
+            BTreeMap::from([(1, value_1.bytes().clone()), (2, value_2.bytes().clone())]);
+        let data = HashMap::from([(
+            group_key,
+            StateValue::new_legacy(bcs::to_bytes(&group).unwrap().into()),
+        )]);
+
+        let holder = ComparisonHolder::new(data, 1000);
+        // Transaction 1 reads only the values of the members.
+        let member_views = holder.new_view();
+        assert_ok_eq!(
+            member_views
+                .latest_view_par
+                .get_resource_from_group(&group_key, &1, None),
+            Some(value_1.bytes().clone())
+        );
+        assert_ok_eq!(
+            member_views
+                .latest_view_par
+                .get_resource_from_group(&group_key, &3, None),
+            None
+        );
+        let member_reads = member_views.latest_view_par.take_parallel_reads().unwrap();
+        // The group metadata is not captured by the member reads.
+        assert!(!member_reads.is_resource_read(&group_key));
+
+        // Another incarnation of transaction 1 reads the metadata of the group.
+        let metadata_views = holder.new_view();
+        let metadata = assert_ok!(metadata_views
+            .latest_view_par
+            .get_resource_state_value_metadata(&group_key));
+        let metadata_reads = metadata_views
+            .latest_view_par
+            .take_parallel_reads()
+            .unwrap();
+        assert_some_eq!(
+            metadata_reads.get_by_kind(&group_key, None, ReadKind::Metadata),
+            DataRead::Metadata(metadata.clone())
+        );
+        assert_none!(metadata_reads.get_by_kind(&group_key, None, ReadKind::Value));
+
+        // A lower transaction rewrites only the metadata of the group.
+        holder.versioned_map.data().write(
+            group_key,
+            0,
+            0,
+            Arc::new(ValueType::with_len_and_metadata(1, raw_metadata(2))),
+            None,
+        );
+        holder
+            .versioned_map
+            .group_data()
+            .write(group_key, 0, 0, vec![]);
+        assert_ne!(metadata, Some(raw_metadata(2)));
+
+        let data_map = holder.versioned_map.data();
+        let group_map = holder.versioned_map.group_data();
+        assert!(member_reads.validate_data_reads(data_map, 1));
+        assert!(member_reads.validate_group_reads(group_map, 1));
+        assert!(member_reads.validate_group_metadata_reads(data_map, [&group_key], 1));
+
+        assert!(!metadata_reads.validate_data_reads(data_map, 1));
+        assert!(!metadata_reads.validate_group_metadata_reads(data_map, [&group_key], 1));
+    }
+
+    #[test]
+    fn test_resource_from_group_with_group_size() {
+        let group_key = KeyType::<u32>(1, false);
+        let value_1 = create_state_value(&Value::u64(5), &MoveTypeLayout::U64);
+        let value_2 = create_state_value(&Value::u128(7), &MoveTypeLayout::U128);
+        let group: BTreeMap<u32, Bytes> =
+            BTreeMap::from([(1, value_1.bytes().clone()), (2, value_2.bytes().clone())]);
+        let expected_size =
+            group_size_as_sum(group.iter().map(|(tag, bytes)| (tag, bytes.len()))).unwrap();
+        let data = HashMap::from([(
+            group_key,
+            StateValue::new_legacy(bcs::to_bytes(&group).unwrap().into()),
+        )]);
+
+        let holder = ComparisonHolder::new(data, 1000);
+        let views = holder.new_view();
+        for view in [&views.latest_view_par, &views.latest_view_seq] {
+            assert_ok_eq!(
+                view.get_resource_from_group_with_group_size(&group_key, &2, None),
+                (Some(value_2.bytes().clone()), expected_size)
+            );
+            assert_ok_eq!(
+                view.get_resource_from_group_with_group_size(&group_key, &3, None),
+                (None, expected_size)
+            );
+        }
+        assert_eq!(
+            views.latest_view_par.get_read_summary(),
+            views.latest_view_seq.get_read_summary()
+        );
+
+        // Both the group size and the member reads are captured.
+        let captured_reads = views.latest_view_par.take_parallel_reads().unwrap();
+        assert_some_eq!(captured_reads.group_size(&group_key), expected_size);
+        assert_some!(captured_reads.get_by_kind(&group_key, Some(&2), ReadKind::Value));
+        assert_some!(captured_reads.get_by_kind(&group_key, Some(&3), ReadKind::Value));
+        assert!(captured_reads.validate_group_reads(holder.versioned_map.group_data(), 1));
+    }
+
+    #[test]
+    fn test_resources_from_group() {
+        let group_key = KeyType::<u32>(1, false);
+        let value_1 = create_state_value(&Value::u64(5), &MoveTypeLayout::U64);
+        let value_2 = create_state_value(&Value::u128(7), &MoveTypeLayout::U128);
+        let group: BTreeMap<u32, Bytes> =
+            BTreeMap::from([(1, value_1.bytes().clone()), (2, value_2.bytes().clone())]);
+        let data = HashMap::from([(
+            group_key,
+            StateValue::new_legacy(bcs::to_bytes(&group).unwrap().into()),
+        )]);
+
+        let holder = ComparisonHolder::new(data, 1000);
+        let views = holder.new_view();
+        for view in [&views.latest_view_par, &views.latest_view_seq] {
+            // Results are in the order of the tags.
+            assert_ok_eq!(
+                view.get_resources_from_group(&group_key, &[2, 3, 1], &[None, None, None]),
+                vec![
+                    Some(value_2.bytes().clone()),
+                    None,
+                    Some(value_1.bytes().clone())
+                ]
+            );
+            assert!(view
+                .get_resources_from_group(&group_key, &[1, 2], &[None])
+                .is_err());
+        }
+        assert_eq!(
+            views.latest_view_par.get_read_summary(),
+            views.latest_view_seq.get_read_summary()
+        );
+
+        // Plain resource reads are not included in the group keys.
+        assert_ok_eq!(
+            views.get_resource_state_value(&KeyType::<u32>(2, false), None),
+            None
+        );
+        for view in [&views.latest_view_par, &views.latest_view_seq] {
+            assert_eq!(view.group_keys_read(), HashSet::from([group_key]));
+        }
+
+        // Each member read is captured.
+        let captured_reads = views.latest_view_par.take_parallel_reads().unwrap();
+        for tag in [1, 2, 3] {
+            assert_some!(captured_reads.get_by_kind(&group_key, Some(&tag), ReadKind::Value));
+        }
+        assert!(captured_reads.validate_group_reads(holder.versioned_map.group_data(), 1));
+    }
+
+    #[test]
+    fn test_group_tags() {
+        let group_key = KeyType::<u32>(1, false);
+        let value_1 = create_state_value(&Value::u64(5), &MoveTypeLayout::U64);
+        let value_2 = create_state_value(&Value::u128(7), &MoveTypeLayout::U128);
+        let group: BTreeMap<u32, Bytes> =
+            BTreeMap::from([(2, value_2.bytes().clone()), (1, value_1.bytes().clone())]);
+        let data = HashMap::from([(
+            group_key,
+            StateValue::new_legacy(bcs::to_bytes(&group).unwrap().into()),
+        )]);
+
+        let holder = ComparisonHolder::new(data, 1000);
+        let views = holder.new_view();
+        for view in [&views.latest_view_par, &views.latest_view_seq] {
+            // Group contents are initialized, even though the size does not require them.
+            assert_ok_eq!(view.group_tags(&group_key), vec![1, 2]);
+            assert_ok_eq!(view.group_tags(&KeyType::<u32>(2, false)), vec![]);
+        }
+
+        // The tags are validated by the captured group size.
+        let captured_reads = views.latest_view_par.take_parallel_reads().unwrap();
+        assert_some!(captured_reads.group_size(&group_key));
+        assert!(captured_reads.validate_group_reads(holder.versioned_map.group_data(), 1));
+
+        holder
+            .versioned_map
+            .group_data()
+            .write(group_key, 0, 0, vec![
+                (1, (TransactionWrite::from_state_value(None), None)),
+                (
+                    3,
+                    (
+                        TransactionWrite::from_state_value(Some(value_1.clone())),
+                        None,
+                    ),
+                ),
+            ]);
+        assert!(!captured_reads.validate_group_reads(holder.versioned_map.group_data(), 1));
+
+        // Speculative writes of lower transactions are reflected.
+        let views = holder.new_view();
+        assert_ok_eq!(views.latest_view_par.group_tags(&group_key), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_sequential_group_size_cache() {
+        let group_key = KeyType::<u32>(1, false);
+        let value_1 = create_state_value(&Value::u64(5), &MoveTypeLayout::U64);
+        let group: BTreeMap<u32, Bytes> = BTreeMap::from([(1, value_1.bytes().clone())]);
+        let data = HashMap::from([(
+            group_key,
+            StateValue::new_legacy(bcs::to_bytes(&group).unwrap().into()),
+        )]);
+
+        let holder = Holder::new(data, 1000);
+        let latest_view = create_sequential_latest_view(&holder);
+        let ViewState::Unsync(state) = &latest_view.latest_view else {
+            unreachable!("Sequential view must have sequential state");
+        };
+        // The base value is updated below, which requires the group contents.
+        assert_ok!(latest_view.initialize_mvhashmap_base_group_contents(&group_key));
+
+        let group_size = latest_view.resource_group_size(&group_key).unwrap();
+        assert_eq!(*state.num_group_size_computations.borrow(), 1);
+        assert_ok_eq!(latest_view.resource_group_size(&group_key), group_size);
+        assert_eq!(*state.num_group_size_computations.borrow(), 1);
+
+        // Updating the base value invalidates the cached size.
+        let value_2 = create_state_value(&Value::u128(5), &MoveTypeLayout::U128);
+        state.update_tagged_base_value_with_layout(
+            group_key,
+            1,
+            TransactionWrite::from_state_value(Some(value_2.clone())),
+            None,
+        );
+        let expected_size =
+            group_size_as_sum(std::iter::once((&1_u32, value_2.bytes().len()))).unwrap();
+        assert_ok_eq!(latest_view.resource_group_size(&group_key), expected_size);
+        assert_ne!(expected_size, group_size);
+        assert_eq!(*state.num_group_size_computations.borrow(), 2);
+        assert_ok_eq!(latest_view.resource_group_size(&group_key), expected_size);
+        assert_eq!(*state.num_group_size_computations.borrow(), 2);
+    }
+
+    #[test]
+    fn test_resource_group_size_without_contents() {
+        let group_key = KeyType::<u32>(1, false);
+        let value_1 = create_state_value(&Value::u64(5), &MoveTypeLayout::U64);
+        let value_2 = create_state_value(&Value::u128(7), &MoveTypeLayout::U128);
+        let group: BTreeMap<u32, Bytes> =
+            BTreeMap::from([(1, value_1.bytes().clone()), (2, value_2.bytes().clone())]);
+        let expected_size =
+            group_size_as_sum(group.iter().map(|(tag, bytes)| (tag, bytes.len()))).unwrap();
+        let data = HashMap::from([(
+            group_key,
+            StateValue::new_legacy(bcs::to_bytes(&group).unwrap().into()),
+        )]);
+
+        let holder = ComparisonHolder::new(data, 1000);
+        let views = holder.new_view();
+        for view in [&views.latest_view_par, &views.latest_view_seq] {
+            assert_ok_eq!(view.resource_group_size(&group_key), expected_size);
+            assert_ok_eq!(
+                view.resource_group_size(&KeyType::<u32>(2, false)),
+                ResourceGroupSize::zero_combined()
+            );
+        }
+        // The group contents are not initialized.
+        assert_matches!(
+            holder
+                .versioned_map
+                .group_data()
+                .fetch_tagged_data(&group_key, &1, 1),
+            Err(MVGroupError::Uninitialized)
+        );
+        assert_none!(holder.holder.unsync_map.fetch_group_data(&group_key));
+
+        // The size read is captured, and validates until a lower transaction writes.
+        let captured_reads = views.latest_view_par.take_parallel_reads().unwrap();
+        assert_some_eq!(captured_reads.group_size(&group_key), expected_size);
+        assert!(captured_reads.validate_group_reads(holder.versioned_map.group_data(), 1));
+
+        holder
+            .versioned_map
+            .group_data()
+            .write(group_key, 0, 0, vec![(
+                1,
+                (
+                    TransactionWrite::from_state_value(Some(value_2.clone())),
+                    None,
+                ),
+            )]);
+        assert!(!captured_reads.validate_group_reads(holder.versioned_map.group_data(), 1));
+
+        // The size above the write is read from the initialized contents.
+        let expected_size = group_size_as_sum(
+            [(&1_u32, value_2.bytes().len()), (&2, value_2.bytes().len())].into_iter(),
+        )
+        .unwrap();
+        let views = holder.new_view();
+        assert_ok_eq!(
+            views.latest_view_par.resource_group_size(&group_key),
+            expected_size
+        );
+        assert_some!(holder
+            .versioned_map
+            .group_data()
+            .fetch_tagged_data(&group_key, &2, 1)
+            .ok());
+    }
+
+    #[test]
+    fn test_view_error_conversion() {
+        for err in [
+            ViewError::SpeculativeAbort(
+                SpeculativeHaltReason::ExecutionHalted,
+                "halted".to_string(),
+            ),
+            ViewError::SpeculativeAbort(
+                SpeculativeHaltReason::DependencyTimeout,
+                "timeout".to_string(),
+            ),
+        ] {
+            assert_eq!(
+                err.status_code(),
+                StatusCode::SPECULATIVE_EXECUTION_ABORT_ERROR
+            );
+            assert_eq!(
+                PartialVMError::from(err).major_status(),
+                StatusCode::SPECULATIVE_EXECUTION_ABORT_ERROR
+            );
+        }
+
+        let err = PartialVMError::from(ViewError::StorageError("storage".to_string()));
+        assert_eq!(err.major_status(), StatusCode::STORAGE_ERROR);
+        assert_eq!(err.message(), Some("storage"));
+
+        // Errors that are not due to speculation are passed through.
+        let serialization_error =
+            PartialVMError::new(StatusCode::VALUE_SERIALIZATION_ERROR).with_message("tag".into());
+        let err: ViewError = serialization_error.clone().into();
+        assert_eq!(err.status_code(), StatusCode::VALUE_SERIALIZATION_ERROR);
+        assert_eq!(PartialVMError::from(err), serialization_error);
+
+        let err: ViewError = code_invariant_error("invariant").into();
+        assert_matches!(err, ViewError::CodeInvariant(_));
+        assert_eq!(
+            err.status_code(),
+            StatusCode::DELAYED_MATERIALIZATION_CODE_INVARIANT_ERROR
+        );
+        assert_eq!(
+            PartialVMError::from(err).major_status(),
+            StatusCode::DELAYED_MATERIALIZATION_CODE_INVARIANT_ERROR
+        );
+    }
+
+    #[test]
+    fn test_release_group_cache() {
+        let storage_layout =
+            create_struct_layout(create_aggregator_storage_layout(MoveTypeLayout::U64));
+        let layout = create_struct_layout(create_aggregator_layout_u64());
+        let aggregator_value = create_state_value(
+            &create_struct_value(create_aggregator_value_u64(25, 30)),
+            &storage_layout,
+        );
+
+        let group_keys = [KeyType::<u32>(1, false), KeyType::<u32>(2, false)];
+        let groups: Vec<BTreeMap<u32, Bytes>> = group_keys
+            .iter()
+            .map(|group_key| {
+                (0..3_u32)
+                    .map(|tag| {
+                        let bytes = if tag == 0 {
+                            aggregator_value.bytes().clone()
+                        } else {
+                            create_state_value(
+                                &Value::u64((group_key.0 * 10 + tag) as u64),
+                                &MoveTypeLayout::U64,
+                            )
+                            .bytes()
+                            .clone()
+                        };
+                        (tag, bytes)
+                    })
+                    .collect()
+            })
+            .collect();
+        let data = group_keys
+            .iter()
+            .zip(groups.iter())
+            .map(|(group_key, group)| {
+                (
+                    *group_key,
+                    StateValue::new_legacy(bcs::to_bytes(group).unwrap().into()),
+                )
+            })
+            .collect();
+
+        let holder = ComparisonHolder::new(data, 1000);
+        let views = holder.new_view();
+        for group_key in &group_keys {
+            assert_ok!(views
+                .latest_view_seq
+                .initialize_mvhashmap_base_group_contents(group_key));
+            assert_ok!(views
+                .latest_view_par
+                .initialize_mvhashmap_base_group_contents(group_key));
+        }
+        // The member with a delayed field is exchanged, but released with the value.
+        assert_some!(views
+            .latest_view_seq
+            .get_resource_from_group(&group_keys[0], &0, Some(&layout))
+            .unwrap());
+
+        let expected: HashMap<KeyType<u32>, BTreeMap<u32, Bytes>> =
+            group_keys.into_iter().zip(groups).collect();
+        assert_some_eq!(views.latest_view_seq.release_group_cache(), expected);
+        assert_none!(views.latest_view_par.release_group_cache());
+    }
+
+    #[test]
+    fn test_layout_mismatch_telemetry() {
+        let key = KeyType::<u32>(1, false);
+        let storage_layout =
+            create_struct_layout(create_aggregator_storage_layout(MoveTypeLayout::U64));
+        let layout = create_struct_layout(create_aggregator_layout_u64());
+        let state_value = create_state_value(
+            &create_struct_value(create_aggregator_value_u64(25, 30)),
+            &storage_layout,
+        );
+        let data = HashMap::from([(key, state_value)]);
+
+        let start_counter = 1000;
+        let id = DelayedFieldID::new_with_width(start_counter, 8);
+        let patched_state_value = create_state_value(
+            &create_struct_value(create_aggregator_value_u64(id.as_u64(), 30)),
+            &storage_layout,
+        );
+
+        let holder = ComparisonHolder::new(data, start_counter);
+        let observer = LayoutMismatchObserver::new();
+        let new_par_view = || {
+            LatestView::<TestTransactionType, MockStateView, MockExecutable>::new(
+                &holder.base_view,
+                ViewState::Sync(ParallelState::new(
+                    &holder.versioned_map,
+                    &holder.scheduler,
+                    holder.start_counter,
+                    &holder.counter,
+                    &holder.key_interner,
+                    None,
+                    Some(&observer),
+                    ParallelStateConfig::default(),
+                )),
+                1,
+            )
+        };
+        let latest_view_seq = LatestView::<TestTransactionType, MockStateView, MockExecutable>::new(
+            &holder.holder.base_view,
+            ViewState::Unsync(SequentialStateRef::Owned(SequentialState::new(
+                &holder.holder.unsync_map,
+                start_counter,
+                &holder.holder.counter,
+                false,
+                None,
+                Some(&observer),
+                None,
+            ))),
+            1,
+        );
+
+        let mismatch_count = |is_parallel| {
+            counters::LAYOUT_MISMATCH_COUNT
+                .with_label_values(&ViewCounterLabels::new(is_parallel, None).values())
+                .get()
+        };
+        let mismatch_counts = (mismatch_count(true), mismatch_count(false));
+        for latest_view in [&new_par_view(), &latest_view_seq] {
+            assert_ok_eq!(
+                latest_view.get_resource_state_value(&key, Some(&layout)),
+                Some(patched_state_value.clone())
+            );
+        }
+
+        // The read with a different layout is still served (the stored value is unchanged).
+        for latest_view in [&new_par_view(), &latest_view_seq] {
+            assert_ok_eq!(
+                latest_view.get_resource_state_value(&key, Some(&storage_layout)),
+                Some(patched_state_value.clone())
+            );
+            assert!(!latest_view.is_incorrect_use());
+        }
+        assert!(mismatch_count(true) > mismatch_counts.0);
+        assert!(mismatch_count(false) > mismatch_counts.1);
+    }
+
+    #[test]
+    fn test_hashed_value_reads() {
+        let key = KeyType::<u32>(1, false);
+        let state_value = StateValue::new_legacy(vec![5_u8; 1000].into());
+        let data = HashMap::from([(key, state_value.clone())]);
+
+        let holder = ComparisonHolder::new(data, 1000);
+        let new_par_view = |hashed_value_read_threshold| {
+            LatestView::<TestTransactionType, MockStateView, MockExecutable>::new(
+                &holder.base_view,
+                ViewState::Sync(ParallelState::new(
+                    &holder.versioned_map,
+                    &holder.scheduler,
+                    holder.start_counter,
+                    &holder.counter,
+                    &holder.key_interner,
+                    None,
+                    None,
+                    ParallelStateConfig {
+                        hashed_value_read_threshold,
+                        ..ParallelStateConfig::default()
+                    },
+                )),
+                1,
+            )
+        };
+        let stored_value = || match holder.versioned_map.data().fetch_data(&key, 1) {
+            Ok(MVDataOutput::Versioned(_, ValueWithLayout::Exchanged(v, None))) => v,
+            _ => unreachable!("Exchanged value must be stored"),
+        };
+
+        let latest_view_par = new_par_view(None);
+        assert_ok_eq!(
+            latest_view_par.get_resource_state_value(&key, None),
+            Some(state_value.clone())
+        );
+        let captured_reads = latest_view_par.take_parallel_reads().unwrap();
+        // Held by the map, the captured read and the test.
+        assert_eq!(Arc::strong_count(&stored_value()), 3);
+        drop(captured_reads);
+
+        // The large value read is served the actual value (also when read again), but the
+        // captured read does not retain the value.
+        let latest_view_par = new_par_view(Some(100));
+        for _ in 0..2 {
+            assert_ok_eq!(
+                latest_view_par.get_resource_state_value(&key, None),
+                Some(state_value.clone())
+            );
+        }
+        assert_ok_eq!(
+            latest_view_par.get_resource_state_value_metadata(&key),
+            Some(state_value.clone().into_metadata())
+        );
+        let captured_reads = latest_view_par.take_parallel_reads().unwrap();
+        assert_matches!(
+            captured_reads.get_by_kind(&key, None, ReadKind::Value),
+            Some(DataRead::Hashed(Err(StorageVersion), _))
+        );
+        assert_eq!(Arc::strong_count(&stored_value()), 2);
+        assert!(captured_reads.validate_data_reads(holder.versioned_map.data(), 1));
+
+        // The same value written at a lower transaction validates, a different one does not.
+        let write_value = |bytes: Vec<u8>, incarnation| {
+            holder.versioned_map.data().write(
+                key,
+                0,
+                incarnation,
+                Arc::new(TransactionWrite::from_state_value(Some(
+                    StateValue::new_legacy(bytes.into()),
+                ))),
+                None,
+            );
+        };
+        write_value(vec![5_u8; 1000], 0);
+        assert!(captured_reads.validate_data_reads(holder.versioned_map.data(), 1));
+        write_value(vec![6_u8; 1000], 1);
+        assert!(!captured_reads.validate_data_reads(holder.versioned_map.data(), 1));
+
+        // The change is also detected by the read of the captured key.
+        let latest_view_par = new_par_view(Some(100));
+        assert_ok!(latest_view_par.get_resource_state_value(&key, None));
+        write_value(vec![7_u8; 1000], 2);
+        assert_err!(latest_view_par.get_resource_state_value(&key, None));
+        assert!(!latest_view_par
+            .take_parallel_reads()
+            .unwrap()
+            .validate_data_reads(holder.versioned_map.data(), 1));
+
+        // A hashed read does not retain the value, hence can not be served without a refetch.
+        let hashed_read = captured_reads
+            .get_by_kind(&key, None, ReadKind::Value)
+            .unwrap();
+        assert_err!(ReadResult::from_data_read(hashed_read));
+    }
+
+    #[test]
+    fn test_resource_size_reads() {
+        let key = KeyType::<u32>(1, false);
+        let other_key = KeyType::<u32>(2, false);
+        let missing_key = KeyType::<u32>(3, false);
+        let state_value = create_state_value(&Value::u64(12321), &MoveTypeLayout::U64);
+        let data = HashMap::from([(key, state_value.clone()), (other_key, state_value.clone())]);
+        let size = Some(state_value.size() as u64);
+
+        let holder = ComparisonHolder::new(data, 1000);
+        let views = holder.new_view();
+
+        // Size then value read.
+        assert_ok_eq!(views.get_resource_state_value_size(&key), size);
+        assert_eq!(views.latest_view_seq.get_read_summary(), HashSet::new());
+        assert_ok_eq!(
+            views.get_resource_state_value(&key, None),
+            Some(state_value.clone())
+        );
+        // Value then size read.
+        assert_ok_eq!(
+            views.get_resource_state_value(&other_key, None),
+            Some(state_value.clone())
+        );
+        assert_ok_eq!(views.get_resource_state_value_size(&other_key), size);
+        assert_ok_eq!(views.get_resource_state_value_size(&missing_key), None);
+
+        let captured_reads = views.latest_view_par.take_parallel_reads().unwrap();
+        for k in [&key, &other_key] {
+            assert_matches!(
+                captured_reads.get_by_kind(k, None, ReadKind::Value),
+                Some(DataRead::Versioned(Err(StorageVersion), _, None))
+            );
+            assert_some_eq!(
+                captured_reads.get_by_kind(k, None, ReadKind::Size),
+                DataRead::Size(Some((StateValueMetadata::none(), size.unwrap())))
+            );
+        }
+        assert_some_eq!(
+            captured_reads.get_by_kind(&missing_key, None, ReadKind::Size),
+            DataRead::Size(None)
+        );
+        assert!(captured_reads.validate_data_reads(holder.versioned_map.data(), 1));
+
+        // A size read is invalidated by a write of a different size.
+        let latest_view_par = holder.new_view().latest_view_par;
+        assert_ok_eq!(latest_view_par.get_resource_state_value_size(&key), size);
+        let captured_reads = latest_view_par.take_parallel_reads().unwrap();
+        assert_none!(captured_reads.get_by_kind(&key, None, ReadKind::Value));
+        assert!(captured_reads.validate_data_reads(holder.versioned_map.data(), 1));
+        holder.versioned_map.data().write(
+            key,
+            0,
+            0,
+            Arc::new(ValueType::with_len_and_metadata(
+                size.unwrap() as usize + 1,
+                StateValueMetadata::none(),
+            )),
+            None,
+        );
+        assert!(!captured_reads.validate_data_reads(holder.versioned_map.data(), 1));
+    }
+
+    #[test]
+    fn test_read_summary() {
+        let key = KeyType::<u32>(1, false);
+        let missing_key = KeyType::<u32>(2, false);
+        let state_value = create_state_value(&Value::u64(12321), &MoveTypeLayout::U64);
+        let data = HashMap::from([(key, state_value.clone())]);
+
+        let holder = ComparisonHolder::new(data, 1000);
+        let views = holder.new_view();
+        for _ in 0..2 {
+            assert_ok_eq!(
+                views.get_resource_state_value(&key, None),
+                Some(state_value.clone())
+            );
+        }
+        assert_ok_eq!(views.resource_exists(&key), true);
+        assert_ok_eq!(views.get_resource_state_value(&missing_key, None), None);
+
+        // The first read of each key is not initialized, and fetches the value from storage.
+        // The parallel read then patches the base value and refetches, while the subsequent
+        // reads of the key are served from the captured reads.
+        assert_eq!(views.latest_view_par.read_summary(), ReadSummary {
+            num_resource_reads: 2,
+            num_captured_read_hits: 2,
+            num_map_fetches: 6,
+            num_base_view_fetches: 2,
+            ..ReadSummary::default()
+        });
+        // The sequential reads fetch from the unsync map every time.
+        assert_eq!(views.latest_view_seq.read_summary(), ReadSummary {
+            num_resource_reads: 2,
+            num_map_fetches: 6,
+            num_base_view_fetches: 2,
+            ..ReadSummary::default()
+        });
+    }
+
+    #[test]
+    fn test_prefetch_base_values() {
+        let key = KeyType::<u32>(1, false);
+        let missing_key = KeyType::<u32>(2, false);
+        let read_key = KeyType::<u32>(3, false);
+        let state_value = create_state_value(&Value::u64(12321), &MoveTypeLayout::U64);
+        let data = HashMap::from([(key, state_value.clone()), (read_key, state_value.clone())]);
+
+        let holder = ComparisonHolder::new(data, 1000);
+        let views = holder.new_view();
+        assert_ok_eq!(
+            views.get_resource_state_value(&read_key, None),
+            Some(state_value.clone())
+        );
+
+        for view in [&views.latest_view_par, &views.latest_view_seq] {
+            // Keys with a base value are not fetched again.
+            assert_ok!(view.prefetch_base_values(&[key, missing_key, read_key]));
+            assert_eq!(view.read_summary().num_base_view_fetches, 3);
+            assert_ok!(view.prefetch_base_values(&[key, missing_key]));
+            assert_eq!(view.read_summary().num_base_view_fetches, 3);
+        }
+
+        // The reads of the prefetched keys are served from the maps.
+        assert_ok_eq!(
+            views.get_resource_state_value(&key, None),
+            Some(state_value.clone())
+        );
+        assert_ok_eq!(views.resource_exists(&missing_key), false);
+        for view in [&views.latest_view_par, &views.latest_view_seq] {
+            assert_eq!(view.read_summary().num_base_view_fetches, 3);
+        }
+    }
+
+    #[test]
+    fn test_resource_value_and_metadata() {
+        let key = KeyType::<u32>(1, false);
+        let missing_key = KeyType::<u32>(2, false);
+        let state_value = StateValue::new_with_metadata(
+            Value::u64(12321)
+                .simple_serialize(&MoveTypeLayout::U64)
+                .unwrap()
+                .into(),
+            raw_metadata(1),
+        );
+        let data = HashMap::from([(key, state_value.clone())]);
+
+        let holder = ComparisonHolder::new(data, 1000);
+        let views = holder.new_view();
+        for view in [&views.latest_view_par, &views.latest_view_seq] {
+            assert_ok_eq!(
+                view.get_resource_state_value_and_metadata(&key, None),
+                (Some(state_value.clone()), Some(raw_metadata(1)))
+            );
+            assert_ok_eq!(
+                view.get_resource_state_value_and_metadata(&missing_key, None),
+                (None, None)
+            );
+            assert_eq!(view.read_summary().num_base_view_fetches, 2);
+        }
+
+        // The value reads are captured, and serve the metadata reads.
+        let captured_hits = views.latest_view_par.read_summary().num_captured_read_hits;
+        assert_ok_eq!(
+            views.get_resource_state_value_metadata(&key),
+            Some(raw_metadata(1))
+        );
+        assert_ok_eq!(views.get_resource_state_value_metadata(&missing_key), None);
+        assert_eq!(
+            views.latest_view_par.read_summary().num_captured_read_hits,
+            captured_hits + 2
+        );
+
+        let captured_reads = views.latest_view_par.take_parallel_reads().unwrap();
+        for key in [key, missing_key] {
+            assert_some!(captured_reads.get_by_kind(&key, None, ReadKind::Value));
+        }
+        assert!(captured_reads.validate_data_reads(holder.versioned_map.data(), 1));
+    }
+
+    #[test]
+    fn test_batched_resource_reads() {
+        // Keys captured before the batch, written to the versioned map, in storage only,
+        // and missing from storage.
+        let captured_key = KeyType::<u32>(1, false);
+        let map_key = KeyType::<u32>(2, false);
+        let storage_key = KeyType::<u32>(3, false);
+        let missing_key = KeyType::<u32>(4, false);
+        let state_value = |v: u64| create_state_value(&Value::u64(v), &MoveTypeLayout::U64);
+        let data = HashMap::from([
+            (captured_key, state_value(1)),
+            (map_key, state_value(2)),
+            (storage_key, state_value(3)),
+        ]);
+        let keys = [
+            (captured_key, None),
+            (map_key, None),
+            (storage_key, Some(&MoveTypeLayout::U64)),
+            (missing_key, None),
+        ];
+
+        let holder = ComparisonHolder::new(data, 1000);
+        holder.versioned_map.data().write(
+            map_key,
+            0,
+            0,
+            Arc::new(TransactionWrite::from_state_value(Some(state_value(20)))),
+            None,
+        );
+
+        let views = holder.new_view();
+        for latest_view in [&views.latest_view_par, &views.latest_view_seq] {
+            assert_ok_eq!(
+                latest_view.get_resource_state_value(&captured_key, None),
+                Some(state_value(1))
+            );
+        }
+
+        // The sequential view does not observe the versioned map.
+        let expected_values = |map_value| {
+            vec![
+                Some(state_value(1)),
+                Some(state_value(map_value)),
+                Some(state_value(3)),
+                None,
+            ]
+        };
+        assert_ok_eq!(
+            views.latest_view_par.get_resource_state_values(&keys),
+            expected_values(20)
+        );
+        assert_ok_eq!(
+            views.latest_view_seq.get_resource_state_values(&keys),
+            expected_values(2)
+        );
+        assert_ok_eq!(
+            views.latest_view_par.get_resource_state_values(&keys),
+            expected_values(20)
+        );
+
+        // The first batch is served the captured read, a single fetch of the write, and two
+        // fetches of the uninitialized keys (and two refetches each after setting the base
+        // values from storage). The second batch is served from the captured reads.
+        assert_eq!(
+            views.latest_view_par.read_summary(),
+            ReadSummary {
+                num_resource_reads: 4,
+                num_captured_read_hits: 5,
+                num_map_fetches: 10,
+                num_base_view_fetches: 3,
+                ..ReadSummary::default()
+            }
+        );
+
+        // The batched reads are captured and validated as the single reads.
+        let captured_reads = views.latest_view_par.take_parallel_reads().unwrap();
+        assert!(captured_reads.validate_data_reads(holder.versioned_map.data(), 1));
+        holder.versioned_map.data().write(
+            storage_key,
+            0,
+            0,
+            Arc::new(TransactionWrite::from_state_value(Some(state_value(30)))),
+            None,
+        );
+        assert!(!captured_reads.validate_data_reads(holder.versioned_map.data(), 1));
+    }
+
+    #[test]
+    fn test_batched_resource_reads_order_and_capture() {
+        let keys: Vec<_> = (1..=4).map(|i| KeyType::<u32>(i, false)).collect();
+        let state_value = |v: u64| create_state_value(&Value::u64(v), &MoveTypeLayout::U64);
+        // The last key is missing from storage.
+        let data: HashMap<_, _> = keys[..3]
+            .iter()
+            .map(|key| (*key, state_value(key.0 as u64)))
+            .collect();
+
+        let holder = ComparisonHolder::new(data, 1000);
+        let views = holder.new_view();
+        let batch: Vec<_> = keys.iter().rev().map(|key| (*key, None)).collect();
+        for latest_view in [&views.latest_view_par, &views.latest_view_seq] {
+            assert_ok_eq!(
+                latest_view.get_resource_state_values(&batch),
+                vec![
+                    None,
+                    Some(state_value(3)),
+                    Some(state_value(2)),
+                    Some(state_value(1))
+                ]
+            );
+        }
+
+        // Each read is captured individually, as the single reads would be.
+        let captured_reads = views.latest_view_par.take_parallel_reads().unwrap();
+        for key in &keys {
+            assert_some!(captured_reads.get_by_kind(key, None, ReadKind::Value));
+        }
+        let sequential_reads = views.latest_view_seq.take_sequential_reads().unwrap();
+        assert_eq!(
+            sequential_reads.resource_reads,
+            keys.iter().copied().collect::<HashSet<_>>()
+        );
+
+        // A write to any single key of the batch invalidates the reads.
+        assert!(captured_reads.validate_data_reads(holder.versioned_map.data(), 1));
+        holder.versioned_map.data().write(
+            keys[3],
+            0,
+            0,
+            Arc::new(TransactionWrite::from_state_value(Some(state_value(40)))),
+            None,
+        );
+        assert!(!captured_reads.validate_data_reads(holder.versioned_map.data(), 1));
+    }
+
+    #[test]
+    fn test_reexecution_with_prior_reads() {
+        let keys: Vec<_> = (1..=3).map(|i| KeyType::<u32>(i, false)).collect();
+        let unrelated_key = KeyType::<u32>(4, false);
+        let state_value = |v: u64| create_state_value(&Value::u64(v), &MoveTypeLayout::U64);
+        let data: HashMap<_, _> = keys
+            .iter()
+            .map(|key| (*key, state_value(key.0 as u64)))
+            .collect();
+
+        let holder = ComparisonHolder::new(data, 1000);
+        let view_with_prior_reads = |prior_reads| {
+            LatestView::<TestTransactionType, MockStateView, MockExecutable>::new(
+                &holder.base_view,
+                ViewState::Sync(
+                    ParallelState::new(
+                        &holder.versioned_map,
+                        &holder.scheduler,
+                        holder.start_counter,
+                        &holder.counter,
+                        &holder.key_interner,
+                        None,
+                        None,
+                        ParallelStateConfig::default(),
+                    )
+                    .with_prior_reads(prior_reads),
+                ),
+                1,
+            )
+        };
+        let write = |key, v| {
+            holder.versioned_map.data().write(
+                key,
+                0,
+                0,
+                Arc::new(TransactionWrite::from_state_value(Some(state_value(v)))),
+                None,
+            );
+        };
+
+        let views = holder.new_view();
+        for key in &keys {
+            assert_ok_eq!(
+                views.latest_view_par.get_resource_state_value(key, None),
+                Some(state_value(key.0 as u64))
+            );
+        }
+        let captured_reads = views.latest_view_par.take_parallel_reads().unwrap();
+
+        // A write of a key that was not read: all the reads are reused by the re-execution.
+        write(unrelated_key, 40);
+        let latest_view =
+            view_with_prior_reads(captured_reads.into_validated_subset(&holder.versioned_map, 1));
+        for key in &keys {
+            assert_ok_eq!(
+                latest_view.get_resource_state_value(key, None),
+                Some(state_value(key.0 as u64))
+            );
+        }
+        let read_summary = latest_view.read_summary();
+        assert_eq!(read_summary.num_captured_read_hits, 3);
+        assert_eq!(read_summary.num_map_fetches, 0);
+        assert_eq!(read_summary.num_base_view_fetches, 0);
+
+        // A write of a read key: the invalidated read is fetched fresh, the rest are reused.
+        let captured_reads = latest_view.take_parallel_reads().unwrap();
+        write(keys[1], 20);
+        let validated_reads = captured_reads.into_validated_subset(&holder.versioned_map, 1);
+        assert_eq!(validated_reads.read_summary().num_resource_reads, 2);
+        let latest_view = view_with_prior_reads(validated_reads);
+        for (key, v) in keys.iter().zip([1, 20, 3]) {
+            assert_ok_eq!(
+                latest_view.get_resource_state_value(key, None),
+                Some(state_value(v))
+            );
+        }
+        let read_summary = latest_view.read_summary();
+        assert_eq!(read_summary.num_captured_read_hits, 2);
+        assert_eq!(read_summary.num_map_fetches, 1);
+
+        let captured_reads = latest_view.take_parallel_reads().unwrap();
+        assert!(captured_reads.validate_data_reads(holder.versioned_map.data(), 1));
+    }
+
+    #[test]
+    fn test_merge_health_fragments() {
+        let keys = [KeyType::<u32>(1, false), KeyType::<u32>(2, false)];
+        let state_value = create_state_value(&Value::u64(12321), &MoveTypeLayout::U64);
+        let data = HashMap::from([
+            (keys[0], state_value.clone()),
+            (keys[1], state_value.clone()),
+        ]);
+        let holder = ComparisonHolder::new(data, 1000);
+
+        // The first transaction fetches a key from storage, the second transaction reads the
+        // same key from the versioned map and fetches another key, and the third transaction
+        // observes an inconsistency.
+        let views = holder.new_view();
+        assert_ok_eq!(
+            views
+                .latest_view_par
+                .get_resource_state_value(&keys[0], None),
+            Some(state_value.clone())
+        );
+        let first = views.latest_view_par.health_fragment();
+        assert_eq!(first.num_storage_fetches, 1);
+
+        let views = holder.new_view();
+        for key in &keys {
+            assert_ok_eq!(
+                views.latest_view_par.get_resource_state_value(key, None),
+                Some(state_value.clone())
+            );
+        }
+        let second = views.latest_view_par.health_fragment();
+        assert_eq!(second.num_storage_fetches, 1);
+
+        let views = holder.new_view();
+        match &views.latest_view_par.latest_view {
+            ViewState::Sync(state) => state.captured_reads.borrow_mut().mark_failure(),
+            ViewState::Unsync(_) => unreachable!("Parallel view expected"),
+        }
+        let third = views.latest_view_par.health_fragment();
+        assert_eq!(third.total_speculative_halts(), 1);
+
+        let summary = merge_view_health([&first, &second, &third]);
+        assert_eq!(summary.num_storage_fetches, 2);
+        assert_eq!(
+            summary.storage_fetch_bytes,
+            2 * state_value.bytes().len() as u64
+        );
+        assert_eq!(
+            summary.num_speculative_halts(SpeculativeHaltReason::InconsistentRead),
+            1
+        );
+        assert_eq!(summary.num_incorrect_uses, 0);
+        assert_eq!(summary.num_dependency_waits, 0);
+        assert_eq!(summary.num_exchanges, 0);
+        assert_eq!(summary.num_group_initializations, 0);
+    }
+
+    #[test]
+    fn test_speculative_halt_counted_per_reason() {
+        let key = KeyType::<u32>(1, false);
+        let state_value = create_state_value(&Value::u64(12321), &MoveTypeLayout::U64);
+        let holder = ComparisonHolder::new(HashMap::new(), 1000);
+        let views = holder.new_view();
+
+        for (is_parallel, view) in [
+            (false, &views.latest_view_seq),
+            (true, &views.latest_view_par),
+        ] {
+            let halt_count = |reason: SpeculativeHaltReason| {
+                let [mode, shard] = ViewCounterLabels::new(is_parallel, None).values();
+                counters::SPECULATIVE_HALT_COUNT
+                    .with_label_values(&[mode, shard, reason.as_str()])
+                    .get()
+            };
+            let initial_halt_count = halt_count(SpeculativeHaltReason::PatchFailure);
+
+            let resource_state = view.latest_view.get_resource_state();
+            resource_state.set_base_value(
+                key,
+                ValueWithLayout::RawFromStorage(Arc::new(TransactionWrite::from_state_value(
+                    Some(state_value.clone()),
+                ))),
+            );
+            assert_matches!(
+                resource_state.read_cached_data_by_kind(
+                    1,
+                    &key,
+                    ReadKind::Value,
+                    UnknownOrLayout::Known(None),
+                    &|_, _| Err(PartialVMError::new(
+                        StatusCode::UNEXPECTED_DESERIALIZATION_ERROR
+                    )),
+                ),
+                Err(ViewError::SpeculativeAbort(
+                    SpeculativeHaltReason::PatchFailure,
+                    _
+                ))
+            );
+            assert!(halt_count(SpeculativeHaltReason::PatchFailure) > initial_halt_count);
+
+            // The halt is recorded in the health of the view with its reason.
+            let health = view.health_fragment();
+            assert_eq!(
+                health.num_speculative_halts(SpeculativeHaltReason::PatchFailure),
+                1
+            );
+            assert_eq!(health.total_speculative_halts(), 1);
+            assert_eq!(health.num_incorrect_uses, 1);
+        }
+    }
+
+    #[test]
+    fn test_take_reads_once() {
+        let key = KeyType::<u32>(1, false);
+        let state_value = create_state_value(&Value::u64(12321), &MoveTypeLayout::U64);
+        let data = HashMap::from([(key, state_value.clone())]);
+
+        let holder = ComparisonHolder::new(data, 1000);
+        let views = holder.new_view();
+        assert_ok_eq!(
+            views.get_resource_state_value(&key, None),
+            Some(state_value.clone())
+        );
+
+        let captured_reads = views.latest_view_par.take_parallel_reads().unwrap();
+        assert_eq!(captured_reads.read_summary().num_resource_reads, 1);
+        assert!(!views.latest_view_par.is_incorrect_use());
+        assert_err!(views.latest_view_par.take_parallel_reads());
+        assert!(views.latest_view_par.is_incorrect_use());
+
+        let read_set = views.latest_view_seq.take_sequential_reads().unwrap();
+        assert!(read_set.resource_reads.contains(&key));
+        assert_err!(views.latest_view_seq.take_sequential_reads());
+    }
+
+    #[test]
+    fn test_sequential_module_reads_recorded() {
+        let module_key = KeyType::<u32>(1, true);
+        let missing_key = KeyType::<u32>(2, true);
+        let state_value = StateValue::new_legacy(vec![1, 2, 3].into());
+        let data = HashMap::from([(module_key, state_value.clone())]);
+
+        let holder = Holder::new(data, 1000);
+        let view = create_sequential_latest_view(&holder);
+        assert_ok_eq!(view.get_module_state_value(&module_key), Some(state_value));
+        assert_ok_eq!(view.get_module_state_value(&missing_key), None);
+
+        // Missing modules are recorded as well, as a later publish would be observed.
+        assert_eq!(view.read_summary().num_module_reads, 2);
+        let read_set = view.take_sequential_reads().unwrap();
+        assert_eq!(
+            read_set.module_reads,
+            HashSet::from([module_key, missing_key])
+        );
+        assert!(read_set.resource_reads.is_empty());
+    }
+
+    #[test]
+    fn test_sequential_delayed_field_reads_recorded() {
+        let value_id = DelayedFieldID::new_with_width(1000, 8);
+        let try_add_id = DelayedFieldID::new_with_width(1001, 8);
+        let exists_id = DelayedFieldID::new_with_width(1002, 8);
+        let unread_id = DelayedFieldID::new_with_width(1003, 8);
+        let holder = Holder::new(HashMap::new(), 1004);
+        for id in [value_id, try_add_id, exists_id, unread_id] {
+            holder
+                .unsync_map
+                .set_base_delayed_field(id, DelayedFieldValue::Aggregator(10));
+        }
+
+        let view = create_sequential_latest_view(&holder);
+        assert_ok_eq!(
+            view.get_delayed_field_value(&value_id),
+            DelayedFieldValue::Aggregator(10)
+        );
+        assert_ok_eq!(
+            view.delayed_field_try_add_delta_outcome(
+                &try_add_id,
+                &SignedU128::Positive(0),
+                &SignedU128::Positive(95),
+                100
+            ),
+            false
+        );
+        assert_ok_eq!(view.delayed_field_exists(&exists_id), true);
+        assert_eq!(view.read_summary().num_delayed_field_reads, 3);
+        assert_eq!(
+            view.captured_delayed_field_ids(),
+            BTreeSet::from([value_id, try_add_id, exists_id])
+        );
+
+        // The ids are recorded for the change set validation of the output, so that e.g. a
+        // delta on an id that was not read can be detected.
+        let read_set = view.take_sequential_reads().unwrap();
+        assert_eq!(
+            read_set.delayed_field_reads,
+            HashSet::from([value_id, try_add_id, exists_id])
+        );
+        assert!(read_set.resource_reads.is_empty());
+    }
+
+    #[test]
+    fn test_modules_read() {
+        let module_key = KeyType::<u32>(1, true);
+        let missing_key = KeyType::<u32>(2, true);
+        let state_value = StateValue::new_legacy(vec![1, 2, 3].into());
+        let data = HashMap::from([(module_key, state_value.clone())]);
+
+        let holder = ComparisonHolder::new(data, 1000);
+        let views = holder.new_view();
+        for view in [&views.latest_view_par, &views.latest_view_seq] {
+            assert!(view.modules_read().is_empty());
+            assert_ok_eq!(
+                view.get_module_state_value_metadata(&module_key),
+                Some(state_value.clone().into_metadata())
+            );
+            assert_ok_eq!(view.get_module_state_value_metadata(&missing_key), None);
+            // Reading the same module again does not duplicate the key.
+            assert_ok_eq!(
+                view.get_module_state_value(&module_key),
+                Some(state_value.clone())
+            );
+
+            let mut keys = view.modules_read();
+            keys.sort();
+            assert_eq!(keys, vec![module_key, missing_key]);
+        }
+    }
+
+    #[test]
+    fn test_delayed_field_id_exhaustion() {
+        let start_counter = DELAYED_FIELD_ID_INDEX_BOUND - 2;
+        let holder = ComparisonHolder::new(HashMap::new(), start_counter);
+        let views = holder.new_view();
+        for view in [&views.latest_view_par, &views.latest_view_seq] {
+            for _ in 0..2 {
+                let id = assert_ok!(view.generate_delayed_field_id(8));
+                assert_ok!(view.validate_delayed_field_id(&id));
+            }
+
+            // The remaining indices are reserved.
+            assert_err!(view.generate_delayed_field_id(8));
+            assert_err!(view.generate_delayed_field_id(8));
+            let reserved_id = DelayedFieldID::new_for_test_for_u64(DELAYED_FIELD_ID_INDEX_BOUND);
+            assert_err!(view.validate_delayed_field_id(&reserved_id));
+        }
+
+        // Both counters stop at the bound, the failed generations do not increment them.
+        assert_eq!(
+            *holder.holder.counter.borrow(),
+            DELAYED_FIELD_ID_INDEX_BOUND
+        );
+        assert_eq!(
+            holder.counter.load(Ordering::SeqCst),
+            DELAYED_FIELD_ID_INDEX_BOUND
+        );
+    }
+
+    #[test]
+    fn test_take_reads_round_trip() {
+        let key = KeyType::<u32>(1, false);
+        let module_key = KeyType::<u32>(2, true);
+        let data = HashMap::from([
+            (key, StateValue::new_legacy(vec![1].into())),
+            (module_key, StateValue::new_legacy(vec![2].into())),
+        ]);
+
+        let holder = ComparisonHolder::new(data, 1000);
+        let views = holder.new_view();
+        for view in [&views.latest_view_par, &views.latest_view_seq] {
+            assert_ok!(view.get_resource_state_value(&key, None));
+            assert_ok!(view.get_module_state_value(&module_key));
+
+            let reads = view.take_reads().unwrap();
+            let bytes = bcs::to_bytes(&reads).unwrap();
+            let deserialized: TakenReads<TestTransactionType> = bcs::from_bytes(&bytes).unwrap();
+            assert_eq!(bcs::to_bytes(&deserialized).unwrap(), bytes);
+
+            let (summary, deserialized_summary) = match (reads, deserialized) {
+                (TakenReads::Parallel(reads), TakenReads::Parallel(deserialized)) => {
+                    assert!(matches!(view.latest_view, ViewState::Sync(_)));
+                    (reads.read_summary(), deserialized.read_summary())
+                },
+                (TakenReads::Sequential(reads), TakenReads::Sequential(deserialized)) => {
+                    assert!(matches!(view.latest_view, ViewState::Unsync(_)));
+                    (reads.read_summary(), deserialized.read_summary())
+                },
+                _ => unreachable!("Reads deserialized in a different setting"),
+            };
+            assert_eq!(deserialized_summary, summary);
+            assert_eq!(summary.num_resource_reads, 1);
+            assert_eq!(summary.num_module_reads, 1);
+
+            // The reads may be taken only once.
+            assert!(view.take_reads().is_err());
+        }
+    }
+
+    #[test]
+    fn test_validate_captured_reads_against() {
+        let key = KeyType::<u32>(1, false);
+        let metadata_key = KeyType::<u32>(2, false);
+        let group_key = KeyType::<u32>(3, false);
+        let module_key = KeyType::<u32>(4, true);
+        let value_1 = create_state_value(&Value::u64(5), &MoveTypeLayout::U64);
+        let value_2 = create_state_value(&Value::u128(7), &MoveTypeLayout::U128);
+        let group_state_value = |members: &[(u32, &StateValue)]| {
+            let group: BTreeMap<u32, Bytes> = members
+                .iter()
+                .map(|(tag, value)| (*tag, value.bytes().clone()))
+                .collect();
+            StateValue::new_legacy(bcs::to_bytes(&group).unwrap().into())
+        };
+        let published_module = StateValue::new_legacy(vec![2].into());
+        let data = HashMap::from([
+            (key, value_1.clone()),
+            (
+                metadata_key,
+                StateValue::new_with_metadata(value_2.bytes().clone(), raw_metadata(1)),
+            ),
+            (
+                group_key,
+                group_state_value(&[(1, &value_1), (2, &value_2)]),
+            ),
+            (module_key, published_module.clone()),
+        ]);
+
+        let holder = ComparisonHolder::new(data.clone(), 1000);
+        // The module is published by a lower transaction in the block.
+        holder.versioned_map.modules().write(
+            module_key,
+            0,
+            TransactionWrite::from_state_value(Some(published_module)),
+        );
+        let views = holder.new_view();
+        let view = &views.latest_view_par;
+        assert_ok!(view.get_resource_state_value(&key, None));
+        assert_ok!(view.get_resource_state_value_metadata(&metadata_key));
+        assert_ok!(view.resource_group_size(&group_key));
+        assert_ok!(view.get_resource_from_group(&group_key, &1, None));
+        assert_ok_eq!(view.resource_exists_in_group(&group_key, &3), false);
+        assert_ok!(view.get_module_state_value(&module_key));
+        let mut captured_reads = view.take_parallel_reads().unwrap();
+
+        let validate = |data: HashMap<KeyType<u32>, StateValue>| {
+            validate_captured_reads_against::<TestTransactionType, _, MockExecutable>(
+                &captured_reads,
+                &MockStateView::new(data),
+                1,
+            )
+        };
+        assert_ok_eq!(validate(data.clone()), None);
+
+        // The reads are compared by content: only the metadata of the value was read.
+        let mut replayed = data.clone();
+        replayed.insert(
+            metadata_key,
+            StateValue::new_with_metadata(value_1.bytes().clone(), raw_metadata(1)),
+        );
+        assert_ok_eq!(validate(replayed.clone()), None);
+
+        // A member of the same size changes the member read, but not the size of the group.
+        let value_3 = create_state_value(&Value::u64(6), &MoveTypeLayout::U64);
+        replayed.insert(
+            group_key,
+            group_state_value(&[(1, &value_3), (2, &value_2)]),
+        );
+        assert_ok_eq!(
+            validate(replayed.clone()),
+            Some(InputOutputKey::Group(group_key, 1))
+        );
+
+        // A new member changes the size of the group (and the existence read).
+        replayed.insert(
+            group_key,
+            group_state_value(&[(1, &value_1), (2, &value_2), (3, &value_1)]),
+        );
+        assert_ok_eq!(
+            validate(replayed.clone()),
+            Some(InputOutputKey::Resource(group_key))
+        );
+
+        // The data reads are replayed first, hence the first divergence is at the key.
+        replayed.insert(key, value_3);
+        assert_ok_eq!(
+            validate(replayed.clone()),
+            Some(InputOutputKey::Resource(key))
+        );
+
+        // A module published in the block is compared by the hash.
+        let mut replayed = data.clone();
+        replayed.insert(module_key, StateValue::new_legacy(vec![3].into()));
+        assert_ok_eq!(
+            validate(replayed),
+            Some(InputOutputKey::Resource(module_key))
+        );
+
+        // The reads of a failed execution are incomplete, and may not be replayed.
+        captured_reads.mark_failure();
+        assert_err!(validate_captured_reads_against::<
+            TestTransactionType,
+            _,
+            MockExecutable,
+        >(&captured_reads, &MockStateView::new(data), 1));
+    }
+
+    #[test]
+    fn test_read_after_take_reads() {
+        let key = KeyType::<u32>(1, false);
+        let state_value = create_state_value(&Value::u64(12321), &MoveTypeLayout::U64);
+        let data = HashMap::from([(key, state_value)]);
+
+        let holder = ComparisonHolder::new(data, 1000);
+        let views = holder.new_view();
+        assert_ok!(views.latest_view_par.take_parallel_reads());
+
+        // The read would never be validated, hence it fails and the use is marked incorrect.
+        let err = views
+            .latest_view_par
+            .get_resource_state_value(&key, None)
+            .unwrap_err();
+        assert_eq!(
+            err.major_status(),
+            StatusCode::SPECULATIVE_EXECUTION_ABORT_ERROR
+        );
+        assert!(views.latest_view_par.is_incorrect_use());
+    }
+
+    #[test]
+    fn test_sequential_state_reset() {
+        let key = KeyType::<u32>(1, false);
+        let state_value = create_state_value(&Value::u64(12321), &MoveTypeLayout::U64);
+        let data = HashMap::from([(key, state_value)]);
+
+        let holder = Holder::new(data, 1000);
+        let latest_view = create_sequential_latest_view(&holder);
+        let state = match &latest_view.latest_view {
+            ViewState::Unsync(state) => state,
+            ViewState::Sync(_) => unreachable!("Sequential view expected"),
+        };
+
+        assert_ok!(latest_view.get_resource_state_value(&key, None));
+        assert_eq!(latest_view.read_summary().num_resource_reads, 1);
+        *state.incorrect_use.borrow_mut() = true;
+        let capacity = state.read_set.borrow().resource_reads.capacity();
+
+        state.reset();
+        assert!(!latest_view.is_incorrect_use());
+        assert_eq!(latest_view.read_summary(), ReadSummary::default());
+        assert_eq!(state.read_set.borrow().resource_reads.capacity(), capacity);
+        assert!(state.write_journal.borrow().is_empty());
+
+        // The read set can be taken again after the reset.
+        assert_ok!(latest_view.get_resource_state_value(&key, None));
+        assert_ok!(latest_view.take_sequential_reads());
+        state.reset();
+        assert_ok!(latest_view.get_resource_state_value(&key, None));
+        let reads = assert_ok!(latest_view.take_sequential_reads());
+        assert_eq!(reads.resource_reads, HashSet::from([key]));
+        // The unsync map is left untouched.
+        assert_some!(holder.unsync_map.fetch_data(&key));
+    }
+
+    #[test]
+    fn test_borrowed_sequential_state_reused_across_views() {
+        let key = KeyType::<u32>(1, false);
+        let other_key = KeyType::<u32>(2, false);
+        let state_value = create_state_value(&Value::u64(12321), &MoveTypeLayout::U64);
+        let data = HashMap::from([(key, state_value.clone()), (other_key, state_value)]);
+
+        let holder = Holder::new(data, 1000);
+        let state = SequentialState::new(
+            &holder.unsync_map,
+            *holder.counter.borrow(),
+            &holder.counter,
+            false,
+            None,
+            None,
+            None,
+        );
+
+        for (idx, read_key) in [key, other_key].into_iter().enumerate() {
+            state.reset();
+            let latest_view = LatestView::<TestTransactionType, MockStateView, MockExecutable>::new(
+                &holder.base_view,
+                ViewState::Unsync(SequentialStateRef::Borrowed(&state)),
+                idx as TxnIndex,
+            );
+            assert!(!latest_view.is_incorrect_use());
+            assert_ok!(latest_view.get_resource_state_value(&read_key, None));
+            latest_view.mark_incorrect_use();
+
+            // Only the read of the current view is recorded in the shared state.
+            assert_eq!(latest_view.read_summary().num_resource_reads, 1);
+            assert_eq!(
+                state.read_set.borrow().resource_reads,
+                HashSet::from([read_key])
+            );
+        }
+        assert!(*state.incorrect_use.borrow());
+    }
+
+    #[test_case(0)]
+    #[test_case(3)]
+    fn test_dependency_wait_retry_limit(retry_limit: usize) {
+        let key = KeyType::<u32>(1, false);
+        let group_key = KeyType::<u32>(2, false);
+        let holder = ComparisonHolder::new(HashMap::new(), 1000);
+
+        // Txn 0 is executed, but its writes are estimates (as if aborted), hence every wait of
+        // txn 1 is resolved immediately, and the re-fetch observes the dependency again.
+        assert_matches!(
+            holder.scheduler.next_task(),
+            SchedulerTask::ExecutionTask(0, 0, _)
+        );
+        assert_ok!(holder.scheduler.finish_execution(0, 0, false));
+        let value = ValueType::with_len_and_metadata(10, raw_metadata(1));
+        let data = holder.versioned_map.data();
+        data.write(key, 0, 0, Arc::new(value.clone()), None);
+        data.mark_estimate(&key, 0);
+        let group_data = holder.versioned_map.group_data();
+        group_data.set_raw_base_values(group_key, vec![]);
+        group_data.write(group_key, 0, 0, vec![(3, (value, None))]);
+        group_data.mark_estimate(&group_key, 0);
+
+        let latest_view = LatestView::<TestTransactionType, MockStateView, MockExecutable>::new(
+            &holder.base_view,
+            ViewState::Sync(ParallelState::new(
+                &holder.versioned_map,
+                &holder.scheduler,
+                holder.start_counter,
+                &holder.counter,
+                &holder.key_interner,
+                None,
+                None,
+                ParallelStateConfig {
+                    dependency_wait_retry_limit: Some(retry_limit),
+                    ..ParallelStateConfig::default()
+                },
+            )),
+            1,
+        );
+        let state = match &latest_view.latest_view {
+            ViewState::Sync(state) => state,
+            ViewState::Unsync(_) => unreachable!("Parallel view expected"),
+        };
+
+        let reads: [&dyn Fn() -> PartialVMResult<()>; 4] = [
+            &|| latest_view.get_resource_state_value(&key, None).map(|_| ()),
+            &|| latest_view.resource_group_size(&group_key).map(|_| ()),
+            &|| {
+                latest_view
+                    .get_resource_from_group(&group_key, &3, None)
+                    .map(|_| ())
+            },
+            // The group tags are read after the group size, hence directly from the state.
+            &|| Ok(state.read_group_tags(&group_key, 1).map(|_| ())?),
+        ];
+        for (i, read) in reads.iter().enumerate() {
+            let err = read().unwrap_err();
+            assert_eq!(
+                err.major_status(),
+                StatusCode::SPECULATIVE_EXECUTION_ABORT_ERROR
+            );
+            // Each read waits as many times as allowed, and then halts the execution.
+            assert_eq!(
+                state.health.borrow().num_dependency_waits,
+                (i + 1) * retry_limit
+            );
+            assert!(state.captured_reads.borrow().is_speculative_failure());
+        }
+    }
+
+    #[test]
+    fn test_dependency_wait_timeout_aborts_incarnation() {
+        let key = KeyType::<u32>(1, false);
+        let holder = ComparisonHolder::new(HashMap::new(), 1000);
+        let scheduler = Scheduler::new(2).with_dependency_wait_timeout(Duration::from_millis(20));
+
+        // Txn 1 reads an estimate of txn 0, which is executing (and never finishes).
+        for txn_idx in 0..2 {
+            assert_matches!(
+                scheduler.next_task(),
+                SchedulerTask::ExecutionTask(idx, 0, _) if idx == txn_idx
+            );
+        }
+        let value = ValueType::with_len_and_metadata(10, raw_metadata(1));
+        let data = holder.versioned_map.data();
+        data.write(key, 0, 0, Arc::new(value), None);
+        data.mark_estimate(&key, 0);
+
+        let latest_view = LatestView::<TestTransactionType, MockStateView, MockExecutable>::new(
+            &holder.base_view,
+            ViewState::Sync(ParallelState::new(
+                &holder.versioned_map,
+                &scheduler,
+                holder.start_counter,
+                &holder.counter,
+                &holder.key_interner,
+                None,
+                None,
+                ParallelStateConfig::default(),
+            )),
+            1,
+        );
+        let err = latest_view
+            .get_resource_state_value(&key, None)
+            .unwrap_err();
+        assert_eq!(
+            err.major_status(),
+            StatusCode::SPECULATIVE_EXECUTION_ABORT_ERROR
+        );
+
+        // Only the incarnation is aborted (not a fallback to the sequential execution).
+        let state = match &latest_view.latest_view {
+            ViewState::Sync(state) => state,
+            ViewState::Unsync(_) => unreachable!("Parallel view expected"),
+        };
+        assert!(state.captured_reads.borrow().is_speculative_failure());
+        assert!(!latest_view.is_incorrect_use());
+        assert_eq!(
+            state
+                .health
+                .borrow()
+                .num_speculative_halts(SpeculativeHaltReason::DependencyTimeout),
+            1
+        );
+        assert_eq!(latest_view.health_fragment().num_incorrect_uses, 0);
+    }
+
+    #[test]
+    fn test_layout_fingerprint() {
+        let layout = create_struct_layout(create_aggregator_layout_u64());
+        assert_eq!(
+            layout_fingerprint(&layout),
+            layout_fingerprint(&create_struct_layout(create_aggregator_layout_u64()))
+        );
+
+        for other_layout in [
+            create_struct_layout(create_aggregator_layout(MoveTypeLayout::U128)),
+            create_struct_layout(create_snapshot_layout(MoveTypeLayout::U64)),
+            create_struct_layout(create_aggregator_storage_layout(MoveTypeLayout::U64)),
+            create_aggregator_layout_u64(),
+        ] {
+            assert_ne!(
+                layout_fingerprint(&layout),
+                layout_fingerprint(&other_layout)
+            );
+        }
+        assert_ne!(
+            layout_fingerprint(&MoveTypeLayout::U64),
+            layout_fingerprint(&MoveTypeLayout::U128)
+        );
+
+        // The number of fields is hashed, so that the nesting of the fields is distinguished.
+        let nested_first = MoveTypeLayout::Struct(MoveStructLayout::new(vec![
+            create_struct_layout(MoveTypeLayout::U64),
+            MoveTypeLayout::U64,
+        ]));
+        let nested_both =
+            create_struct_layout(MoveTypeLayout::Struct(MoveStructLayout::new(vec![
+                MoveTypeLayout::U64,
+                MoveTypeLayout::U64,
+            ])));
+        assert_ne!(
+            layout_fingerprint(&nested_first),
+            layout_fingerprint(&nested_both)
+        );
+
+        // The fingerprint does not depend on the process or the toolchain.
+        assert_eq!(
+            layout_fingerprint(&MoveTypeLayout::U64),
+            0xaf63_b94c_8601_b113
+        );
+    }
+
+    #[test]
+    fn test_layout_fingerprint_memoized() {
+        let cache = LayoutFingerprintCache::new();
+        let layout = Arc::new(create_struct_layout(create_aggregator_layout_u64()));
+        let equal_layout = Arc::new(create_struct_layout(create_aggregator_layout_u64()));
+        let other_layout = Arc::new(create_struct_layout(create_snapshot_layout(
+            MoveTypeLayout::U64,
+        )));
+
+        let fingerprint = cache.fingerprint(&layout);
+        assert_eq!(fingerprint, layout_fingerprint(&layout));
+        assert_eq!(cache.fingerprint(&layout.clone()), fingerprint);
+        assert_eq!(*cache.num_fingerprint_computations.borrow(), 1);
+
+        // Pointer equality is checked first, without the fingerprints.
+        assert!(cache.layouts_equal(&layout, &layout));
+        assert_eq!(*cache.num_fingerprint_computations.borrow(), 1);
+
+        assert!(cache.layouts_equal(&layout, &equal_layout));
+        assert!(!cache.layouts_equal(&layout, &other_layout));
+        assert!(!cache.layouts_equal(&other_layout, &equal_layout));
+        assert_eq!(*cache.num_fingerprint_computations.borrow(), 3);
+        assert_eq!(cache.fingerprint(&equal_layout), fingerprint);
+        assert_eq!(*cache.num_fingerprint_computations.borrow(), 3);
+
+        // The view memoizes the fingerprints in its own cache.
+        let holder = Holder::new(HashMap::new(), 1000);
+        let latest_view = create_sequential_latest_view(&holder);
+        assert_eq!(latest_view.layout_fingerprint(&layout), fingerprint);
+        assert!(latest_view.layouts_equal(&layout, &equal_layout));
+        assert_eq!(
+            *latest_view
+                .layout_fingerprints
+                .num_fingerprint_computations
+                .borrow(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_exchange_status() {
+        let aggregator_key = KeyType::<u32>(1, false);
+        let plain_key = KeyType::<u32>(2, false);
+        let unread_key = KeyType::<u32>(3, false);
+        let missing_key = KeyType::<u32>(4, false);
+        let storage_layout =
+            create_struct_layout(create_aggregator_storage_layout(MoveTypeLayout::U64));
+        let layout = create_struct_layout(create_aggregator_layout_u64());
+        let aggregator_value = create_state_value(
+            &create_struct_value(create_aggregator_value_u64(25, 30)),
+            &storage_layout,
+        );
+        let plain_value = create_state_value(&Value::u64(12321), &MoveTypeLayout::U64);
+        let data = HashMap::from([
+            (aggregator_key, aggregator_value),
+            (plain_key, plain_value.clone()),
+            (unread_key, plain_value.clone()),
+        ]);
+
+        let holder = ComparisonHolder::new(data, 1000);
+        let views = holder.new_view();
+        assert_ok!(views.get_resource_state_value(&aggregator_key, Some(&layout)));
+        assert_ok!(views.get_resource_state_value(&plain_key, Some(&MoveTypeLayout::U64)));
+        assert_ok!(views.get_resource_state_value(&missing_key, Some(&layout)));
+        assert_ok_eq!(views.resource_exists(&unread_key), true);
+
+        let id = DelayedFieldID::new_with_width(1000, 8);
+        for view in [&views.latest_view_par, &views.latest_view_seq] {
+            assert_ok_eq!(
+                view.exchange_status(&aggregator_key),
+                ExchangeStatus::Exchanged {
+                    ids: BTreeSet::from([id])
+                }
+            );
+            assert_ok_eq!(
+                view.exchange_status(&plain_key),
+                ExchangeStatus::LayoutHadNoDelayedFields
+            );
+            // Read by existence only, and a deletion.
+            assert_ok_eq!(
+                view.exchange_status(&unread_key),
+                ExchangeStatus::NotApplicable
+            );
+            assert_ok_eq!(
+                view.exchange_status(&missing_key),
+                ExchangeStatus::NotApplicable
+            );
+        }
+
+        // The status does not record the ids as scanned.
+        assert!(views
+            .latest_view_par
+            .captured_delayed_field_ids()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_exchange_status_capability_off() {
+        let aggregator_key = KeyType::<u32>(1, false);
+        let missing_key = KeyType::<u32>(2, false);
+        let storage_layout =
+            create_struct_layout(create_aggregator_storage_layout(MoveTypeLayout::U64));
+        let aggregator_value = create_state_value(
+            &create_struct_value(create_aggregator_value_u64(25, 30)),
+            &storage_layout,
+        );
+        let data = HashMap::from([(aggregator_key, aggregator_value.clone())]);
+
+        let holder = ComparisonHolder::new(data, 1000);
+        let views = holder.new_view();
+        let views = ViewsComparison {
+            latest_view_seq: views
+                .latest_view_seq
+                .with_delayed_field_optimization_disabled(),
+            latest_view_par: views
+                .latest_view_par
+                .with_delayed_field_optimization_disabled(),
+        };
+        // Without the capability, the VM reads the resources as is.
+        assert_ok_eq!(
+            views.get_resource_state_value(&aggregator_key, None),
+            Some(aggregator_value)
+        );
+        assert_ok_eq!(views.get_resource_state_value(&missing_key, None), None);
+
+        for view in [&views.latest_view_par, &views.latest_view_seq] {
+            assert!(!view.is_delayed_field_optimization_capable());
+            assert_ok_eq!(
+                view.exchange_status(&aggregator_key),
+                ExchangeStatus::SkippedCapabilityOff
+            );
+            assert_ok_eq!(
+                view.exchange_status(&missing_key),
+                ExchangeStatus::NotApplicable
+            );
+        }
+    }
+
+    #[test]
+    fn test_group_apis_on_resource_key() {
+        let key = KeyType::<u32>(1, false);
+        let state_value = create_state_value(&Value::u64(12321), &MoveTypeLayout::U64);
+        let data = HashMap::from([(key, state_value.clone())]);
+
+        let holder = ComparisonHolder::new(data, 1000);
+        let views = holder.new_view();
+        assert_ok_eq!(
+            views.get_resource_state_value(&key, None),
+            Some(state_value.clone())
+        );
+
+        let expected_message = format!("Key {:?} is not a resource group", key);
+        for view in [&views.latest_view_seq, &views.latest_view_par] {
+            let err = view.resource_group_size(&key).unwrap_err();
+            assert_eq!(
+                err.major_status(),
+                StatusCode::UNEXPECTED_DESERIALIZATION_ERROR
+            );
+            assert_eq!(err.message(), Some(expected_message.as_str()));
+            assert_none!(err.sub_status());
+
+            let err = view.get_resource_from_group(&key, &5, None).unwrap_err();
+            assert_eq!(
+                err.major_status(),
+                StatusCode::UNEXPECTED_DESERIALIZATION_ERROR
+            );
+            assert_eq!(err.message(), Some(expected_message.as_str()));
+            assert_none!(err.sub_status());
+
+            assert!(!view.is_incorrect_use());
+        }
+
+        // Plain reads of the key still work.
+        assert_ok_eq!(
+            views.get_resource_state_value(&key, None),
+            Some(state_value.clone())
+        );
+        assert_ok_eq!(views.resource_exists(&key), true);
+        assert_ok_eq!(
+            views.get_resource_state_value_metadata(&key),
+            Some(state_value.into_metadata())
+        );
+    }
+
+    fn assert_representation_flip_error(
+        view: &LatestView<TestTransactionType, MockStateView, MockExecutable>,
+        err: PartialVMError,
+        direction: &str,
+    ) {
+        match &view.latest_view {
+            ViewState::Sync(_) => {
+                // Falls back to the sequential execution.
+                assert_eq!(
+                    err.major_status(),
+                    StatusCode::SPECULATIVE_EXECUTION_ABORT_ERROR
+                );
+                assert!(view.is_incorrect_use());
+            },
+            ViewState::Unsync(_) => {
+                // Told apart from other deserialization errors by the sub-status.
+                assert_eq!(
+                    err.major_status(),
+                    StatusCode::UNEXPECTED_DESERIALIZATION_ERROR
+                );
+                assert_eq!(
+                    err.sub_status(),
+                    Some(unexpected_deserialization_error::ERESOURCE_GROUP_REPRESENTATION_FLIP)
+                );
+                assert!(!view.is_incorrect_use());
+            },
+        }
+        assert!(err.message().unwrap().contains(direction));
+    }
+
+    #[test]
+    fn test_resource_flipped_to_group() {
+        let key = KeyType::<u32>(1, false);
+        let storage_layout =
+            create_struct_layout(create_aggregator_storage_layout(MoveTypeLayout::U64));
+        let layout = create_struct_layout(create_aggregator_layout_u64());
+        let value = create_state_value(
+            &create_struct_value(create_aggregator_value_u64(25, 30)),
+            &storage_layout,
+        );
+        let data = HashMap::from([(key, value)]);
+
+        let holder = ComparisonHolder::new(data, 1000);
+        // A transaction reads the key as a resource with delayed fields.
+        let views = holder.new_view();
+        assert_ok!(views.get_resource_state_value(&key, Some(&layout)));
+
+        // A later transaction reads the key as a resource group.
+        let views = holder.new_view();
+        for view in [&views.latest_view_seq, &views.latest_view_par] {
+            let [mode, shard] = view.latest_view.counter_labels().values();
+            let flip_count = || {
+                counters::RESOURCE_GROUP_REPRESENTATION_FLIP_COUNT
+                    .with_label_values(&[mode, shard, "resource_to_group"])
+                    .get()
+            };
+            let initial_flip_count = flip_count();
+
+            let err = view.resource_group_size(&key).unwrap_err();
+            assert_representation_flip_error(view, err, "resource_to_group");
+            let err = view.get_resource_from_group(&key, &5, None).unwrap_err();
+            assert_representation_flip_error(view, err, "resource_to_group");
+            assert_eq!(flip_count(), initial_flip_count + 2);
+        }
+        assert!(!holder.versioned_map.group_data().contains_group(&key));
+    }
+
+    #[test]
+    fn test_group_flipped_to_resource() {
+        let group_key = KeyType::<u32>(1, false);
+        let layout = create_struct_layout(create_aggregator_layout_u64());
+        let group: BTreeMap<u32, Bytes> = BTreeMap::from([(
+            1,
+            create_state_value(&Value::u64(5), &MoveTypeLayout::U64)
+                .bytes()
+                .clone(),
+        )]);
+        let data = HashMap::from([(
+            group_key,
+            StateValue::new_legacy(bcs::to_bytes(&group).unwrap().into()),
+        )]);
+
+        let holder = ComparisonHolder::new(data, 1000);
+        // A transaction reads the key as a resource group.
+        let views = holder.new_view();
+        for view in [&views.latest_view_seq, &views.latest_view_par] {
+            assert_ok!(view.get_resource_from_group(&group_key, &1, None));
+        }
+
+        // A later transaction reads the key as a resource with delayed fields.
+        let views = holder.new_view();
+        for view in [&views.latest_view_seq, &views.latest_view_par] {
+            let [mode, shard] = view.latest_view.counter_labels().values();
+            let flip_count = || {
+                counters::RESOURCE_GROUP_REPRESENTATION_FLIP_COUNT
+                    .with_label_values(&[mode, shard, "group_to_resource"])
+                    .get()
+            };
+            let initial_flip_count = flip_count();
+
+            let err = view
+                .get_resource_state_value(&group_key, Some(&layout))
+                .unwrap_err();
+            assert_representation_flip_error(view, err, "group_to_resource");
+            let err = view
+                .get_resource_state_values(&[(group_key, Some(&layout))])
+                .unwrap_err()
+                .downcast::<PartialVMError>()
+                .unwrap();
+            assert_representation_flip_error(view, err, "group_to_resource");
+            assert_eq!(flip_count(), initial_flip_count + 2);
+
+            // Reads without delayed fields (e.g. of the group metadata) are still served.
+            assert_ok!(view.get_resource_state_value_metadata(&group_key));
+        }
+    }
+
+    #[test]
+    fn test_corrupt_resource_group() {
+        let group_key = KeyType::<u32>(1, false);
+        let short_key = KeyType::<u32>(2, false);
+        // Two group members are declared, but the bytes of the members are truncated.
+        let bytes: Vec<u8> = [2].into_iter().chain(0..30).collect();
+        let data = HashMap::from([
+            (group_key, StateValue::new_legacy(bytes.into())),
+            (short_key, StateValue::new_legacy(vec![2, 0, 1].into())),
+        ]);
+
+        let holder = ComparisonHolder::new(data, 1000);
+        for (key, expected) in [
+            (
+                group_key,
+                "31 bytes, starting with [2, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14]",
+            ),
+            (short_key, "3 bytes, starting with [2, 0, 1]"),
+        ] {
+            let views = holder.new_view();
+            for view in [&views.latest_view_seq, &views.latest_view_par] {
+                let err = view.get_resource_from_group(&key, &5, None).unwrap_err();
+                assert_eq!(
+                    err.major_status(),
+                    StatusCode::UNEXPECTED_DESERIALIZATION_ERROR
+                );
+                let message = err.message().unwrap();
+                assert!(message.contains(&format!("{:?}", key)));
+                assert!(message.contains(expected), "{}", message);
+                // A corrupt group in storage is not due to speculation.
+                assert!(view.is_incorrect_use());
+            }
+        }
+    }
+
+    #[test]
+    fn test_interned_read_keys() {
+        let key = KeyType::<u32>(1, false);
+        let other_key = KeyType::<u32>(2, false);
+        let state_value = create_state_value(&Value::u64(12321), &MoveTypeLayout::U64);
+        let data = HashMap::from([(key, state_value.clone()), (other_key, state_value)]);
+
+        let holder = ComparisonHolder::new(data, 1000);
+        let views = holder.new_view();
+        let other_views = holder.new_view();
+        for _ in 0..3 {
+            assert_ok!(views.latest_view_par.get_resource_state_value(&key, None));
+            assert_ok!(views.latest_view_par.resource_exists(&key));
+            assert_ok!(other_views
+                .latest_view_par
+                .get_resource_state_value(&key, None));
+        }
+        assert_ok!(views.latest_view_par.resource_exists(&other_key));
+
+        // Both views captured the reads, but each key was allocated once.
+        let captured_reads = views.latest_view_par.take_parallel_reads().unwrap();
+        let other_captured_reads = other_views.latest_view_par.take_parallel_reads().unwrap();
+        assert_eq!(holder.key_interner.num_keys(), 2);
+        let interned_key = holder.key_interner.intern(&key);
+        // Held by the interner, the captured reads of both views, and interned_key.
+        assert_eq!(Arc::strong_count(&interned_key), 4);
+        let interned_other_key = holder.key_interner.intern(&other_key);
+        assert_eq!(Arc::strong_count(&interned_other_key), 3);
+
+        assert!(captured_reads.validate_data_reads(holder.versioned_map.data(), 1));
+        assert!(other_captured_reads.validate_data_reads(holder.versioned_map.data(), 1));
+
+        // A write by transaction 0 invalidates the reads of both views.
+        holder.versioned_map.data().write(
+            key,
+            0,
+            0,
+            Arc::new(ValueType::with_len_and_metadata(1, raw_metadata(1))),
+            None,
+        );
+        assert!(!captured_reads.validate_data_reads(holder.versioned_map.data(), 1));
+        assert!(!other_captured_reads.validate_data_reads(holder.versioned_map.data(), 1));
+
+        drop(captured_reads);
+        drop(other_captured_reads);
+        assert_eq!(Arc::strong_count(&interned_key), 2);
+    }
+
+    #[test]
+    fn test_eager_group_member_patching() {
+        let group_key = KeyType::<u32>(1, false);
+        let storage_layout =
+            create_struct_layout(create_aggregator_storage_layout(MoveTypeLayout::U64));
+        let layout = create_struct_layout(create_aggregator_layout_u64());
+        let aggregator_value = create_state_value(
+            &create_struct_value(create_aggregator_value_u64(25, 30)),
+            &storage_layout,
+        );
+        let u64_value = create_state_value(&Value::u64(12321), &MoveTypeLayout::U64);
+        let group: BTreeMap<u32, Bytes> = BTreeMap::from([
+            (1, aggregator_value.bytes().clone()),
+            (2, u64_value.bytes().clone()),
+            (3, u64_value.bytes().clone()),
+        ]);
+        let data = HashMap::from([(
+            group_key,
+            StateValue::new_legacy(bcs::to_bytes(&group).unwrap().into()),
+        )]);
+
+        let start_counter = 1000;
+        let id = DelayedFieldID::new_with_width(start_counter, 8);
+        let patched_aggregator_value = create_state_value(
+            &create_struct_value(create_aggregator_value_u64(id.as_u64(), 30)),
+            &storage_layout,
+        );
+
+        let holder = ComparisonHolder::new(data, start_counter);
+        // Layouts of tags 1 and 2 are known, while tag 3 has not been read.
+        let registry = TagLayoutRegistry::new();
+        registry.record(&1, Some(&layout));
+        registry.record(&2, None);
+
+        let latest_view_par = LatestView::<TestTransactionType, MockStateView, MockExecutable>::new(
+            &holder.base_view,
+            ViewState::Sync(ParallelState::new(
+                &holder.versioned_map,
+                &holder.scheduler,
+                holder.start_counter,
+                &holder.counter,
+                &holder.key_interner,
+                Some(&registry),
+                None,
+                ParallelStateConfig::default(),
+            )),
+            1,
+        );
+        let latest_view_seq = LatestView::<TestTransactionType, MockStateView, MockExecutable>::new(
+            &holder.holder.base_view,
+            ViewState::Unsync(SequentialStateRef::Owned(SequentialState::new(
+                &holder.holder.unsync_map,
+                start_counter,
+                &holder.holder.counter,
+                false,
+                Some(&registry),
+                None,
+                None,
+            ))),
+            1,
+        );
+
+        assert_ok!(latest_view_par.initialize_mvhashmap_base_group_contents(&group_key));
+        assert_ok!(latest_view_seq.initialize_mvhashmap_base_group_contents(&group_key));
+
+        let fetch_par = |tag: u32| {
+            holder
+                .versioned_map
+                .group_data()
+                .fetch_tagged_data(&group_key, &tag, 1)
+                .ok()
+                .map(|(_, value_with_layout)| value_with_layout)
+        };
+        let fetch_seq = |tag: u32| {
+            holder
+                .holder
+                .unsync_map
+                .fetch_group_tagged_data(&group_key, &tag)
+                .ok()
+        };
+        for fetched in [fetch_par(1), fetch_seq(1)] {
+            assert_fetch_eq(
+                fetched,
+                Some(TransactionWrite::from_state_value(Some(
+                    patched_aggregator_value.clone(),
+                ))),
+                Some(layout.clone()),
+            );
+        }
+        for fetched in [fetch_par(2), fetch_seq(2)] {
+            assert_fetch_eq(
+                fetched,
+                Some(TransactionWrite::from_state_value(Some(u64_value.clone()))),
+                None,
+            );
+        }
+        assert_matches!(fetch_par(3), Some(ValueWithLayout::RawFromStorage(_)));
+        assert_matches!(fetch_seq(3), Some(ValueWithLayout::RawFromStorage(_)));
+
+        // Eagerly patched members are read without patching.
+        for latest_view in [&latest_view_par, &latest_view_seq] {
+            let group_read = latest_view
+                .latest_view
+                .get_resource_group_state()
+                .read_cached_group_tagged_data(
+                    1,
+                    &group_key,
+                    &1,
+                    ReadKind::Value,
+                    Some(&layout),
+                    &|_: &ValueType, _: Option<&MoveTypeLayout>| -> PartialVMResult<ValueType> {
+                        unreachable!("Eagerly patched value must not be patched on read")
+                    },
+                )
+                .unwrap();
+            assert_eq!(
+                group_read.into_value().0,
+                Some(patched_aggregator_value.bytes().clone())
+            );
+        }
+
+        // Reads record the layouts of the tags.
+        assert_ok_eq!(
+            latest_view_par.get_resource_from_group(&group_key, &3, None),
+            Some(u64_value.bytes().clone())
+        );
+        assert_some_eq!(registry.get(&3), None);
+    }
+
+    #[test]
+    fn test_resource_size_in_group_with_recorded_layout() {
+        let group_key = KeyType::<u32>(1, false);
+        let storage_layout =
+            create_struct_layout(create_aggregator_storage_layout(MoveTypeLayout::U64));
+        let layout = create_struct_layout(create_aggregator_layout_u64());
+        let aggregator_value = create_state_value(
+            &create_struct_value(create_aggregator_value_u64(25, 30)),
+            &storage_layout,
+        );
+        let group: BTreeMap<u32, Bytes> = BTreeMap::from([(1, aggregator_value.bytes().clone())]);
+        let data = HashMap::from([(
+            group_key,
+            StateValue::new_legacy(bcs::to_bytes(&group).unwrap().into()),
+        )]);
+
+        let start_counter = 1000;
+        let id = DelayedFieldID::new_with_width(start_counter, 8);
+        let patched_aggregator_value = create_state_value(
+            &create_struct_value(create_aggregator_value_u64(id.as_u64(), 30)),
+            &storage_layout,
+        );
+
+        let holder = ComparisonHolder::new(data, start_counter);
+        let registry = TagLayoutRegistry::new();
+        let latest_view_par = LatestView::<TestTransactionType, MockStateView, MockExecutable>::new(
+            &holder.base_view,
+            ViewState::Sync(ParallelState::new(
+                &holder.versioned_map,
+                &holder.scheduler,
+                holder.start_counter,
+                &holder.counter,
+                &holder.key_interner,
+                Some(&registry),
+                None,
+                ParallelStateConfig::default(),
+            )),
+            1,
+        );
+        let latest_view_seq = LatestView::<TestTransactionType, MockStateView, MockExecutable>::new(
+            &holder.holder.base_view,
+            ViewState::Unsync(SequentialStateRef::Owned(SequentialState::new(
+                &holder.holder.unsync_map,
+                start_counter,
+                &holder.holder.counter,
+                false,
+                Some(&registry),
+                None,
+                None,
+            ))),
+            1,
+        );
+
+        // The group is initialized before the layout of the tag is recorded (e.g. by a read
+        // of another transaction), so the member is not eagerly patched.
+        assert_ok!(latest_view_par.initialize_mvhashmap_base_group_contents(&group_key));
+        assert_ok!(latest_view_seq.initialize_mvhashmap_base_group_contents(&group_key));
+        registry.record(&1, Some(&layout));
+
+        for latest_view in [&latest_view_par, &latest_view_seq] {
+            assert_ok_eq!(
+                latest_view.resource_size_in_group(&group_key, &1),
+                aggregator_value.bytes().len()
+            );
+        }
+
+        // The size read installed the base value with the recorded layout.
+        assert_fetch_eq(
+            holder
+                .versioned_map
+                .group_data()
+                .fetch_tagged_data(&group_key, &1, 1)
+                .ok()
+                .map(|(_, value_with_layout)| value_with_layout),
+            Some(TransactionWrite::from_state_value(Some(
+                patched_aggregator_value.clone(),
+            ))),
+            Some(layout.clone()),
+        );
+        assert_fetch_eq(
+            holder
+                .holder
+                .unsync_map
+                .fetch_group_tagged_data(&group_key, &1)
+                .ok(),
+            Some(TransactionWrite::from_state_value(Some(
+                patched_aggregator_value,
+            ))),
+            Some(layout),
+        );
+    }
+
+    #[test]
+    fn test_rollback_minted_delayed_fields_impl() {
+        let mut view = FakeVersionedDelayedFieldView::default();
+        let minted_ids: Vec<_> = (0..3).map(DelayedFieldID::new_for_test_for_u64).collect();
+        for id in &minted_ids {
+            view.set_value(*id, DelayedFieldValue::Aggregator(10));
+        }
+        let other_id = DelayedFieldID::new_for_test_for_u64(3);
+        view.set_value(other_id, DelayedFieldValue::Snapshot(20));
+
+        let minted_delayed_field_ids = RefCell::new(minted_ids.iter().cloned().collect());
+        assert_ok!(rollback_minted_delayed_fields_impl::<TestTransactionType>(
+            &minted_delayed_field_ids,
+            &view
+        ));
+        assert!(minted_ids.iter().all(|id| !view.contains(id)));
+        assert!(view.contains(&other_id));
+        assert!(minted_delayed_field_ids.borrow().is_empty());
+
+        // Nothing left to roll back.
+        assert_ok!(rollback_minted_delayed_fields_impl::<TestTransactionType>(
+            &minted_delayed_field_ids,
+            &view
+        ));
+    }
+
+    #[test]
+    fn test_confirm_patched_base_value_impl() {
+        let ids: Vec<_> = (0..4).map(DelayedFieldID::new_for_test_for_u64).collect();
+        let minted_delayed_field_ids = RefCell::new(ids.iter().cloned().collect::<HashSet<_>>());
+        let patched_delayed_field_ids = RefCell::new(vec![ids[0], ids[1]]);
+
+        // The base value exchanged by a concurrent reader was kept, so the ids of the patch
+        // are not referenced by the shared map and remain minted (to be rolled back).
+        confirm_patched_base_value_impl::<TestTransactionType>(
+            &patched_delayed_field_ids,
+            &minted_delayed_field_ids,
+            false,
+        );
+        assert!(patched_delayed_field_ids.borrow().is_empty());
+        assert_eq!(minted_delayed_field_ids.borrow().len(), 4);
+
+        // The patched value was installed, and its ids are untracked.
+        patched_delayed_field_ids
+            .borrow_mut()
+            .extend([ids[2], ids[3]]);
+        confirm_patched_base_value_impl::<TestTransactionType>(
+            &patched_delayed_field_ids,
+            &minted_delayed_field_ids,
+            true,
+        );
+        assert!(patched_delayed_field_ids.borrow().is_empty());
+        assert_eq!(
+            *minted_delayed_field_ids.borrow(),
+            HashSet::from([ids[0], ids[1]])
+        );
+    }
+
+    // Drives the upgrade of the base value from RawFromStorage to Exchanged (patch, set and
+    // refetch) of ParallelState::read_cached_data_by_kind from concurrent threads: two readers
+    // patch the same key (with distinct values), while a lower transaction installs a
+    // speculative write. No reader may serve the RawFromStorage value at the Value kind, and
+    // the exchanged value served by the readers of the base value is never replaced.
+    #[test]
+    fn test_concurrent_base_value_exchange() {
+        let key = KeyType::<u32>(1, false);
+        let layout = MoveTypeLayout::U64;
+        let raw_value = ValueType::from_value(vec![0], true);
+        let write_value = ValueType::from_value(vec![100], true);
+
+        for _ in 0..200 {
+            let versioned_map =
+                MVHashMap::<KeyType<u32>, u32, ValueType, MockExecutable, DelayedFieldID>::new();
+            let scheduler = Scheduler::new(3);
+            let counter = AtomicU32::new(0);
+            let key_interner = KeyInterner::new();
+            let barrier = std::sync::Barrier::new(3);
+
+            let served: Vec<_> = std::thread::scope(|s| {
+                let readers: Vec<_> = [1, 2]
+                    .into_iter()
+                    .map(|txn_idx: TxnIndex| {
+                        let (versioned_map, scheduler, counter, key_interner, barrier) = (
+                            &versioned_map,
+                            &scheduler,
+                            &counter,
+                            &key_interner,
+                            &barrier,
+                        );
+                        let (key, layout, raw_value) = (&key, &layout, &raw_value);
+                        s.spawn(move || {
+                            let state = ParallelState::<TestTransactionType, MockExecutable>::new(
+                                versioned_map,
+                                scheduler,
+                                0,
+                                counter,
+                                key_interner,
+                                None,
+                                None,
+                                ParallelStateConfig::default(),
+                            );
+                            let patched_value = ValueType::from_value(vec![txn_idx as u8], true);
+                            let read = || {
+                                state.read_cached_data_by_kind(
+                                    txn_idx,
+                                    key,
+                                    ReadKind::Value,
+                                    UnknownOrLayout::Known(Some(layout)),
+                                    &|_, _| Ok(patched_value.clone()),
+                                )
+                            };
+
+                            barrier.wait();
+                            let mut result = read();
+                            if matches!(result, Ok(ReadResult::Uninitialized)) {
+                                // As by LatestView, the value from storage is set as the base
+                                // value, and the key is read again.
+                                versioned_map.data().set_base_value(
+                                    key.clone(),
+                                    ValueWithLayout::RawFromStorage(Arc::new(raw_value.clone())),
+                                );
+                                result = read();
+                            }
+                            match result {
+                                Ok(ReadResult::Value(Some(state_value), _)) => state_value,
+                                result => panic!("Unexpected read result {:?}", result),
+                            }
+                        })
+                    })
+                    .collect();
+
+                s.spawn(|| {
+                    barrier.wait();
+                    versioned_map.data().write(
+                        key.clone(),
+                        0,
+                        0,
+                        Arc::new(write_value.clone()),
+                        None,
+                    );
+                });
+
+                readers
+                    .into_iter()
+                    .map(|reader| reader.join().unwrap())
+                    .collect()
+            });
+
+            let base_value = match versioned_map.data().fetch_data(&key, 0) {
+                Ok(MVDataOutput::Versioned(_, base_value)) => base_value,
+                result => panic!("Base value must be set, got {:?}", result),
+            };
+            for state_value in served {
+                assert_ne!(Some(state_value.bytes()), raw_value.bytes());
+                if Some(state_value.bytes()) != write_value.bytes() {
+                    // The base value that was served is the exchanged value in the map.
+                    assert_matches!(
+                        &base_value,
+                        ValueWithLayout::Exchanged(value, _)
+                            if value.bytes() == Some(state_value.bytes())
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_read_committed_delayed_field_values_impl() {
+        /*
+            layout = Struct {
+                aggregators: vec![Aggregator<u64>]
+            }
+        */
+        let storage_layout = create_struct_layout(create_vector_layout(
+            create_aggregator_storage_layout(MoveTypeLayout::U64),
+        ));
+        let layout = create_struct_layout(create_vector_layout(create_aggregator_layout_u64()));
+        let ids: Vec<_> = (10..15)
+            .map(|index| DelayedFieldID::new_with_width(index, 8))
+            .collect();
+        let exchanged_value = create_struct_value(create_vector_value(
+            ids.iter()
+                .map(|id| create_aggregator_value_u64(id.as_u64(), 100))
+                .collect(),
+        ));
+        let bytes: Bytes = exchanged_value
+            .simple_serialize(&storage_layout)
+            .unwrap()
+            .into();
+
+        let mut view = FakeVersionedDelayedFieldView::default();
+        for (i, id) in ids.iter().enumerate() {
+            view.set_value(*id, DelayedFieldValue::Aggregator(i as u128));
+        }
+
+        let extracted_ids: Vec<_> = extract_identifiers_from_value::<TestTransactionType>(
+            &bytes,
+            &layout,
+            &ViewCounterLabels::new(true, None),
+        )
+        .unwrap()
+        .into_iter()
+        .collect();
+        assert_eq!(extracted_ids.len(), ids.len());
+        let values = assert_ok!(read_committed_delayed_field_values_impl::<
+            TestTransactionType,
+        >(&view, &extracted_ids, 3));
+        assert_eq!(
+            values,
+            ids.iter()
+                .enumerate()
+                .map(|(i, id)| (*id, DelayedFieldValue::Aggregator(i as u128)))
+                .collect()
+        );
+        // All values are read in one batch, rather than one read per identifier.
+        assert_eq!(*view.num_batched_committed_reads.borrow(), 1);
+        assert_eq!(*view.num_committed_reads.borrow(), 0);
+
+        // A missing value is an invariant violation, as for the reads of individual values.
+        let missing_id = DelayedFieldID::new_with_width(15, 8);
+        assert_err!(read_committed_delayed_field_values_impl::<
+            TestTransactionType,
+        >(&view, &[ids[0], missing_id], 3));
+    }
+
+    #[test]
+    fn test_rollback_minted_delayed_fields() {
+        let storage_layout =
+            create_struct_layout(create_aggregator_storage_layout(MoveTypeLayout::U64));
+        let layout = create_struct_layout(create_aggregator_layout_u64());
+        let state_value = create_state_value(
+            &create_struct_value(create_aggregator_value_u64(25, 30)),
+            &storage_layout,
+        );
+        let key = KeyType::<u32>(1, false);
+        let data = HashMap::from([(key, state_value.clone())]);
+
+        let start_counter = 1000;
+        let unpublished_id = DelayedFieldID::new_with_width(start_counter, 8);
+        let published_id = DelayedFieldID::new_with_width(start_counter + 1, 8);
+        let holder = ComparisonHolder::new(data, start_counter);
+        let views = holder.new_view();
+
+        // Ids are minted, but the replaced value is not installed in the shared map.
+        for latest_view in [&views.latest_view_par, &views.latest_view_seq] {
+            let (_, ids) = latest_view
+                .replace_values_with_identifiers(state_value.clone(), &layout)
+                .unwrap();
+            assert_eq!(ids, HashSet::from([unpublished_id]));
+        }
+        // The patched base value of the resource references the id minted for it.
+        assert_some!(views.get_resource_state_value(&key, Some(&layout)).unwrap());
+
+        for latest_view in [&views.latest_view_par, &views.latest_view_seq] {
+            assert_ok!(latest_view.rollback_minted_delayed_fields());
+        }
+        assert_err_eq!(
+            holder
+                .versioned_map
+                .delayed_fields()
+                .read_latest_committed_value(&unpublished_id, 1, ReadPosition::BeforeCurrentTxn),
+            MVDelayedFieldsError::NotFound
+        );
+        assert_ok_eq!(
+            holder
+                .versioned_map
+                .delayed_fields()
+                .read_latest_committed_value(&published_id, 1, ReadPosition::BeforeCurrentTxn),
+            DelayedFieldValue::Aggregator(25)
+        );
+        assert_none!(holder
+            .holder
+            .unsync_map
+            .fetch_delayed_field(&unpublished_id));
+        assert_some_eq!(
+            holder.holder.unsync_map.fetch_delayed_field(&published_id),
+            DelayedFieldValue::Aggregator(25)
+        );
+
+        // The counters are not rewound.
+        assert_eq!(holder.counter.load(Ordering::SeqCst), start_counter + 2);
+        assert_eq!(*holder.holder.counter.borrow(), start_counter + 2);
+    }
+
+    #[test]
+    fn test_delayed_field_exists() {
+        let id = DelayedFieldID::new_with_width(1000, 8);
+        let missing_id = DelayedFieldID::new_with_width(1001, 8);
+        let holder = ComparisonHolder::new(HashMap::new(), 1002);
+        holder
+            .versioned_map
+            .delayed_fields()
+            .set_base_value(id, DelayedFieldValue::Aggregator(10));
+        holder
+            .holder
+            .unsync_map
+            .set_base_delayed_field(id, DelayedFieldValue::Aggregator(10));
+
+        // One execution only checks the existence, the other also reads the value.
+        let exists_views = holder.new_view();
+        let value_views = holder.new_view();
+        for latest_view in [
+            &exists_views.latest_view_par,
+            &exists_views.latest_view_seq,
+            &value_views.latest_view_par,
+        ] {
+            assert_ok_eq!(latest_view.delayed_field_exists(&id), true);
+            assert_ok_eq!(latest_view.delayed_field_exists(&missing_id), false);
+        }
+        assert_ok_eq!(
+            value_views.latest_view_par.get_delayed_field_value(&id),
+            DelayedFieldValue::Aggregator(10)
+        );
+
+        let exists_reads = exists_views.latest_view_par.take_parallel_reads().unwrap();
+        let value_reads = value_views.latest_view_par.take_parallel_reads().unwrap();
+        assert_some_eq!(
+            exists_reads.get_delayed_field_by_kind(&id, DelayedFieldReadKind::Exists),
+            DelayedFieldRead::Exists { exists: true }
+        );
+        // The value read replaced the existence check.
+        assert_some_eq!(
+            value_reads.get_delayed_field_by_kind(&id, DelayedFieldReadKind::Exists),
+            DelayedFieldRead::Value {
+                value: DelayedFieldValue::Aggregator(10),
+            }
+        );
+
+        // A lower transaction only applies a delta.
+        let delayed_fields = holder.versioned_map.delayed_fields();
+        assert_ok!(delayed_fields.record_change(
+            id,
+            0,
+            DelayedEntry::Apply(DelayedApplyEntry::AggregatorDelta {
+                delta: DeltaOp::new(SignedU128::Positive(5), 1000, DeltaHistory::new()),
+            }),
+        ));
+        assert!(delayed_fields.try_commit(0, vec![id]).is_ok());
+
+        assert_ok_eq!(
+            exists_reads.validate_delayed_field_reads(delayed_fields, 1),
+            true
+        );
+        assert_ok_eq!(
+            value_reads.validate_delayed_field_reads(delayed_fields, 1),
+            false
+        );
+    }
+
+    #[test]
+    fn test_delayed_field_latest_committed_value() {
+        let id = DelayedFieldID::new_with_width(1000, 8);
+        let holder = ComparisonHolder::new(HashMap::new(), 1001);
+        holder
+            .versioned_map
+            .delayed_fields()
+            .set_base_value(id, DelayedFieldValue::Aggregator(10));
+        holder
+            .holder
+            .unsync_map
+            .set_base_delayed_field(id, DelayedFieldValue::Aggregator(10));
+
+        // A lower transaction applies a delta, which is not yet committed.
+        let delayed_fields = holder.versioned_map.delayed_fields();
+        assert_ok!(delayed_fields.record_change(
+            id,
+            0,
+            DelayedEntry::Apply(DelayedApplyEntry::AggregatorDelta {
+                delta: DeltaOp::new(SignedU128::Positive(5), 1000, DeltaHistory::new()),
+            }),
+        ));
+
+        let views = holder.new_view();
+        let read_positions = [
+            ReadPosition::BeforeCurrentTxn,
+            ReadPosition::AfterCurrentTxn,
+        ];
+        for latest_view in [&views.latest_view_par, &views.latest_view_seq] {
+            for read_position in read_positions {
+                assert_ok_eq!(
+                    latest_view.get_delayed_field_latest_committed_value(&id, read_position),
+                    DelayedFieldValue::Aggregator(10)
+                );
+            }
+        }
+        // The speculative value (with the pending delta) is inconsistent with the committed
+        // value read, and cannot be returned by the same execution.
+        assert_err!(views.latest_view_par.get_delayed_field_value(&id));
+        assert_eq!(
+            views
+                .latest_view_seq
+                .take_sequential_reads()
+                .unwrap()
+                .delayed_field_reads,
+            HashSet::from([id])
+        );
+
+        let committed_reads = holder.new_view().latest_view_par;
+        assert_ok_eq!(
+            committed_reads
+                .get_delayed_field_latest_committed_value(&id, ReadPosition::BeforeCurrentTxn),
+            DelayedFieldValue::Aggregator(10)
+        );
+        let committed_reads = committed_reads.take_parallel_reads().unwrap();
+        assert_some_eq!(
+            committed_reads.get_delayed_field_by_kind(&id, DelayedFieldReadKind::Exists),
+            DelayedFieldRead::Committed {
+                value: DelayedFieldValue::Aggregator(10),
+            }
+        );
+
+        // The read is validated against the committed value only, i.e. it remains valid
+        // until the delta of the lower transaction is committed.
+        assert_ok_eq!(
+            committed_reads.validate_delayed_field_reads(delayed_fields, 1),
+            true
+        );
+        assert!(delayed_fields.try_commit(0, vec![id]).is_ok());
+        assert_ok_eq!(
+            committed_reads.validate_delayed_field_reads(delayed_fields, 1),
+            false
+        );
+    }
+
+    #[test]
+    fn test_capture_disabled() {
+        let key = KeyType::<u32>(1, false);
+        let state_value = StateValue::new_legacy(vec![5_u8; 10].into());
+        let data = HashMap::from([(key, state_value.clone())]);
+        let id = DelayedFieldID::new_with_width(1000, 8);
+
+        let holder = ComparisonHolder::new(data, 1001);
+        holder
+            .versioned_map
+            .delayed_fields()
+            .set_base_value(id, DelayedFieldValue::Aggregator(10));
+        let latest_view_par = LatestView::<TestTransactionType, MockStateView, MockExecutable>::new(
+            &holder.base_view,
+            ViewState::Sync(
+                ParallelState::new(
+                    &holder.versioned_map,
+                    &holder.scheduler,
+                    holder.start_counter,
+                    &holder.counter,
+                    &holder.key_interner,
+                    None,
+                    None,
+                    ParallelStateConfig::default(),
+                )
+                .with_capture_disabled(),
+            ),
+            1,
+        );
+
+        // The reads are served (also when repeated), but not recorded.
+        for _ in 0..2 {
+            assert_ok_eq!(
+                latest_view_par.get_resource_state_value(&key, None),
+                Some(state_value.clone())
+            );
+        }
+        assert_ok_eq!(
+            latest_view_par.delayed_field_try_add_delta_outcome(
+                &id,
+                &SignedU128::Positive(0),
+                &SignedU128::Positive(50),
+                100
+            ),
+            true
+        );
+        // Without the recorded history, the outcome of the subsequent try_add (after the
+        // delta of the successful one) is computed from the committed value.
+        assert_ok_eq!(
+            latest_view_par.delayed_field_try_add_delta_outcome(
+                &id,
+                &SignedU128::Positive(50),
+                &SignedU128::Positive(50),
+                100
+            ),
+            false
+        );
+        assert_ok_eq!(
+            latest_view_par.delayed_field_try_add_delta_outcome(
+                &id,
+                &SignedU128::Positive(50),
+                &SignedU128::Positive(40),
+                100
+            ),
+            true
+        );
+        assert_ok_eq!(
+            latest_view_par.get_delayed_field_value(&id),
+            DelayedFieldValue::Aggregator(10)
+        );
+        assert!(!latest_view_par.is_incorrect_use());
+
+        let captured_reads = latest_view_par.take_parallel_reads().unwrap();
+        assert_none!(captured_reads.get_by_kind(&key, None, ReadKind::Exists));
+        assert_none!(captured_reads.get_delayed_field_by_kind(&id, DelayedFieldReadKind::Exists));
+        assert!(captured_reads.get_read_summary().is_empty());
+    }
+
+    #[test]
+    fn test_try_add_delta_all_or_nothing() {
+        let id_a = DelayedFieldID::new_with_width(1000, 8);
+        let id_b = DelayedFieldID::new_with_width(1001, 8);
+        let holder = ComparisonHolder::new(HashMap::new(), 1002);
+        for (id, value) in [(id_a, 100), (id_b, 500)] {
+            holder
+                .versioned_map
+                .delayed_fields()
+                .set_base_value(id, DelayedFieldValue::Aggregator(value));
+            holder
+                .holder
+                .unsync_map
+                .set_base_delayed_field(id, DelayedFieldValue::Aggregator(value));
+        }
+
+        let views = holder.new_view();
+        let zero = SignedU128::Positive(0);
+        let entry = |id, delta| (id, zero, SignedU128::Positive(delta), 600);
+        let failing_batch = [entry(id_a, 200), entry(id_b, 200)];
+        let succeeding_batch = [entry(id_a, 200), entry(id_b, 50)];
+        for latest_view in [&views.latest_view_par, &views.latest_view_seq] {
+            assert_ok_eq!(
+                latest_view.try_add_delta_all_or_nothing(&failing_batch),
+                vec![true, false]
+            );
+        }
+        // The history of id_a is rolled back (no read is captured, as there was none).
+        match &views.latest_view_par.latest_view {
+            ViewState::Sync(state) => {
+                let captured_reads = state.captured_reads.borrow();
+                assert_none!(captured_reads.get_delayed_field_read(&id_a));
+                assert_some!(captured_reads.get_delayed_field_read(&id_b));
+            },
+            ViewState::Unsync(_) => unreachable!("Parallel view expected"),
+        }
+
+        for latest_view in [&views.latest_view_par, &views.latest_view_seq] {
+            assert_ok_eq!(
+                latest_view.try_add_delta_all_or_nothing(&succeeding_batch),
+                vec![true, true]
+            );
+        }
+        assert!(!views.latest_view_par.is_incorrect_use());
+
+        let captured_reads = views.latest_view_par.take_parallel_reads().unwrap();
+        assert_some_eq!(
+            captured_reads.get_delayed_field_read(&id_a),
+            &DelayedFieldRead::HistoryBounded {
+                restriction: DeltaHistory {
+                    max_achieved_positive_delta: 200,
+                    ..DeltaHistory::new()
+                },
+                max_value: 600,
+                inner_aggregator_value: 100,
+            }
+        );
+        assert_some_eq!(
+            captured_reads.get_delayed_field_read(&id_b),
+            &DelayedFieldRead::HistoryBounded {
+                restriction: DeltaHistory {
+                    max_achieved_positive_delta: 50,
+                    min_overflow_positive_delta: Some(200),
+                    ..DeltaHistory::new()
+                },
+                max_value: 600,
+                inner_aggregator_value: 500,
+            }
+        );
+    }
+
+    #[test]
+    fn test_captured_delayed_field_ids() {
+        let storage_layout =
+            create_struct_layout(create_aggregator_storage_layout(MoveTypeLayout::U64));
+        let layout = create_struct_layout(create_aggregator_layout_u64());
+        let state_value = create_state_value(
+            &create_struct_value(create_aggregator_value_u64(25, 30)),
+            &storage_layout,
+        );
+        let keys: Vec<_> = (1..=3).map(|i| KeyType::<u32>(i, false)).collect();
+        let data: HashMap<_, _> = keys.iter().map(|key| (*key, state_value.clone())).collect();
+
+        let start_counter = 1000;
+        let ids: Vec<_> = (0..3)
+            .map(|i| DelayedFieldID::new_with_width(start_counter + i, 8))
+            .collect();
+        let holder = ComparisonHolder::new(data, start_counter);
+        let views = holder.new_view();
+
+        for key in &keys {
+            assert_some!(views.get_resource_state_value(key, Some(&layout)).unwrap());
+        }
+        assert_ok_eq!(
+            views.get_delayed_field_value(&ids[1]),
+            DelayedFieldValue::Aggregator(25)
+        );
+        for latest_view in [&views.latest_view_par, &views.latest_view_seq] {
+            assert_ok_eq!(
+                latest_view.delayed_field_try_add_delta_outcome(
+                    &ids[2],
+                    &SignedU128::Positive(0),
+                    &SignedU128::Positive(5),
+                    30,
+                ),
+                true
+            );
+        }
+
+        // The read values have not been scanned yet.
+        for latest_view in [&views.latest_view_par, &views.latest_view_seq] {
+            assert_eq!(
+                latest_view.captured_delayed_field_ids(),
+                BTreeSet::from([ids[1], ids[2]])
+            );
+        }
+
+        // Only the value of the first resource is scanned.
+        assert!(views
+            .get_reads_needing_exchange(&HashSet::new(), &HashSet::from([keys[1], keys[2]]))
+            .unwrap()
+            .is_empty());
+        for latest_view in [&views.latest_view_par, &views.latest_view_seq] {
+            assert_eq!(
+                latest_view.captured_delayed_field_ids(),
+                BTreeSet::from([ids[0], ids[1], ids[2]])
+            );
+        }
+    }
+
+    #[test]
+    fn test_reads_needing_exchange_size_threshold() {
+        let small_key = KeyType::<u32>(1, false);
+        let large_key = KeyType::<u32>(2, false);
+        let small_storage_layout =
+            create_struct_layout(create_aggregator_storage_layout(MoveTypeLayout::U64));
+        let small_layout = create_struct_layout(create_aggregator_layout_u64());
+        let large_layout = |aggregator_layout| {
+            MoveTypeLayout::Struct(MoveStructLayout::new(vec![
+                aggregator_layout,
+                MoveTypeLayout::Vector(Box::new(MoveTypeLayout::U8)),
+            ]))
+        };
+        let large_storage_layout =
+            large_layout(create_aggregator_storage_layout(MoveTypeLayout::U64));
+        let large_value = Value::struct_(Struct::pack(vec![
+            create_aggregator_value_u64(25, 30),
+            Value::vector_u8(vec![5_u8; 1000]),
+        ]));
+        let data = HashMap::from([
+            (
+                small_key,
+                create_state_value(
+                    &create_struct_value(create_aggregator_value_u64(25, 30)),
+                    &small_storage_layout,
+                ),
+            ),
+            (
+                large_key,
+                create_state_value(&large_value, &large_storage_layout),
+            ),
+        ]);
+
+        let start_counter = 1000;
+        let holder = ComparisonHolder::new(data, start_counter);
+        let latest_view_seq = create_sequential_latest_view(&holder.holder);
+        let latest_view_par = LatestView::<TestTransactionType, MockStateView, MockExecutable>::new(
+            &holder.base_view,
+            ViewState::Sync(ParallelState::new(
+                &holder.versioned_map,
+                &holder.scheduler,
+                holder.start_counter,
+                &holder.counter,
+                &holder.key_interner,
+                None,
+                None,
+                ParallelStateConfig {
+                    exchange_size_threshold: Some(100),
+                    ..ParallelStateConfig::default()
+                },
+            )),
+            1,
+        );
+
+        let large_layout = large_layout(create_aggregator_layout_u64());
+        for latest_view in [&latest_view_par, &latest_view_seq] {
+            assert_some!(latest_view
+                .get_resource_state_value(&small_key, Some(&small_layout))
+                .unwrap());
+            assert_some!(latest_view
+                .get_resource_state_value(&large_key, Some(&large_layout))
+                .unwrap());
+        }
+        let ids = HashSet::from([
+            DelayedFieldID::new_with_width(start_counter, 8),
+            DelayedFieldID::new_with_width(start_counter + 1, 8),
+        ]);
+
+        let ViewState::Sync(parallel_state) = &latest_view_par.latest_view else {
+            unreachable!("Parallel view must have parallel state");
+        };
+        let ViewState::Unsync(sequential_state) = &latest_view_seq.latest_view else {
+            unreachable!("Sequential view must have sequential state");
+        };
+        let reads_needing_exchange = |delayed_write_set_ids: &HashSet<DelayedFieldID>| {
+            [
+                parallel_state
+                    .captured_reads
+                    .borrow()
+                    .get_read_values_with_delayed_fields(
+                        delayed_write_set_ids,
+                        &HashSet::new(),
+                        &mut BTreeSet::new(),
+                        Some(100),
+                        &latest_view_par.delayed_field_layouts,
+                        &parallel_state.counter_labels,
+                    )
+                    .unwrap(),
+                latest_view_seq
+                    .get_reads_needing_exchange_sequential(
+                        &sequential_state.read_set.borrow().resource_reads,
+                        sequential_state.unsync_map,
+                        delayed_write_set_ids,
+                        &HashSet::new(),
+                        Some(100),
+                    )
+                    .unwrap(),
+            ]
+        };
+
+        // The large value is not scanned, and only its key is returned.
+        for reads in reads_needing_exchange(&ids) {
+            assert_eq!(reads.reads.keys().collect::<Vec<_>>(), vec![&small_key]);
+            assert_eq!(reads.fallback_keys, vec![large_key]);
+        }
+        // If no delayed fields were written, no value needs exchange.
+        for reads in reads_needing_exchange(&HashSet::new()) {
+            assert_eq!(reads, ReadsNeedingExchange::default());
+        }
+
+        // Skipping the large value does not require the fallback.
+        assert_eq!(
+            latest_view_par
+                .get_reads_needing_exchange(&ids, &HashSet::from([large_key]))
+                .unwrap()
+                .into_keys()
+                .collect::<Vec<_>>(),
+            vec![small_key]
+        );
+        assert!(!parallel_state
+            .captured_reads
+            .borrow()
+            .is_exchange_size_threshold_exceeded());
+        // Parallel execution falls back if the large value needs to be considered, which is
+        // not an invariant violation (the reads are marked for the executor instead).
+        assert_eq!(
+            latest_view_par
+                .get_reads_needing_exchange(&ids, &HashSet::new())
+                .unwrap()
+                .into_keys()
+                .collect::<Vec<_>>(),
+            vec![small_key]
+        );
+        assert!(parallel_state
+            .captured_reads
+            .borrow()
+            .is_exchange_size_threshold_exceeded());
+        assert!(!latest_view_par.is_incorrect_use());
+        assert_eq!(
+            latest_view_seq
+                .get_reads_needing_exchange(&ids, &HashSet::new())
+                .unwrap()
+                .into_keys()
+                .collect::<Vec<_>>(),
+            vec![small_key, large_key]
+        );
+
+        // The status reports the large value as quarantined in the parallel execution only.
+        assert_ok_eq!(
+            latest_view_par.exchange_status(&large_key),
+            ExchangeStatus::SkippedQuarantined
+        );
+        assert_ok_eq!(
+            latest_view_seq.exchange_status(&large_key),
+            ExchangeStatus::Exchanged {
+                ids: BTreeSet::from([DelayedFieldID::new_with_width(start_counter + 1, 8)])
+            }
+        );
+        for latest_view in [&latest_view_par, &latest_view_seq] {
+            assert_ok_eq!(
+                latest_view.exchange_status(&small_key),
+                ExchangeStatus::Exchanged {
+                    ids: BTreeSet::from([DelayedFieldID::new_with_width(start_counter, 8)])
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_exchange_event_payload() {
+        let id = DelayedFieldID::new_with_width(10, 16);
+        let storage_layout = create_snapshot_storage_layout(MoveTypeLayout::U128);
+        let layout = create_snapshot_layout(MoveTypeLayout::U128);
+        let payload: Bytes = create_snapshot_value(Value::u128(id.as_u64() as u128))
+            .simple_serialize(&storage_layout)
+            .unwrap()
+            .into();
+        let expected_payload: Bytes = create_snapshot_value(Value::u128(100))
+            .simple_serialize(&storage_layout)
+            .unwrap()
+            .into();
+
+        let holder = ComparisonHolder::new(HashMap::new(), 1000);
+        holder
+            .holder
+            .unsync_map
+            .set_base_delayed_field(id, DelayedFieldValue::Snapshot(100));
+        holder
+            .versioned_map
+            .delayed_fields()
+            .set_base_value(id, DelayedFieldValue::Snapshot(100));
+        let views = holder.new_view();
+
+        for latest_view in [&views.latest_view_par, &views.latest_view_seq] {
+            assert!(latest_view.captured_delayed_field_ids().is_empty());
+            assert_ok_eq!(
+                latest_view.exchange_event_payload(&payload, &layout),
+                (expected_payload.clone(), BTreeSet::from([id]))
+            );
+            assert_eq!(
+                latest_view.captured_delayed_field_ids(),
+                BTreeSet::from([id])
+            );
+        }
+    }
+
+    #[test]
+    fn test_exchange_without_delayed_fields() {
+        // Not a valid serialization of a u64: exchanged as is, without the round-trip.
+        let bytes = Bytes::from(vec![1, 2, 3]);
+        let state_value = StateValue::new_legacy(bytes.clone());
+        let layout = create_snapshot_layout(MoveTypeLayout::U64);
+
+        let holder = ComparisonHolder::new(HashMap::new(), 1000);
+        let views = holder.new_view();
+        for latest_view in [&views.latest_view_par, &views.latest_view_seq] {
+            let (exchanged_value, ids) = latest_view
+                .replace_values_with_identifiers(state_value.clone(), &MoveTypeLayout::U64)
+                .unwrap();
+            assert_eq!(exchanged_value, state_value);
+            assert!(ids.is_empty());
+            assert_ok_eq!(
+                latest_view.replace_identifiers_with_values(&bytes, &MoveTypeLayout::U64),
+                (bytes.clone(), HashSet::new())
+            );
+
+            // The layouts with delayed fields still go through the round-trip.
+            assert_err!(latest_view.replace_values_with_identifiers(state_value.clone(), &layout));
+            assert_err!(latest_view.replace_identifiers_with_values(&bytes, &layout));
+        }
+    }
 }
@@ -0,0 +1,225 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{counters, counters::ViewCounterLabels};
+use aptos_logger::info;
+use std::time::Duration;
+
+/// Reasons for which a view halts the speculative execution of a transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum SpeculativeHaltReason {
+    /// Inconsistent speculative reads.
+    InconsistentRead = 0,
+    /// The block execution was halted while waiting on a dependency.
+    ExecutionHalted = 1,
+    /// A dependency wait timed out (only the incarnation is aborted).
+    DependencyTimeout = 2,
+    /// Patching the delayed fields of a base value failed (an incorrect use).
+    PatchFailure = 3,
+    /// A fetched value could not be downcast to the kind of the read (an incorrect use).
+    DowncastFailure = 4,
+    /// Applying an aggregator v1 delta failed (must be due to speculation).
+    DeltaApplicationFailure = 5,
+    /// A read waited on dependencies as many times as allowed.
+    DependencyWaitLimit = 6,
+    /// Waiting on a dependency returned an error (an incorrect use).
+    DependencyWaitError = 7,
+}
+
+impl SpeculativeHaltReason {
+    pub(crate) const ALL: [Self; 8] = [
+        Self::InconsistentRead,
+        Self::ExecutionHalted,
+        Self::DependencyTimeout,
+        Self::PatchFailure,
+        Self::DowncastFailure,
+        Self::DeltaApplicationFailure,
+        Self::DependencyWaitLimit,
+        Self::DependencyWaitError,
+    ];
+
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Self::InconsistentRead => "inconsistent_read",
+            Self::ExecutionHalted => "execution_halted",
+            Self::DependencyTimeout => "dependency_timeout",
+            Self::PatchFailure => "patch_failure",
+            Self::DowncastFailure => "downcast_failure",
+            Self::DeltaApplicationFailure => "delta_application_failure",
+            Self::DependencyWaitLimit => "dependency_wait_limit",
+            Self::DependencyWaitError => "dependency_wait_error",
+        }
+    }
+}
+
+/// Statistics of the health of the view of a transaction execution (a fragment), or of all
+/// the views of a block (a summary). Fragments are merged by adding up all the fields, so the
+/// merge is associative (and commutative), and does not allocate.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ViewHealthSummary {
+    /// Halts of speculative executions, indexed by SpeculativeHaltReason.
+    num_speculative_halts: [usize; SpeculativeHaltReason::ALL.len()],
+    /// Executions that marked an incorrect use of the view (leading to the fallback).
+    pub(crate) num_incorrect_uses: usize,
+    /// Dependencies encountered by the reads, and the time spent waiting on them.
+    pub(crate) num_dependency_waits: usize,
+    pub(crate) dependency_wait_time: Duration,
+    /// Values fetched from the base view (storage), and their total size.
+    pub(crate) num_storage_fetches: usize,
+    pub(crate) storage_fetch_bytes: u64,
+    /// Values whose delayed fields were exchanged for identifiers, and their total size.
+    pub(crate) num_exchanges: usize,
+    pub(crate) exchange_bytes: u64,
+    /// Resource groups initialized from storage.
+    pub(crate) num_group_initializations: usize,
+    /// Identifiers of delayed fields generated when exchanging values.
+    pub(crate) num_minted_delayed_field_ids: usize,
+}
+
+impl ViewHealthSummary {
+    pub(crate) fn num_speculative_halts(&self, reason: SpeculativeHaltReason) -> usize {
+        self.num_speculative_halts[reason as usize]
+    }
+
+    pub(crate) fn total_speculative_halts(&self) -> usize {
+        self.num_speculative_halts.iter().sum()
+    }
+
+    pub(crate) fn record_speculative_halt(&mut self, reason: SpeculativeHaltReason) {
+        self.num_speculative_halts[reason as usize] += 1;
+    }
+
+    pub(crate) fn merge(&mut self, other: &Self) {
+        for (num_halts, other_num_halts) in self
+            .num_speculative_halts
+            .iter_mut()
+            .zip(other.num_speculative_halts)
+        {
+            *num_halts += other_num_halts;
+        }
+        self.num_incorrect_uses += other.num_incorrect_uses;
+        self.num_dependency_waits += other.num_dependency_waits;
+        self.dependency_wait_time += other.dependency_wait_time;
+        self.num_storage_fetches += other.num_storage_fetches;
+        self.storage_fetch_bytes += other.storage_fetch_bytes;
+        self.num_exchanges += other.num_exchanges;
+        self.exchange_bytes += other.exchange_bytes;
+        self.num_group_initializations += other.num_group_initializations;
+        self.num_minted_delayed_field_ids += other.num_minted_delayed_field_ids;
+    }
+
+    pub(crate) fn finish_update_counters_and_log_info(&self, counter_labels: &ViewCounterLabels) {
+        counters::update_view_health_counters(self, counter_labels);
+
+        let [mode, shard] = counter_labels.values();
+        info!(
+            speculative_halts_inconsistent_read =
+                self.num_speculative_halts(SpeculativeHaltReason::InconsistentRead),
+            speculative_halts_execution_halted =
+                self.num_speculative_halts(SpeculativeHaltReason::ExecutionHalted),
+            speculative_halts_dependency_timeout =
+                self.num_speculative_halts(SpeculativeHaltReason::DependencyTimeout),
+            speculative_halts_patch_failure =
+                self.num_speculative_halts(SpeculativeHaltReason::PatchFailure),
+            speculative_halts_downcast_failure =
+                self.num_speculative_halts(SpeculativeHaltReason::DowncastFailure),
+            speculative_halts_delta_application_failure =
+                self.num_speculative_halts(SpeculativeHaltReason::DeltaApplicationFailure),
+            speculative_halts_dependency_wait_limit =
+                self.num_speculative_halts(SpeculativeHaltReason::DependencyWaitLimit),
+            speculative_halts_dependency_wait_error =
+                self.num_speculative_halts(SpeculativeHaltReason::DependencyWaitError),
+            incorrect_uses = self.num_incorrect_uses,
+            dependency_waits = self.num_dependency_waits,
+            dependency_wait_micros = self.dependency_wait_time.as_micros() as u64,
+            storage_fetches = self.num_storage_fetches,
+            storage_fetch_bytes = self.storage_fetch_bytes,
+            exchanges = self.num_exchanges,
+            exchange_bytes = self.exchange_bytes,
+            group_initializations = self.num_group_initializations,
+            minted_delayed_field_ids = self.num_minted_delayed_field_ids,
+            "[BlockSTM]: {} execution view health (shard {})",
+            mode,
+            shard,
+        );
+    }
+}
+
+/// Merges the fragments of the views of the executed transactions into a block summary.
+pub(crate) fn merge_view_health<'a>(
+    fragments: impl IntoIterator<Item = &'a ViewHealthSummary>,
+) -> ViewHealthSummary {
+    let mut summary = ViewHealthSummary::default();
+    for fragment in fragments {
+        summary.merge(fragment);
+    }
+    summary
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn fragment(scale: usize, halt_reason: Option<SpeculativeHaltReason>) -> ViewHealthSummary {
+        let mut fragment = ViewHealthSummary {
+            num_incorrect_uses: usize::from(
+                halt_reason == Some(SpeculativeHaltReason::PatchFailure),
+            ),
+            num_dependency_waits: scale,
+            dependency_wait_time: Duration::from_micros(10 * scale as u64),
+            num_storage_fetches: 2 * scale,
+            storage_fetch_bytes: 100 * scale as u64,
+            num_exchanges: 3 * scale,
+            exchange_bytes: 50 * scale as u64,
+            num_group_initializations: scale,
+            num_minted_delayed_field_ids: 4 * scale,
+            ..ViewHealthSummary::default()
+        };
+        if let Some(reason) = halt_reason {
+            fragment.record_speculative_halt(reason);
+        }
+        fragment
+    }
+
+    #[test]
+    fn merge_fragments() {
+        let fragments = [
+            fragment(1, None),
+            fragment(2, Some(SpeculativeHaltReason::InconsistentRead)),
+            fragment(3, Some(SpeculativeHaltReason::PatchFailure)),
+        ];
+
+        let summary = merge_view_health(&fragments);
+        assert_eq!(
+            summary.num_speculative_halts(SpeculativeHaltReason::InconsistentRead),
+            1
+        );
+        assert_eq!(
+            summary.num_speculative_halts(SpeculativeHaltReason::ExecutionHalted),
+            0
+        );
+        assert_eq!(
+            summary.num_speculative_halts(SpeculativeHaltReason::PatchFailure),
+            1
+        );
+        assert_eq!(summary.total_speculative_halts(), 2);
+        assert_eq!(summary.num_incorrect_uses, 1);
+        assert_eq!(summary.num_dependency_waits, 6);
+        assert_eq!(summary.dependency_wait_time, Duration::from_micros(60));
+        assert_eq!(summary.num_storage_fetches, 12);
+        assert_eq!(summary.storage_fetch_bytes, 600);
+        assert_eq!(summary.num_exchanges, 18);
+        assert_eq!(summary.exchange_bytes, 300);
+        assert_eq!(summary.num_group_initializations, 6);
+        assert_eq!(summary.num_minted_delayed_field_ids, 24);
+
+        // The merge is associative (and commutative).
+        let mut left = merge_view_health(&fragments[..2]);
+        left.merge(&fragments[2]);
+        let mut right = merge_view_health(&fragments[1..]);
+        right.merge(&fragments[0]);
+        assert_eq!(left, summary);
+        assert_eq!(right, summary);
+        assert_eq!(merge_view_health([]), ViewHealthSummary::default());
+    }
+}
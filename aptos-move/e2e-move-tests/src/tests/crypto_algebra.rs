@@ -0,0 +1,179 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{assert_abort, assert_success, tests::common, MoveHarness};
+use aptos_language_e2e_tests::account::Account;
+use aptos_package_builder::PackageBuilder;
+use aptos_types::{
+    account_address::AccountAddress,
+    on_chain_config::FeatureFlag,
+    transaction::{EntryFunction, TransactionPayload},
+};
+use move_core_types::{identifier::Identifier, language_storage::ModuleId};
+
+/// Equivalent to `std::error::not_implemented(0)` in Move, the abort code of the natives called
+/// on a structure whose feature flag is disabled.
+const NOT_IMPLEMENTED: u64 = 0x0C_0001;
+
+fn payload(function: &str, arg: u64) -> TransactionPayload {
+    TransactionPayload::EntryFunction(EntryFunction::new(
+        ModuleId::new(
+            AccountAddress::from_hex_literal("0xcafe").unwrap(),
+            Identifier::new("test").unwrap(),
+        ),
+        Identifier::new(function).unwrap(),
+        vec![],
+        vec![bcs::to_bytes(&arg).unwrap()],
+    ))
+}
+
+fn publish_test_module(h: &mut MoveHarness, package_name: &str, source: &str) -> Account {
+    let acc = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+    let mut builder = PackageBuilder::new(package_name);
+    builder.add_source("test", source);
+    builder.add_local_dep(
+        "AptosStdlib",
+        &common::framework_dir_path("aptos-stdlib")
+            .display()
+            .to_string(),
+    );
+    let dir = builder.write_to_temp().unwrap();
+    assert_success!(h.publish_package(&acc, dir.path()));
+    acc
+}
+
+#[test]
+fn add_many_gas_scales_with_num_elements() {
+    let mut h = MoveHarness::new();
+    let acc = publish_test_module(
+        &mut h,
+        "AddMany",
+        "
+module 0xcafe::test {
+    use aptos_std::bls12381_algebra::G2;
+    use aptos_std::crypto_algebra::{add_many, one, Element};
+
+    fun elements(num_elements: u64): vector<Element<G2>> {
+        let elements = vector[];
+        while (num_elements > 0) {
+            std::vector::push_back(&mut elements, one<G2>());
+            num_elements = num_elements - 1;
+        };
+        elements
+    }
+
+    public entry fun build(num_elements: u64) {
+        elements(num_elements);
+    }
+
+    public entry fun build_and_add(num_elements: u64) {
+        add_many(&elements(num_elements));
+    }
+}
+    ",
+    );
+
+    // Gas (in external units) charged for summing up the elements, including the conversion of
+    // the elements to their handles.
+    let mut add_many_gas = |num_elements| {
+        h.evaluate_gas(&acc, payload("build_and_add", num_elements))
+            - h.evaluate_gas(&acc, payload("build", num_elements))
+    };
+    let small = add_many_gas(32);
+    let large = add_many_gas(512);
+
+    // Every element is charged (at least) a projective addition in G2, i.e. 119106 internal gas
+    // units, with 1000000 internal gas units per external gas unit (up to rounding).
+    assert!(large >= small + (512 - 32) * 119106 / 1_000_000 - 2);
+}
+
+const SQUARE_SOURCE: &str = "
+module 0xcafe::test {
+    use aptos_std::bn254_algebra::Fr;
+    use aptos_std::crypto_algebra::{eq, from_u64, mul};
+
+    public entry fun square(value: u64) {
+        let element = from_u64<Fr>(value);
+        assert!(eq(&mul(&element, &element), &from_u64<Fr>(value * value)), 1);
+    }
+}
+";
+
+#[test]
+fn disabled_structure_is_not_charged_by_natives() {
+    let mut h = MoveHarness::new();
+    h.enable_features(
+        vec![
+            FeatureFlag::CRYPTOGRAPHY_ALGEBRA_NATIVES,
+            FeatureFlag::BN254_STRUCTURES,
+        ],
+        vec![],
+    );
+    let acc = publish_test_module(&mut h, "Square", SQUARE_SOURCE);
+    h.enable_features(vec![], vec![FeatureFlag::BN254_STRUCTURES]);
+
+    let disabled_square_gas = |h: &mut MoveHarness| {
+        let txn = h.create_transaction_payload(&acc, payload("square", 3));
+        let output = h.run_raw(txn);
+        assert_abort!(output.status().to_owned(), NOT_IMPLEMENTED);
+        output.gas_used()
+    };
+    let gas = disabled_square_gas(&mut h);
+
+    // The natives abort before charging, so the gas used does not depend on their gas
+    // parameters.
+    h.modify_gas_schedule(|gas_params| {
+        let algebra = &mut gas_params.natives.aptos_framework;
+        algebra.algebra_ark_bn254_fr_from_u64 = 1_000_000_000.into();
+        algebra.algebra_ark_bn254_fr_mul = 1_000_000_000.into();
+        algebra.algebra_ark_bn254_fr_eq = 1_000_000_000.into();
+    });
+    assert_eq!(disabled_square_gas(&mut h), gas);
+}
+
+#[test]
+fn mul_follows_feature_flag_between_transactions() {
+    let mut h = MoveHarness::new();
+    h.enable_features(
+        vec![
+            FeatureFlag::CRYPTOGRAPHY_ALGEBRA_NATIVES,
+            FeatureFlag::BN254_STRUCTURES,
+        ],
+        vec![],
+    );
+    let acc = publish_test_module(&mut h, "Square", SQUARE_SOURCE);
+
+    assert_success!(h.run_transaction_payload(&acc, payload("square", 3)));
+
+    // The transactions after the flag is disabled abort, and the ones after it is re-enabled
+    // succeed again.
+    h.enable_features(vec![], vec![FeatureFlag::BN254_STRUCTURES]);
+    assert_abort!(
+        h.run_transaction_payload(&acc, payload("square", 3)),
+        NOT_IMPLEMENTED
+    );
+    h.enable_features(vec![FeatureFlag::BN254_STRUCTURES], vec![]);
+    assert_success!(h.run_transaction_payload(&acc, payload("square", 3)));
+}
+
+#[test]
+fn mul_charges_its_gas_parameter_once() {
+    let mut h = MoveHarness::new();
+    h.enable_features(
+        vec![
+            FeatureFlag::CRYPTOGRAPHY_ALGEBRA_NATIVES,
+            FeatureFlag::BN254_STRUCTURES,
+        ],
+        vec![],
+    );
+    let acc = publish_test_module(&mut h, "Square", SQUARE_SOURCE);
+    let gas = h.evaluate_gas(&acc, payload("square", 3));
+
+    // The square is computed with a single multiplication, charged exactly its gas parameter
+    // (with 1000000 internal gas units per external gas unit).
+    h.modify_gas_schedule(|gas_params| {
+        let algebra = &mut gas_params.natives.aptos_framework;
+        algebra.algebra_ark_bn254_fr_mul += 1_000_000_000.into();
+    });
+    assert_eq!(h.evaluate_gas(&acc, payload("square", 3)), gas + 1000);
+}
@@ -516,6 +516,7 @@ impl FakeExecutor {
                 },
                 allow_fallback: self.allow_block_executor_fallback,
                 discard_failed_blocks: false,
+                ..Default::default()
             },
             onchain: onchain_config,
         };
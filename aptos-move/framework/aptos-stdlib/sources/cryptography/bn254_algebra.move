@@ -606,7 +606,7 @@ module std::bn254_algebra {
     }
 
     #[test_only]
-    use aptos_std::crypto_algebra::{zero, one, from_u64, eq, deserialize, serialize, neg, add, sub, mul, div, inv, rand_insecure, sqr, order, scalar_mul, multi_scalar_mul, double, upcast, enable_cryptography_algebra_natives, pairing, multi_pairing, downcast, Element};
+    use aptos_std::crypto_algebra::{zero, one, from_u64, eq, deserialize, serialize, neg, add, add_many, sub, mul, div, inv, rand_insecure, sqr, order, serialized_size, scalar_mul, multi_scalar_mul, double, upcast, enable_cryptography_algebra_natives, pairing, multi_pairing, downcast, Element};
 
     #[test_only]
     const FR_VAL_0_SERIALIZED_LSB: vector<u8> = x"0000000000000000000000000000000000000000000000000000000000000000";
@@ -848,6 +848,152 @@ module std::bn254_algebra {
         }
     }
 
+    #[test_only]
+    /// Returns the little-endian bytes of `k`, padded with zeros to `len` bytes.
+    fun u64_to_padded_le_bytes(k: u64, len: u64): vector<u8> {
+        let bytes = std::bcs::to_bytes(&k);
+        while (std::vector::length(&bytes) < len) {
+            std::vector::push_back(&mut bytes, 0);
+        };
+        bytes
+    }
+
+    #[test_only]
+    const FROM_U64_TEST_VALUES: vector<u64> = vector[2, 3, 7, 8, 255, 256, 65537, 4294967296, 18446744073709551615];
+
+    #[test(fx = @std)]
+    fun test_from_u64_matches_deserialize(fx: signer) {
+        enable_cryptography_algebra_natives(&fx);
+        let values = FROM_U64_TEST_VALUES;
+        let i = 0;
+        while (i < 32) {
+            std::vector::push_back(&mut values, i);
+            i = i + 1;
+        };
+        std::vector::for_each(values, |k| {
+            let expected = std::option::extract(&mut deserialize<Fr, FormatFrLsb>(&u64_to_padded_le_bytes(k, 32)));
+            assert!(eq(&from_u64<Fr>(k), &expected), 1);
+            let expected = std::option::extract(&mut deserialize<Fq, FormatFqLsb>(&u64_to_padded_le_bytes(k, 32)));
+            assert!(eq(&from_u64<Fq>(k), &expected), 1);
+            let expected = std::option::extract(&mut deserialize<Fq12, FormatFq12LscLsb>(&u64_to_padded_le_bytes(k, 384)));
+            assert!(eq(&from_u64<Fq12>(k), &expected), 1);
+        });
+    }
+
+    #[test(fx = @std)]
+    #[expected_failure(abort_code = 0x0c0001, location = aptos_std::crypto_algebra)]
+    fun test_from_u64_should_abort_for_g1(fx: signer) {
+        enable_cryptography_algebra_natives(&fx);
+        from_u64<G1>(7);
+    }
+
+    #[test(fx = @std)]
+    #[expected_failure(abort_code = 0x0c0001, location = aptos_std::crypto_algebra)]
+    fun test_from_u64_should_abort_for_g2(fx: signer) {
+        enable_cryptography_algebra_natives(&fx);
+        from_u64<G2>(7);
+    }
+
+    #[test(fx = @std)]
+    #[expected_failure(abort_code = 0x0c0001, location = aptos_std::crypto_algebra)]
+    fun test_from_u64_should_abort_for_gt(fx: signer) {
+        enable_cryptography_algebra_natives(&fx);
+        from_u64<Gt>(7);
+    }
+
+    #[test(fx = @std)]
+    fun test_fr_non_canonical_deserialization_legacy(fx: signer) {
+        enable_cryptography_algebra_natives(&fx);
+        std::features::change_feature_flags_for_testing(&fx, vector[], vector[std::features::get_strict_algebra_canonical_deserialization_feature()]);
+
+        // `r` is of the right size but not less than the modulus.
+        assert!(std::option::is_none(&deserialize<Fr, FormatFrLsb>(&R_SERIALIZED)), 1);
+        let val_7 = std::option::extract(&mut deserialize<Fr, FormatFrLsb>(&FR_VAL_7_SERIALIZED_LSB));
+        assert!(eq(&from_u64<Fr>(49), &mul(&val_7, &val_7)), 1);
+    }
+
+    #[test(fx = @std)]
+    #[expected_failure(abort_code = 0x010004, location = aptos_std::crypto_algebra)]
+    fun test_fr_non_canonical_deserialization_strict(fx: signer) {
+        enable_cryptography_algebra_natives(&fx);
+        std::features::change_feature_flags_for_testing(&fx, vector[std::features::get_strict_algebra_canonical_deserialization_feature()], vector[]);
+
+        // Canonical encodings are still accepted.
+        let val_7 = std::option::extract(&mut deserialize<Fr, FormatFrLsb>(&FR_VAL_7_SERIALIZED_LSB));
+        assert!(eq(&from_u64<Fr>(49), &mul(&val_7, &val_7)), 1);
+
+        // `r` is of the right size but not less than the modulus.
+        deserialize<Fr, FormatFrLsb>(&R_SERIALIZED);
+    }
+
+    #[test_only]
+    fun assert_serialized_size<S, F>(element: &Element<S>) {
+        assert!(std::vector::length(&serialize<S, F>(element)) == serialized_size<S, F>(), 1);
+    }
+
+    #[test(fx = @std)]
+    fun test_serialized_size(fx: signer) {
+        enable_cryptography_algebra_natives(&fx);
+        assert_serialized_size<Fr, FormatFrLsb>(&rand_insecure<Fr>());
+        assert_serialized_size<Fr, FormatFrMsb>(&rand_insecure<Fr>());
+        assert_serialized_size<Fq, FormatFqLsb>(&rand_insecure<Fq>());
+        assert_serialized_size<Fq, FormatFqMsb>(&rand_insecure<Fq>());
+        assert_serialized_size<Fq12, FormatFq12LscLsb>(&rand_insecure<Fq12>());
+        assert_serialized_size<G1, FormatG1Uncompr>(&rand_insecure<G1>());
+        assert_serialized_size<G1, FormatG1Compr>(&rand_insecure<G1>());
+        assert_serialized_size<G2, FormatG2Uncompr>(&rand_insecure<G2>());
+        assert_serialized_size<G2, FormatG2Compr>(&rand_insecure<G2>());
+        assert_serialized_size<Gt, FormatGt>(&rand_insecure<Gt>());
+    }
+
+    #[test(fx = @std)]
+    #[expected_failure(abort_code = 0x0c0001, location = aptos_std::crypto_algebra)]
+    fun test_serialized_size_should_abort_for_unsupported_format(fx: signer) {
+        enable_cryptography_algebra_natives(&fx);
+        serialized_size<G1, FormatFrLsb>();
+    }
+
+    #[test_only]
+    fun assert_add_many<G>(num_elements: u64) {
+        let elements = rand_vector<G>(num_elements);
+        let expected = zero<G>();
+        let i = 0;
+        while (i < num_elements) {
+            expected = add(&expected, std::vector::borrow(&elements, i));
+            i = i + 1;
+        };
+        assert!(eq(&expected, &add_many(&elements)), 1);
+    }
+
+    #[test(fx = @std)]
+    fun test_add_many(fx: signer) {
+        enable_cryptography_algebra_natives(&fx);
+
+        // The sum of no elements is the identity.
+        assert!(eq(&zero<G1>(), &add_many<G1>(&vector[])), 1);
+        assert!(eq(&zero<G2>(), &add_many<G2>(&vector[])), 1);
+
+        // Agrees with the folded pairwise addition.
+        let num_elements = 1;
+        while (num_elements < 10) {
+            assert_add_many<G1>(num_elements);
+            assert_add_many<G2>(num_elements);
+            num_elements = num_elements + 1;
+        };
+
+        // Repeated elements, opposite elements and the identity.
+        let element_p = rand_insecure<G1>();
+        assert!(eq(&double(&element_p), &add_many(&vector[element_p, zero<G1>(), element_p])), 1);
+        assert!(eq(&zero<G1>(), &add_many(&vector[element_p, neg(&element_p)])), 1);
+    }
+
+    #[test(fx = @std)]
+    #[expected_failure(abort_code = 0x0c0001, location = aptos_std::crypto_algebra)]
+    fun test_add_many_should_abort_for_non_group(fx: signer) {
+        enable_cryptography_algebra_natives(&fx);
+        add_many(&vector[one<Fr>()]);
+    }
+
     //
     // (Tests end here.)
     //
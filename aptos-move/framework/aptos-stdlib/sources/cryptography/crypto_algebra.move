@@ -10,6 +10,7 @@
 /// - `one()` for getting the group generator (if exists).
 /// - `neg()` for group element inversion.
 /// - `add()` for group operation (i.e., a group addition).
+/// - `add_many()` for efficient summation of many group elements.
 /// - `sub()` for group element subtraction.
 /// - `double()` for efficient doubling.
 /// - `scalar_mul()` for group scalar multiplication.
@@ -42,6 +43,7 @@ module aptos_std::crypto_algebra {
     const E_NOT_IMPLEMENTED: u64 = 1;
     const E_NON_EQUAL_LENGTHS: u64 = 2;
     const E_TOO_MUCH_MEMORY_USED: u64 = 3;
+    const E_NON_CANONICAL_ENCODING: u64 = 4;
 
     /// This struct represents an element of a structure `S`.
     struct Element<phantom S> has copy, drop {
@@ -98,6 +100,16 @@ module aptos_std::crypto_algebra {
         }
     }
 
+    /// Compute `P[0]+...+P[n-1]`, where `P[]` are `n` elements of group `G` represented by parameter `elements`.
+    /// Cheaper than folding `add()` over the elements. Return the group identity if `elements` is empty.
+    public fun add_many<G>(elements: &vector<Element<G>>): Element<G> {
+        abort_unless_cryptography_algebra_natives_enabled();
+        let element_handles = handles_from_elements(elements);
+        Element<G> {
+            handle: add_many_internal<G>(element_handles)
+        }
+    }
+
     /// Compute `x - y` for elements `x` and `y` of a structure `S`.
     public fun sub<S>(x: &Element<S>, y: &Element<S>): Element<S> {
         abort_unless_cryptography_algebra_natives_enabled();
@@ -207,6 +219,9 @@ module aptos_std::crypto_algebra {
 
     /// Try deserializing a byte array to an element of an algebraic structure `S` using a given serialization format `F`.
     /// Return none if the deserialization failed.
+    ///
+    /// If feature `STRICT_ALGEBRA_CANONICAL_DESERIALIZATION` is enabled, abort with code `std::error::invalid_argument(E_NON_CANONICAL_ENCODING)`
+    /// if `S` is a field and `bytes` is of the right size but not a canonical encoding (e.g., a value not less than the modulus).
     public fun deserialize<S, F>(bytes: &vector<u8>): Option<Element<S>> {
         abort_unless_cryptography_algebra_natives_enabled();
         let (succeeded, handle) = deserialize_internal<S, F>(bytes);
@@ -223,6 +238,13 @@ module aptos_std::crypto_algebra {
         serialize_internal<S, F>(element.handle)
     }
 
+    /// Get the size in bytes of the serialization of any element of an algebraic structure `S` using a given serialization format `F`.
+    /// Useful for sizing buffers of serialized elements without hard-coding the sizes.
+    public fun serialized_size<S, F>(): u64 {
+        abort_unless_cryptography_algebra_natives_enabled();
+        serialized_size_internal<S, F>()
+    }
+
     /// Get the order of structure `S`, a big integer little-endian encoded as a byte array.
     public fun order<S>(): vector<u8> {
         abort_unless_cryptography_algebra_natives_enabled();
@@ -309,6 +331,7 @@ module aptos_std::crypto_algebra {
     //
 
     native fun add_internal<S>(handle_1: u64, handle_2: u64): u64;
+    native fun add_many_internal<G>(element_handles: vector<u64>): u64;
     native fun deserialize_internal<S, F>(bytes: &vector<u8>): (bool, u64);
     native fun div_internal<F>(handle_1: u64, handle_2: u64): (bool, u64);
     native fun double_internal<G>(element_handle: u64): u64;
@@ -328,6 +351,7 @@ module aptos_std::crypto_algebra {
     native fun pairing_internal<G1,G2,Gt>(g1_handle: u64, g2_handle: u64): u64;
     native fun scalar_mul_internal<G, S>(element_handle: u64, scalar_handle: u64): u64;
     native fun serialize_internal<S, F>(handle: u64): vector<u8>;
+    native fun serialized_size_internal<S, F>(): u64;
     native fun sqr_internal<G>(handle: u64): u64;
     native fun sub_internal<G>(handle_1: u64, handle_2: u64): u64;
     native fun upcast_internal<S,L>(handle: u64): u64;
@@ -9,6 +9,10 @@ spec aptos_std::crypto_algebra {
         pragma opaque;
     }
 
+    spec add_many_internal<G>(element_handles: vector<u64>): u64 {
+        pragma opaque;
+    }
+
     spec deserialize_internal<S, F>(bytes: &vector<u8>): (bool, u64) {
         pragma opaque;
     }
@@ -77,6 +81,10 @@ spec aptos_std::crypto_algebra {
         pragma opaque;
     }
 
+    spec serialized_size_internal<S, F>(): u64 {
+        pragma opaque;
+    }
+
     spec sqr_internal<G>(handle: u64): u64 {
         pragma opaque;
     }
@@ -530,6 +530,20 @@ module std::features {
         is_enabled(DISPATCHABLE_FUNGIBLE_ASSET)
     }
 
+    /// Whether the field deserialization of the algebra natives aborts on a non-canonical encoding
+    /// (e.g., a value not less than the modulus), instead of returning none.
+    ///
+    /// Lifetime: transient
+    const STRICT_ALGEBRA_CANONICAL_DESERIALIZATION: u64 = 64;
+
+    public fun get_strict_algebra_canonical_deserialization_feature(): u64 {
+        STRICT_ALGEBRA_CANONICAL_DESERIALIZATION
+    }
+
+    public fun strict_algebra_canonical_deserialization_enabled(): bool acquires Features {
+        is_enabled(STRICT_ALGEBRA_CANONICAL_DESERIALIZATION)
+    }
+
     // ============================================================================================
     // Feature Flag Implementation
 
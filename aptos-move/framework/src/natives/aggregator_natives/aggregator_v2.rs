@@ -134,7 +134,9 @@ fn create_aggregator_with_max_value(
 ) -> SafeNativeResult<SmallVec<[Value; 1]>> {
     let value = if let Some((resolver, mut delayed_field_data)) = get_context_data(context) {
         let width = get_width_by_type(aggregator_value_ty, EUNSUPPORTED_AGGREGATOR_TYPE)?;
-        let id = resolver.generate_delayed_field_id(width);
+        let id = resolver
+            .generate_delayed_field_id(width)
+            .map_err(|e| SafeNativeError::InvariantViolation(PartialVMError::from(e)))?;
         delayed_field_data.create_new_aggregator(id);
         Value::delayed_value(id)
     } else {
@@ -370,7 +372,7 @@ fn native_create_snapshot(
     let snapshot_value = if let Some((resolver, mut delayed_field_data)) = get_context_data(context)
     {
         let width = get_width_by_type(snapshot_value_ty, EUNSUPPORTED_AGGREGATOR_TYPE)?;
-        let snapshot_id = delayed_field_data.create_new_snapshot(value, width, resolver);
+        let snapshot_id = delayed_field_data.create_new_snapshot(value, width, resolver)?;
         Value::delayed_value(snapshot_id)
     } else {
         create_value_by_type(
@@ -10,9 +10,15 @@ use crate::{
     },
     safe_borrow_element, store_element, structure_from_ty_arg,
 };
+use aptos_gas_algebra::{Arg, GasExpression};
 use aptos_gas_schedule::gas_params::natives::aptos_framework::*;
-use aptos_native_interface::{SafeNativeContext, SafeNativeError, SafeNativeResult};
+use aptos_native_interface::{
+    safely_pop_arg, SafeNativeContext, SafeNativeError, SafeNativeResult,
+};
+use ark_ec::CurveGroup;
+use move_core_types::gas_algebra::NumArgs;
 use move_vm_types::{loaded_data::runtime_types::Type, values::Value};
+use num_traits::Zero;
 use smallvec::{smallvec, SmallVec};
 use std::{
     collections::VecDeque,
@@ -103,3 +109,76 @@ pub fn add_internal(
         }),
     }
 }
+
+macro_rules! ark_add_many_internal {
+    (
+        $context:expr, $args:ident, $element_typ:ty, $proj_to_affine_cost:expr, $proj_add_cost:expr
+    ) => {{
+        let element_handles = safely_pop_arg!($args, Vec<u64>);
+        let num_elements = element_handles.len();
+        $context.charge(
+            $proj_to_affine_cost + $proj_add_cost.per::<Arg>() * NumArgs::from(num_elements as u64),
+        )?;
+        let mut elements = Vec::with_capacity(num_elements);
+        for handle in element_handles {
+            safe_borrow_element!(
+                $context,
+                handle as usize,
+                $element_typ,
+                element_ptr,
+                element
+            );
+            elements.push(*element);
+        }
+        // The elements are normalized together (with a single field inversion), so that they
+        // are summed up with mixed additions, which are cheaper than projective additions.
+        let new_element = <$element_typ>::normalize_batch(&elements)
+            .iter()
+            .fold(<$element_typ>::zero(), |sum, element| sum + element);
+        let new_handle = store_element!($context, new_element)?;
+        Ok(smallvec![Value::u64(new_handle as u64)])
+    }};
+}
+
+pub fn add_many_internal(
+    context: &mut SafeNativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> SafeNativeResult<SmallVec<[Value; 1]>> {
+    assert_eq!(1, ty_args.len());
+    let structure_opt = structure_from_ty_arg!(context, &ty_args[0]);
+    abort_unless_arithmetics_enabled_for_structure!(context, structure_opt);
+    match structure_opt {
+        Some(Structure::BLS12381G1) => ark_add_many_internal!(
+            context,
+            args,
+            ark_bls12_381::G1Projective,
+            ALGEBRA_ARK_BLS12_381_G1_PROJ_TO_AFFINE,
+            ALGEBRA_ARK_BLS12_381_G1_PROJ_ADD
+        ),
+        Some(Structure::BLS12381G2) => ark_add_many_internal!(
+            context,
+            args,
+            ark_bls12_381::G2Projective,
+            ALGEBRA_ARK_BLS12_381_G2_PROJ_TO_AFFINE,
+            ALGEBRA_ARK_BLS12_381_G2_PROJ_ADD
+        ),
+        Some(Structure::BN254G1) => ark_add_many_internal!(
+            context,
+            args,
+            ark_bn254::G1Projective,
+            ALGEBRA_ARK_BN254_G1_PROJ_TO_AFFINE,
+            ALGEBRA_ARK_BN254_G1_PROJ_ADD
+        ),
+        Some(Structure::BN254G2) => ark_add_many_internal!(
+            context,
+            args,
+            ark_bn254::G2Projective,
+            ALGEBRA_ARK_BN254_G2_PROJ_TO_AFFINE,
+            ALGEBRA_ARK_BN254_G2_PROJ_ADD
+        ),
+        _ => Err(SafeNativeError::Abort {
+            abort_code: MOVE_ABORT_CODE_NOT_IMPLEMENTED,
+        }),
+    }
+}
@@ -36,3 +36,72 @@ macro_rules! ark_unary_op_internal {
         Ok(smallvec![Value::u64(new_handle as u64)])
     }};
 }
+
+/// Computes a new element from a fixed number of elements of type `$ark_typ`, given by their
+/// handles. The result is the expression after the parameters, in which each parameter is a
+/// reference to the element of the corresponding argument, e.g. `|a, b, c| a.mul(b).add(c)`.
+/// Aborts with an invariant violation on an unexpected number of arguments or on invalid
+/// handles, before charging `$gas`.
+#[macro_export]
+macro_rules! ark_nary_op_internal {
+    ($context:expr, $args:ident, $ark_typ:ty, |$($element:ident),+| $ark_op:expr, $gas:expr) => {{
+        let num_elements = [$(stringify!($element)),+].len();
+        aptos_native_interface::safely_assert_eq!($args.len(), num_elements);
+        // The arguments are popped from the back, i.e. the handle of the last element first.
+        let mut handles = Vec::with_capacity(num_elements);
+        for _ in 0..num_elements {
+            handles.push(aptos_native_interface::safely_pop_arg!($args, u64) as usize);
+        }
+        $(
+            let handle = handles.pop().ok_or_else(abort_invariant_violated)?;
+            safe_borrow_element!($context, handle, $ark_typ, element_ptr, $element);
+        )+
+        $context.charge($gas)?;
+        let new_element: $ark_typ = $ark_op;
+        let new_handle = store_element!($context, new_element)?;
+        Ok(smallvec![Value::u64(new_handle as u64)])
+    }};
+}
+
+/// `ark_nary_op_internal` for the operations on three elements (e.g. fused multiply-add).
+#[macro_export]
+macro_rules! ark_ternary_op_internal {
+    (
+        $context:expr, $args:ident, $ark_typ:ty,
+        |$element_1:ident, $element_2:ident, $element_3:ident| $ark_op:expr, $gas:expr
+    ) => {{
+        $crate::ark_nary_op_internal!(
+            $context,
+            $args,
+            $ark_typ,
+            |$element_1, $element_2, $element_3| $ark_op,
+            $gas
+        )
+    }};
+}
+
+/// Computes a new element from a vector of elements of type `$ark_typ`, given by a vector of
+/// handles. The result is the expression after the parameter, which is bound to the vector of
+/// (copies of) the elements. The gas is the expression after the second parameter, which is
+/// bound to the number of elements, and is charged before the elements are borrowed (hence
+/// also for invalid handles, which abort with an invariant violation).
+#[macro_export]
+macro_rules! ark_vector_op_internal {
+    (
+        $context:expr, $args:ident, $ark_typ:ty,
+        |$elements:ident| $ark_op:expr, |$num_elements:ident| $gas:expr
+    ) => {{
+        aptos_native_interface::safely_assert_eq!($args.len(), 1);
+        let element_handles = aptos_native_interface::safely_pop_arg!($args, Vec<u64>);
+        let $num_elements = element_handles.len();
+        $context.charge($gas)?;
+        let mut $elements = Vec::with_capacity($num_elements);
+        for handle in element_handles {
+            safe_borrow_element!($context, handle as usize, $ark_typ, element_ptr, element);
+            $elements.push(*element);
+        }
+        let new_element: $ark_typ = $ark_op;
+        let new_handle = store_element!($context, new_element)?;
+        Ok(smallvec![Value::u64(new_handle as u64)])
+    }};
+}
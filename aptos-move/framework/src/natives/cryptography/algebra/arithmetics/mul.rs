@@ -3,7 +3,7 @@
 
 use crate::{
     abort_unless_arithmetics_enabled_for_structure, abort_unless_feature_flag_enabled,
-    ark_binary_op_internal,
+    ark_nary_op_internal,
     natives::cryptography::algebra::{
         abort_invariant_violated, feature_flag_from_structure, AlgebraContext, Structure,
         E_TOO_MUCH_MEMORY_USED, MEMORY_LIMIT_IN_BYTES, MOVE_ABORT_CODE_NOT_IMPLEMENTED,
@@ -25,35 +25,41 @@ pub fn mul_internal(
     let structure_opt = structure_from_ty_arg!(context, &ty_args[0]);
     abort_unless_arithmetics_enabled_for_structure!(context, structure_opt);
     match structure_opt {
-        Some(Structure::BLS12381Fr) => ark_binary_op_internal!(
+        Some(Structure::BLS12381Fr) => ark_nary_op_internal!(
             context,
             args,
             ark_bls12_381::Fr,
-            mul,
+            |element_1, element_2| element_1.mul(element_2),
             ALGEBRA_ARK_BLS12_381_FR_MUL
         ),
-        Some(Structure::BLS12381Fq12) => ark_binary_op_internal!(
+        Some(Structure::BLS12381Fq12) => ark_nary_op_internal!(
             context,
             args,
             ark_bls12_381::Fq12,
-            mul,
+            |element_1, element_2| element_1.mul(element_2),
             ALGEBRA_ARK_BLS12_381_FQ12_MUL
         ),
-        Some(Structure::BN254Fr) => {
-            ark_binary_op_internal!(context, args, ark_bn254::Fr, mul, ALGEBRA_ARK_BN254_FR_MUL)
-        },
-        Some(Structure::BN254Fq) => {
-            ark_binary_op_internal!(context, args, ark_bn254::Fq, mul, ALGEBRA_ARK_BN254_FQ_MUL)
-        },
-        Some(Structure::BN254Fq12) => {
-            ark_binary_op_internal!(
-                context,
-                args,
-                ark_bn254::Fq12,
-                mul,
-                ALGEBRA_ARK_BN254_FQ12_MUL
-            )
-        },
+        Some(Structure::BN254Fr) => ark_nary_op_internal!(
+            context,
+            args,
+            ark_bn254::Fr,
+            |element_1, element_2| element_1.mul(element_2),
+            ALGEBRA_ARK_BN254_FR_MUL
+        ),
+        Some(Structure::BN254Fq) => ark_nary_op_internal!(
+            context,
+            args,
+            ark_bn254::Fq,
+            |element_1, element_2| element_1.mul(element_2),
+            ALGEBRA_ARK_BN254_FQ_MUL
+        ),
+        Some(Structure::BN254Fq12) => ark_nary_op_internal!(
+            context,
+            args,
+            ark_bn254::Fq12,
+            |element_1, element_2| element_1.mul(element_2),
+            ALGEBRA_ARK_BN254_FQ12_MUL
+        ),
         _ => Err(SafeNativeError::Abort {
             abort_code: MOVE_ABORT_CODE_NOT_IMPLEMENTED,
         }),
@@ -5,8 +5,12 @@
 use crate::natives::cryptography::algebra::rand::rand_insecure_internal;
 use crate::natives::cryptography::algebra::{
     arithmetics::{
-        add::add_internal, double::double_internal, mul::mul_internal, neg::neg_internal,
-        sqr::sqr_internal, sub::sub_internal,
+        add::{add_internal, add_many_internal},
+        double::double_internal,
+        mul::mul_internal,
+        neg::neg_internal,
+        sqr::sqr_internal,
+        sub::sub_internal,
     },
     casting::{downcast_internal, upcast_internal},
     constants::{one_internal, order_internal, zero_internal},
@@ -14,7 +18,7 @@ use crate::natives::cryptography::algebra::{
     hash_to_structure::hash_to_internal,
     new::from_u64_internal,
     pairing::{multi_pairing_internal, pairing_internal},
-    serialization::{deserialize_internal, serialize_internal},
+    serialization::{deserialize_internal, serialize_internal, serialized_size_internal},
 };
 use aptos_native_interface::{RawSafeNative, SafeNativeBuilder};
 use aptos_types::on_chain_config::FeatureFlag;
@@ -49,6 +53,9 @@ const MOVE_ABORT_CODE_INPUT_VECTOR_SIZES_NOT_MATCHING: u64 = 0x01_0002;
 /// Equivalent to `std::error::not_implemented(0)` in Move.
 const MOVE_ABORT_CODE_NOT_IMPLEMENTED: u64 = 0x0C_0001;
 
+/// Equivalent to `std::error::invalid_argument(4)` in Move.
+const MOVE_ABORT_CODE_NON_CANONICAL_ENCODING: u64 = 0x01_0004;
+
 /// This encodes an algebraic structure defined in `*_algebra.move`.
 #[derive(Copy, Clone, Eq, Hash, PartialEq)]
 pub enum Structure {
@@ -254,6 +261,8 @@ fn feature_flag_from_structure(structure_opt: Option<Structure>) -> Option<Featu
     }
 }
 
+/// Aborts unless the feature flag of the structure is enabled. See
+/// `abort_unless_feature_flag_enabled` for when it must be invoked.
 #[macro_export]
 macro_rules! abort_unless_arithmetics_enabled_for_structure {
     ($context:ident, $structure_opt:expr) => {
@@ -262,6 +271,13 @@ macro_rules! abort_unless_arithmetics_enabled_for_structure {
     };
 }
 
+/// Aborts with `MOVE_ABORT_CODE_NOT_IMPLEMENTED` unless the flag is given and enabled.
+///
+/// Natives invoke it before charging any gas, so a call on a disabled (or unsupported)
+/// structure is not charged by the native at all, whichever the structure or operation. The
+/// flags are the ones of the environment the transaction executes in, so a call made after
+/// the flag was disabled aborts (without charges), and a call made after it was re-enabled
+/// is charged as usual.
 #[macro_export]
 macro_rules! abort_unless_feature_flag_enabled {
     ($context:ident, $flag_opt:expr) => {
@@ -329,6 +345,7 @@ pub fn make_all(
         ("downcast_internal", downcast_internal),
         ("eq_internal", eq_internal),
         ("add_internal", add_internal),
+        ("add_many_internal", add_many_internal),
         ("div_internal", div_internal),
         ("inv_internal", inv_internal),
         ("mul_internal", mul_internal),
@@ -346,6 +363,7 @@ pub fn make_all(
         ("multi_pairing_internal", multi_pairing_internal),
         ("pairing_internal", pairing_internal),
         ("serialize_internal", serialize_internal),
+        ("serialized_size_internal", serialized_size_internal),
         ("upcast_internal", upcast_internal),
     ]);
 
@@ -6,7 +6,7 @@ use crate::{
     natives::cryptography::algebra::{
         abort_invariant_violated, AlgebraContext, SerializationFormat, Structure,
         BLS12381_R_SCALAR, BN254_R_SCALAR, E_TOO_MUCH_MEMORY_USED, MEMORY_LIMIT_IN_BYTES,
-        MOVE_ABORT_CODE_NOT_IMPLEMENTED,
+        MOVE_ABORT_CODE_NON_CANONICAL_ENCODING, MOVE_ABORT_CODE_NOT_IMPLEMENTED,
     },
     safe_borrow_element, store_element, structure_from_ty_arg,
 };
@@ -52,6 +52,35 @@ pub fn feature_flag_of_serialization_format(
     }
 }
 
+/// Returns the size in bytes of the serialization of any element of the structure in the
+/// format, or None if the format is not supported for the structure. Consulted by all the
+/// serialization natives, so that the sizes cannot diverge.
+pub fn serialized_size(structure: Structure, format: SerializationFormat) -> Option<usize> {
+    match (structure, format) {
+        (Structure::BLS12381Fr, SerializationFormat::BLS12381FrLsb)
+        | (Structure::BLS12381Fr, SerializationFormat::BLS12381FrMsb) => Some(32),
+        (Structure::BLS12381Fq12, SerializationFormat::BLS12381Fq12LscLsb) => Some(576),
+        (Structure::BLS12381G1, SerializationFormat::BLS12381G1Uncompressed) => Some(96),
+        (Structure::BLS12381G1, SerializationFormat::BLS12381G1Compressed) => Some(48),
+        (Structure::BLS12381G2, SerializationFormat::BLS12381G2Uncompressed) => Some(192),
+        (Structure::BLS12381G2, SerializationFormat::BLS12381G2Compressed) => Some(96),
+        (Structure::BLS12381Gt, SerializationFormat::BLS12381Gt) => Some(576),
+        (Structure::BN254Fr, SerializationFormat::BN254FrLsb)
+        | (Structure::BN254Fr, SerializationFormat::BN254FrMsb)
+        | (Structure::BN254Fq, SerializationFormat::BN254FqLsb)
+        | (Structure::BN254Fq, SerializationFormat::BN254FqMsb) => Some(32),
+        // 32*12 bytes.
+        (Structure::BN254Fq12, SerializationFormat::BN254Fq12LscLsb) => Some(384),
+        (Structure::BN254G1, SerializationFormat::BN254G1Uncompressed) => Some(64),
+        (Structure::BN254G1, SerializationFormat::BN254G1Compressed) => Some(32),
+        (Structure::BN254G2, SerializationFormat::BN254G2Uncompressed) => Some(128),
+        (Structure::BN254G2, SerializationFormat::BN254G2Compressed) => Some(64),
+        // 32*12 bytes.
+        (Structure::BN254Gt, SerializationFormat::BN254Gt) => Some(384),
+        _ => None,
+    }
+}
+
 macro_rules! abort_unless_serialization_format_enabled {
     ($context:ident, $format_opt:expr) => {
         let flag_opt = feature_flag_of_serialization_format($format_opt);
@@ -80,7 +109,9 @@ macro_rules! serialize_element {
           ($field_structure,$field_format) => {
             let handle = safely_pop_arg!($args, u64) as usize;
             safe_borrow_element!($context, handle, $field_ty, element_ptr, element);
-            let mut buf = vec![];
+            let mut buf = Vec::with_capacity(
+                serialized_size($structure_to_match, $format_to_match).unwrap_or_default(),
+            );
             $context.charge($field_serialization_gas)?;
             element
                 .$field_serialization_func(&mut buf)
@@ -88,6 +119,10 @@ macro_rules! serialize_element {
             if $reverse {
                 buf.reverse();
             }
+            debug_assert_eq!(
+                Some(buf.len()),
+                serialized_size($structure_to_match, $format_to_match)
+            );
             Ok(smallvec![Value::vector_u8(buf)])
           }
         )*
@@ -102,11 +137,17 @@ macro_rules! serialize_element {
                 element
             );
             let element_affine = element.into_affine();
-            let mut buf = Vec::new();
+            let mut buf = Vec::with_capacity(
+                serialized_size($structure_to_match, $format_to_match).unwrap_or_default(),
+            );
             $context.charge($curve_serialization_gas)?;
             element_affine
                 .$curve_serialization_func(&mut buf)
                 .map_err(|_e| abort_invariant_violated())?;
+            debug_assert_eq!(
+                Some(buf.len()),
+                serialized_size($structure_to_match, $format_to_match)
+            );
             Ok(smallvec![Value::vector_u8(buf)])
           }
         )*
@@ -281,6 +322,9 @@ pub fn serialize_internal(
 }
 
 /// Macros that implements `deserialize_internal()` using arkworks libraries.
+/// Deserializes a field element. The callers check the size of the input, so the input is rejected
+/// by arkworks only if it is not a canonical encoding (i.e., some coefficient is not less than the
+/// modulus), which aborts under `STRICT_ALGEBRA_CANONICAL_DESERIALIZATION`.
 macro_rules! ark_deserialize_internal {
     ($context:expr, $bytes:expr, $ark_typ:ty, $ark_deser_func:ident, $gas:expr) => {{
         $context.charge($gas)?;
@@ -291,7 +335,16 @@ macro_rules! ark_deserialize_internal {
             },
             Err(ark_serialize::SerializationError::InvalidData)
             | Err(ark_serialize::SerializationError::UnexpectedFlags) => {
-                Ok(smallvec![Value::bool(false), Value::u64(0)])
+                if $context
+                    .get_feature_flags()
+                    .is_enabled(FeatureFlag::STRICT_ALGEBRA_CANONICAL_DESERIALIZATION)
+                {
+                    Err(SafeNativeError::Abort {
+                        abort_code: MOVE_ABORT_CODE_NON_CANONICAL_ENCODING,
+                    })
+                } else {
+                    Ok(smallvec![Value::bool(false), Value::u64(0)])
+                }
             },
             _ => Err(SafeNativeError::InvariantViolation(
                 abort_invariant_violated(),
@@ -332,14 +385,15 @@ pub fn deserialize_internal(
     let vector_ref = safely_pop_arg!(args, VectorRef);
     let bytes_ref = vector_ref.as_bytes_ref();
     let bytes = bytes_ref.as_slice();
+    // NOTE: Arkworks deserialization cost grows as the input size grows.
+    // So exit early if the size is incorrect, for gas safety.
+    if let (Some(structure), Some(format)) = (structure_opt, format_opt) {
+        if serialized_size(structure, format).is_some_and(|size| bytes.len() != size) {
+            return Ok(smallvec![Value::bool(false), Value::u64(0)]);
+        }
+    }
     match (structure_opt, format_opt) {
         (Some(Structure::BLS12381Fr), Some(SerializationFormat::BLS12381FrLsb)) => {
-            // Valid BLS12381FrLsb serialization should be 32-byte.
-            // NOTE: Arkworks deserialization cost grows as the input size grows.
-            // So exit early if the size is incorrect, for gas safety. (Also applied to other cases across this file.)
-            if bytes.len() != 32 {
-                return Ok(smallvec![Value::bool(false), Value::u64(0)]);
-            }
             ark_deserialize_internal!(
                 context,
                 bytes,
@@ -349,10 +403,6 @@ pub fn deserialize_internal(
             )
         },
         (Some(Structure::BLS12381Fr), Some(SerializationFormat::BLS12381FrMsb)) => {
-            // Valid BLS12381FrMsb serialization should be 32-byte.
-            if bytes.len() != 32 {
-                return Ok(smallvec![Value::bool(false), Value::u64(0)]);
-            }
             let mut bytes_copy: Vec<u8> = bytes.to_vec();
             bytes_copy.reverse();
             let bytes = bytes_copy.as_slice();
@@ -365,10 +415,6 @@ pub fn deserialize_internal(
             )
         },
         (Some(Structure::BLS12381Fq12), Some(SerializationFormat::BLS12381Fq12LscLsb)) => {
-            // Valid BLS12381Fq12LscLsb serialization should be 576-byte.
-            if bytes.len() != 576 {
-                return Ok(smallvec![Value::bool(false), Value::u64(0)]);
-            }
             ark_deserialize_internal!(
                 context,
                 bytes,
@@ -378,10 +424,6 @@ pub fn deserialize_internal(
             )
         },
         (Some(Structure::BLS12381G1), Some(SerializationFormat::BLS12381G1Uncompressed)) => {
-            // Valid BLS12381G1AffineUncompressed serialization should be 96-byte.
-            if bytes.len() != 96 {
-                return Ok(smallvec![Value::bool(false), Value::u64(0)]);
-            }
             ark_ec_point_deserialize_internal!(
                 context,
                 bytes,
@@ -391,10 +433,6 @@ pub fn deserialize_internal(
             )
         },
         (Some(Structure::BLS12381G1), Some(SerializationFormat::BLS12381G1Compressed)) => {
-            // Valid BLS12381G1AffineCompressed serialization should be 48-byte.
-            if bytes.len() != 48 {
-                return Ok(smallvec![Value::bool(false), Value::u64(0)]);
-            }
             ark_ec_point_deserialize_internal!(
                 context,
                 bytes,
@@ -404,10 +442,6 @@ pub fn deserialize_internal(
             )
         },
         (Some(Structure::BLS12381G2), Some(SerializationFormat::BLS12381G2Uncompressed)) => {
-            // Valid BLS12381G2AffineUncompressed serialization should be 192-byte.
-            if bytes.len() != 192 {
-                return Ok(smallvec![Value::bool(false), Value::u64(0)]);
-            }
             ark_ec_point_deserialize_internal!(
                 context,
                 bytes,
@@ -417,10 +451,6 @@ pub fn deserialize_internal(
             )
         },
         (Some(Structure::BLS12381G2), Some(SerializationFormat::BLS12381G2Compressed)) => {
-            // Valid BLS12381G2AffineCompressed serialization should be 96-byte.
-            if bytes.len() != 96 {
-                return Ok(smallvec![Value::bool(false), Value::u64(0)]);
-            }
             ark_ec_point_deserialize_internal!(
                 context,
                 bytes,
@@ -430,10 +460,6 @@ pub fn deserialize_internal(
             )
         },
         (Some(Structure::BLS12381Gt), Some(SerializationFormat::BLS12381Gt)) => {
-            // Valid BLS12381Gt serialization should be 576-byte.
-            if bytes.len() != 576 {
-                return Ok(smallvec![Value::bool(false), Value::u64(0)]);
-            }
             context.charge(ALGEBRA_ARK_BLS12_381_FQ12_DESER)?;
             match <ark_bls12_381::Fq12>::deserialize_uncompressed(bytes) {
                 Ok(element) => {
@@ -451,9 +477,6 @@ pub fn deserialize_internal(
             }
         },
         (Some(Structure::BN254Fr), Some(SerializationFormat::BN254FrLsb)) => {
-            if bytes.len() != 32 {
-                return Ok(smallvec![Value::bool(false), Value::u64(0)]);
-            }
             ark_deserialize_internal!(
                 context,
                 bytes,
@@ -463,9 +486,6 @@ pub fn deserialize_internal(
             )
         },
         (Some(Structure::BN254Fr), Some(SerializationFormat::BN254FrMsb)) => {
-            if bytes.len() != 32 {
-                return Ok(smallvec![Value::bool(false), Value::u64(0)]);
-            }
             let mut bytes_copy: Vec<u8> = bytes.to_vec();
             bytes_copy.reverse();
             let bytes = bytes_copy.as_slice();
@@ -478,9 +498,6 @@ pub fn deserialize_internal(
             )
         },
         (Some(Structure::BN254Fq), Some(SerializationFormat::BN254FqLsb)) => {
-            if bytes.len() != 32 {
-                return Ok(smallvec![Value::bool(false), Value::u64(0)]);
-            }
             ark_deserialize_internal!(
                 context,
                 bytes,
@@ -490,9 +507,6 @@ pub fn deserialize_internal(
             )
         },
         (Some(Structure::BN254Fq), Some(SerializationFormat::BN254FqMsb)) => {
-            if bytes.len() != 32 {
-                return Ok(smallvec![Value::bool(false), Value::u64(0)]);
-            }
             let mut bytes_copy: Vec<u8> = bytes.to_vec();
             bytes_copy.reverse();
             let bytes = bytes_copy.as_slice();
@@ -505,10 +519,6 @@ pub fn deserialize_internal(
             )
         },
         (Some(Structure::BN254Fq12), Some(SerializationFormat::BN254Fq12LscLsb)) => {
-            // Valid BN254Fq12LscLsb serialization should be 32*12 = 64-byte.
-            if bytes.len() != 384 {
-                return Ok(smallvec![Value::bool(false), Value::u64(0)]);
-            }
             ark_deserialize_internal!(
                 context,
                 bytes,
@@ -518,10 +528,6 @@ pub fn deserialize_internal(
             )
         },
         (Some(Structure::BN254G1), Some(SerializationFormat::BN254G1Uncompressed)) => {
-            // Valid BN254G1AffineUncompressed serialization should be 64-byte.
-            if bytes.len() != 64 {
-                return Ok(smallvec![Value::bool(false), Value::u64(0)]);
-            }
             ark_ec_point_deserialize_internal!(
                 context,
                 bytes,
@@ -531,10 +537,6 @@ pub fn deserialize_internal(
             )
         },
         (Some(Structure::BN254G1), Some(SerializationFormat::BN254G1Compressed)) => {
-            // Valid BN254G1AffineCompressed serialization should be 32-byte.
-            if bytes.len() != 32 {
-                return Ok(smallvec![Value::bool(false), Value::u64(0)]);
-            }
             ark_ec_point_deserialize_internal!(
                 context,
                 bytes,
@@ -544,10 +546,6 @@ pub fn deserialize_internal(
             )
         },
         (Some(Structure::BN254G2), Some(SerializationFormat::BN254G2Uncompressed)) => {
-            // Valid BN254G2AffineUncompressed serialization should be 128-byte.
-            if bytes.len() != 128 {
-                return Ok(smallvec![Value::bool(false), Value::u64(0)]);
-            }
             ark_ec_point_deserialize_internal!(
                 context,
                 bytes,
@@ -557,10 +555,6 @@ pub fn deserialize_internal(
             )
         },
         (Some(Structure::BN254G2), Some(SerializationFormat::BN254G2Compressed)) => {
-            // Valid BN254G2AffineCompressed serialization should be 64-byte.
-            if bytes.len() != 64 {
-                return Ok(smallvec![Value::bool(false), Value::u64(0)]);
-            }
             ark_ec_point_deserialize_internal!(
                 context,
                 bytes,
@@ -570,10 +564,6 @@ pub fn deserialize_internal(
             )
         },
         (Some(Structure::BN254Gt), Some(SerializationFormat::BN254Gt)) => {
-            // Valid BN254Gt serialization should be 32*12=384-byte.
-            if bytes.len() != 384 {
-                return Ok(smallvec![Value::bool(false), Value::u64(0)]);
-            }
             context.charge(ALGEBRA_ARK_BN254_FQ12_DESER)?;
             match <ark_bn254::Fq12>::deserialize_uncompressed(bytes) {
                 Ok(element) => {
@@ -593,3 +583,24 @@ pub fn deserialize_internal(
         }),
     }
 }
+
+pub fn serialized_size_internal(
+    context: &mut SafeNativeContext,
+    ty_args: Vec<Type>,
+    _args: VecDeque<Value>,
+) -> SafeNativeResult<SmallVec<[Value; 1]>> {
+    assert_eq!(2, ty_args.len());
+    let structure_opt = structure_from_ty_arg!(context, &ty_args[0]);
+    let format_opt = format_from_ty_arg!(context, &ty_args[1]);
+    abort_unless_serialization_format_enabled!(context, format_opt);
+    context.charge(ALGEBRA_SERIALIZED_SIZE_BASE)?;
+    match structure_opt
+        .zip(format_opt)
+        .and_then(|(structure, format)| serialized_size(structure, format))
+    {
+        Some(size) => Ok(smallvec![Value::u64(size as u64)]),
+        None => Err(SafeNativeError::Abort {
+            abort_code: MOVE_ABORT_CODE_NOT_IMPLEMENTED,
+        }),
+    }
+}
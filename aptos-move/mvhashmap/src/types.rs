@@ -15,6 +15,7 @@ use bytes::Bytes;
 use derivative::Derivative;
 use move_binary_format::errors::PartialVMError;
 use move_core_types::value::MoveTypeLayout;
+use serde::{Deserialize, Serialize};
 use std::sync::{atomic::AtomicU32, Arc};
 
 pub type AtomicTxnIndex = AtomicU32;
@@ -24,7 +25,7 @@ pub type Incarnation = u32;
 /// Custom error type representing storage version. Result<Index, StorageVersion>
 /// then represents either index of some type (i.e. TxnIndex, Version), or a
 /// version corresponding to the storage (pre-block) state.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct StorageVersion;
 
 // TODO: Find better representations for this, a similar one for TxnIndex.
@@ -73,6 +74,7 @@ pub enum MVModulesError {
 #[derive(Debug, Eq, PartialEq)]
 pub enum GroupReadResult {
     Value(Option<Bytes>, Option<Arc<MoveTypeLayout>>),
+    Exists(bool),
     Size(ResourceGroupSize),
     Uninitialized,
 }
@@ -85,6 +87,13 @@ impl GroupReadResult {
         }
     }
 
+    pub fn into_exists(self) -> bool {
+        match self {
+            GroupReadResult::Exists(exists) => exists,
+            _ => unreachable!("Expected exists"),
+        }
+    }
+
     pub fn into_size(self) -> ResourceGroupSize {
         match self {
             GroupReadResult::Size(size) => size,
@@ -13,7 +13,7 @@ use aptos_types::{
     executable::{Executable, ExecutableDescriptor, ModulePath},
     write_set::TransactionWrite,
 };
-use aptos_vm_types::resource_group_adapter::group_size_as_sum;
+use aptos_vm_types::{resolver::ResourceGroupSize, resource_group_adapter::group_size_as_sum};
 use move_binary_format::errors::PartialVMResult;
 use move_core_types::value::MoveTypeLayout;
 use serde::Serialize;
@@ -45,6 +45,8 @@ pub struct UnsyncMap<
     // Optional hash can store the hash of the module to avoid re-computations.
     module_map: RefCell<HashMap<K, (Arc<V>, Option<HashValue>)>>,
     group_cache: RefCell<HashMap<K, RefCell<HashMap<T, ValueWithLayout<V>>>>>,
+    // Sizes of the groups at the storage version, served until the group is cached.
+    group_base_sizes: RefCell<HashMap<K, ResourceGroupSize>>,
     executable_cache: RefCell<HashMap<HashValue, Arc<X>>>,
     executable_bytes: RefCell<usize>,
     delayed_field_map: RefCell<HashMap<I, DelayedFieldValue>>,
@@ -66,6 +68,7 @@ impl<
             resource_map: RefCell::new(HashMap::new()),
             module_map: RefCell::new(HashMap::new()),
             group_cache: RefCell::new(HashMap::new()),
+            group_base_sizes: RefCell::new(HashMap::new()),
             executable_cache: RefCell::new(HashMap::new()),
             executable_bytes: RefCell::new(0),
             delayed_field_map: RefCell::new(HashMap::new()),
@@ -116,6 +119,14 @@ impl<
         );
     }
 
+    /// Sets the size of the group at the storage version, computed without the group members.
+    /// The group must be cached (by set_group_base_values) prior to any writes.
+    pub fn set_group_base_size(&self, group_key: K, base_size: ResourceGroupSize) {
+        self.group_base_sizes
+            .borrow_mut()
+            .insert(group_key, base_size);
+    }
+
     pub fn update_tagged_base_value_with_layout(
         &self,
         group_key: K,
@@ -131,6 +142,32 @@ impl<
             .insert(tag, ValueWithLayout::Exchanged(Arc::new(value), layout));
     }
 
+    /// Restores the value of the group member to the prior value (e.g. replaced by a patched
+    /// value), or removes the member if there was none. The group must be cached.
+    pub fn restore_tagged_base_value(
+        &self,
+        group_key: &K,
+        tag: T,
+        prior: Option<ValueWithLayout<V>>,
+    ) -> Result<(), PanicError> {
+        let group_cache = self.group_cache.borrow();
+        let mut group_map = group_cache
+            .get(group_key)
+            .ok_or_else(|| code_invariant_error("Restoring a member of an uncached group"))?
+            .borrow_mut();
+        match prior {
+            Some(prior) => group_map.insert(tag, prior),
+            None => group_map.remove(&tag),
+        };
+        Ok(())
+    }
+
+    /// Returns true if the key was initialized (or written) as a resource group.
+    pub fn contains_group(&self, group_key: &K) -> bool {
+        self.group_cache.borrow().contains_key(group_key)
+            || self.group_base_sizes.borrow().contains_key(group_key)
+    }
+
     pub fn get_group_size(&self, group_key: &K) -> PartialVMResult<GroupReadResult> {
         Ok(match self.group_cache.borrow().get(group_key) {
             Some(group_map) => GroupReadResult::Size(group_size_as_sum(
@@ -139,7 +176,10 @@ impl<
                     .iter()
                     .flat_map(|(t, v)| v.bytes_len().map(|s| (t, s))),
             )?),
-            None => GroupReadResult::Uninitialized,
+            None => match self.group_base_sizes.borrow().get(group_key) {
+                Some(base_size) => GroupReadResult::Size(*base_size),
+                None => GroupReadResult::Uninitialized,
+            },
         })
     }
 
@@ -223,6 +263,24 @@ impl<
         }
     }
 
+    /// Returns the latest contents of all the groups in the cache.
+    pub fn fetch_all_group_data(&self) -> Vec<(K, Vec<(T, ValueWithLayout<V>)>)> {
+        self.group_cache
+            .borrow()
+            .iter()
+            .map(|(key, group_map)| {
+                (
+                    key.clone(),
+                    group_map
+                        .borrow()
+                        .iter()
+                        .map(|(tag, value)| (tag.clone(), value.clone()))
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
     pub fn fetch_group_data(&self, key: &K) -> Option<Vec<(Arc<T>, ValueWithLayout<V>)>> {
         self.group_cache.borrow().get(key).map(|group_map| {
             group_map
@@ -280,6 +338,27 @@ impl<
         }
     }
 
+    /// Restores the base value of the resource to the prior value (e.g. replaced by a patched
+    /// value), or removes the base value if there was none.
+    pub fn restore_base_value(&self, key: K, prior: Option<ValueWithLayout<V>>) {
+        match prior {
+            Some(prior) => {
+                self.resource_map.borrow_mut().insert(key, prior);
+            },
+            None => {
+                if let Some(size) = self
+                    .resource_map
+                    .borrow_mut()
+                    .remove(&key)
+                    .and_then(|value| value.bytes_len())
+                {
+                    self.total_base_resource_size
+                        .fetch_sub(size as u64, Ordering::Relaxed);
+                }
+            },
+        }
+    }
+
     /// We return false if the executable was already stored, as this isn't supposed to happen
     /// during sequential execution (and the caller may choose to e.g. log a message).
     /// Versioned modules storage does not cache executables at storage version, hence directly
@@ -314,6 +393,36 @@ impl<
         );
         self.delayed_field_map.borrow_mut().insert(id, value);
     }
+
+    /// Removes the base value of the delayed field, generated by an execution that failed.
+    pub fn remove_base_delayed_field(&self, id: &I) -> Result<(), PanicError> {
+        match self.delayed_field_map.borrow_mut().remove(id) {
+            Some(value) => {
+                self.total_base_delayed_field_size.fetch_sub(
+                    value.get_approximate_memory_size() as u64,
+                    Ordering::Relaxed,
+                );
+                Ok(())
+            },
+            None => Err(code_invariant_error(
+                "Removing base value of a DelayedField that does not exist",
+            )),
+        }
+    }
+
+    /// Restores the base value of the delayed field to the prior value, or removes it if
+    /// there was none.
+    pub fn restore_base_delayed_field(
+        &self,
+        id: I,
+        prior: Option<DelayedFieldValue>,
+    ) -> Result<(), PanicError> {
+        self.remove_base_delayed_field(&id)?;
+        if let Some(prior) = prior {
+            self.set_base_delayed_field(id, prior);
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -511,6 +620,33 @@ mod test {
         assert_ok_eq!(map.get_group_size(&ap), GroupReadResult::Size(exp_size));
     }
 
+    #[test]
+    fn group_base_size() {
+        let ap = KeyType(b"/foo/f".to_vec());
+        let map = UnsyncMap::<KeyType<Vec<u8>>, usize, TestValue, ExecutableTestType, ()>::new();
+
+        let tag: usize = 5;
+        let one_entry_len = TestValue::creation_with_len(1).bytes().unwrap().len();
+        let two_entry_len = TestValue::creation_with_len(2).bytes().unwrap().len();
+        let base_size = group_size_as_sum(vec![(&tag, one_entry_len); 2].into_iter()).unwrap();
+
+        map.set_group_base_size(ap.clone(), base_size);
+        assert_ok_eq!(map.get_group_size(&ap), GroupReadResult::Size(base_size));
+        assert_none!(map.fetch_group_data(&ap));
+
+        // Once cached, the size is computed from the group members.
+        map.set_group_base_values(
+            ap.clone(),
+            (0..2).map(|i| (i, TestValue::creation_with_len(1))),
+        );
+        assert_ok_eq!(map.get_group_size(&ap), GroupReadResult::Size(base_size));
+        assert_ok!(map.insert_group_op(&ap, 0, TestValue::modification_with_len(2), None));
+        let exp_size =
+            group_size_as_sum(vec![(&tag, one_entry_len), (&tag, two_entry_len)].into_iter())
+                .unwrap();
+        assert_ok_eq!(map.get_group_size(&ap), GroupReadResult::Size(exp_size));
+    }
+
     #[test]
     fn group_value() {
         let ap = KeyType(b"/foo/f".to_vec());
@@ -574,4 +710,80 @@ mod test {
             ValueWithLayout::RawFromStorage(Arc::new(TestValue::creation_with_len(4)),)
         );
     }
+
+    #[test]
+    fn remove_base_delayed_field() {
+        let map = UnsyncMap::<KeyType<Vec<u8>>, usize, TestValue, ExecutableTestType, u32>::new();
+        map.set_base_delayed_field(1, DelayedFieldValue::Aggregator(10));
+        map.set_base_delayed_field(2, DelayedFieldValue::Snapshot(20));
+        let base_size = map.stats().base_delayed_fields_size;
+
+        assert_ok!(map.remove_base_delayed_field(&1));
+        assert_none!(map.fetch_delayed_field(&1));
+        assert_some_eq!(map.fetch_delayed_field(&2), DelayedFieldValue::Snapshot(20));
+        assert_eq!(
+            map.stats().base_delayed_fields_size,
+            base_size - DelayedFieldValue::Aggregator(10).get_approximate_memory_size() as u64
+        );
+
+        assert_err!(map.remove_base_delayed_field(&1));
+    }
+
+    #[test]
+    fn restore_base_values() {
+        let key = KeyType(b"/foo/a".to_vec());
+        let group_key = KeyType(b"/foo/g".to_vec());
+        let map = UnsyncMap::<KeyType<Vec<u8>>, usize, TestValue, ExecutableTestType, u32>::new();
+        let raw = ValueWithLayout::RawFromStorage(Arc::new(TestValue::creation_with_len(2)));
+        let exchanged = ValueWithLayout::Exchanged(Arc::new(TestValue::creation_with_len(2)), None);
+
+        // Resources: the patched value is restored to the raw one, and a new one is removed.
+        map.set_base_value(key.clone(), raw.clone());
+        let base_size = map.stats().base_resources_size;
+        map.set_base_value(key.clone(), exchanged);
+        map.restore_base_value(key.clone(), Some(raw.clone()));
+        assert_some_eq!(map.fetch_data(&key), raw.clone());
+        map.restore_base_value(key.clone(), None);
+        assert_none!(map.fetch_data(&key));
+        assert_eq!(map.stats().base_resources_size, base_size - 2);
+
+        // Group members.
+        map.set_group_base_values(
+            group_key.clone(),
+            vec![(1, TestValue::creation_with_len(2))],
+        );
+        map.update_tagged_base_value_with_layout(
+            group_key.clone(),
+            1,
+            TestValue::creation_with_len(2),
+            None,
+        );
+        map.update_tagged_base_value_with_layout(
+            group_key.clone(),
+            2,
+            TestValue::creation_with_len(3),
+            None,
+        );
+        assert_ok!(map.restore_tagged_base_value(&group_key, 1, Some(raw.clone())));
+        assert_ok!(map.restore_tagged_base_value(&group_key, 2, None));
+        assert_ok_eq!(map.fetch_group_tagged_data(&group_key, &1), raw);
+        assert_err_eq!(
+            map.fetch_group_tagged_data(&group_key, &2),
+            UnsyncGroupError::TagNotFound
+        );
+        assert_err!(map.restore_tagged_base_value(&key, 1, None));
+
+        // Delayed fields.
+        map.set_base_delayed_field(1, DelayedFieldValue::Aggregator(10));
+        map.set_base_delayed_field(1, DelayedFieldValue::Aggregator(20));
+        map.set_base_delayed_field(2, DelayedFieldValue::Aggregator(30));
+        assert_ok!(map.restore_base_delayed_field(1, Some(DelayedFieldValue::Aggregator(10))));
+        assert_ok!(map.restore_base_delayed_field(2, None));
+        assert_some_eq!(
+            map.fetch_delayed_field(&1),
+            DelayedFieldValue::Aggregator(10)
+        );
+        assert_none!(map.fetch_delayed_field(&2));
+        assert_err!(map.restore_base_delayed_field(2, None));
+    }
 }
@@ -283,7 +283,10 @@ impl<K: Hash + Clone + Debug + Eq, V: TransactionWrite> VersionedData<K, V> {
         }
     }
 
-    pub fn set_base_value(&self, key: K, value: ValueWithLayout<V>) {
+    /// Sets the base value of the key, returning true if the given value was installed, i.e.
+    /// there was no base value, or the raw value from storage was upgraded to the given
+    /// exchanged value (and false if the existing base value was kept).
+    pub fn set_base_value(&self, key: K, value: ValueWithLayout<V>) -> bool {
         let mut v = self.values.entry(key).or_default();
         // For base value, incarnation is irrelevant, and is always set to 0.
 
@@ -296,6 +299,7 @@ impl<K: Hash + Clone + Debug + Eq, V: TransactionWrite> VersionedData<K, V> {
                         .fetch_add(base_size as u64, Ordering::Relaxed);
                 }
                 v.insert(CachePadded::new(Entry::new_write_from(0, value)));
+                true
             },
             Occupied(mut o) => {
                 if let EntryCell::Write(i, existing_value) = &o.get().cell {
@@ -304,14 +308,17 @@ impl<K: Hash + Clone + Debug + Eq, V: TransactionWrite> VersionedData<K, V> {
                         (RawFromStorage(ev), RawFromStorage(v)) => {
                             // Base value from storage needs to be identical
                             // Assert the length of bytes for efficiency (instead of full equality)
-                            assert!(v.bytes().map(|b| b.len()) == ev.bytes().map(|b| b.len()))
+                            assert!(v.bytes().map(|b| b.len()) == ev.bytes().map(|b| b.len()));
+                            false
                         },
                         (Exchanged(_, _), RawFromStorage(_)) => {
                             // Stored value contains more info, nothing to do.
+                            false
                         },
                         (RawFromStorage(_), Exchanged(_, _)) => {
                             // Received more info, update.
                             o.insert(CachePadded::new(Entry::new_write_from(0, value)));
+                            true
                         },
                         (Exchanged(ev, e_layout), Exchanged(v, layout)) => {
                             // base value may have already been provided by another transaction
@@ -326,11 +333,14 @@ impl<K: Hash + Clone + Debug + Eq, V: TransactionWrite> VersionedData<K, V> {
                             if layout.is_none() {
                                 assert_eq!(v.bytes().map(|b| b.len()), ev.bytes().map(|b| b.len()));
                             }
+                            false
                         },
                     }
+                } else {
+                    false
                 }
             },
-        };
+        }
     }
 
     /// Versioned write of data at a given key (and version).
@@ -338,6 +338,31 @@ impl<K: Copy + Clone + Debug + Eq> VersionedValue<K> {
             },
         )
     }
+
+    // Returns whether the delayed field exists at a given version, without resolving its
+    // value. Delayed fields are never deleted, and a delta can only apply to a delayed field
+    // that existed before, so only estimates of values (that may have created the delayed
+    // field) need to be waited on, when there are no other entries below.
+    fn exists(&self, txn_idx: TxnIndex) -> Result<bool, MVDelayedFieldsError> {
+        use EstimatedEntry::*;
+        use VersionEntry::*;
+
+        if self.base_value.is_some() {
+            return Ok(true);
+        }
+
+        let mut dependency = None;
+        for (idx, entry) in self.versioned_map.range(0..txn_idx).rev() {
+            match entry.as_ref().deref() {
+                Value(_, _) | Apply(_) | Estimate(Bypass(_)) => return Ok(true),
+                Estimate(NoBypass) => {
+                    dependency.get_or_insert(*idx);
+                },
+            }
+        }
+
+        dependency.map_or(Ok(false), |idx| Err(MVDelayedFieldsError::Dependency(idx)))
+    }
 }
 
 pub trait TVersionedDelayedFieldView<K> {
@@ -356,6 +381,33 @@ pub trait TVersionedDelayedFieldView<K> {
         current_txn_idx: TxnIndex,
         read_position: ReadPosition,
     ) -> Result<DelayedFieldValue, MVDelayedFieldsError>;
+
+    /// Returns the base value of the delayed field (set by set_base_value), regardless of the
+    /// versions. This is the committed value before the first transaction of the block.
+    fn read_base_value(&self, id: &K) -> Result<DelayedFieldValue, MVDelayedFieldsError>;
+
+    /// Returns the committed values of the ids (in the same order), as read by
+    /// read_latest_committed_value, or the first error. Allows resolving all identifiers in
+    /// a resource with a single call.
+    fn read_latest_committed_values(
+        &self,
+        ids: &[K],
+        current_txn_idx: TxnIndex,
+        read_position: ReadPosition,
+    ) -> Result<Vec<DelayedFieldValue>, MVDelayedFieldsError> {
+        ids.iter()
+            .map(|id| self.read_latest_committed_value(id, current_txn_idx, read_position))
+            .collect()
+    }
+
+    /// Returns whether the delayed field with the given id exists at the given version,
+    /// without resolving its value (i.e. deltas are not aggregated).
+    fn exists(&self, id: &K, txn_idx: TxnIndex) -> Result<bool, MVDelayedFieldsError>;
+
+    /// Removes the delayed field with the given id, provided that only its base value was
+    /// set (i.e. there are no versions). Used to clean up the ids that were generated by
+    /// an execution that got discarded, and hence can not be referenced by other transactions.
+    fn remove_base_value(&self, id: &K) -> Result<(), PanicError>;
 }
 
 /// Maps each ID (access path) to an internal VersionedValue, managing versioned updates to the
@@ -724,6 +776,58 @@ impl<K: Eq + Hash + Clone + Debug + Copy> TVersionedDelayedFieldView<K>
                 )
             })
     }
+
+    fn read_base_value(&self, id: &K) -> Result<DelayedFieldValue, MVDelayedFieldsError> {
+        self.values
+            .get(id)
+            .and_then(|v| v.base_value.clone())
+            .ok_or(MVDelayedFieldsError::NotFound)
+    }
+
+    /// The commit index is loaded once, so that all values are read as of the same commit.
+    fn read_latest_committed_values(
+        &self,
+        ids: &[K],
+        current_txn_idx: TxnIndex,
+        read_position: ReadPosition,
+    ) -> Result<Vec<DelayedFieldValue>, MVDelayedFieldsError> {
+        let next_idx_to_commit = match read_position {
+            ReadPosition::BeforeCurrentTxn => current_txn_idx,
+            ReadPosition::AfterCurrentTxn => current_txn_idx + 1,
+        }
+        .min(self.next_idx_to_commit.load(Ordering::Relaxed));
+
+        ids.iter()
+            .map(|id| {
+                self.values
+                    .get_mut(id)
+                    .ok_or(MVDelayedFieldsError::NotFound)
+                    .and_then(|v| v.read_latest_committed_value(next_idx_to_commit))
+            })
+            .collect()
+    }
+
+    fn exists(&self, id: &K, txn_idx: TxnIndex) -> Result<bool, MVDelayedFieldsError> {
+        self.values.get(id).map_or(Ok(false), |v| v.exists(txn_idx))
+    }
+
+    fn remove_base_value(&self, id: &K) -> Result<(), PanicError> {
+        match self.values.remove_if(id, |_, v| v.versioned_map.is_empty()) {
+            Some((_, v)) => {
+                if let Some(base_value) = v.base_value {
+                    self.total_base_value_size.fetch_sub(
+                        base_value.get_approximate_memory_size() as u64,
+                        Ordering::Relaxed,
+                    );
+                }
+                Ok(())
+            },
+            None => Err(code_invariant_error(format!(
+                "Removing base value of DelayedField {:?} that does not exist or has versions",
+                id
+            ))),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -733,7 +837,7 @@ mod test {
         bounded_math::SignedU128, delta_change_set::DeltaOp, delta_math::DeltaHistory,
     };
     use aptos_types::delayed_fields::SnapshotToStringFormula;
-    use claims::{assert_err_eq, assert_ok_eq, assert_some};
+    use claims::{assert_err, assert_err_eq, assert_ok, assert_ok_eq, assert_some};
     use move_vm_types::delayed_values::delayed_field_id::DelayedFieldID;
     use test_case::test_case;
 
@@ -1287,5 +1391,140 @@ mod test {
         assert_err_eq!(v.read(3), PanicOr::Or(MVDelayedFieldsError::Dependency(2)));
     }
 
+    #[test]
+    fn remove_base_value() {
+        let delayed_fields = VersionedDelayedFields::<DelayedFieldID>::new();
+        let id = DelayedFieldID::new_for_test_for_u64(1);
+        let other_id = DelayedFieldID::new_for_test_for_u64(2);
+        delayed_fields.set_base_value(id, DelayedFieldValue::Aggregator(10));
+        delayed_fields.set_base_value(other_id, DelayedFieldValue::Aggregator(20));
+        assert_ok!(delayed_fields
+            .values
+            .get_mut(&other_id)
+            .unwrap()
+            .insert_speculative_value(3, aggregator_entry(VALUE_AGGREGATOR).unwrap()));
+        let base_value_size = delayed_fields.total_base_value_size.load(Ordering::Relaxed);
+
+        assert_ok!(delayed_fields.remove_base_value(&id));
+        assert_err_eq!(
+            delayed_fields.read_latest_committed_value(&id, 5, ReadPosition::BeforeCurrentTxn),
+            MVDelayedFieldsError::NotFound
+        );
+        assert_eq!(
+            delayed_fields.total_base_value_size.load(Ordering::Relaxed),
+            base_value_size
+                - DelayedFieldValue::Aggregator(10).get_approximate_memory_size() as u64
+        );
+
+        // Not found, or has versions.
+        assert_err!(delayed_fields.remove_base_value(&id));
+        assert_err!(delayed_fields.remove_base_value(&other_id));
+        assert_ok_eq!(
+            delayed_fields.read_latest_committed_value(
+                &other_id,
+                2,
+                ReadPosition::BeforeCurrentTxn
+            ),
+            DelayedFieldValue::Aggregator(20)
+        );
+    }
+
+    #[test]
+    fn exists() {
+        let mut v = VersionedValue::new(None);
+        assert_ok_eq!(v.exists(5), false);
+
+        v.insert_speculative_value(2, aggregator_entry(VALUE_AGGREGATOR).unwrap())
+            .unwrap();
+        v.insert_speculative_value(4, aggregator_entry(APPLY_AGGREGATOR).unwrap())
+            .unwrap();
+        assert_ok_eq!(v.exists(2), false);
+        assert_ok_eq!(v.exists(3), true);
+        assert_ok_eq!(v.exists(5), true);
+
+        // A delta (even as an estimate) implies that the delayed field existed before.
+        v.mark_estimate(2);
+        v.mark_estimate(4);
+        assert_ok_eq!(v.exists(5), true);
+        // The estimate of a value may have created the delayed field.
+        assert_err_eq!(v.exists(3), MVDelayedFieldsError::Dependency(2));
+
+        // Delayed fields with a base value always exist.
+        let v = VersionedValue::<DelayedFieldID>::new(Some(DelayedFieldValue::Aggregator(5)));
+        assert_ok_eq!(v.exists(0), true);
+
+        let delayed_fields = VersionedDelayedFields::<DelayedFieldID>::new();
+        let id = DelayedFieldID::new_for_test_for_u64(1);
+        assert_ok_eq!(delayed_fields.exists(&id, 5), false);
+        delayed_fields.set_base_value(id, DelayedFieldValue::Aggregator(10));
+        assert_ok_eq!(delayed_fields.exists(&id, 5), true);
+    }
+
+    #[test]
+    fn read_latest_committed_values() {
+        let delayed_fields = VersionedDelayedFields::<DelayedFieldID>::new();
+        let ids: Vec<_> = (1..=3).map(DelayedFieldID::new_for_test_for_u64).collect();
+        for (id, value) in ids.iter().zip([10, 20, 30]) {
+            delayed_fields.set_base_value(*id, DelayedFieldValue::Aggregator(value));
+        }
+
+        let values = [10, 20, 30].map(DelayedFieldValue::Aggregator).to_vec();
+        assert_ok_eq!(
+            delayed_fields.read_latest_committed_values(&ids, 5, ReadPosition::AfterCurrentTxn),
+            values
+        );
+
+        // As for the reads of the individual values, a missing id is not found.
+        let missing_id = DelayedFieldID::new_for_test_for_u64(4);
+        assert_err_eq!(
+            delayed_fields.read_latest_committed_values(
+                &[ids[0], missing_id],
+                5,
+                ReadPosition::AfterCurrentTxn
+            ),
+            MVDelayedFieldsError::NotFound
+        );
+    }
+
+    #[test]
+    fn read_committed_value_before_first_txn() {
+        let delayed_fields = VersionedDelayedFields::<DelayedFieldID>::new();
+        let id = DelayedFieldID::new_for_test_for_u64(1);
+        delayed_fields.set_base_value(id, DelayedFieldValue::Aggregator(5));
+        assert_ok!(delayed_fields
+            .values
+            .get_mut(&id)
+            .unwrap()
+            .insert_speculative_value(0, aggregator_entry(VALUE_AGGREGATOR).unwrap()));
+        assert_ok!(delayed_fields.try_commit(0, vec![id]));
+
+        // The committed value before the transaction 0 is the base value, also after the
+        // transaction 0 is committed.
+        assert_ok_eq!(
+            delayed_fields.read_latest_committed_value(&id, 0, ReadPosition::BeforeCurrentTxn),
+            DelayedFieldValue::Aggregator(5)
+        );
+        assert_ok_eq!(
+            delayed_fields.read_base_value(&id),
+            DelayedFieldValue::Aggregator(5)
+        );
+        for (txn_idx, read_position) in [
+            (0, ReadPosition::AfterCurrentTxn),
+            (1, ReadPosition::BeforeCurrentTxn),
+        ] {
+            assert_ok_eq!(
+                delayed_fields.read_latest_committed_value(&id, txn_idx, read_position),
+                DelayedFieldValue::Aggregator(10)
+            );
+        }
+
+        // Delayed fields without a base value (e.g. created by a transaction) are not found.
+        let created_id = DelayedFieldID::new_for_test_for_u64(2);
+        assert_err_eq!(
+            delayed_fields.read_base_value(&created_id),
+            MVDelayedFieldsError::NotFound
+        );
+    }
+
     // TODO[agg_v2](tests): add tests for try-commit
 }
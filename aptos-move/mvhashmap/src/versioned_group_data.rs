@@ -62,6 +62,11 @@ pub(crate) struct VersionedGroupValue<T, V> {
 
     /// Group contents corresponding to the latest committed version.
     committed_group: HashMap<T, ValueWithLayout<V>>,
+
+    /// Size of the group at the storage version, computed from the raw bytes of the group
+    /// (without deserializing the members). Allows serving the group size before the base
+    /// members are provided, as long as no lower transaction wrote to the group.
+    raw_base_size: Option<ResourceGroupSize>,
 }
 
 /// Maps each key (access path) to an internal VersionedValue.
@@ -77,6 +82,7 @@ impl<T: Hash + Clone + Debug + Eq + Serialize, V: TransactionWrite> Default
             versioned_map: HashMap::new(),
             idx_to_update: BTreeMap::new(),
             committed_group: HashMap::new(),
+            raw_base_size: None,
         }
     }
 }
@@ -113,12 +119,22 @@ impl<T: Hash + Clone + Debug + Eq + Serialize, V: TransactionWrite> VersionedGro
         }
     }
 
+    fn set_raw_base_size(&mut self, base_size: ResourceGroupSize) {
+        // Base size may have already been provided by another transaction executed
+        // simultaneously, and computed from the same storage bytes.
+        assert_eq!(
+            *self.raw_base_size.get_or_insert(base_size),
+            base_size,
+            "Reading twice from storage must be consistent"
+        );
+    }
+
     fn update_tagged_base_value_with_layout(
         &mut self,
         tag: T,
         value: V,
         layout: Option<Arc<MoveTypeLayout>>,
-    ) {
+    ) -> bool {
         let zero_idx = ShiftedTxnIndex::zero_idx();
         let v = ValueWithLayout::Exchanged(Arc::new(value), layout.clone());
 
@@ -148,16 +164,18 @@ impl<T: Hash + Clone + Debug + Eq + Serialize, V: TransactionWrite> VersionedGro
                             .expect("Tag must exist in committed when updating for exchange");
                         assert_matches!(existing, &mut ValueWithLayout::RawFromStorage(_));
                         *existing = v;
+                        true
                     },
                     ValueWithLayout::Exchanged(_, _) => {
                         // already exchanged, skipping.
+                        false
                     },
                 }
             },
             Vacant(_) => {
                 unreachable!("Base version must exist when updating for exchange")
             },
-        };
+        }
     }
 
     fn write(
@@ -338,7 +356,21 @@ impl<T: Hash + Clone + Debug + Eq + Serialize, V: TransactionWrite> VersionedGro
             .idx_to_update
             .contains_key(&ShiftedTxnIndex::zero_idx())
         {
-            return Err(MVGroupError::Uninitialized);
+            // Without the base members, the raw base size can only be served if there are no
+            // updates (including estimates) below txn_idx, as the size of an updated member
+            // at the storage version is unknown.
+            return match self.raw_base_size {
+                Some(base_size)
+                    if self
+                        .idx_to_update
+                        .range(..ShiftedTxnIndex::new(txn_idx))
+                        .next()
+                        .is_none() =>
+                {
+                    Ok(base_size)
+                },
+                _ => Err(MVGroupError::Uninitialized),
+            };
         }
 
         let sizes = self
@@ -363,6 +395,39 @@ impl<T: Hash + Clone + Debug + Eq + Serialize, V: TransactionWrite> VersionedGro
             .collect::<Result<Vec<_>, MVGroupError>>()?;
         group_size_as_sum(sizes.into_iter()).map_err(MVGroupError::TagSerializationError)
     }
+
+    fn get_latest_group_members(
+        &self,
+        txn_idx: TxnIndex,
+    ) -> Result<Vec<(T, ValueWithLayout<V>)>, MVGroupError> {
+        if !self
+            .idx_to_update
+            .contains_key(&ShiftedTxnIndex::zero_idx())
+        {
+            return Err(MVGroupError::Uninitialized);
+        }
+
+        self.versioned_map
+            .iter()
+            .flat_map(|(tag, tree)| {
+                tree.range(ShiftedTxnIndex::zero_idx()..ShiftedTxnIndex::new(txn_idx))
+                    .next_back()
+                    .and_then(|(idx, entry)| {
+                        if entry.flag == Flag::Estimate {
+                            Some(Err(MVGroupError::Dependency(
+                                idx.idx().expect("May not depend on storage version"),
+                            )))
+                        } else {
+                            // Deleted members do not contribute to the group contents.
+                            entry
+                                .value
+                                .bytes_len()
+                                .map(|_| Ok((tag.clone(), entry.value.clone())))
+                        }
+                    })
+            })
+            .collect()
+    }
 }
 
 impl<
@@ -381,6 +446,11 @@ impl<
         self.group_values.len()
     }
 
+    /// Returns true if the key was initialized (or written) as a resource group.
+    pub fn contains_group(&self, key: &K) -> bool {
+        self.group_values.contains_key(key)
+    }
+
     pub fn set_raw_base_values(&self, key: K, base_values: impl IntoIterator<Item = (T, V)>) {
         // Incarnation is irrelevant for storage version, set to 0.
         self.group_values
@@ -389,18 +459,29 @@ impl<
             .set_raw_base_values(base_values.into_iter());
     }
 
+    /// Sets the size of the group at the storage version, to be served by get_group_size
+    /// until the base values are provided, unless there is a lower update to the group.
+    pub fn set_raw_base_size(&self, key: K, base_size: ResourceGroupSize) {
+        self.group_values
+            .entry(key)
+            .or_default()
+            .set_raw_base_size(base_size);
+    }
+
+    /// Upgrades the raw base value of the group member to the exchanged value, returning
+    /// true if the given value was installed (and false if the member was already exchanged).
     pub fn update_tagged_base_value_with_layout(
         &self,
         key: K,
         tag: T,
         value: V,
         layout: Option<Arc<MoveTypeLayout>>,
-    ) {
+    ) -> bool {
         // Incarnation is irrelevant for storage version, set to 0.
         self.group_values
             .entry(key)
             .or_default()
-            .update_tagged_base_value_with_layout(tag, value, layout);
+            .update_tagged_base_value_with_layout(tag, value, layout)
     }
 
     pub fn write(
@@ -469,6 +550,20 @@ impl<
         }
     }
 
+    /// Returns the latest values of all existing group members (and their respective tags),
+    /// collected in the same way as for get_group_size. Used to cross-validate the group
+    /// size against the group contents.
+    pub fn fetch_latest_group(
+        &self,
+        key: &K,
+        txn_idx: TxnIndex,
+    ) -> Result<Vec<(T, ValueWithLayout<V>)>, MVGroupError> {
+        match self.group_values.get(key) {
+            Some(g) => g.get_latest_group_members(txn_idx),
+            None => Err(MVGroupError::Uninitialized),
+        }
+    }
+
     /// For a given key that corresponds to a group, and an index of a transaction the last
     /// incarnation of which wrote to at least one tag of the group, finalizes the latest
     /// contents of the group. This method works on pointers only and is relatively lighweight,
@@ -772,6 +867,48 @@ mod test {
         assert_ok_eq!(map.get_group_size(&ap, 6), exp_size_4);
     }
 
+    #[test]
+    fn raw_base_group_size() {
+        use MVGroupError::*;
+        let ap = KeyType(b"/foo/g".to_vec());
+        let map = VersionedGroupData::<KeyType<Vec<u8>>, usize, TestValue>::new();
+
+        let tag: usize = 5;
+        let one_entry_len = TestValue::creation_with_len(1).bytes().unwrap().len();
+        let two_entry_len = TestValue::creation_with_len(2).bytes().unwrap().len();
+        let base_size = group_size_as_sum(vec![(&tag, one_entry_len); 2].into_iter()).unwrap();
+
+        map.set_raw_base_size(ap.clone(), base_size);
+        assert_ok_eq!(map.get_group_size(&ap, 7), base_size);
+        assert_matches!(map.fetch_tagged_data(&ap, &0, 7), Err(Uninitialized));
+
+        // The raw base size is not served above an update to the group.
+        map.write(ap.clone(), 5, 0, vec![(
+            0,
+            (TestValue::creation_with_len(2), None),
+        )]);
+        assert_ok_eq!(map.get_group_size(&ap, 5), base_size);
+        assert_matches!(map.get_group_size(&ap, 7), Err(Uninitialized));
+        map.mark_estimate(&ap, 5);
+        assert_matches!(map.get_group_size(&ap, 7), Err(Uninitialized));
+
+        // Once the base values are provided, the size is computed from the members.
+        map.set_raw_base_values(
+            ap.clone(),
+            (0..2).map(|i| (i, TestValue::creation_with_len(1))),
+        );
+        map.remove(&ap, 5);
+        assert_ok_eq!(map.get_group_size(&ap, 7), base_size);
+        map.write(ap.clone(), 5, 1, vec![(
+            0,
+            (TestValue::creation_with_len(2), None),
+        )]);
+        let exp_size =
+            group_size_as_sum(vec![(&tag, one_entry_len), (&tag, two_entry_len)].into_iter())
+                .unwrap();
+        assert_ok_eq!(map.get_group_size(&ap, 7), exp_size);
+    }
+
     fn finalize_group_as_hashmap(
         map: &VersionedGroupData<KeyType<Vec<u8>>, usize, TestValue>,
         key: &KeyType<Vec<u8>>,
@@ -1,8 +1,11 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::types::{Flag, MVModulesError, MVModulesOutput, TxnIndex};
-use aptos_crypto::hash::{DefaultHasher, HashValue};
+use crate::{
+    types::{Flag, MVModulesError, MVModulesOutput, TxnIndex},
+    utils::module_hash,
+};
+use aptos_crypto::hash::HashValue;
 use aptos_types::{
     executable::{Executable, ExecutableDescriptor},
     write_set::TransactionWrite,
@@ -46,14 +49,7 @@ pub struct VersionedModules<K, V: TransactionWrite, X: Executable> {
 
 impl<V: TransactionWrite> Entry<V> {
     pub fn new_write_from(module: V) -> Entry<V> {
-        let hash = module
-            .extract_raw_bytes()
-            .map(|bytes| {
-                let mut hasher = DefaultHasher::new(b"Module");
-                hasher.update(&bytes);
-                hasher.finish()
-            })
-            .expect("Module can't be deleted");
+        let hash = module_hash(&module);
 
         Entry {
             flag: Flag::Done,
@@ -79,7 +75,10 @@ impl<V: TransactionWrite, X: Executable> VersionedValue<V, X> {
         }
     }
 
-    fn read(&self, txn_idx: TxnIndex) -> anyhow::Result<(Arc<V>, HashValue), MVModulesError> {
+    fn read(
+        &self,
+        txn_idx: TxnIndex,
+    ) -> anyhow::Result<(TxnIndex, Arc<V>, HashValue), MVModulesError> {
         match self.versioned_map.range(0..txn_idx).next_back() {
             Some((idx, entry)) => {
                 if entry.flag() == Flag::Estimate {
@@ -87,7 +86,7 @@ impl<V: TransactionWrite, X: Executable> VersionedValue<V, X> {
                     return Err(MVModulesError::Dependency(*idx));
                 }
 
-                Ok((entry.module.clone(), entry.hash))
+                Ok((*idx, entry.module.clone(), entry.hash))
             },
             None => Err(MVModulesError::NotFound),
         }
@@ -146,16 +145,28 @@ impl<K: Hash + Clone + Eq, V: TransactionWrite, X: Executable> VersionedModules<
         key: &K,
         txn_idx: TxnIndex,
     ) -> anyhow::Result<MVModulesOutput<V, X>, MVModulesError> {
+        self.fetch_module_with_version(key, txn_idx)
+            .map(|(_, output)| output)
+    }
+
+    /// Same as fetch_module, but also returns the index of the transaction that published
+    /// the module, so that the read can be validated against the exact version.
+    pub fn fetch_module_with_version(
+        &self,
+        key: &K,
+        txn_idx: TxnIndex,
+    ) -> anyhow::Result<(TxnIndex, MVModulesOutput<V, X>), MVModulesError> {
         use MVModulesError::*;
         use MVModulesOutput::*;
 
         match self.values.get(key) {
-            Some(v) => v
-                .read(txn_idx)
-                .map(|(module, hash)| match v.executables.get(&hash) {
+            Some(v) => v.read(txn_idx).map(|(idx, module, hash)| {
+                let output = match v.executables.get(&hash) {
                     Some(x) => Executable((x.clone(), ExecutableDescriptor::Published(hash))),
                     None => Module((module, hash)),
-                }),
+                };
+                (idx, output)
+            }),
             None => Err(NotFound),
         }
     }
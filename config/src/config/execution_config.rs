@@ -8,7 +8,10 @@ use crate::config::{
     node_config_loader::NodeType, transaction_filter_type::Filter, utils::RootPath, Error,
     NodeConfig,
 };
-use aptos_types::{chain_id::ChainId, transaction::Transaction, waypoint::Waypoint};
+use aptos_types::{
+    block_executor::config::BlockExecutorOptInConfig, chain_id::ChainId, transaction::Transaction,
+    waypoint::Waypoint,
+};
 use serde::{Deserialize, Serialize};
 use serde_yaml::Value;
 use std::{
@@ -54,6 +57,8 @@ pub struct ExecutionConfig {
     pub transaction_filter: Filter,
     /// Used during DB bootstrapping
     pub genesis_waypoint: Option<WaypointConfig>,
+    /// Opt-in checks and tuning of the block executor (for tests and canaries)
+    pub block_executor_opt_in: BlockExecutorOptInConfig,
 }
 
 impl std::fmt::Debug for ExecutionConfig {
@@ -86,6 +91,7 @@ impl Default for ExecutionConfig {
             processed_transactions_detailed_counters: false,
             transaction_filter: Filter::empty(),
             genesis_waypoint: None,
+            block_executor_opt_in: BlockExecutorOptInConfig::default(),
         }
     }
 }
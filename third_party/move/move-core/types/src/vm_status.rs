@@ -998,4 +998,9 @@ pub mod sub_status {
         // User provided typetag failed to load.
         pub const EUSER_TYPE_LOADING_FAILURE: u64 = 0x1;
     }
+
+    pub mod unexpected_deserialization_error {
+        // A key was read both as a resource and as a resource group within a block.
+        pub const ERESOURCE_GROUP_REPRESENTATION_FLIP: u64 = 0x1;
+    }
 }
@@ -17,12 +17,13 @@ use move_binary_format::{
     file_format_common::size_u32_as_uleb128,
 };
 use move_core_types::value::MoveTypeLayout;
+use serde::{Deserialize, Serialize};
 
 const BITS_FOR_SIZE: usize = 32;
 
 /// Ephemeral identifier type used by delayed fields (e.g., aggregators, snapshots)
 /// during execution.
-#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct DelayedFieldID {
     unique_index: u32,
     // Exact number of bytes serialized delayed field will take.
@@ -14,6 +14,82 @@ pub struct BlockExecutorLocalConfig {
     // If true, we will discard the failed blocks and continue with the next block.
     // (allow_fallback needs to be set)
     pub discard_failed_blocks: bool,
+    // Opt-in checks and tuning of the block executor.
+    pub opt_in: BlockExecutorOptInConfig,
+    // Id of the shard executing the block (in the sharded execution), used to label the
+    // counters of the block executor.
+    pub shard_id: Option<usize>,
+}
+
+impl Default for BlockExecutorLocalConfig {
+    fn default() -> Self {
+        Self {
+            concurrency_level: 1,
+            allow_fallback: true,
+            discard_failed_blocks: false,
+            opt_in: BlockExecutorOptInConfig::default(),
+            shard_id: None,
+        }
+    }
+}
+
+/// Opt-in checks, diagnostics and tuning of the block executor, configured per node (meant for
+/// tests and canaries). All of them are disabled by default.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BlockExecutorOptInConfig {
+    // If true, every group size read is cross-validated against the size of the group
+    // contents, and mismatches are reported (meant for tests and canaries).
+    pub group_size_consistency_checks: bool,
+    // If true, members of a resource group are patched (delayed field values exchanged with
+    // identifiers) when the group is initialized from storage, for all tags with a known layout.
+    // Otherwise, each member is patched lazily, on the first read with a layout.
+    pub eager_group_member_patching: bool,
+    // If true, the layouts supplied with value reads are compared against the layouts stored
+    // with the exchanged values, and mismatches are reported (observe-only, for canaries).
+    pub layout_mismatch_telemetry: bool,
+    // If true, the reads captured by each parallel execution are checked for pairs of reads
+    // across a writer boundary (a key read at the version of a writer, and another key written
+    // by the same writer read at an earlier version), which are reported (for canaries).
+    pub snapshot_consistency_checks: bool,
+    // If true, the group size reads captured by each committed transaction are re-validated
+    // against the group sizes recomputed from the group contents, and discrepancies are
+    // reported. The recomputation serializes the groups, and is meant for canaries only.
+    pub post_commit_group_size_validation: bool,
+    // If true, when the outcome of an aggregator try_add is computed from the history captured
+    // by the execution, the latest committed value of the aggregator is re-read, and if the base
+    // of the history moved, the execution is stopped early (instead of failing the validation).
+    // Shortens the wasted executions on hot aggregators, at the cost of the additional reads.
+    pub history_base_revalidation: bool,
+    // If specified, value reads of resources (without delayed fields) larger than the threshold
+    // (in bytes) are captured by the hash of the value instead of retaining the value. Such a
+    // read is also valid if the version changed, but the value remained the same, at the cost
+    // of hashing the current value during the validation.
+    pub hashed_value_read_threshold: Option<usize>,
+    // If specified, values read with delayed fields that are larger than the threshold (in
+    // bytes) are not deserialized in the parallel execution to determine whether they need
+    // exchange. If such a value needs to be considered, the parallel execution falls back to
+    // the sequential execution (allow_fallback needs to be set).
+    pub exchange_size_threshold: Option<usize>,
+    // If specified, a read of the parallel execution waits on the read dependencies at most the
+    // given number of times (re-fetching after each wait), after which the execution of the
+    // transaction is halted. A safety valve against suspected livelocks; unbounded otherwise.
+    pub dependency_wait_retry_limit: Option<usize>,
+    // If specified, a read of the parallel execution waits on an unresolved dependency for at
+    // most the given time (in milliseconds), after which the incarnation of the transaction is
+    // aborted (and re-executed). A safety valve against suspected hangs; unbounded otherwise.
+    pub dependency_wait_timeout_ms: Option<u64>,
+    // If specified, a read of the parallel execution polls the status of an unresolved
+    // dependency (at most) the given number of times before blocking on it. Short dependencies
+    // are often resolved in the meantime, which avoids parking the thread; no polling otherwise.
+    pub dependency_wait_spin_count: Option<usize>,
+    // If true, the aggregator V1 values are read with the u128 layout, i.e. through the same
+    // exchange path as the resources with delayed fields (to cross-test the aggregator flows).
+    pub lift_aggregator_v1_values: bool,
+    // If true, the re-execution of a transaction starts with the reads of its prior incarnation
+    // that are still valid, which are then served without fetching from the multi-versioned
+    // data-structure (at the cost of validating them before the re-execution).
+    pub reuse_prior_reads: bool,
 }
 
 /// Configuration from on-chain configuration, that is
@@ -71,8 +147,7 @@ impl BlockExecutorConfig {
         Self {
             local: BlockExecutorLocalConfig {
                 concurrency_level,
-                allow_fallback: true,
-                discard_failed_blocks: false,
+                ..BlockExecutorLocalConfig::default()
             },
             onchain: BlockExecutorConfigFromOnchain::new_no_block_limit(),
         }
@@ -85,8 +160,7 @@ impl BlockExecutorConfig {
         Self {
             local: BlockExecutorLocalConfig {
                 concurrency_level,
-                allow_fallback: true,
-                discard_failed_blocks: false,
+                ..BlockExecutorLocalConfig::default()
             },
             onchain: BlockExecutorConfigFromOnchain::new_maybe_block_limit(maybe_block_gas_limit),
         }
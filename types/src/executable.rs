@@ -6,9 +6,10 @@ use crate::{
     state_store::state_key::{inner::StateKeyInner, StateKey},
 };
 use aptos_crypto::HashValue;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum ExecutableDescriptor {
     /// Possibly speculative, based on code published during the block.
     Published(HashValue),
@@ -75,6 +75,7 @@ pub enum FeatureFlag {
     PRIMARY_APT_FUNGIBLE_STORE_AT_USER_ADDRESS = 61,
     OBJECT_NATIVE_DERIVED_ADDRESS = 62,
     DISPATCHABLE_FUNGIBLE_ASSET = 63,
+    STRICT_ALGEBRA_CANONICAL_DESERIALIZATION = 64,
 }
 
 impl FeatureFlag {
@@ -161,6 +161,25 @@ impl StateValueMetadata {
     }
 }
 
+impl<'de> Deserialize<'de> for StateValueMetadata {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let persisted = Option::<PersistedStateValueMetadata>::deserialize(deserializer)?;
+        Ok(persisted.map_or_else(Self::none, PersistedStateValueMetadata::into_in_mem_form))
+    }
+}
+
+impl Serialize for StateValueMetadata {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.clone().into_persistable().serialize(serializer)
+    }
+}
+
 #[derive(Clone, Debug, CryptoHasher)]
 pub struct StateValue {
     inner: StateValueInner,
@@ -37,7 +37,7 @@ pub static TOTAL_SUPPLY_STATE_KEY: Lazy<StateKey> = Lazy::new(|| {
     )
 });
 
-#[derive(Eq, Clone, Debug, PartialEq)]
+#[derive(Eq, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum WriteOpKind {
     Creation,
     Modification,